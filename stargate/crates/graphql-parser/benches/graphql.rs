@@ -1,42 +1,53 @@
-#![feature(test)]
-extern crate test;
-
-extern crate graphql_parser;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 use graphql_parser::{parse_query, parse_schema};
 
-#[bench]
-fn bench_minimal(b: &mut test::Bencher) {
-    let src = include_str!("../tests/minimal_query.graphql");
-    b.iter(|| parse_query(src).unwrap());
-}
-
-#[bench]
-fn bench_inline_fragment(b: &mut test::Bencher) {
-    let src = include_str!("../tests/inline_fragment.graphql");
-    b.iter(|| parse_query(src).unwrap());
+fn bench_queries(c: &mut Criterion) {
+    let queries = [
+        ("minimal", include_str!("../tests/minimal_query.graphql")),
+        (
+            "inline_fragment",
+            include_str!("../tests/inline_fragment.graphql"),
+        ),
+        (
+            "directive_args",
+            include_str!("../tests/directive_args.graphql"),
+        ),
+        ("query_vars", include_str!("../tests/query_vars.graphql")),
+        (
+            "kitchen_sink",
+            include_str!("../tests/query_kitchen_sink.graphql"),
+        ),
+    ];
+    for (name, src) in queries.iter() {
+        c.bench_function(name, |b| b.iter(|| parse_query(black_box(src)).unwrap()));
+    }
 }
 
-#[bench]
-fn bench_directive_args(b: &mut test::Bencher) {
-    let src = include_str!("../tests/directive_args.graphql");
-    b.iter(|| parse_query(src).unwrap());
+fn bench_schemas(c: &mut Criterion) {
+    let schemas = [
+        ("github", include_str!("samples/github.graphql")),
+        ("csdl", include_str!("samples/csdl.graphql")),
+    ];
+    for (name, src) in schemas.iter() {
+        c.bench_function(name, |b| b.iter(|| parse_schema(black_box(src)).unwrap()));
+    }
 }
 
-#[bench]
-fn bench_query_vars(b: &mut test::Bencher) {
-    let src = include_str!("../tests/query_vars.graphql");
-    b.iter(|| parse_query(src).unwrap());
+fn bench_string_values(c: &mut Criterion) {
+    let fields: Vec<String> = (0..100)
+        .map(|i| {
+            format!(
+                r#"f{}(text: "a string argument without any escape sequences in it")"#,
+                i
+            )
+        })
+        .collect();
+    let src = format!("{{ {} }}", fields.join(" "));
+    c.bench_function("string_values", |b| {
+        b.iter(|| parse_query(black_box(&src)).unwrap())
+    });
 }
 
-#[bench]
-fn bench_kitchen_sink(b: &mut test::Bencher) {
-    let src = include_str!("../tests/query_kitchen_sink.graphql");
-    b.iter(|| parse_query(src).unwrap());
-}
-
-#[bench]
-fn bench_github(b: &mut test::Bencher) {
-    let src = include_str!("samples/github.graphql");
-    b.iter(|| parse_schema(src));
-}
+criterion_group!(benches, bench_queries, bench_schemas, bench_string_values);
+criterion_main!(benches);
@@ -1,94 +1,271 @@
+use std::borrow::{Borrow, Cow};
 use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
 
-use combine::easy::Error;
-use combine::error::StreamError;
-use combine::parser::choice::{choice, optional};
-use combine::parser::item::position;
-use combine::parser::repeat::{many, many1};
-use combine::{parser, ParseResult, Parser};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::helpers::{ident, kind, name, punct};
-use crate::position::Pos;
-use crate::tokenizer::{Kind as T, Token, TokenStream};
+use crate::helpers::{ParseResult, Parser};
+use crate::position::{Pos, Span, Spanned};
+use crate::tokenizer::Kind as T;
 use ordered_float::NotNan;
 
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
-pub struct Directive<'a> {
-    #[derivative(Hash = "ignore")]
+/// Text of the names in the AST: `&str` when borrowing them from the parsed
+/// source, `String` when owning them.
+pub trait Text: AsRef<str> + Borrow<str> + Into<String> + Clone + Eq + Ord + Hash + Debug {}
+
+impl Text for &str {}
+
+impl Text for String {}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct Directive<S> {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     pub position: Pos,
-    pub name: &'a str,
-    pub arguments: Vec<(Txt<'a>, Value<'a>)>,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub arguments: Vec<Argument<S>>,
 }
 
+/// An argument, whose span is from the start of its name to the end of its value.
+pub type Argument<S> = (Spanned<S>, Spanned<Value<S>>);
+
 pub type Txt<'a> = &'a str;
 
-#[derive(Debug, Clone, PartialEq, Hash)]
-pub enum Value<'a> {
-    Variable(Txt<'a>),
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub enum Value<S> {
+    Variable(S),
     Int(i64),
     Float(NotNan<f64>),
-    String(String),
+    String(StringValue<S>),
     Boolean(bool),
     Null,
-    Enum(Txt<'a>),
-    List(Vec<Value<'a>>),
-    Object(BTreeMap<Txt<'a>, Value<'a>>),
+    Enum(S),
+    List(Vec<Spanned<Value<S>>>),
+    Object(BTreeMap<S, Spanned<Value<S>>>),
+}
+
+/// A string literal, borrowed from the parsed source unless it had to be
+/// unescaped.
+///
+/// Values compare, hash, print and serialize as the string they stand for,
+/// whichever variant holds it.
+#[derive(Clone)]
+pub enum StringValue<S> {
+    /// A string without escape sequences, without its quotes.
+    Source(S),
+    /// The content of a block string, without its quotes. Its indentation is
+    /// only removed when the value is read.
+    Block(S),
+    /// An unescaped or built string.
+    Owned(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(bound(deserialize = "S: Text + Deserialize<'de>"))]
+pub enum Type<S> {
+    NamedType(S),
+    ListType(Box<Type<S>>),
+    NonNullType(Box<Type<S>>),
+}
+
+impl<S: Text> Directive<S> {
+    /// Converts the directive into one owning its names.
+    pub fn into_static(self) -> Directive<String> {
+        Directive {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            arguments: arguments_into_static(self.arguments),
+        }
+    }
+}
+
+impl<S: Text> Value<S> {
+    /// Converts the value into one owning its names.
+    pub fn into_static(self) -> Value<String> {
+        match self {
+            Value::Variable(name) => Value::Variable(name.into()),
+            Value::Int(i) => Value::Int(i),
+            Value::Float(f) => Value::Float(f),
+            Value::String(s) => Value::String(s.into_static()),
+            Value::Boolean(b) => Value::Boolean(b),
+            Value::Null => Value::Null,
+            Value::Enum(name) => Value::Enum(name.into()),
+            Value::List(items) => Value::List(
+                items
+                    .into_iter()
+                    .map(|item| item.map(Value::into_static))
+                    .collect(),
+            ),
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name.into(), value.map(Value::into_static)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<S: Text> StringValue<S> {
+    /// Returns the string, which is only allocated for block strings.
+    pub fn to_str(&self) -> Cow<'_, str> {
+        match self {
+            StringValue::Source(s) => Cow::Borrowed(s.as_ref()),
+            StringValue::Block(s) => Cow::Owned(dedent_block_string(s.as_ref())),
+            StringValue::Owned(s) => Cow::Borrowed(s),
+        }
+    }
+
+    /// Returns the string, or `None` for a block string, which has to be
+    /// dedented with `to_str` first.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            StringValue::Source(s) => Some(s.as_ref()),
+            StringValue::Block(_) => None,
+            StringValue::Owned(s) => Some(s),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self {
+            StringValue::Source(s) => s.into(),
+            StringValue::Block(s) => dedent_block_string(s.as_ref()),
+            StringValue::Owned(s) => s,
+        }
+    }
+
+    /// Converts the value into one owning its string, keeping a block
+    /// string to dedent.
+    pub fn into_static(self) -> StringValue<String> {
+        match self {
+            StringValue::Source(s) => StringValue::Source(s.into()),
+            StringValue::Block(s) => StringValue::Block(s.into()),
+            StringValue::Owned(s) => StringValue::Owned(s),
+        }
+    }
+}
+
+impl<S> From<String> for StringValue<S> {
+    fn from(s: String) -> Self {
+        StringValue::Owned(s)
+    }
+}
+
+impl<S: Text> PartialEq for StringValue<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_str() == other.to_str()
+    }
+}
+
+impl<S: Text> PartialEq<str> for StringValue<S> {
+    fn eq(&self, other: &str) -> bool {
+        self.to_str() == other
+    }
+}
+
+impl<'a, S: Text> PartialEq<&'a str> for StringValue<S> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.to_str() == *other
+    }
+}
+
+impl<S: Text> Hash for StringValue<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_str().hash(state)
+    }
+}
+
+impl<S: Text> Debug for StringValue<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.to_str(), f)
+    }
+}
+
+impl<S: Text> Serialize for StringValue<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.serialize_str(&self.to_str())
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash)]
-pub enum Type<'a> {
-    NamedType(Txt<'a>),
-    ListType(Box<Type<'a>>),
-    NonNullType(Box<Type<'a>>),
+impl<'de, S> Deserialize<'de> for StringValue<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(StringValue::Owned)
+    }
 }
 
-pub fn directives<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<Directive<'a>>, TokenStream<'a>> {
-    many(
-        position()
-            .skip(punct("@"))
-            .and(name::<'a>())
-            .and(parser(arguments))
-            .map(|((position, name), arguments)| Directive {
-                position,
-                name,
-                arguments,
-            }),
-    )
-    .parse_stream(input)
+impl<S: Text> Type<S> {
+    /// Converts the type into one owning its names.
+    pub fn into_static(self) -> Type<String> {
+        match self {
+            Type::NamedType(name) => Type::NamedType(name.into()),
+            Type::ListType(typ) => Type::ListType(Box::new(typ.into_static())),
+            Type::NonNullType(typ) => Type::NonNullType(Box::new(typ.into_static())),
+        }
+    }
 }
 
-pub fn arguments<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<(Txt<'a>, Value<'a>)>, TokenStream<'a>> {
-    optional(
-        punct("(")
-            .with(many1(name::<'a>().skip(punct(":")).and(parser(value))))
-            .skip(punct(")")),
-    )
-    .map(|opt| opt.unwrap_or_else(Vec::new))
-    .parse_stream(input)
+pub(crate) fn arguments_into_static<S: Text>(arguments: Vec<Argument<S>>) -> Vec<Argument<String>> {
+    arguments
+        .into_iter()
+        .map(|(name, value)| (name.map(Into::into), value.map(Value::into_static)))
+        .collect()
 }
 
-pub fn int_value<'a>(input: &mut TokenStream<'a>) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    kind(T::IntValue)
-        .and_then(|tok| tok.value.parse())
-        .map(Value::Int)
-        .parse_stream(input)
+pub(crate) fn directives_into_static<S: Text>(
+    directives: Vec<Directive<S>>,
+) -> Vec<Directive<String>> {
+    directives.into_iter().map(Directive::into_static).collect()
 }
 
-pub fn float_value<'a>(input: &mut TokenStream<'a>) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    kind(T::FloatValue)
-        .and_then(|tok| tok.value.parse::<NotNan<f64>>())
-        .map(Value::Float)
-        .parse_stream(input)
+pub fn directives<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<Directive<Txt<'a>>>> {
+    let mut directives = vec![];
+    while input.is_punct("@") {
+        let position = input.position();
+        input.bump()?;
+        let name = input.name()?;
+        let arguments = arguments(input)?;
+        directives.push(Directive {
+            position,
+            span: Span::new(position, input.end_position()),
+            name,
+            arguments,
+        });
+    }
+    Ok(directives)
 }
 
-fn unquote_block_string(src: &str) -> Result<String, Error<Token, Token>> {
-    debug_assert!(src.starts_with("\"\"\"") && src.ends_with("\"\"\""));
-    let indent = src[3..src.len() - 3]
+pub fn arguments<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<Argument<Txt<'a>>>> {
+    let mut arguments = vec![];
+    if input.eat_punct("(")? {
+        loop {
+            let name = input.spanned(Parser::name)?;
+            input.punct(":")?;
+            arguments.push((name, value(input)?));
+            if input.eat_punct(")")? {
+                break;
+            }
+        }
+    }
+    Ok(arguments)
+}
+
+/// Removes the common indentation of the lines of a block string, given
+/// without its quotes, and unescapes its triple quotes.
+fn dedent_block_string(raw: &str) -> String {
+    let indent = raw
         .lines()
         .skip(1)
         .filter_map(|line| {
@@ -101,8 +278,8 @@ fn unquote_block_string(src: &str) -> Result<String, Error<Token, Token>> {
         })
         .min()
         .unwrap_or(0);
-    let mut result = String::with_capacity(src.len() - 6);
-    let mut lines = src[3..src.len() - 3].lines();
+    let mut result = String::with_capacity(raw.len());
+    let mut lines = raw.lines();
     if let Some(first) = lines.next() {
         let stripped = first.trim();
         if !stripped.is_empty() {
@@ -122,10 +299,10 @@ fn unquote_block_string(src: &str) -> Result<String, Error<Token, Token>> {
         result.truncate(last_line);
     }
 
-    Ok(result)
+    result
 }
 
-fn unquote_string(s: &str) -> Result<String, Error<Token, Token>> {
+fn unquote_string(s: &str) -> Result<String, String> {
     let mut res = String::with_capacity(s.len());
     debug_assert!(s.starts_with('"') && s.ends_with('"'));
     let mut chars = s[1..s.len() - 1].chars();
@@ -146,10 +323,10 @@ fn unquote_string(s: &str) -> Result<String, Error<Token, Token>> {
                             match chars.next() {
                                 Some(inner_c) => temp_code_point.push(inner_c),
                                 None => {
-                                    return Err(Error::unexpected_message(format_args!(
+                                    return Err(format!(
                                         "\\u must have 4 characters after it, only found '{}'",
                                         temp_code_point
-                                    )))
+                                    ))
                                 }
                             }
                         }
@@ -158,18 +335,15 @@ fn unquote_string(s: &str) -> Result<String, Error<Token, Token>> {
                         match u32::from_str_radix(&temp_code_point, 16).map(std::char::from_u32) {
                             Ok(Some(unicode_char)) => res.push(unicode_char),
                             _ => {
-                                return Err(Error::unexpected_message(format_args!(
+                                return Err(format!(
                                     "{} is not a valid unicode code point",
                                     temp_code_point
-                                )))
+                                ))
                             }
                         }
                     }
                     c => {
-                        return Err(Error::unexpected_message(format_args!(
-                            "bad escaped char {:?}",
-                            c
-                        )));
+                        return Err(format!("bad escaped char {:?}", c));
                     }
                 }
             }
@@ -180,94 +354,139 @@ fn unquote_string(s: &str) -> Result<String, Error<Token, Token>> {
     Ok(res)
 }
 
-pub fn string<'a>(input: &mut TokenStream<'a>) -> ParseResult<String, TokenStream<'a>> {
-    choice((
-        kind(T::StringValue).and_then(|tok| unquote_string(tok.value)),
-        kind(T::BlockString).and_then(|tok| unquote_block_string(tok.value)),
-    ))
-    .parse_stream(input)
-}
-
-pub fn string_value<'a>(input: &mut TokenStream<'a>) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    kind(T::StringValue)
-        .and_then(|tok| unquote_string(tok.value))
-        .map(Value::String)
-        .parse_stream(input)
+/// Parses a string literal if there is one, borrowing it unless it has escape
+/// sequences.
+fn string_literal<'a>(input: &mut Parser<'a>) -> ParseResult<Option<StringValue<Txt<'a>>>> {
+    let position = input.position();
+    let token = match input.peek() {
+        Some(token) if token.kind == T::StringValue || token.kind == T::BlockString => token,
+        _ => return Ok(None),
+    };
+    input.bump()?;
+    if token.kind == T::BlockString {
+        debug_assert!(token.value.starts_with("\"\"\"") && token.value.ends_with("\"\"\""));
+        return Ok(Some(StringValue::Block(
+            &token.value[3..token.value.len() - 3],
+        )));
+    }
+    let raw = &token.value[1..token.value.len() - 1];
+    if raw.contains('\\') {
+        match unquote_string(token.value) {
+            Ok(s) => Ok(Some(StringValue::Owned(s))),
+            Err(message) => Err(input.invalid(position, message)),
+        }
+    } else {
+        Ok(Some(StringValue::Source(raw)))
+    }
 }
 
-pub fn block_string_value<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    kind(T::BlockString)
-        .and_then(|tok| unquote_block_string(tok.value))
-        .map(Value::String)
-        .parse_stream(input)
+/// Parses a string if there is one, e.g. a description.
+pub fn string<'a>(input: &mut Parser<'a>) -> ParseResult<Option<String>> {
+    Ok(string_literal(input)?.map(StringValue::into_string))
 }
 
-pub fn plain_value<'a>(input: &mut TokenStream<'a>) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    ident("true")
-        .map(|_| Value::Boolean(true))
-        .or(ident("false").map(|_| Value::Boolean(false)))
-        .or(ident("null").map(|_| Value::Null))
-        .or(name::<'a>().map(Value::Enum))
-        .or(parser(int_value))
-        .or(parser(float_value))
-        .or(parser(string_value))
-        .or(parser(block_string_value))
-        .parse_stream(input)
+pub fn value<'a>(input: &mut Parser<'a>) -> ParseResult<Spanned<Value<Txt<'a>>>> {
+    input.spanned(|input| input.nested(|input| parse_value(input, false)))
 }
 
-pub fn value<'a>(input: &mut TokenStream<'a>) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    parser(plain_value)
-        .or(punct("$").with(name::<'a>()).map(Value::Variable))
-        .or(punct("[")
-            .with(many(parser(value)))
-            .skip(punct("]"))
-            .map(Value::List))
-        .or(punct("{")
-            .with(many(name::<'a>().skip(punct(":")).and(parser(value))))
-            .skip(punct("}"))
-            .map(Value::Object))
-        .parse_stream(input)
+/// Parses a constant value, in which variables are not allowed.
+pub fn default_value<'a>(input: &mut Parser<'a>) -> ParseResult<Spanned<Value<Txt<'a>>>> {
+    input.spanned(|input| input.nested(|input| parse_value(input, true)))
 }
 
-pub fn default_value<'a>(input: &mut TokenStream<'a>) -> ParseResult<Value<'a>, TokenStream<'a>> {
-    parser(plain_value)
-        .or(punct("[")
-            .with(many(parser(default_value)))
-            .skip(punct("]"))
-            .map(Value::List))
-        .or(punct("{")
-            .with(many(
-                name::<'a>().skip(punct(":")).and(parser(default_value)),
-            ))
-            .skip(punct("}"))
-            .map(Value::Object))
-        .parse_stream(input)
+fn parse_value<'a>(input: &mut Parser<'a>, constant: bool) -> ParseResult<Value<Txt<'a>>> {
+    let item = if constant { default_value } else { value };
+    let position = input.position();
+    if let Some(string) = string_literal(input)? {
+        return Ok(Value::String(string));
+    }
+    let token = match input.peek() {
+        Some(token) => token,
+        None => return Err(input.expected("Value")),
+    };
+    match (token.kind, token.value) {
+        (T::Name, name) => {
+            input.bump()?;
+            Ok(match name {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                "null" => Value::Null,
+                name => Value::Enum(name),
+            })
+        }
+        (T::IntValue, int) => {
+            input.bump()?;
+            int.parse()
+                .map(Value::Int)
+                .map_err(|e| input.invalid(position, format!("{}", e)))
+        }
+        (T::FloatValue, float) => {
+            input.bump()?;
+            float
+                .parse()
+                .map(Value::Float)
+                .map_err(|e| input.invalid(position, format!("{}", e)))
+        }
+        (T::Punctuator, "$") if !constant => {
+            input.bump()?;
+            Ok(Value::Variable(input.name()?))
+        }
+        (T::Punctuator, "[") => {
+            input.bump()?;
+            let mut items = vec![];
+            while !input.eat_punct("]")? {
+                items.push(item(input)?);
+            }
+            Ok(Value::List(items))
+        }
+        (T::Punctuator, "{") => {
+            input.bump()?;
+            let mut fields = BTreeMap::new();
+            while !input.eat_punct("}")? {
+                let name = input.name()?;
+                input.punct(":")?;
+                fields.insert(name, item(input)?);
+            }
+            Ok(Value::Object(fields))
+        }
+        _ => Err(input.expected("Value")),
+    }
 }
 
-pub fn parse_type<'a>(input: &mut TokenStream<'a>) -> ParseResult<Type<'a>, TokenStream<'a>> {
-    name::<'a>()
-        .map(Type::NamedType)
-        .or(punct("[")
-            .with(parser(parse_type))
-            .skip(punct("]"))
-            .map(Box::new)
-            .map(Type::ListType))
-        .and(optional(punct("!")).map(|v| v.is_some()))
-        .map(|(typ, strict)| {
-            if strict {
-                Type::NonNullType(Box::new(typ))
-            } else {
-                typ
-            }
-        })
-        .parse_stream(input)
+pub fn parse_type<'a>(input: &mut Parser<'a>) -> ParseResult<Type<Txt<'a>>> {
+    input.nested(|input| {
+        let typ = if input.is_name() {
+            Type::NamedType(input.bump()?.value)
+        } else if input.eat_punct("[")? {
+            let typ = parse_type(input)?;
+            input.punct("]")?;
+            Type::ListType(Box::new(typ))
+        } else {
+            return Err(input.unexpected());
+        };
+        if input.eat_punct("!")? {
+            Ok(Type::NonNullType(Box::new(typ)))
+        } else {
+            Ok(typ)
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::unquote_string;
+    use super::{unquote_string, StringValue, Value};
+    use crate::parse_query;
+    use crate::query::{Definition, Selection};
+
+    fn argument<'a>(query: &'a str) -> Value<&'a str> {
+        match parse_query(query).unwrap().definitions.remove(0) {
+            Definition::SelectionSet(mut set) => match set.items.remove(0) {
+                Selection::Field(mut field) => field.arguments.remove(0).1.node,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
 
     #[test]
     fn unquote_unicode_string() {
@@ -284,4 +503,33 @@ mod tests {
             "\u{0009} hello \u{000A} there"
         );
     }
+
+    #[test]
+    fn borrows_strings_without_escapes() {
+        let query = r#"{ a(fields: "id sku") }"#;
+        match argument(query) {
+            Value::String(StringValue::Source(s)) => {
+                assert_eq!(s, "id sku");
+                assert_eq!(s.as_ptr(), query[13..].as_ptr());
+            }
+            value => panic!("expected a borrowed string, got {:?}", value),
+        }
+        match argument(r#"{ a(fields: "id\nsku") }"#) {
+            Value::String(s @ StringValue::Owned(_)) => assert_eq!(s, "id\nsku"),
+            value => panic!("expected an owned string, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn dedents_block_strings_when_read() {
+        let value = argument("{ a(text: \"\"\"\n    one\n      two\n  \"\"\") }");
+        let string = match &value {
+            Value::String(s) => s,
+            value => panic!("expected a string, got {:?}", value),
+        };
+        assert_eq!(string.as_str(), None);
+        assert_eq!(string.to_str(), "one\n  two\n");
+        assert_eq!(value, Value::String("one\n  two\n".to_string().into()));
+        assert_eq!(value.clone().into_static(), value.into_static());
+    }
 }
@@ -0,0 +1,622 @@
+//! Lossless concrete syntax tree (CST)
+//!
+//! Unlike the AST, the syntax tree keeps every byte of the source: each token holds the
+//! whitespace, commas and comments ("trivia") before it, and the tree holds the trivia
+//! after the last token. Printing a tree gives back its source byte for byte, so tools
+//! working on the tree (formatters, codemods) don't lose developers' comments.
+//!
+//! The tree is built from the AST: tokens are grouped into nodes by the spans of the AST
+//! nodes, e.g. a `Field` node holds the tokens of a field, along with the nodes of its
+//! arguments, directives and selection set. Tokens outside of any node, e.g. descriptions
+//! or the `extend` keyword, are held by the closest enclosing node.
+//!
+//! ```rust
+//! use graphql_parser::cst;
+//!
+//! let source = "# comment\ntype Query {\n  a: Int, # another comment\n}\n";
+//! let tree = cst::parse_schema(source).unwrap();
+//! assert_eq!(tree.to_string(), source);
+//! assert_eq!(tree.to_schema_document().unwrap().to_string(), "type Query {\n  a: Int\n}\n");
+//! ```
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::position::{Pos, Span};
+use crate::tokenizer::TokenStream;
+use crate::{query, schema, ParseError};
+
+pub use crate::tokenizer::Kind as TokenKind;
+
+/// Parses a piece of query language into a lossless syntax tree.
+pub fn parse_query(source: &str) -> Result<SyntaxTree, ParseError> {
+    let document = query::parse_query(source)?;
+    let mut nodes = vec![];
+    for definition in &document.definitions {
+        query_definition(definition, &mut nodes);
+    }
+    Ok(SyntaxTree::build(source, nodes))
+}
+
+/// Parses a piece of schema language into a lossless syntax tree.
+pub fn parse_schema(source: &str) -> Result<SyntaxTree, ParseError> {
+    let document = schema::parse_schema(source)?;
+    let mut nodes = vec![];
+    for definition in &document.definitions {
+        schema_definition(definition, &mut nodes);
+    }
+    Ok(SyntaxTree::build(source, nodes))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTree<'a> {
+    pub source: &'a str,
+    /// The `Document` node.
+    pub root: SyntaxNode<'a>,
+    /// Whitespace, commas and comments after the last token.
+    pub trailing_trivia: Vec<Trivia<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode<'a> {
+    pub kind: SyntaxKind,
+    pub span: Span,
+    pub children: Vec<SyntaxElement<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement<'a> {
+    Node(SyntaxNode<'a>),
+    Token(SyntaxToken<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxToken<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+    /// Whitespace, commas and comments before the token.
+    pub leading_trivia: Vec<Trivia<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: &'a str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs, line terminators and byte order marks.
+    Whitespace,
+    Comma,
+    /// A comment, from `#` to the end of the line, excluding the line terminator.
+    Comment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Document,
+    OperationDefinition,
+    FragmentDefinition,
+    VariableDefinition,
+    SelectionSet,
+    Field,
+    FragmentSpread,
+    InlineFragment,
+    Directive,
+    /// An argument, from its name to the end of its value.
+    Argument,
+    Type,
+    /// A default value.
+    Value,
+    SchemaDefinition,
+    SchemaExtension,
+    ScalarTypeDefinition,
+    ObjectTypeDefinition,
+    InterfaceTypeDefinition,
+    UnionTypeDefinition,
+    EnumTypeDefinition,
+    InputObjectTypeDefinition,
+    ScalarTypeExtension,
+    ObjectTypeExtension,
+    InterfaceTypeExtension,
+    UnionTypeExtension,
+    EnumTypeExtension,
+    InputObjectTypeExtension,
+    FieldDefinition,
+    InputValueDefinition,
+    EnumValueDefinition,
+    DirectiveDefinition,
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// Converts the tree into the AST of a piece of query language. The AST is parsed from
+    /// the printed tree rather than `source`, so it reflects any edits made to the tokens.
+    pub fn to_query_document(&self) -> Result<query::owned::Document, ParseError> {
+        Ok(query::parse_query(&self.to_string())?.into_static())
+    }
+
+    /// Converts the tree into the AST of a piece of schema language. The AST is parsed from
+    /// the printed tree rather than `source`, so it reflects any edits made to the tokens.
+    pub fn to_schema_document(&self) -> Result<schema::owned::Document, ParseError> {
+        Ok(schema::parse_schema(&self.to_string())?.into_static())
+    }
+
+    /// Builds the tree of `source`, which must be valid, with the spans of its AST nodes
+    /// in pre-order.
+    fn build(source: &'a str, mut nodes: Vec<(SyntaxKind, Span)>) -> SyntaxTree<'a> {
+        let (tokens, trailing_trivia, end) = tokenize(source);
+        // Empty spans, e.g. of missing selection sets, have no tokens.
+        nodes.retain(|(_, span)| span.start.offset < span.end.offset);
+        // Sorting is stable, so nodes with the same span stay nested in pre-order.
+        nodes.sort_by_key(|(_, span)| (span.start.offset, Reverse(span.end.offset)));
+        let mut nodes = nodes.into_iter().peekable();
+
+        let mut stack = vec![SyntaxNode {
+            kind: SyntaxKind::Document,
+            span: Span::new(
+                Pos {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                },
+                end,
+            ),
+            children: vec![],
+        }];
+        for token in tokens {
+            while stack.len() > 1
+                && stack[stack.len() - 1].span.end.offset <= token.span.start.offset
+            {
+                close(&mut stack);
+            }
+            while let Some(&(kind, span)) = nodes.peek() {
+                if span.start.offset > token.span.start.offset {
+                    break;
+                }
+                nodes.next();
+                stack.push(SyntaxNode {
+                    kind,
+                    span,
+                    children: vec![],
+                });
+            }
+            let parent = stack.len() - 1;
+            stack[parent].children.push(SyntaxElement::Token(token));
+        }
+        while stack.len() > 1 {
+            close(&mut stack);
+        }
+
+        SyntaxTree {
+            source,
+            root: stack.pop().expect("the document node is never closed"),
+            trailing_trivia,
+        }
+    }
+}
+
+fn close(stack: &mut Vec<SyntaxNode>) {
+    let node = stack.pop().expect("there is a node to close");
+    let parent = stack.len() - 1;
+    stack[parent].children.push(SyntaxElement::Node(node));
+}
+
+impl<'a> SyntaxNode<'a> {
+    /// Returns the child nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode<'a>> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Returns all the tokens of the node, including those of its descendants.
+    pub fn tokens(&self) -> Vec<&SyntaxToken<'a>> {
+        let mut tokens = vec![];
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+}
+
+/// Splits valid `source` into tokens, and returns them along with the trivia after the
+/// last one and the position of the end of `source`.
+fn tokenize(source: &str) -> (Vec<SyntaxToken>, Vec<Trivia>, Pos) {
+    let mut stream = TokenStream::new(source);
+    let mut tokens = vec![];
+    let mut trivia_start = 0;
+    loop {
+        let start = stream.position();
+        match stream.next_token() {
+            Ok(Some(token)) => {
+                let end = stream.end_position();
+                tokens.push(SyntaxToken {
+                    kind: token.kind,
+                    text: token.value,
+                    span: Span::new(start, end),
+                    leading_trivia: trivia(&source[trivia_start..start.offset]),
+                });
+                trivia_start = end.offset;
+            }
+            // the source is valid, so this is the end of input
+            Ok(None) | Err(_) => return (tokens, trivia(&source[trivia_start..]), start),
+        }
+    }
+}
+
+fn trivia(text: &str) -> Vec<Trivia> {
+    let mut trivia = vec![];
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = match c {
+            '#' => (
+                TriviaKind::Comment,
+                rest.find(&['\r', '\n'][..]).unwrap_or(rest.len()),
+            ),
+            ',' => (
+                TriviaKind::Comma,
+                rest.find(|c| c != ',').unwrap_or(rest.len()),
+            ),
+            _ => (
+                TriviaKind::Whitespace,
+                rest.find(&['#', ','][..]).unwrap_or(rest.len()),
+            ),
+        };
+        trivia.push(Trivia {
+            kind,
+            text: &rest[..len],
+        });
+        rest = &rest[len..];
+    }
+    trivia
+}
+
+impl<'a> fmt::Display for SyntaxTree<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.root)?;
+        for trivia in &self.trailing_trivia {
+            f.write_str(trivia.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for SyntaxNode<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => write!(f, "{}", node)?,
+                SyntaxElement::Token(token) => write!(f, "{}", token)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for SyntaxToken<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trivia in &self.leading_trivia {
+            f.write_str(trivia.text)?;
+        }
+        f.write_str(self.text)
+    }
+}
+
+fn query_definition(definition: &query::Definition, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    match definition {
+        query::Definition::SelectionSet(selection_set) => query_selection_set(selection_set, nodes),
+        query::Definition::Operation(operation) => operation_definition(operation, nodes),
+        query::Definition::Fragment(fragment) => fragment_definition(fragment, nodes),
+    }
+}
+
+fn operation_definition(
+    operation: &query::OperationDefinition,
+    nodes: &mut Vec<(SyntaxKind, Span)>,
+) {
+    nodes.push((SyntaxKind::OperationDefinition, operation.span));
+    for variable in &operation.variable_definitions {
+        nodes.push((SyntaxKind::VariableDefinition, variable.span));
+        nodes.push((SyntaxKind::Type, variable.var_type.span));
+        if let Some(value) = &variable.default_value {
+            nodes.push((SyntaxKind::Value, value.span));
+        }
+        directives(&variable.directives, nodes);
+    }
+    directives(&operation.directives, nodes);
+    query_selection_set(&operation.selection_set, nodes);
+}
+
+fn fragment_definition(fragment: &query::FragmentDefinition, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.push((SyntaxKind::FragmentDefinition, fragment.span));
+    directives(&fragment.directives, nodes);
+    query_selection_set(&fragment.selection_set, nodes);
+}
+
+fn query_selection_set(selection_set: &query::SelectionSet, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.push((SyntaxKind::SelectionSet, selection_set.span));
+    for selection in &selection_set.items {
+        match selection {
+            query::Selection::Field(field) => {
+                nodes.push((SyntaxKind::Field, field.span));
+                arguments(&field.arguments, nodes);
+                directives(&field.directives, nodes);
+                query_selection_set(&field.selection_set, nodes);
+            }
+            query::Selection::FragmentSpread(spread) => {
+                nodes.push((SyntaxKind::FragmentSpread, spread.span));
+                directives(&spread.directives, nodes);
+            }
+            query::Selection::InlineFragment(inline) => {
+                nodes.push((SyntaxKind::InlineFragment, inline.span));
+                directives(&inline.directives, nodes);
+                query_selection_set(&inline.selection_set, nodes);
+            }
+        }
+    }
+}
+
+fn directives(directives: &[query::Directive], nodes: &mut Vec<(SyntaxKind, Span)>) {
+    for directive in directives {
+        nodes.push((SyntaxKind::Directive, directive.span));
+        arguments(&directive.arguments, nodes);
+    }
+}
+
+fn arguments(arguments: &[query::Argument], nodes: &mut Vec<(SyntaxKind, Span)>) {
+    nodes.extend(arguments.iter().map(|(name, value)| {
+        (
+            SyntaxKind::Argument,
+            Span::new(name.span.start, value.span.end),
+        )
+    }));
+}
+
+fn schema_definition(definition: &schema::Definition, nodes: &mut Vec<(SyntaxKind, Span)>) {
+    use schema::{TypeDefinition as T, TypeExtension as E};
+    match definition {
+        schema::Definition::Schema(schema) => {
+            nodes.push((SyntaxKind::SchemaDefinition, schema.span));
+            directives(&schema.directives, nodes);
+        }
+        schema::Definition::SchemaExtension(schema) => {
+            nodes.push((SyntaxKind::SchemaExtension, schema.span));
+            directives(&schema.directives, nodes);
+        }
+        schema::Definition::Type(T::Scalar(scalar)) => {
+            nodes.push((SyntaxKind::ScalarTypeDefinition, scalar.span));
+            directives(&scalar.directives, nodes);
+        }
+        schema::Definition::Type(T::Object(object)) => {
+            nodes.push((SyntaxKind::ObjectTypeDefinition, object.span));
+            directives(&object.directives, nodes);
+            fields(&object.fields, nodes);
+        }
+        schema::Definition::Type(T::Interface(interface)) => {
+            nodes.push((SyntaxKind::InterfaceTypeDefinition, interface.span));
+            directives(&interface.directives, nodes);
+            fields(&interface.fields, nodes);
+        }
+        schema::Definition::Type(T::Union(union)) => {
+            nodes.push((SyntaxKind::UnionTypeDefinition, union.span));
+            directives(&union.directives, nodes);
+        }
+        schema::Definition::Type(T::Enum(enum_type)) => {
+            nodes.push((SyntaxKind::EnumTypeDefinition, enum_type.span));
+            directives(&enum_type.directives, nodes);
+            enum_values(&enum_type.values, nodes);
+        }
+        schema::Definition::Type(T::InputObject(input)) => {
+            nodes.push((SyntaxKind::InputObjectTypeDefinition, input.span));
+            directives(&input.directives, nodes);
+            input_values(&input.fields, nodes);
+        }
+        schema::Definition::TypeExtension(E::Scalar(scalar)) => {
+            nodes.push((SyntaxKind::ScalarTypeExtension, scalar.span));
+            directives(&scalar.directives, nodes);
+        }
+        schema::Definition::TypeExtension(E::Object(object)) => {
+            nodes.push((SyntaxKind::ObjectTypeExtension, object.span));
+            directives(&object.directives, nodes);
+            fields(&object.fields, nodes);
+        }
+        schema::Definition::TypeExtension(E::Interface(interface)) => {
+            nodes.push((SyntaxKind::InterfaceTypeExtension, interface.span));
+            directives(&interface.directives, nodes);
+            fields(&interface.fields, nodes);
+        }
+        schema::Definition::TypeExtension(E::Union(union)) => {
+            nodes.push((SyntaxKind::UnionTypeExtension, union.span));
+            directives(&union.directives, nodes);
+        }
+        schema::Definition::TypeExtension(E::Enum(enum_type)) => {
+            nodes.push((SyntaxKind::EnumTypeExtension, enum_type.span));
+            directives(&enum_type.directives, nodes);
+            enum_values(&enum_type.values, nodes);
+        }
+        schema::Definition::TypeExtension(E::InputObject(input)) => {
+            nodes.push((SyntaxKind::InputObjectTypeExtension, input.span));
+            directives(&input.directives, nodes);
+            input_values(&input.fields, nodes);
+        }
+        schema::Definition::Directive(directive) => {
+            nodes.push((SyntaxKind::DirectiveDefinition, directive.span));
+            input_values(&directive.arguments, nodes);
+        }
+        schema::Definition::Operation(operation) => operation_definition(operation, nodes),
+        schema::Definition::Fragment(fragment) => fragment_definition(fragment, nodes),
+    }
+}
+
+fn fields(fields: &[schema::Field], nodes: &mut Vec<(SyntaxKind, Span)>) {
+    for field in fields {
+        nodes.push((SyntaxKind::FieldDefinition, field.span));
+        input_values(&field.arguments, nodes);
+        nodes.push((SyntaxKind::Type, field.field_type.span));
+        directives(&field.directives, nodes);
+    }
+}
+
+fn input_values(input_values: &[schema::InputValue], nodes: &mut Vec<(SyntaxKind, Span)>) {
+    for input_value in input_values {
+        nodes.push((SyntaxKind::InputValueDefinition, input_value.span));
+        nodes.push((SyntaxKind::Type, input_value.value_type.span));
+        if let Some(value) = &input_value.default_value {
+            nodes.push((SyntaxKind::Value, value.span));
+        }
+        directives(&input_value.directives, nodes);
+    }
+}
+
+fn enum_values(enum_values: &[schema::EnumValue], nodes: &mut Vec<(SyntaxKind, Span)>) {
+    for enum_value in enum_values {
+        nodes.push((SyntaxKind::EnumValueDefinition, enum_value.span));
+        directives(&enum_value.directives, nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_query, parse_schema, SyntaxElement, SyntaxKind, SyntaxNode, TriviaKind};
+
+    /// Returns the kinds of the node and its descendants, in pre-order.
+    fn kinds(node: &SyntaxNode) -> Vec<SyntaxKind> {
+        let mut kinds = vec![node.kind];
+        for child in node.nodes() {
+            kinds.extend(self::kinds(child));
+        }
+        kinds
+    }
+
+    /// Returns the first node of `kind` among the node and its descendants, in pre-order.
+    fn find<'t, 'a>(node: &'t SyntaxNode<'a>, kind: SyntaxKind) -> Option<&'t SyntaxNode<'a>> {
+        if node.kind == kind {
+            return Some(node);
+        }
+        node.nodes().find_map(|child| find(child, kind))
+    }
+
+    #[test]
+    fn it_should_round_trip_the_source() {
+        let source = "\u{feff}# leading\r\nquery Q($a: Int = 1,,) {\r\n  a(b: $a) , # trailing\n\tc @d\n}\n\n# end";
+        let tree = parse_query(source).unwrap();
+        assert_eq!(tree.to_string(), source);
+        assert_eq!(
+            tree.to_query_document().unwrap().to_string(),
+            "query Q($a: Int = 1) {\n  a(b: $a)\n  c @d\n}\n"
+        );
+        assert_eq!(tree.trailing_trivia.len(), 2);
+        assert_eq!(tree.trailing_trivia[1].kind, TriviaKind::Comment);
+        assert_eq!(tree.trailing_trivia[1].text, "# end");
+    }
+
+    #[test]
+    fn it_should_nest_tokens_in_nodes() {
+        let source = "{ a(b: 1) { ...F # c\n } }";
+        let tree = parse_query(source).unwrap();
+        use SyntaxKind::*;
+        assert_eq!(
+            kinds(&tree.root),
+            vec![
+                Document,
+                SelectionSet,
+                Field,
+                Argument,
+                SelectionSet,
+                FragmentSpread
+            ]
+        );
+
+        assert_eq!(
+            find(&tree.root, FragmentSpread).unwrap().to_string(),
+            " ...F"
+        );
+        let field = find(&tree.root, Field).unwrap();
+        assert_eq!(field.to_string(), " a(b: 1) { ...F # c\n }");
+        let close = match &field.children[..] {
+            [.., SyntaxElement::Node(selection_set)] => selection_set.tokens().pop().unwrap(),
+            _ => panic!("the field has no selection set"),
+        };
+        assert_eq!(close.text, "}");
+        let trivia: Vec<_> = close
+            .leading_trivia
+            .iter()
+            .map(|t| (t.kind, t.text))
+            .collect();
+        assert_eq!(
+            trivia,
+            vec![
+                (TriviaKind::Whitespace, " "),
+                (TriviaKind::Comment, "# c"),
+                (TriviaKind::Whitespace, "\n "),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_keep_comments_in_schemas() {
+        let source = r#"
+# The query type
+"""
+Description
+"""
+type Query implements A & B @key(fields: "id") {
+  # a field
+  a(
+    # an argument
+    arg: [Int!] = [1, 2]
+  ): String # after
+}
+
+extend enum E { # values
+  A
+  B
+}
+"#;
+        let tree = parse_schema(source).unwrap();
+        assert_eq!(tree.to_string(), source);
+        use SyntaxKind::*;
+        assert_eq!(
+            kinds(&tree.root),
+            vec![
+                Document,
+                ObjectTypeDefinition,
+                Directive,
+                Argument,
+                FieldDefinition,
+                InputValueDefinition,
+                Type,
+                Value,
+                Type,
+                EnumTypeExtension,
+                EnumValueDefinition,
+                EnumValueDefinition,
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_convert_edited_trees() {
+        fn rename<'a>(node: &mut SyntaxNode<'a>, from: &str, to: &'a str) {
+            for child in &mut node.children {
+                match child {
+                    SyntaxElement::Node(node) => rename(node, from, to),
+                    SyntaxElement::Token(token) if token.text == from => token.text = to,
+                    SyntaxElement::Token(_) => {}
+                }
+            }
+        }
+
+        let mut tree = parse_schema("type Query { a: Int }").unwrap();
+        rename(&mut tree.root, "Int", "String");
+        assert_eq!(
+            tree.to_schema_document().unwrap().to_string(),
+            "type Query {\n  a: String\n}\n"
+        );
+    }
+}
@@ -1,21 +1,167 @@
-use combine::easy::Errors;
+use std::fmt::{self, Write};
+
 use thiserror::Error;
 
 use crate::position::Pos;
-use crate::tokenizer::Token;
-
-pub type InternalError<'a> = Errors<Token<'a>, Token<'a>, Pos>;
 
 /// Parse error
 ///
-/// This structure is opaque for forward compatibility. We are exploring a
-/// way to improve both error message and API.
-#[derive(Error, Debug)]
-#[error("parse error: {}", _0)]
-pub struct ParseError(String);
+/// Besides the message, which is what `Display` prints, the error exposes where parsing failed
+/// and what was found and expected there, e.g. to return `locations` in GraphQL errors.
+/// The details are boxed, so `Result`s holding the error stay small.
+#[derive(Error, Clone, PartialEq)]
+#[error("parse error: {}", .0.message)]
+pub struct ParseError(Box<Inner>);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Inner {
+    kind: ParseErrorKind,
+    message: String,
+    position: Option<Pos>,
+    unexpected: Option<String>,
+    expected: Vec<String>,
+    snippet: Option<String>,
+}
+
+/// What was found where parsing failed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Unexpected<'a> {
+    Token(&'a str),
+    EndOfInput,
+    /// A description of the error, e.g. of a token which couldn't be read.
+    Message(String),
+}
+
+/// What made parsing fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The document is not valid GraphQL.
+    Syntax,
+    /// The document is larger than `ParserOptions::max_document_size`.
+    DocumentTooLarge,
+    /// The document has more tokens than `ParserOptions::max_tokens`.
+    TooManyTokens,
+    /// The document is nested deeper than `ParserOptions::max_depth`.
+    RecursionLimitExceeded,
+}
+
+impl ParseError {
+    /// Builds the error of a syntax error at `position` in `source`, where one of
+    /// `expected` would have been valid instead.
+    pub(crate) fn syntax(
+        position: Pos,
+        unexpected: Unexpected,
+        expected: &[&str],
+        source: &str,
+    ) -> ParseError {
+        let mut message = format!("Parse error at {}\n", position);
+        let unexpected = match unexpected {
+            Unexpected::Token(token) => {
+                writeln!(message, "Unexpected `{}`", token).unwrap();
+                String::from(token)
+            }
+            Unexpected::EndOfInput => {
+                message.push_str("Unexpected end of input\n");
+                String::from("end of input")
+            }
+            Unexpected::Message(text) => {
+                writeln!(message, "{}", text).unwrap();
+                text
+            }
+        };
+        let mut expected_tokens: Vec<String> = vec![];
+        for token in expected {
+            if !expected_tokens.iter().any(|e| e == token) {
+                expected_tokens.push(String::from(*token));
+            }
+        }
+        if let Some((last, rest)) = expected_tokens.split_last() {
+            message.push_str("Expected ");
+            for (i, token) in rest.iter().enumerate() {
+                if i > 0 {
+                    message.push_str(", ");
+                }
+                write!(message, "`{}`", token).unwrap();
+            }
+            if !rest.is_empty() {
+                message.push_str(" or ");
+            }
+            writeln!(message, "`{}`", last).unwrap();
+        }
+
+        ParseError(Box::new(Inner {
+            kind: ParseErrorKind::Syntax,
+            message,
+            position: Some(position),
+            unexpected: Some(unexpected),
+            expected: expected_tokens,
+            snippet: snippet(source, position),
+        }))
+    }
+
+    /// Builds the error of a limit of `ParserOptions` exceeded at `position`, if known.
+    pub(crate) fn limit_exceeded(
+        kind: ParseErrorKind,
+        message: String,
+        position: Option<Pos>,
+        source: &str,
+    ) -> ParseError {
+        ParseError(Box::new(Inner {
+            kind,
+            message,
+            position,
+            unexpected: None,
+            expected: vec![],
+            snippet: position.and_then(|position| snippet(source, position)),
+        }))
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.0.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    /// Where parsing failed. `None` when the whole document was rejected,
+    /// e.g. because it is too large.
+    pub fn position(&self) -> Option<Pos> {
+        self.0.position
+    }
+
+    /// The token found where parsing failed, or `end of input`.
+    pub fn unexpected(&self) -> Option<&str> {
+        self.0.unexpected.as_deref()
+    }
 
-impl<'a> From<InternalError<'a>> for ParseError {
-    fn from(e: InternalError<'a>) -> ParseError {
-        ParseError(format!("{}", e))
+    /// The tokens (or kinds of tokens, e.g. `Name`) which would have been valid instead.
+    pub fn expected(&self) -> &[String] {
+        &self.0.expected
     }
+
+    /// The line of the document where parsing failed.
+    pub fn snippet(&self) -> Option<&str> {
+        self.0.snippet.as_deref()
+    }
+}
+
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParseError")
+            .field("kind", &self.0.kind)
+            .field("message", &self.0.message)
+            .field("position", &self.0.position)
+            .field("unexpected", &self.0.unexpected)
+            .field("expected", &self.0.expected)
+            .field("snippet", &self.0.snippet)
+            .finish()
+    }
+}
+
+fn snippet(source: &str, position: Pos) -> Option<String> {
+    source
+        .lines()
+        .nth(position.line.checked_sub(1)?)
+        .map(|line| String::from(line.trim_end_matches('\r')))
 }
@@ -1,7 +1,7 @@
 //! Formatting graphql
 use std::default::Default;
 
-use crate::common::Directive;
+use crate::common::{Directive, Text};
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct Formatter<'a> {
@@ -12,16 +12,24 @@ pub(crate) struct Formatter<'a> {
 
 /// A configuration of formatting style
 ///
-/// Currently we only have indentation configured, other things might be
-/// added later (such as minification).
+/// For the smallest output, e.g. to send over the network, see
+/// `DisplayMinified` instead.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Style {
     indent: u32,
+    sort: bool,
+    max_line_width: Option<usize>,
+    block_strings: bool,
 }
 
 impl Default for Style {
     fn default() -> Style {
-        Style { indent: 2 }
+        Style {
+            indent: 2,
+            sort: false,
+            max_line_width: None,
+            block_strings: true,
+        }
     }
 }
 
@@ -31,12 +39,42 @@ impl Style {
         self.indent = indent;
         self
     }
+
+    /// Sort selections and arguments of queries, and fields and arguments
+    /// of schemas, by name. Fields come before fragment spreads, which come
+    /// before inline fragments.
+    ///
+    /// This makes the output independent of the order of the source, e.g. to
+    /// diff persisted operations, but changes the order of response fields.
+    pub fn sort(&mut self, sort: bool) -> &mut Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Write an argument list one argument per line when it would make its
+    /// line longer than `width` characters.
+    pub fn max_line_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.max_line_width = width;
+        self
+    }
+
+    /// Write strings containing newlines as block strings (the default),
+    /// or always as escaped strings when `false`.
+    pub fn block_strings(&mut self, block_strings: bool) -> &mut Self {
+        self.block_strings = block_strings;
+        self
+    }
 }
 
 pub(crate) trait Displayable {
     fn display(&self, f: &mut Formatter);
 }
 
+/// The key nodes are ordered by when `Style::sort` is set.
+pub(crate) trait SortKey {
+    fn sort_key(&self) -> (u8, &str);
+}
+
 impl<'a> Formatter<'a> {
     pub fn new(style: &Style) -> Formatter {
         Formatter {
@@ -86,17 +124,64 @@ impl<'a> Formatter<'a> {
         self.buf
     }
 
+    /// Returns `items` in the order to write them in.
+    pub fn sorted<'i, T: SortKey>(&self, items: &'i [T]) -> Vec<&'i T> {
+        let mut items: Vec<&T> = items.iter().collect();
+        if self.style.sort {
+            items.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        }
+        items
+    }
+
+    /// Writes `items` in parentheses, separated by commas, or one per line
+    /// if that would make the line longer than `Style::max_line_width`.
+    pub fn write_arguments<T>(&mut self, items: &[&T], write_item: impl Fn(&T, &mut Formatter)) {
+        if items.is_empty() {
+            return;
+        }
+        let start = self.buf.len();
+        self.buf.push('(');
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            write_item(item, self);
+        }
+        self.buf.push(')');
+
+        let line_start = self.buf[..start].rfind('\n').map_or(0, |i| i + 1);
+        let width = self.buf[line_start..]
+            .lines()
+            .next()
+            .map_or(0, |line| line.chars().count());
+        if !matches!(self.style.max_line_width, Some(max) if width > max) {
+            return;
+        }
+        self.buf.truncate(start);
+        self.buf.push('(');
+        self.endline();
+        self.indent += self.style.indent;
+        for item in items {
+            self.indent();
+            write_item(item, self);
+            self.endline();
+        }
+        self.indent -= self.style.indent;
+        self.indent();
+        self.buf.push(')');
+    }
+
     pub fn write_quoted(&mut self, s: &str) {
         let mut has_newline = false;
         let mut has_nonprintable = false;
         for c in s.chars() {
             match c {
                 '\n' => has_newline = true,
-                '\r' | '\t' | '\u{0020}'..='\u{FFFF}' => {}
+                '\r' | '\t' | '\u{0020}'..='\u{10FFFF}' => {}
                 _ => has_nonprintable = true,
             }
         }
-        if !has_newline || has_nonprintable {
+        if !has_newline || has_nonprintable || !self.style.block_strings {
             use std::fmt::Write;
             self.buf.push('"');
             for c in s.chars() {
@@ -106,8 +191,8 @@ impl<'a> Formatter<'a> {
                     '\t' => self.write(r"\t"),
                     '"' => self.write("\\\""),
                     '\\' => self.write(r"\\"),
-                    '\u{0020}'..='\u{FFFF}' => self.buf.push(c),
-                    _ => write!(&mut self.buf, "\\u{:04}", c as u32).unwrap(),
+                    '\u{0020}'..='\u{10FFFF}' => self.buf.push(c),
+                    _ => write!(&mut self.buf, "\\u{:04x}", c as u32).unwrap(),
                 }
             }
             self.buf.push('"');
@@ -129,7 +214,7 @@ impl<'a> Formatter<'a> {
     }
 }
 
-pub(crate) fn format_directives<'a>(dirs: &[Directive<'a>], f: &mut Formatter) {
+pub(crate) fn format_directives<S: Text>(dirs: &[Directive<S>], f: &mut Formatter) {
     for dir in dirs {
         f.write(" ");
         dir.display(f);
@@ -147,6 +232,17 @@ macro_rules! impl_display {
         )+
     };
 
+    (S $($typ: ident, )+) => {
+        $(
+            impl<S: Text> fmt::Display for $typ<S>
+            {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str(&to_string(self))
+                }
+            }
+        )+
+    };
+
     ('a $($typ: ident, )+) => {
         $(
             impl<'a> fmt::Display for $typ<'a>
@@ -158,3 +254,84 @@ macro_rules! impl_display {
         )+
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Style;
+    use crate::{parse_query, parse_schema};
+
+    #[test]
+    fn sorts_selections_and_arguments() {
+        let doc = parse_query("{ b(z: 1, a: 2) ...Frag ... on A { d c } a: x { y } }").unwrap();
+        assert_eq!(
+            doc.format(Style::default().sort(true)),
+            "{
+  a: x {
+    y
+  }
+  b(a: 2, z: 1)
+  ...Frag
+  ... on A {
+    c
+    d
+  }
+}
+"
+        );
+
+        let schema = parse_schema("type Query { b(y: Int, x: Int): Int a: Int }").unwrap();
+        assert_eq!(
+            schema.format(Style::default().sort(true)),
+            "type Query {
+  a: Int
+  b(x: Int, y: Int): Int
+}
+"
+        );
+    }
+
+    #[test]
+    fn wraps_long_argument_lists() {
+        let doc = parse_query(
+            "{ user(id: \"1\") { posts(first: 10, after: \"cursor\", orderBy: NEWEST) { id } } }",
+        )
+        .unwrap();
+        assert_eq!(
+            doc.format(Style::default().max_line_width(Some(30))),
+            r#"{
+  user(id: "1") {
+    posts(
+      first: 10
+      after: "cursor"
+      orderBy: NEWEST
+    ) {
+      id
+    }
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn escapes_strings_instead_of_block_strings() {
+        let schema = parse_schema("\"Two\\nlines\" scalar Date").unwrap();
+        assert_eq!(
+            schema.format(Style::default().block_strings(false)),
+            "\"Two\\nlines\"\nscalar Date\n"
+        );
+        assert_eq!(
+            schema.to_string(),
+            "\"\"\"\n  Two\n  lines\n\"\"\"\nscalar Date\n"
+        );
+    }
+
+    #[test]
+    fn writes_supplementary_characters_unescaped() {
+        let schema = parse_schema("\"Emoji \u{1F600}\\u0007\" scalar Date").unwrap();
+        assert_eq!(
+            schema.to_string(),
+            "\"Emoji \u{1F600}\\u0007\"\nscalar Date\n"
+        );
+    }
+}
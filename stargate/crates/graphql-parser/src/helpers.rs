@@ -1,109 +1,386 @@
-use std::marker::PhantomData;
-
-use combine::error::Tracked;
-use combine::stream::easy::{Error, Errors, Info};
-use combine::{satisfy, ConsumedResult, Parser, StreamOnce};
-
 use crate::common::Txt;
-use crate::position::Pos;
+use crate::position::{Pos, Span, Spanned};
 use crate::tokenizer::{Kind, Token, TokenStream};
+use crate::{ParseError, ParseErrorKind, ParserOptions, Unexpected};
 
-#[derive(Debug, Clone)]
-pub struct TokenMatch<'a> {
-    kind: Kind,
-    phantom: PhantomData<&'a u8>,
-}
+pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Recursive descent parser over the tokens of a document.
+///
+/// The grammar is written as functions taking the parser, which look at the next token to
+/// decide what to parse. Every token looked for and not found there is recorded, so a syntax
+/// error on the next token lists all the tokens which would have been valid instead.
 #[derive(Debug, Clone)]
-pub struct NameMatch<'a> {
-    phantom: PhantomData<&'a u8>,
+pub struct Parser<'a> {
+    source: &'a str,
+    tokens: TokenStream<'a>,
+    /// The next token, `None` at the end of input, or the error of reading it.
+    token: Result<Option<Token<'a>>, String>,
+    /// Where the next token starts and ends.
+    start: Pos,
+    token_end: Pos,
+    /// Where the last token consumed ends.
+    end: Pos,
+    expected: Vec<&'static str>,
+    limits: Limits,
+    /// Syntax errors skipped over so far, when parsing with recovery.
+    recovered: Option<Vec<ParseError>>,
 }
 
-#[derive(Debug, Clone)]
-pub struct Value<'a> {
-    kind: Kind,
-    value: &'static str,
-    phantom: PhantomData<&'a u8>,
+/// Tracks the limits of [ParserOptions] while parsing.
+#[derive(Debug, Clone, Default)]
+struct Limits {
+    max_depth: Option<usize>,
+    max_tokens: Option<usize>,
+    depth: usize,
+    tokens: usize,
 }
 
-pub fn kind<'x>(kind: Kind) -> TokenMatch<'x> {
-    TokenMatch {
-        kind,
-        phantom: PhantomData,
+impl<'a> Parser<'a> {
+    /// Starts parsing `source`, failing if it is larger than `ParserOptions::max_document_size`.
+    pub fn new(source: &'a str, options: &ParserOptions) -> ParseResult<Parser<'a>> {
+        if let Some(max_document_size) = options.max_document_size {
+            if source.len() > max_document_size {
+                return Err(ParseError::limit_exceeded(
+                    ParseErrorKind::DocumentTooLarge,
+                    format!(
+                        "document is larger than the maximum of {} bytes",
+                        max_document_size
+                    ),
+                    None,
+                    source,
+                ));
+            }
+        }
+
+        let tokens = TokenStream::new(source);
+        let mut parser = Parser {
+            source,
+            start: tokens.position(),
+            tokens,
+            token: Ok(None),
+            token_end: Pos::default(),
+            end: Pos::default(),
+            expected: vec![],
+            limits: Limits {
+                max_depth: options.max_depth,
+                max_tokens: options.max_tokens,
+                ..Limits::default()
+            },
+            recovered: None,
+        };
+        parser.read_token()?;
+        Ok(parser)
     }
-}
 
-pub fn name<'a>() -> NameMatch<'a> {
-    NameMatch {
-        phantom: PhantomData,
+    fn read_token(&mut self) -> ParseResult<()> {
+        self.start = self.tokens.position();
+        self.token = self.tokens.next_token();
+        self.token_end = self.tokens.end_position();
+        if self.token == Ok(None) {
+            return Ok(());
+        }
+        self.limits.tokens += 1;
+        match self.limits.max_tokens {
+            Some(max_tokens) if self.limits.tokens > max_tokens => Err(self.limit_exceeded(
+                ParseErrorKind::TooManyTokens,
+                format!(
+                    "document has more than the maximum of {} tokens",
+                    max_tokens
+                ),
+            )),
+            _ => Ok(()),
+        }
     }
-}
 
-impl<'a> Parser for TokenMatch<'a> {
-    type Input = TokenStream<'a>;
-    type Output = Token<'a>;
-    type PartialState = ();
+    /// Returns where the next token starts.
+    pub fn position(&self) -> Pos {
+        self.start
+    }
 
-    #[inline]
-    fn parse_lazy(&mut self, input: &mut Self::Input) -> ConsumedResult<Self::Output, Self::Input> {
-        satisfy(|c: Token<'a>| c.kind == self.kind).parse_lazy(input)
+    /// Returns where the last token consumed ends, e.g. the end of the element just parsed,
+    /// while `position` returns where the next token starts.
+    pub fn end_position(&self) -> Pos {
+        self.end
     }
 
-    fn add_error(&mut self, error: &mut Tracked<Errors<Token<'a>, Token<'a>, Pos>>) {
-        error
-            .error
-            .add_error(Error::Expected(Info::Owned(format!("{:?}", self.kind))));
+    /// Returns the next token, if it could be read.
+    pub fn peek(&self) -> Option<Token<'a>> {
+        match self.token {
+            Ok(Some(token)) => Some(token),
+            _ => None,
+        }
     }
-}
 
-pub fn punct<'s>(value: &'static str) -> Value<'s> {
-    Value {
-        kind: Kind::Punctuator,
-        value,
-        phantom: PhantomData,
+    /// Consumes the next token, which must be there.
+    pub fn bump(&mut self) -> ParseResult<Token<'a>> {
+        let token = self.peek().expect("there is a token to consume");
+        self.end = self.token_end;
+        self.expected.clear();
+        self.read_token()?;
+        Ok(token)
     }
-}
 
-pub fn ident<'s>(value: &'static str) -> Value<'s> {
-    Value {
-        kind: Kind::Name,
-        value,
-        phantom: PhantomData,
+    /// Returns whether the next token is `kind`, recording `expected` as expected if not.
+    fn check(&mut self, kind: Kind, value: Option<&'static str>, expected: &'static str) -> bool {
+        match self.peek() {
+            Some(token) if token.kind == kind && value.unwrap_or(token.value) == token.value => {
+                true
+            }
+            _ => {
+                self.expected.push(expected);
+                false
+            }
+        }
     }
-}
 
-impl<'a> Parser for Value<'a> {
-    type Input = TokenStream<'a>;
-    type Output = Token<'a>;
-    type PartialState = ();
+    pub fn is_punct(&mut self, value: &'static str) -> bool {
+        self.check(Kind::Punctuator, Some(value), value)
+    }
 
-    #[inline]
-    fn parse_lazy(&mut self, input: &mut Self::Input) -> ConsumedResult<Self::Output, Self::Input> {
-        satisfy(|c: Token<'a>| c.kind == self.kind && c.value == self.value).parse_lazy(input)
+    pub fn is_ident(&mut self, value: &'static str) -> bool {
+        self.check(Kind::Name, Some(value), value)
     }
 
-    fn add_error(&mut self, error: &mut Tracked<<Self::Input as StreamOnce>::Error>) {
-        error
-            .error
-            .add_error(Error::Expected(Info::Borrowed(self.value)));
+    pub fn is_name(&mut self) -> bool {
+        self.check(Kind::Name, None, "Name")
+    }
+
+    /// Returns whether the document has been parsed to its end.
+    pub fn is_end(&mut self) -> bool {
+        if self.token == Ok(None) {
+            return true;
+        }
+        self.expected.push("end of input");
+        false
     }
-}
 
-impl<'a> Parser for NameMatch<'a> {
-    type Input = TokenStream<'a>;
-    type Output = Txt<'a>;
-    type PartialState = ();
+    /// Consumes the next token if it is the punctuator `value`.
+    pub fn eat_punct(&mut self, value: &'static str) -> ParseResult<bool> {
+        if !self.is_punct(value) {
+            return Ok(false);
+        }
+        self.bump()?;
+        Ok(true)
+    }
+
+    /// Consumes the next token if it is the keyword `value`.
+    pub fn eat_ident(&mut self, value: &'static str) -> ParseResult<bool> {
+        if !self.is_ident(value) {
+            return Ok(false);
+        }
+        self.bump()?;
+        Ok(true)
+    }
+
+    pub fn punct(&mut self, value: &'static str) -> ParseResult<()> {
+        if self.eat_punct(value)? {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
 
-    #[inline]
-    fn parse_lazy(&mut self, input: &mut Self::Input) -> ConsumedResult<Self::Output, Self::Input> {
-        satisfy(|c: Token<'a>| c.kind == Kind::Name)
-            .map(|t: Token<'a>| t.value)
-            .parse_lazy(input)
+    pub fn ident(&mut self, value: &'static str) -> ParseResult<()> {
+        if self.eat_ident(value)? {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
     }
 
-    fn add_error(&mut self, error: &mut Tracked<Errors<Token<'a>, Token<'a>, Pos>>) {
-        error
-            .error
-            .add_error(Error::Expected(Info::Borrowed("Name")));
+    pub fn name(&mut self) -> ParseResult<Txt<'a>> {
+        if self.is_name() {
+            Ok(self.bump()?.value)
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Returns the error of an unexpected next token, listing the tokens expected instead.
+    pub fn unexpected(&self) -> ParseError {
+        match &self.token {
+            Ok(Some(token)) => ParseError::syntax(
+                self.start,
+                Unexpected::Token(token.value),
+                &self.expected,
+                self.source,
+            ),
+            Ok(None) => ParseError::syntax(
+                self.start,
+                Unexpected::EndOfInput,
+                &self.expected,
+                self.source,
+            ),
+            Err(message) => ParseError::syntax(
+                self.start,
+                Unexpected::Message(message.clone()),
+                &[],
+                self.source,
+            ),
+        }
+    }
+
+    /// Returns the error of an unexpected next token where `what`, e.g. a value, was expected.
+    pub fn expected(&mut self, what: &'static str) -> ParseError {
+        self.expected.push(what);
+        self.unexpected()
+    }
+
+    /// Returns the error of the token at `position`, which is well formed but invalid,
+    /// e.g. an integer too large.
+    pub fn invalid(&self, position: Pos, message: String) -> ParseError {
+        ParseError::syntax(position, Unexpected::Message(message), &[], self.source)
+    }
+
+    fn limit_exceeded(&self, kind: ParseErrorKind, message: String) -> ParseError {
+        ParseError::limit_exceeded(kind, message, Some(self.start), self.source)
+    }
+
+    /// Runs `parse` one level deeper, failing once `ParserOptions::max_depth` is exceeded.
+    pub fn nested<O>(
+        &mut self,
+        parse: impl FnOnce(&mut Parser<'a>) -> ParseResult<O>,
+    ) -> ParseResult<O> {
+        if let Some(max_depth) = self.limits.max_depth {
+            if self.limits.depth >= max_depth {
+                return Err(self.limit_exceeded(
+                    ParseErrorKind::RecursionLimitExceeded,
+                    format!(
+                        "document is nested deeper than the maximum depth of {}",
+                        max_depth
+                    ),
+                ));
+            }
+        }
+        self.limits.depth += 1;
+        let result = parse(self);
+        self.limits.depth -= 1;
+        result
+    }
+
+    /// Runs `parse`, returning its node along with the span of the tokens it consumed.
+    pub fn spanned<O>(
+        &mut self,
+        parse: impl FnOnce(&mut Parser<'a>) -> ParseResult<O>,
+    ) -> ParseResult<Spanned<O>> {
+        let start = self.position();
+        let node = parse(self)?;
+        Ok(Spanned::new(Span::new(start, self.end_position()), node))
+    }
+
+    /// Parses an item of a `{ ... }` block, e.g. a selection of a selection set, with `parse`.
+    ///
+    /// When parsing with recovery, a syntax error is recorded and `None` returned instead, with
+    /// the item skipped up to the next token past the error for which `starts_item` is true,
+    /// or up to the `}` closing the block.
+    pub fn recovering<O>(
+        &mut self,
+        parse: impl FnOnce(&mut Parser<'a>) -> ParseResult<O>,
+        starts_item: impl Fn(Token<'a>) -> bool,
+    ) -> ParseResult<Option<O>> {
+        if self.recovered.is_none() {
+            return parse(self).map(Some);
+        }
+        let start = self.clone();
+        let error = match parse(self) {
+            Ok(item) => return Ok(Some(item)),
+            Err(error) if error.kind() == ParseErrorKind::Syntax => error,
+            Err(error) => return Err(error),
+        };
+        let error_position = error.position().expect("syntax errors have a position");
+        let recovered = self.recovered.take();
+        *self = start;
+        self.recovered = recovered;
+        let skipped = skip_past_error(self, |token, position, depth| {
+            depth == 0
+                && ((token.kind == Kind::Punctuator && token.value == "}")
+                    || (position > error_position && starts_item(token)))
+        });
+        if !skipped {
+            return Err(error);
+        }
+        self.recovered
+            .as_mut()
+            .expect("parsing with recovery")
+            .push(error);
+        Ok(None)
+    }
+}
+
+/// Parses `s` definition by definition, skipping to the next definition after a syntax error,
+/// and returns the definitions which could be parsed along with every error.
+///
+/// After an error, tokens are skipped until one for which `starts_definition` is true is found
+/// past the error, outside of any brackets, or at the start of a line in case brackets are
+/// unbalanced. Within blocks parsed item by item with [Parser::recovering], only the item
+/// with the error is skipped, unless the end of the document is reached while skipping it.
+pub fn parse_recovering<'a, T>(
+    s: &'a str,
+    mut definition: impl FnMut(&mut Parser<'a>) -> ParseResult<T>,
+    starts_definition: impl Fn(Token<'a>) -> bool,
+) -> (Vec<T>, Vec<ParseError>) {
+    let mut parser = Parser::new(s, &ParserOptions::default()).expect("tokens aren't limited");
+    parser.recovered = Some(vec![]);
+    let mut definitions = vec![];
+    let mut errors = vec![];
+    while parser.token != Ok(None) {
+        let start = parser.clone();
+        let result = definition(&mut parser);
+        let mut recovered = parser.recovered.replace(vec![]).unwrap_or_default();
+        match result {
+            Ok(definition) => {
+                definitions.push(definition);
+                errors.append(&mut recovered);
+            }
+            Err(error) => {
+                // Skipping resumes past the first error of the definition, as later ones may
+                // only come from skipping over unbalanced brackets.
+                recovered.push(error);
+                let error_position = recovered[0]
+                    .position()
+                    .expect("errors while parsing have a position");
+                errors.append(&mut recovered);
+                parser = start;
+                skip_past_error(&mut parser, |token, position, depth| {
+                    position > error_position
+                        && starts_definition(token)
+                        && (depth == 0 || position.column == 1)
+                });
+            }
+        }
+    }
+    (definitions, errors)
+}
+
+/// Skips tokens until one for which `stop(token, position, depth)` is true, where `depth` is the
+/// number of brackets opened since skipping started. Returns false at the end of the document.
+fn skip_past_error<'a>(
+    parser: &mut Parser<'a>,
+    stop: impl Fn(Token<'a>, Pos, usize) -> bool,
+) -> bool {
+    let mut depth = 0usize;
+    loop {
+        let position = parser.position();
+        match parser.token {
+            Ok(Some(token)) => {
+                if stop(token, position, depth) {
+                    return true;
+                }
+                if token.kind == Kind::Punctuator {
+                    match token.value {
+                        "{" | "(" | "[" => depth += 1,
+                        "}" | ")" | "]" => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                }
+                parser.bump().expect("no limit is set");
+            }
+            Ok(None) => return false,
+            Err(_) => {
+                parser.tokens.skip_char();
+                parser.read_token().expect("no limit is set");
+            }
+        }
     }
 }
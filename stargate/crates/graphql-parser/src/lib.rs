@@ -165,6 +165,16 @@
 //!         sel_set
 //!           sel")));
 //! ```
+//!
+//! Limits
+//! ------
+//!
+//! The parser is recursive, so `parse_query` and `parse_schema` fail with a
+//! `RecursionLimitExceeded` error on documents nested deeper than [DEFAULT_MAX_DEPTH]
+//! levels of selection sets, list and object values, and list types, which earlier
+//! versions parsed. `parse_query_with_options` and `parse_schema_with_options` take
+//! [ParserOptions] to change or remove that limit, and to limit the number of tokens
+//! and the size of documents.
 #![warn(missing_debug_implementations)]
 
 #[cfg(test)]
@@ -175,12 +185,15 @@ extern crate pretty_assertions;
 extern crate derivative;
 
 mod common;
+pub mod cst;
 #[macro_use]
 mod format;
 mod error;
 mod helpers;
+mod options;
 mod visit;
 pub use error::*;
+pub use options::{ParserOptions, DEFAULT_MAX_DEPTH};
 
 mod position;
 pub mod query;
@@ -188,10 +201,10 @@ pub mod schema;
 mod tokenizer;
 
 pub use crate::format::Style;
-pub use crate::position::Pos;
+pub use crate::position::{Pos, Span, Spanned};
 pub use crate::query::minified::DisplayMinified;
-pub use crate::query::parse_query;
-pub use crate::schema::parse_schema;
+pub use crate::query::{parse_query, parse_query_recovering, parse_query_with_options};
+pub use crate::schema::{parse_schema, parse_schema_recovering, parse_schema_with_options};
 
 mod name;
 pub use crate::name::*;
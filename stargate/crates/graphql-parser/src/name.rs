@@ -1,4 +1,4 @@
-use crate::common::Type;
+use crate::query::Type;
 
 pub trait Name<'a> {
     fn as_name(&self) -> &'a str {
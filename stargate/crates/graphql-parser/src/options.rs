@@ -0,0 +1,27 @@
+/// Nesting allowed by `ParserOptions::default()`.
+///
+/// Far deeper than real queries get, yet shallow enough to parse on a thread with a small stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Limits checked while parsing, so untrusted documents can't exhaust the stack or memory.
+///
+/// By default only the depth is limited, to `DEFAULT_MAX_DEPTH`, since the parser is recursive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserOptions {
+    /// Maximum nesting of selection sets, list and object values, and list types.
+    pub max_depth: Option<usize>,
+    /// Maximum number of tokens in the document.
+    pub max_tokens: Option<usize>,
+    /// Maximum size of the document, in bytes.
+    pub max_document_size: Option<usize>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_tokens: None,
+            max_document_size: None,
+        }
+    }
+}
@@ -1,12 +1,50 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Serialize};
 
 /// Original position of element in source code
-#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Default, Hash)]
+#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Default, Hash, Serialize, Deserialize)]
 pub struct Pos {
     /// One-based line number
     pub line: usize,
     /// One-based column number
     pub column: usize,
+    /// Zero-based byte offset
+    pub offset: usize,
+}
+
+/// Range of source code of an element, from the start of its first token
+/// to the end of its last token.
+#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Default, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+/// An AST node along with its span, for nodes which don't hold their span themselves, e.g. values
+/// and types.
+///
+/// Positions and spans are ignored when comparing and hashing any part of the AST, so the same
+/// value or definition in two documents is equal wherever it is: nodes compare, hash and display
+/// as the node alone, and the other nodes ignore their `position` and `span` fields. Nodes which
+/// aren't from a parsed document, e.g. inserted by a fold, have the default span.
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct Spanned<T> {
+    pub span: Span,
+    pub node: T,
+}
+
+impl Span {
+    pub fn new(start: Pos, end: Pos) -> Span {
+        Span { start, end }
+    }
+
+    /// Returns the source code of the element.
+    pub fn slice<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start.offset..self.end.offset]
+    }
 }
 
 impl fmt::Debug for Pos {
@@ -20,3 +58,66 @@ impl fmt::Display for Pos {
         write!(f, "{}:{}", self.line, self.column)
     }
 }
+
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Span({}..{})", self.start, self.end)
+    }
+}
+
+impl<T> Spanned<T> {
+    pub fn new(span: Span, node: T) -> Spanned<T> {
+        Spanned { span, node }
+    }
+
+    /// Converts the node, keeping its span.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned {
+            span: self.span,
+            node: f(self.node),
+        }
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(node: T) -> Spanned<T> {
+        Spanned {
+            span: Span::default(),
+            node,
+        }
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+impl<T> DerefMut for Spanned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.node
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Spanned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Spanned<T>) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: Hash> Hash for Spanned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.hash(state)
+    }
+}
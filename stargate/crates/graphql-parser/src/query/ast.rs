@@ -5,44 +5,38 @@
 //!
 //! [graphql grammar]: http://facebook.github.io/graphql/October2016/#sec-Appendix-Grammar-Summary
 //!
-pub use crate::common::{Directive, Txt, Type, Value};
-use crate::position::Pos;
-
-/// Root of query data
-#[derive(Debug, Clone, PartialEq)]
-pub struct Document<'a> {
-    pub definitions: Vec<Definition<'a>>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum Definition<'a> {
-    SelectionSet(SelectionSet<'a>),
-    Operation(OperationDefinition<'a>),
-    Fragment(FragmentDefinition<'a>),
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct FragmentDefinition<'a> {
-    pub position: Pos,
-    pub description: Option<String>,
-    pub name: Txt<'a>,
-    pub type_condition: Txt<'a>,
-    pub directives: Vec<Directive<'a>>,
-    pub selection_set: SelectionSet<'a>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct OperationDefinition<'a> {
-    pub position: Pos,
-    pub kind: Operation,
-    pub description: Option<String>,
-    pub name: Option<Txt<'a>>,
-    pub variable_definitions: Vec<VariableDefinition<'a>>,
-    pub directives: Vec<Directive<'a>>,
-    pub selection_set: SelectionSet<'a>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
+//! Every node has a `span`, which starts at its `position`. Values, types and
+//! the names of arguments are wrapped in `Spanned` to get one, e.g. the items
+//! of `Value::List` or `VariableDefinition::var_type`.
+//!
+//! These nodes borrow their names from the parsed source. They are aliases of
+//! the nodes of `query::generic`, which `into_static` converts into the nodes
+//! of `query::owned`, owning their names. Both implement serde's `Serialize`
+//! and `Deserialize`.
+//!
+pub use crate::common::Txt;
+use serde::{Deserialize, Serialize};
+
+use crate::query::generic;
+
+pub type Argument<'a> = generic::Argument<Txt<'a>>;
+pub type Directive<'a> = generic::Directive<Txt<'a>>;
+pub type Value<'a> = generic::Value<Txt<'a>>;
+pub type StringValue<'a> = generic::StringValue<Txt<'a>>;
+pub type Type<'a> = generic::Type<Txt<'a>>;
+
+pub type Document<'a> = generic::Document<Txt<'a>>;
+pub type Definition<'a> = generic::Definition<Txt<'a>>;
+pub type FragmentDefinition<'a> = generic::FragmentDefinition<Txt<'a>>;
+pub type OperationDefinition<'a> = generic::OperationDefinition<Txt<'a>>;
+pub type SelectionSet<'a> = generic::SelectionSet<Txt<'a>>;
+pub type VariableDefinition<'a> = generic::VariableDefinition<Txt<'a>>;
+pub type Selection<'a> = generic::Selection<Txt<'a>>;
+pub type Field<'a> = generic::Field<Txt<'a>>;
+pub type FragmentSpread<'a> = generic::FragmentSpread<Txt<'a>>;
+pub type InlineFragment<'a> = generic::InlineFragment<Txt<'a>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Operation {
     Query,
     Mutation,
@@ -59,59 +53,3 @@ impl Operation {
         }
     }
 }
-
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
-pub struct SelectionSet<'a> {
-    #[derivative(Hash = "ignore")]
-    pub span: (Pos, Pos),
-    pub items: Vec<Selection<'a>>,
-}
-
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
-pub struct VariableDefinition<'a> {
-    #[derivative(Hash = "ignore")]
-    pub position: Pos,
-    pub name: Txt<'a>,
-    pub var_type: Type<'a>,
-    pub default_value: Option<Value<'a>>,
-}
-
-#[derive(Debug, Clone, PartialEq, Hash)]
-pub enum Selection<'a> {
-    Field(Field<'a>),
-    FragmentSpread(FragmentSpread<'a>),
-    InlineFragment(InlineFragment<'a>),
-}
-
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
-pub struct Field<'a> {
-    #[derivative(Hash = "ignore")]
-    pub position: Pos,
-    pub alias: Option<Txt<'a>>,
-    pub name: Txt<'a>,
-    pub arguments: Vec<(Txt<'a>, Value<'a>)>,
-    pub directives: Vec<Directive<'a>>,
-    pub selection_set: SelectionSet<'a>,
-}
-
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
-pub struct FragmentSpread<'a> {
-    #[derivative(Hash = "ignore")]
-    pub position: Pos,
-    pub fragment_name: Txt<'a>,
-    pub directives: Vec<Directive<'a>>,
-}
-
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
-pub struct InlineFragment<'a> {
-    #[derivative(Hash = "ignore")]
-    pub position: Pos,
-    pub type_condition: Option<Txt<'a>>,
-    pub directives: Vec<Directive<'a>>,
-    pub selection_set: SelectionSet<'a>,
-}
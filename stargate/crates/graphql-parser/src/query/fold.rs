@@ -0,0 +1,576 @@
+//! Rewriting queries
+//!
+//! A `Fold` takes each node of a document by value and returns the node
+//! replacing it. Its methods default to the functions of this module of the
+//! same name, which fold the children of the node: a `Fold` overrides the
+//! methods of the nodes it rewrites, calling these functions to also fold
+//! their children.
+//!
+//! Returning `None` from `fold_definition`, `fold_variable_def`,
+//! `fold_selection`, `fold_directive` or `fold_argument` removes the node.
+//! Nodes are inserted by pushing them to their parent, e.g. to
+//! `Field::directives` in `fold_field`.
+//!
+//! Values are folded without their spans, which the folded value keeps.
+//!
+//! Folding is for queries only, as operations are what gets rewritten before
+//! being planned or sent to services. Schemas are read, with `schema::Visitor`,
+//! and aren't rewritten.
+use super::*;
+
+pub trait Fold<'a> {
+    fn fold_document(&mut self, doc: Document<'a>) -> Document<'a> {
+        fold_document(self, doc)
+    }
+    fn fold_definition(&mut self, def: Definition<'a>) -> Option<Definition<'a>> {
+        Some(fold_definition(self, def))
+    }
+    fn fold_operation(&mut self, op: OperationDefinition<'a>) -> OperationDefinition<'a> {
+        fold_operation(self, op)
+    }
+    fn fold_fragment_def(&mut self, frag: FragmentDefinition<'a>) -> FragmentDefinition<'a> {
+        fold_fragment_def(self, frag)
+    }
+    fn fold_variable_def(&mut self, var: VariableDefinition<'a>) -> Option<VariableDefinition<'a>> {
+        Some(fold_variable_def(self, var))
+    }
+    fn fold_selection_set(&mut self, sel_set: SelectionSet<'a>) -> SelectionSet<'a> {
+        fold_selection_set(self, sel_set)
+    }
+    fn fold_selection(&mut self, sel: Selection<'a>) -> Option<Selection<'a>> {
+        Some(fold_selection(self, sel))
+    }
+    fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+        fold_field(self, field)
+    }
+    fn fold_fragment_spread(&mut self, spread: FragmentSpread<'a>) -> FragmentSpread<'a> {
+        fold_fragment_spread(self, spread)
+    }
+    fn fold_inline_fragment(&mut self, inline: InlineFragment<'a>) -> InlineFragment<'a> {
+        fold_inline_fragment(self, inline)
+    }
+    fn fold_directive(&mut self, dir: Directive<'a>) -> Option<Directive<'a>> {
+        Some(fold_directive(self, dir))
+    }
+    fn fold_argument(&mut self, argument: Argument<'a>) -> Option<Argument<'a>> {
+        Some(fold_argument(self, argument))
+    }
+    fn fold_value(&mut self, value: Value<'a>) -> Value<'a> {
+        fold_value(self, value)
+    }
+}
+
+pub fn fold_document<'a, F: Fold<'a> + ?Sized>(folder: &mut F, doc: Document<'a>) -> Document<'a> {
+    Document {
+        definitions: doc
+            .definitions
+            .into_iter()
+            .filter_map(|def| folder.fold_definition(def))
+            .collect(),
+    }
+}
+
+pub fn fold_definition<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    def: Definition<'a>,
+) -> Definition<'a> {
+    match def {
+        Definition::SelectionSet(sel_set) => {
+            Definition::SelectionSet(folder.fold_selection_set(sel_set))
+        }
+        Definition::Operation(op) => Definition::Operation(folder.fold_operation(op)),
+        Definition::Fragment(frag) => Definition::Fragment(folder.fold_fragment_def(frag)),
+    }
+}
+
+pub fn fold_operation<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    op: OperationDefinition<'a>,
+) -> OperationDefinition<'a> {
+    OperationDefinition {
+        variable_definitions: op
+            .variable_definitions
+            .into_iter()
+            .filter_map(|var| folder.fold_variable_def(var))
+            .collect(),
+        directives: fold_directives(folder, op.directives),
+        selection_set: folder.fold_selection_set(op.selection_set),
+        ..op
+    }
+}
+
+pub fn fold_fragment_def<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    frag: FragmentDefinition<'a>,
+) -> FragmentDefinition<'a> {
+    FragmentDefinition {
+        directives: fold_directives(folder, frag.directives),
+        selection_set: folder.fold_selection_set(frag.selection_set),
+        ..frag
+    }
+}
+
+pub fn fold_variable_def<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    var: VariableDefinition<'a>,
+) -> VariableDefinition<'a> {
+    VariableDefinition {
+        default_value: var
+            .default_value
+            .map(|value| value.map(|value| folder.fold_value(value))),
+        directives: fold_directives(folder, var.directives),
+        ..var
+    }
+}
+
+pub fn fold_selection_set<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    sel_set: SelectionSet<'a>,
+) -> SelectionSet<'a> {
+    SelectionSet {
+        span: sel_set.span,
+        items: sel_set
+            .items
+            .into_iter()
+            .filter_map(|sel| folder.fold_selection(sel))
+            .collect(),
+    }
+}
+
+pub fn fold_selection<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    sel: Selection<'a>,
+) -> Selection<'a> {
+    match sel {
+        Selection::Field(field) => Selection::Field(folder.fold_field(field)),
+        Selection::FragmentSpread(spread) => {
+            Selection::FragmentSpread(folder.fold_fragment_spread(spread))
+        }
+        Selection::InlineFragment(inline) => {
+            Selection::InlineFragment(folder.fold_inline_fragment(inline))
+        }
+    }
+}
+
+pub fn fold_field<'a, F: Fold<'a> + ?Sized>(folder: &mut F, field: Field<'a>) -> Field<'a> {
+    Field {
+        arguments: fold_arguments(folder, field.arguments),
+        directives: fold_directives(folder, field.directives),
+        selection_set: folder.fold_selection_set(field.selection_set),
+        ..field
+    }
+}
+
+pub fn fold_fragment_spread<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    spread: FragmentSpread<'a>,
+) -> FragmentSpread<'a> {
+    FragmentSpread {
+        directives: fold_directives(folder, spread.directives),
+        ..spread
+    }
+}
+
+pub fn fold_inline_fragment<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    inline: InlineFragment<'a>,
+) -> InlineFragment<'a> {
+    InlineFragment {
+        directives: fold_directives(folder, inline.directives),
+        selection_set: folder.fold_selection_set(inline.selection_set),
+        ..inline
+    }
+}
+
+pub fn fold_directive<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    dir: Directive<'a>,
+) -> Directive<'a> {
+    Directive {
+        arguments: fold_arguments(folder, dir.arguments),
+        ..dir
+    }
+}
+
+pub fn fold_argument<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    (name, value): Argument<'a>,
+) -> Argument<'a> {
+    (name, value.map(|value| folder.fold_value(value)))
+}
+
+pub fn fold_value<'a, F: Fold<'a> + ?Sized>(folder: &mut F, value: Value<'a>) -> Value<'a> {
+    match value {
+        Value::List(items) => Value::List(
+            items
+                .into_iter()
+                .map(|item| item.map(|item| folder.fold_value(item)))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, value.map(|value| folder.fold_value(value))))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+fn fold_directives<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    directives: Vec<Directive<'a>>,
+) -> Vec<Directive<'a>> {
+    directives
+        .into_iter()
+        .filter_map(|dir| folder.fold_directive(dir))
+        .collect()
+}
+
+fn fold_arguments<'a, F: Fold<'a> + ?Sized>(
+    folder: &mut F,
+    arguments: Vec<Argument<'a>>,
+) -> Vec<Argument<'a>> {
+    arguments
+        .into_iter()
+        .filter_map(|argument| folder.fold_argument(argument))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_query, ParseError, Pos};
+
+    #[test]
+    fn rewrites_a_query() -> Result<(), ParseError> {
+        let source = r#"
+            query SomeQuery($id: ID!, $unused: Int = 1) {
+                user(id: $id, debug: true) {
+                    name @deprecated
+                    secret @skip(if: true)
+                    ...UserFields
+                }
+            }
+
+            fragment UserFields on User {
+                friends(first: 10) { name }
+            }
+            "#;
+        let query = parse_query(source)?;
+
+        struct Rewrite;
+
+        impl<'a> Fold<'a> for Rewrite {
+            fn fold_variable_def(
+                &mut self,
+                var: VariableDefinition<'a>,
+            ) -> Option<VariableDefinition<'a>> {
+                if var.name == "unused" {
+                    None
+                } else {
+                    Some(fold_variable_def(self, var))
+                }
+            }
+
+            fn fold_selection_set(&mut self, sel_set: SelectionSet<'a>) -> SelectionSet<'a> {
+                let mut sel_set = fold_selection_set(self, sel_set);
+                if sel_set.items.is_empty() {
+                    return sel_set;
+                }
+                sel_set.items.push(Selection::Field(Field {
+                    position: Pos::default(),
+                    span: sel_set.span,
+                    alias: None,
+                    name: "__typename",
+                    arguments: vec![],
+                    directives: vec![],
+                    selection_set: SelectionSet {
+                        span: sel_set.span,
+                        items: vec![],
+                    },
+                }));
+                sel_set
+            }
+
+            fn fold_selection(&mut self, sel: Selection<'a>) -> Option<Selection<'a>> {
+                match &sel {
+                    Selection::Field(field)
+                        if field.directives.iter().any(|dir| dir.name == "skip") =>
+                    {
+                        None
+                    }
+                    _ => Some(fold_selection(self, sel)),
+                }
+            }
+
+            fn fold_directive(&mut self, _dir: Directive<'a>) -> Option<Directive<'a>> {
+                None
+            }
+
+            fn fold_argument(&mut self, argument: Argument<'a>) -> Option<Argument<'a>> {
+                if argument.0.node == "debug" {
+                    None
+                } else {
+                    Some(fold_argument(self, argument))
+                }
+            }
+
+            fn fold_value(&mut self, value: Value<'a>) -> Value<'a> {
+                match value {
+                    Value::Int(_) => Value::Int(5),
+                    value => fold_value(self, value),
+                }
+            }
+        }
+
+        let query = Rewrite.fold_document(query);
+        assert_eq!(
+            query.to_string(),
+            r#"query SomeQuery($id: ID!) {
+  user(id: $id) {
+    name
+    ...UserFields
+    __typename
+  }
+  __typename
+}
+
+fragment UserFields on User {
+  friends(first: 5) {
+    name
+    __typename
+  }
+  __typename
+}
+"#
+        );
+
+        let user = match &query.definitions[0] {
+            Definition::Operation(op) => match &op.selection_set.items[0] {
+                Selection::Field(field) => field,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let (name, value) = &user.arguments[0];
+        assert_eq!(name.span.slice(source), "id");
+        assert_eq!(value.span.slice(source), "$id");
+
+        Ok(())
+    }
+
+    fn new_field(name: &str) -> Selection<'_> {
+        Selection::Field(Field {
+            position: Pos::default(),
+            span: Default::default(),
+            alias: None,
+            name,
+            arguments: vec![],
+            directives: vec![],
+            selection_set: SelectionSet {
+                span: Default::default(),
+                items: vec![],
+            },
+        })
+    }
+
+    #[test]
+    fn removes_selections() -> Result<(), ParseError> {
+        let query = parse_query(
+            r#"
+            {
+                secret
+                user {
+                    secret
+                    ... on Admin { secret name }
+                    ...UserFields
+                }
+            }
+
+            fragment UserFields on User {
+                friends { secret }
+            }
+            "#,
+        )?;
+
+        struct RemoveSecrets;
+
+        impl<'a> Fold<'a> for RemoveSecrets {
+            fn fold_selection(&mut self, sel: Selection<'a>) -> Option<Selection<'a>> {
+                match &sel {
+                    Selection::Field(field) if field.name == "secret" => None,
+                    Selection::FragmentSpread(spread) if spread.fragment_name == "UserFields" => {
+                        None
+                    }
+                    _ => Some(fold_selection(self, sel)),
+                }
+            }
+        }
+
+        let query = RemoveSecrets.fold_document(query);
+        assert_eq!(
+            query.to_string(),
+            r#"{
+  user {
+    ... on Admin {
+      name
+    }
+  }
+}
+
+fragment UserFields on User {
+  friends
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inserts_selections() -> Result<(), ParseError> {
+        let query = parse_query(
+            r#"
+            {
+                user { name ... on Admin { level } }
+                version
+            }
+            "#,
+        )?;
+
+        struct AddIds;
+
+        impl<'a> Fold<'a> for AddIds {
+            fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+                let mut field = fold_field(self, field);
+                if !field.selection_set.items.is_empty() {
+                    field.selection_set.items.insert(0, new_field("id"));
+                }
+                field
+            }
+
+            fn fold_inline_fragment(&mut self, inline: InlineFragment<'a>) -> InlineFragment<'a> {
+                let mut inline = fold_inline_fragment(self, inline);
+                inline.selection_set.items.push(new_field("permissions"));
+                inline
+            }
+        }
+
+        let query = AddIds.fold_document(query);
+        assert_eq!(
+            query.to_string(),
+            r#"{
+  user {
+    id
+    name
+    ... on Admin {
+      level
+      permissions
+    }
+  }
+  version
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn folds_nested_fragments() -> Result<(), ParseError> {
+        let query = parse_query(
+            r#"
+            {
+                node {
+                    ... on User {
+                        ... on Admin { old ...AdminFields }
+                        ...UserFields
+                    }
+                }
+            }
+
+            fragment UserFields on User {
+                ... on Admin {
+                    ... @include(if: true) { old }
+                }
+            }
+
+            fragment AdminFields on Admin {
+                old
+            }
+            "#,
+        )?;
+
+        struct Rename;
+
+        impl<'a> Fold<'a> for Rename {
+            fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+                let field = fold_field(self, field);
+                if field.name == "old" {
+                    Field {
+                        name: "new",
+                        ..field
+                    }
+                } else {
+                    field
+                }
+            }
+
+            fn fold_fragment_spread(&mut self, spread: FragmentSpread<'a>) -> FragmentSpread<'a> {
+                let spread = fold_fragment_spread(self, spread);
+                if spread.fragment_name == "AdminFields" {
+                    FragmentSpread {
+                        fragment_name: "AdminFieldsV2",
+                        ..spread
+                    }
+                } else {
+                    spread
+                }
+            }
+
+            fn fold_fragment_def(
+                &mut self,
+                frag: FragmentDefinition<'a>,
+            ) -> FragmentDefinition<'a> {
+                let frag = fold_fragment_def(self, frag);
+                if frag.name == "AdminFields" {
+                    FragmentDefinition {
+                        name: "AdminFieldsV2",
+                        ..frag
+                    }
+                } else {
+                    frag
+                }
+            }
+        }
+
+        let query = Rename.fold_document(query);
+        assert_eq!(
+            query.to_string(),
+            r#"{
+  node {
+    ... on User {
+      ... on Admin {
+        new
+        ...AdminFieldsV2
+      }
+      ...UserFields
+    }
+  }
+}
+
+fragment UserFields on User {
+  ... on Admin {
+    ... @include(if: true) {
+      new
+    }
+  }
+}
+
+fragment AdminFieldsV2 on Admin {
+  new
+}
+"#
+        );
+        Ok(())
+    }
+}
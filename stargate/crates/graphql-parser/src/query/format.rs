@@ -1,13 +1,13 @@
 use std::fmt;
 
-use crate::format::{format_directives, Displayable, Formatter, Style};
+use crate::format::{format_directives, Displayable, Formatter, SortKey, Style};
 
-use crate::query::ast::*;
+use crate::query::generic::*;
 use crate::query::refs::{
     FieldRef, FragmentSpreadRef, InlineFragmentRef, SelectionRef, SelectionSetRef,
 };
 
-impl<'a> Document<'a> {
+impl<S: Text> Document<S> {
     /// Format a document according to style
     pub fn format(&self, style: &Style) -> String {
         let mut formatter = Formatter::new(style);
@@ -23,7 +23,7 @@ fn to_string<T: Displayable>(v: &T) -> String {
     formatter.into_string()
 }
 
-impl<'a> Displayable for Document<'a> {
+impl<S: Text> Displayable for Document<S> {
     fn display(&self, f: &mut Formatter) {
         for item in &self.definitions {
             item.display(f);
@@ -31,7 +31,7 @@ impl<'a> Displayable for Document<'a> {
     }
 }
 
-impl<'a> Displayable for Definition<'a> {
+impl<S: Text> Displayable for Definition<S> {
     fn display(&self, f: &mut Formatter) {
         match *self {
             Definition::SelectionSet(ref s) => s.display(f),
@@ -41,31 +41,31 @@ impl<'a> Displayable for Definition<'a> {
     }
 }
 
-impl<'a> Displayable for FragmentDefinition<'a> {
+impl<S: Text> Displayable for FragmentDefinition<S> {
     fn display(&self, f: &mut Formatter) {
         f.margin();
         f.indent();
         f.write("fragment ");
         f.write(self.name.as_ref());
         f.write(" on ");
-        f.write(self.type_condition);
+        f.write(self.type_condition.as_ref());
         format_directives(&self.directives, f);
         f.write(" ");
         f.start_block();
-        for item in &self.selection_set.items {
+        for item in f.sorted(&self.selection_set.items) {
             item.display(f);
         }
         f.end_block();
     }
 }
 
-impl<'a> Displayable for SelectionSet<'a> {
+impl<S: Text> Displayable for SelectionSet<S> {
     #[inline]
     fn display(&self, f: &mut Formatter) {
         f.margin();
         f.indent();
         f.start_block();
-        for item in &self.items {
+        for item in f.sorted(&self.items) {
             item.display(f);
         }
         f.end_block();
@@ -78,14 +78,14 @@ impl<'a> Displayable for SelectionSetRef<'a> {
         f.margin();
         f.indent();
         f.start_block();
-        for item in &self.items {
+        for item in f.sorted(&self.items) {
             item.display(f);
         }
         f.end_block();
     }
 }
 
-impl<'a> Displayable for Selection<'a> {
+impl<S: Text> Displayable for Selection<S> {
     fn display(&self, f: &mut Formatter) {
         match self {
             Selection::Field(fld) => fld.display(f),
@@ -107,22 +107,45 @@ impl<'a> Displayable for SelectionRef<'a> {
     }
 }
 
-fn format_arguments<'a>(arguments: &[(Txt<'a>, Value<'a>)], f: &mut Formatter) {
-    if !arguments.is_empty() {
-        f.write("(");
-        f.write(arguments[0].0.as_ref());
-        f.write(": ");
-        arguments[0].1.display(f);
-        for arg in &arguments[1..] {
-            f.write(", ");
-            f.write(arg.0.as_ref());
-            f.write(": ");
-            arg.1.display(f);
+impl<S: Text> SortKey for Selection<S> {
+    fn sort_key(&self) -> (u8, &str) {
+        match self {
+            Selection::Field(field) => (0, field.alias.as_ref().unwrap_or(&field.name).as_ref()),
+            Selection::FragmentSpread(spread) => (1, spread.fragment_name.as_ref()),
+            Selection::InlineFragment(inline) => {
+                (2, inline.type_condition.as_ref().map_or("", AsRef::as_ref))
+            }
+        }
+    }
+}
+
+impl<'a> SortKey for SelectionRef<'a> {
+    fn sort_key(&self) -> (u8, &str) {
+        match self {
+            SelectionRef::Ref(selection) => selection.sort_key(),
+            SelectionRef::Field(field) => (0, field.alias.unwrap_or(field.name)),
+            SelectionRef::FieldRef(field) => (0, field.alias.unwrap_or(field.name)),
+            SelectionRef::FragmentSpreadRef(spread) => (1, &spread.name),
+            SelectionRef::InlineFragmentRef(inline) => (2, inline.type_condition.unwrap_or("")),
         }
-        f.write(")");
     }
 }
 
+impl<S: Text> SortKey for Argument<S> {
+    fn sort_key(&self) -> (u8, &str) {
+        (0, self.0.node.as_ref())
+    }
+}
+
+fn format_arguments<S: Text>(arguments: &[Argument<S>], f: &mut Formatter) {
+    let arguments = f.sorted(arguments);
+    f.write_arguments(&arguments, |(name, value), f| {
+        f.write(name.as_ref());
+        f.write(": ");
+        value.display(f);
+    });
+}
+
 macro_rules! field_impl {
     ($self:ident, $f:ident) => {
         $f.indent();
@@ -136,7 +159,7 @@ macro_rules! field_impl {
         if !$self.selection_set.items.is_empty() {
             $f.write(" ");
             $f.start_block();
-            for item in &$self.selection_set.items {
+            for item in $f.sorted(&$self.selection_set.items) {
                 item.display($f);
             }
             $f.end_block();
@@ -146,7 +169,7 @@ macro_rules! field_impl {
     };
 }
 
-impl<'a> Displayable for Field<'a> {
+impl<S: Text> Displayable for Field<S> {
     fn display(&self, f: &mut Formatter) {
         field_impl!(self, f);
     }
@@ -158,7 +181,7 @@ impl<'a> Displayable for FieldRef<'a> {
     }
 }
 
-impl<'a> Displayable for OperationDefinition<'a> {
+impl<S: Text> Displayable for OperationDefinition<S> {
     fn display(&self, f: &mut Formatter) {
         f.margin();
         f.indent();
@@ -179,14 +202,14 @@ impl<'a> Displayable for OperationDefinition<'a> {
         format_directives(&self.directives, f);
         f.write(" ");
         f.start_block();
-        for item in &self.selection_set.items {
+        for item in f.sorted(&self.selection_set.items) {
             item.display(f);
         }
         f.end_block();
     }
 }
 
-impl<'a> Displayable for VariableDefinition<'a> {
+impl<S: Text> Displayable for VariableDefinition<S> {
     fn display(&self, f: &mut Formatter) {
         f.write("$");
         f.write(self.name.as_ref());
@@ -196,10 +219,11 @@ impl<'a> Displayable for VariableDefinition<'a> {
             f.write(" = ");
             default.display(f);
         }
+        format_directives(&self.directives, f);
     }
 }
 
-impl<'a> Displayable for Type<'a> {
+impl<S: Text> Displayable for Type<S> {
     fn display(&self, f: &mut Formatter) {
         match self {
             Type::NamedType(name) => f.write(name.as_ref()),
@@ -216,7 +240,7 @@ impl<'a> Displayable for Type<'a> {
     }
 }
 
-impl<'a> Displayable for Value<'a> {
+impl<S: Text> Displayable for Value<S> {
     fn display(&self, f: &mut Formatter) {
         match self {
             Value::Variable(name) => {
@@ -225,7 +249,7 @@ impl<'a> Displayable for Value<'a> {
             }
             Value::Int(num) => f.write(&format!("{}", num)),
             Value::Float(val) => f.write(&format!("{}", val)),
-            Value::String(val) => f.write_quoted(val),
+            Value::String(val) => f.write_quoted(&val.to_str()),
             Value::Boolean(true) => f.write("true"),
             Value::Boolean(false) => f.write("false"),
             Value::Null => f.write("null"),
@@ -266,19 +290,19 @@ macro_rules! inline_fragment_impl {
         $f.write("...");
         if let Some(ref cond) = $self.type_condition {
             $f.write(" on ");
-            $f.write(cond);
+            $f.write(cond.as_ref());
         }
         format_directives(&$self.directives, $f);
         $f.write(" ");
         $f.start_block();
-        for item in &$self.selection_set.items {
+        for item in $f.sorted(&$self.selection_set.items) {
             item.display($f);
         }
         $f.end_block();
     };
 }
 
-impl<'a> Displayable for InlineFragment<'a> {
+impl<S: Text> Displayable for InlineFragment<S> {
     fn display(&self, f: &mut Formatter) {
         inline_fragment_impl!(self, f);
     }
@@ -290,7 +314,7 @@ impl<'a> Displayable for InlineFragmentRef<'a> {
     }
 }
 
-impl<'a> Displayable for FragmentSpread<'a> {
+impl<S: Text> Displayable for FragmentSpread<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("...");
@@ -309,7 +333,7 @@ impl Displayable for FragmentSpreadRef {
     }
 }
 
-impl<'a> Displayable for Directive<'a> {
+impl<S: Text> Displayable for Directive<S> {
     fn display(&self, f: &mut Formatter) {
         f.write("@");
         f.write(self.name.as_ref());
@@ -318,7 +342,7 @@ impl<'a> Displayable for Directive<'a> {
 }
 
 impl_display!(
-    'a
+    S
     Document,
     Definition,
     OperationDefinition,
@@ -331,6 +355,10 @@ impl_display!(
     InlineFragment,
     FragmentSpread,
     Directive,
+);
+
+impl_display!(
+    'a
     SelectionRef,
     SelectionSetRef,
     FieldRef,
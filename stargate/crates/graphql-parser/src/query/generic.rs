@@ -0,0 +1,298 @@
+//! Query language AST generic over the text of its names
+//!
+//! `S` is `&str` for the nodes of `query::ast`, which borrow their names
+//! from the parsed source, and `String` for the nodes of `query::owned`.
+//!
+pub use crate::common::{Argument, Directive, StringValue, Text, Type, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{arguments_into_static, directives_into_static};
+use crate::position::{Pos, Span, Spanned};
+use crate::query::ast::Operation;
+
+/// Root of query data
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct Document<S> {
+    pub definitions: Vec<Definition<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub enum Definition<S> {
+    SelectionSet(SelectionSet<S>),
+    Operation(OperationDefinition<S>),
+    Fragment(FragmentDefinition<S>),
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct FragmentDefinition<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub type_condition: S,
+    pub directives: Vec<Directive<S>>,
+    pub selection_set: SelectionSet<S>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct OperationDefinition<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub kind: Operation,
+    pub description: Option<String>,
+    pub name: Option<S>,
+    pub variable_definitions: Vec<VariableDefinition<S>>,
+    pub directives: Vec<Directive<S>>,
+    pub selection_set: SelectionSet<S>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct SelectionSet<S> {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
+    pub items: Vec<Selection<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct VariableDefinition<S> {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub var_type: Spanned<Type<S>>,
+    pub default_value: Option<Spanned<Value<S>>>,
+    pub directives: Vec<Directive<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub enum Selection<S> {
+    Field(Field<S>),
+    FragmentSpread(FragmentSpread<S>),
+    InlineFragment(InlineFragment<S>),
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct Field<S> {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
+    pub alias: Option<S>,
+    pub name: S,
+    pub arguments: Vec<Argument<S>>,
+    pub directives: Vec<Directive<S>>,
+    pub selection_set: SelectionSet<S>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct FragmentSpread<S> {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
+    pub fragment_name: S,
+    pub directives: Vec<Directive<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"), Hash(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct InlineFragment<S> {
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
+    pub type_condition: Option<S>,
+    pub directives: Vec<Directive<S>>,
+    pub selection_set: SelectionSet<S>,
+}
+
+impl<S: Text> Document<S> {
+    /// Converts the document into one owning its names, which no longer
+    /// borrows from the source it was parsed from.
+    pub fn into_static(self) -> Document<String> {
+        Document {
+            definitions: self
+                .definitions
+                .into_iter()
+                .map(Definition::into_static)
+                .collect(),
+        }
+    }
+}
+
+impl<S: Text> Definition<S> {
+    /// Converts the definition into one owning its names.
+    pub fn into_static(self) -> Definition<String> {
+        match self {
+            Definition::SelectionSet(s) => Definition::SelectionSet(s.into_static()),
+            Definition::Operation(o) => Definition::Operation(o.into_static()),
+            Definition::Fragment(f) => Definition::Fragment(f.into_static()),
+        }
+    }
+}
+
+impl<S: Text> FragmentDefinition<S> {
+    /// Converts the fragment definition into one owning its names.
+    pub fn into_static(self) -> FragmentDefinition<String> {
+        FragmentDefinition {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            type_condition: self.type_condition.into(),
+            directives: directives_into_static(self.directives),
+            selection_set: self.selection_set.into_static(),
+        }
+    }
+}
+
+impl<S: Text> OperationDefinition<S> {
+    /// Converts the operation into one owning its names.
+    pub fn into_static(self) -> OperationDefinition<String> {
+        OperationDefinition {
+            position: self.position,
+            span: self.span,
+            kind: self.kind,
+            description: self.description,
+            name: self.name.map(Into::into),
+            variable_definitions: self
+                .variable_definitions
+                .into_iter()
+                .map(VariableDefinition::into_static)
+                .collect(),
+            directives: directives_into_static(self.directives),
+            selection_set: self.selection_set.into_static(),
+        }
+    }
+}
+
+impl<S: Text> SelectionSet<S> {
+    /// Converts the selection set into one owning its names.
+    pub fn into_static(self) -> SelectionSet<String> {
+        SelectionSet {
+            span: self.span,
+            items: self.items.into_iter().map(Selection::into_static).collect(),
+        }
+    }
+}
+
+impl<S: Text> VariableDefinition<S> {
+    /// Converts the variable definition into one owning its names.
+    pub fn into_static(self) -> VariableDefinition<String> {
+        VariableDefinition {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            var_type: self.var_type.map(Type::into_static),
+            default_value: self
+                .default_value
+                .map(|value| value.map(Value::into_static)),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> Selection<S> {
+    /// Converts the selection into one owning its names.
+    pub fn into_static(self) -> Selection<String> {
+        match self {
+            Selection::Field(f) => Selection::Field(f.into_static()),
+            Selection::FragmentSpread(f) => Selection::FragmentSpread(f.into_static()),
+            Selection::InlineFragment(f) => Selection::InlineFragment(f.into_static()),
+        }
+    }
+}
+
+impl<S: Text> Field<S> {
+    /// Converts the field into one owning its names.
+    pub fn into_static(self) -> Field<String> {
+        Field {
+            position: self.position,
+            span: self.span,
+            alias: self.alias.map(Into::into),
+            name: self.name.into(),
+            arguments: arguments_into_static(self.arguments),
+            directives: directives_into_static(self.directives),
+            selection_set: self.selection_set.into_static(),
+        }
+    }
+}
+
+impl<S: Text> FragmentSpread<S> {
+    /// Converts the fragment spread into one owning its names.
+    pub fn into_static(self) -> FragmentSpread<String> {
+        FragmentSpread {
+            position: self.position,
+            span: self.span,
+            fragment_name: self.fragment_name.into(),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> InlineFragment<S> {
+    /// Converts the inline fragment into one owning its names.
+    pub fn into_static(self) -> InlineFragment<String> {
+        InlineFragment {
+            position: self.position,
+            span: self.span,
+            type_condition: self.type_condition.map(Into::into),
+            directives: directives_into_static(self.directives),
+            selection_set: self.selection_set.into_static(),
+        }
+    }
+}
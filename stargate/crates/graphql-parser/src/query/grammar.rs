@@ -1,209 +1,273 @@
-use combine::parser::choice::{choice, optional};
-use combine::parser::item::{eof, position};
-use combine::parser::repeat::many1;
-use combine::{parser, ParseResult, Parser};
-
 use crate::common::{arguments, default_value, directives, parse_type};
-use crate::helpers::{ident, name, punct};
+use crate::helpers::{parse_recovering, ParseResult, Parser};
+use crate::position::Span;
 use crate::query::ast::*;
-use crate::tokenizer::TokenStream;
-use crate::ParseError;
-
-pub fn field<'a>(input: &mut TokenStream<'a>) -> ParseResult<Field<'a>, TokenStream<'a>> {
-    (
-        position(),
-        name::<'a>(),
-        optional(punct(":").with(name::<'a>())),
-        parser(arguments),
-        parser(directives),
-        optional(parser(selection_set)),
-    )
-        .map(
-            |(position, name_or_alias, opt_name, arguments, directives, sel)| {
-                let (name, alias) = match opt_name {
-                    Some(name) => (name, Some(name_or_alias)),
-                    None => (name_or_alias, None),
-                };
-                Field {
-                    position,
-                    name,
-                    alias,
-                    arguments,
-                    directives,
-                    selection_set: sel.unwrap_or_else(|| SelectionSet {
-                        span: (position, position),
-                        items: Vec::new(),
-                    }),
-                }
-            },
-        )
-        .parse_stream(input)
+use crate::tokenizer::{Kind as T, Token};
+use crate::{ParseError, ParserOptions};
+
+pub fn field<'a>(input: &mut Parser<'a>) -> ParseResult<Field<'a>> {
+    let position = input.position();
+    let name_or_alias = input.name()?;
+    let (name, alias) = if input.eat_punct(":")? {
+        (input.name()?, Some(name_or_alias))
+    } else {
+        (name_or_alias, None)
+    };
+    let arguments = arguments(input)?;
+    let directives = directives(input)?;
+    let selection_set = if input.is_punct("{") {
+        selection_set(input)?
+    } else {
+        SelectionSet {
+            span: Span::new(position, position),
+            items: Vec::new(),
+        }
+    };
+    Ok(Field {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        alias,
+        arguments,
+        directives,
+        selection_set,
+    })
 }
 
-pub fn selection<'a>(input: &mut TokenStream<'a>) -> ParseResult<Selection<'a>, TokenStream<'a>> {
-    parser(field)
-        .map(Selection::Field)
-        .or(punct("...").with(
-            (
-                position(),
-                optional(ident("on").with(name::<'a>())),
-                parser(directives),
-                parser(selection_set),
-            )
-                .map(
-                    |(position, type_condition, directives, selection_set)| InlineFragment {
-                        position,
-                        type_condition,
-                        selection_set,
-                        directives,
-                    },
-                )
-                .map(Selection::InlineFragment)
-                .or((position(), name::<'a>(), parser(directives))
-                    .map(|(position, fragment_name, directives)| FragmentSpread {
-                        position,
-                        fragment_name,
-                        directives,
-                    })
-                    .map(Selection::FragmentSpread)),
-        ))
-        .parse_stream(input)
+pub fn selection<'a>(input: &mut Parser<'a>) -> ParseResult<Selection<'a>> {
+    if input.is_name() {
+        return field(input).map(Selection::Field);
+    }
+    // spans of fragments include the `...`
+    let start = input.position();
+    input.punct("...")?;
+    let position = input.position();
+    if !input.is_ident("on") && input.is_name() {
+        let fragment_name = input.bump()?.value;
+        let directives = directives(input)?;
+        return Ok(Selection::FragmentSpread(FragmentSpread {
+            position,
+            span: Span::new(start, input.end_position()),
+            fragment_name,
+            directives,
+        }));
+    }
+    let type_condition = if input.eat_ident("on")? {
+        Some(input.name()?)
+    } else {
+        None
+    };
+    let directives = directives(input)?;
+    let selection_set = selection_set(input)?;
+    Ok(Selection::InlineFragment(InlineFragment {
+        position,
+        span: Span::new(start, input.end_position()),
+        type_condition,
+        selection_set,
+        directives,
+    }))
 }
 
-pub fn selection_set<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<SelectionSet<'a>, TokenStream<'a>> {
-    (
-        position().skip(punct("{")),
-        many1(parser(selection)),
-        position().skip(punct("}")),
-    )
-        .map(|(start, items, end)| SelectionSet {
-            span: (start, end),
+pub fn selection_set<'a>(input: &mut Parser<'a>) -> ParseResult<SelectionSet<'a>> {
+    input.nested(|input| {
+        let start = input.position();
+        input.punct("{")?;
+        let mut items = vec![];
+        loop {
+            if let Some(selection) = input.recovering(selection, starts_selection)? {
+                items.push(selection);
+            }
+            if input.eat_punct("}")? {
+                break;
+            }
+        }
+        Ok(SelectionSet {
+            span: Span::new(start, input.end_position()),
             items,
         })
-        .parse_stream(input)
+    })
 }
 
-pub fn operation_definition<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<OperationDefinition<'a>, TokenStream<'a>> {
-    (
-        position(),
-        choice((
-            ident("query").map(|_| Operation::Query),
-            ident("mutation").map(|_| Operation::Mutation),
-            ident("subscription").map(|_| Operation::Subscription),
-        )),
-        optional(name::<'a>()),
-        optional(
-            punct("(")
-                .with(many1(
-                    (
-                        position(),
-                        punct("$").with(name::<'a>()).skip(punct(":")),
-                        parser(parse_type),
-                        optional(punct("=").with(parser(default_value))),
-                    )
-                        .map(|(position, name, var_type, default_value)| {
-                            VariableDefinition {
-                                position,
-                                name,
-                                var_type,
-                                default_value,
-                            }
-                        }),
-                ))
-                .skip(punct(")")),
-        )
-        .map(|vars| vars.unwrap_or_else(Vec::new)),
-        parser(directives),
-        parser(selection_set),
+fn starts_selection(token: Token) -> bool {
+    matches!(
+        (token.kind, token.value),
+        (T::Name, _) | (T::Punctuator, "...")
     )
-        .flat_map(
-            |(position, kind, name, variable_definitions, directives, selection_set)| {
-                Ok(OperationDefinition {
-                    position,
-                    description: None,
-                    kind,
-                    name,
-                    variable_definitions,
-                    directives,
-                    selection_set,
-                })
-            },
-        )
-        .parse_stream(input)
 }
 
-pub fn fragment_definition<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<FragmentDefinition<'a>, TokenStream<'a>> {
-    (
-        position().skip(ident("fragment")),
-        name::<'a>(),
-        ident("on").with(name::<'a>()),
-        parser(directives),
-        parser(selection_set),
-    )
-        .map(
-            |(position, name, type_condition, directives, selection_set)| FragmentDefinition {
-                position,
-                description: None,
-                name,
-                type_condition,
-                directives,
-                selection_set,
-            },
-        )
-        .parse_stream(input)
+fn variable_definition<'a>(input: &mut Parser<'a>) -> ParseResult<VariableDefinition<'a>> {
+    let position = input.position();
+    input.punct("$")?;
+    let name = input.name()?;
+    input.punct(":")?;
+    let var_type = input.spanned(parse_type)?;
+    let default_value = if input.eat_punct("=")? {
+        Some(default_value(input)?)
+    } else {
+        None
+    };
+    let directives = directives(input)?;
+    Ok(VariableDefinition {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        var_type,
+        default_value,
+        directives,
+    })
+}
+
+pub fn operation_definition<'a>(input: &mut Parser<'a>) -> ParseResult<OperationDefinition<'a>> {
+    let position = input.position();
+    let kind = if input.eat_ident("query")? {
+        Operation::Query
+    } else if input.eat_ident("mutation")? {
+        Operation::Mutation
+    } else if input.eat_ident("subscription")? {
+        Operation::Subscription
+    } else {
+        return Err(input.unexpected());
+    };
+    let name = if input.is_name() {
+        Some(input.bump()?.value)
+    } else {
+        None
+    };
+    let mut variable_definitions = vec![];
+    if input.eat_punct("(")? {
+        loop {
+            variable_definitions.push(variable_definition(input)?);
+            if input.eat_punct(")")? {
+                break;
+            }
+        }
+    }
+    let directives = directives(input)?;
+    let selection_set = selection_set(input)?;
+    Ok(OperationDefinition {
+        position,
+        span: Span::new(position, selection_set.span.end),
+        description: None,
+        kind,
+        name,
+        variable_definitions,
+        directives,
+        selection_set,
+    })
 }
 
-pub fn definition<'a>(input: &mut TokenStream<'a>) -> ParseResult<Definition<'a>, TokenStream<'a>> {
-    parser(selection_set)
-        .map(Definition::SelectionSet)
-        .or(parser(operation_definition).map(Definition::Operation))
-        .or(parser(fragment_definition).map(Definition::Fragment))
-        .parse_stream(input)
+pub fn fragment_definition<'a>(input: &mut Parser<'a>) -> ParseResult<FragmentDefinition<'a>> {
+    let position = input.position();
+    input.ident("fragment")?;
+    let name = input.name()?;
+    input.ident("on")?;
+    let type_condition = input.name()?;
+    let directives = directives(input)?;
+    let selection_set = selection_set(input)?;
+    Ok(FragmentDefinition {
+        position,
+        span: Span::new(position, selection_set.span.end),
+        description: None,
+        name,
+        type_condition,
+        directives,
+        selection_set,
+    })
 }
 
-/// Parses a piece of query language and returns an AST
+pub fn definition<'a>(input: &mut Parser<'a>) -> ParseResult<Definition<'a>> {
+    if input.is_punct("{") {
+        selection_set(input).map(Definition::SelectionSet)
+    } else if input.is_ident("query")
+        || input.is_ident("mutation")
+        || input.is_ident("subscription")
+    {
+        operation_definition(input).map(Definition::Operation)
+    } else if input.is_ident("fragment") {
+        fragment_definition(input).map(Definition::Fragment)
+    } else {
+        Err(input.unexpected())
+    }
+}
+
+/// Parses a piece of query language and returns an AST, with the default `ParserOptions`
 pub fn parse_query(s: &str) -> Result<Document, ParseError> {
-    let mut tokens = TokenStream::new(s);
-    let (doc, _) = many1(parser(definition))
-        .map(|d| Document { definitions: d })
-        .skip(eof())
-        .parse_stream(&mut tokens)
-        .map_err(|e| e.into_inner().error)?;
-
-    Ok(doc)
+    parse_query_with_options(s, &ParserOptions::default())
+}
+
+/// Parses a piece of query language, failing as soon as one of the limits of `options` is exceeded.
+pub fn parse_query_with_options<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Document<'a>, ParseError> {
+    let mut input = Parser::new(s, options)?;
+    let mut definitions = vec![];
+    loop {
+        definitions.push(definition(&mut input)?);
+        if input.is_end() {
+            return Ok(Document { definitions });
+        }
+    }
+}
+
+/// Parses a piece of query language, carrying on after syntax errors, and returns the
+/// definitions which could be parsed along with all the errors found.
+///
+/// After an error, parsing resumes at the next `query`, `mutation`, `subscription`,
+/// `fragment` or `{` outside of the definition that failed. Within a selection set, parsing
+/// resumes at the next selection or the end of the selection set instead, so the valid
+/// selections are kept.
+pub fn parse_query_recovering(s: &str) -> (Document, Vec<ParseError>) {
+    let (definitions, errors) = parse_recovering(s, definition, |token: Token| {
+        matches!(
+            (token.kind, token.value),
+            (T::Name, "query")
+                | (T::Name, "mutation")
+                | (T::Name, "subscription")
+                | (T::Name, "fragment")
+                | (T::Punctuator, "{")
+        )
+    });
+    (Document { definitions }, errors)
 }
 
 #[cfg(test)]
 mod test {
     use super::{Definition, Document, Field, Selection, SelectionSet, Value};
-    use crate::position::Pos;
+    use crate::position::{Pos, Span, Spanned};
     use crate::query::grammar::*;
+    use crate::ParseErrorKind;
 
     fn ast(s: &str) -> Document {
         parse_query(&s).unwrap()
     }
 
+    /// Span of the bytes `start..end` of the first line.
+    fn span(start: usize, end: usize) -> Span {
+        let pos = |offset| Pos {
+            line: 1,
+            column: offset + 1,
+            offset,
+        };
+        Span::new(pos(start), pos(end))
+    }
+
     #[test]
     fn one_field() {
         assert_eq!(
             ast("{ a }"),
             Document {
                 definitions: vec![Definition::SelectionSet(SelectionSet {
-                    span: (Pos { line: 1, column: 1 }, Pos { line: 1, column: 5 }),
+                    span: span(0, 5),
                     items: vec![Selection::Field(Field {
-                        position: Pos { line: 1, column: 3 },
+                        position: span(2, 2).start,
+                        span: span(2, 3),
                         alias: None,
                         name: "a",
                         arguments: Vec::new(),
                         directives: Vec::new(),
                         selection_set: SelectionSet {
-                            span: (Pos { line: 1, column: 3 }, Pos { line: 1, column: 3 }),
+                            span: span(2, 2),
                             items: Vec::new()
                         },
                     }),],
@@ -218,25 +282,29 @@ mod test {
             ast("{ a(t: true, f: false, n: null) }"),
             Document {
                 definitions: vec![Definition::SelectionSet(SelectionSet {
-                    span: (
-                        Pos { line: 1, column: 1 },
-                        Pos {
-                            line: 1,
-                            column: 33
-                        }
-                    ),
+                    span: span(0, 33),
                     items: vec![Selection::Field(Field {
-                        position: Pos { line: 1, column: 3 },
+                        position: span(2, 2).start,
+                        span: span(2, 31),
                         alias: None,
                         name: "a",
                         arguments: vec![
-                            ("t", Value::Boolean(true)),
-                            ("f", Value::Boolean(false)),
-                            ("n", Value::Null),
+                            (
+                                Spanned::new(span(4, 5), "t"),
+                                Spanned::new(span(7, 11), Value::Boolean(true))
+                            ),
+                            (
+                                Spanned::new(span(13, 14), "f"),
+                                Spanned::new(span(16, 21), Value::Boolean(false))
+                            ),
+                            (
+                                Spanned::new(span(23, 24), "n"),
+                                Spanned::new(span(26, 30), Value::Null)
+                            ),
                         ],
                         directives: Vec::new(),
                         selection_set: SelectionSet {
-                            span: (Pos { line: 1, column: 3 }, Pos { line: 1, column: 3 }),
+                            span: span(2, 2),
                             items: Vec::new()
                         },
                     }),],
@@ -245,6 +313,73 @@ mod test {
         );
     }
 
+    #[test]
+    fn spans() {
+        let source = "query Q($a: [Int!] = [1], $b: B) @d(x: 1) {\n  a: b(c: {d: $a}) @e { ...F }\n  ... on T { f }\n}\nfragment F on T { g }";
+        let doc = ast(source);
+        let text = |span: Span| span.slice(source);
+        let (operation, fragment) = match &doc.definitions[..] {
+            [Definition::Operation(operation), Definition::Fragment(fragment)] => {
+                (operation, fragment)
+            }
+            _ => panic!("unexpected definitions"),
+        };
+        assert!(text(operation.span).starts_with("query Q("));
+        assert!(text(operation.span).ends_with("... on T { f }\n}"));
+        assert_eq!(text(fragment.span), "fragment F on T { g }");
+
+        let variable = &operation.variable_definitions[0];
+        assert_eq!(text(variable.span), "$a: [Int!] = [1]");
+        assert_eq!(text(variable.var_type.span), "[Int!]");
+        let default_value = variable.default_value.as_ref().unwrap();
+        assert_eq!(text(default_value.span), "[1]");
+        match &default_value.node {
+            Value::List(items) => assert_eq!(text(items[0].span), "1"),
+            _ => panic!("unexpected default value"),
+        }
+        assert_eq!(operation.variable_definitions[1].default_value, None);
+
+        let directive = &operation.directives[0];
+        assert_eq!(text(directive.span), "@d(x: 1)");
+        let (name, value) = &directive.arguments[0];
+        assert_eq!((text(name.span), text(value.span)), ("x", "1"));
+
+        let (field, inline) = match &operation.selection_set.items[..] {
+            [Selection::Field(field), Selection::InlineFragment(inline)] => (field, inline),
+            _ => panic!("unexpected selections"),
+        };
+        assert_eq!(text(field.span), "a: b(c: {d: $a}) @e { ...F }");
+        let (name, value) = &field.arguments[0];
+        assert_eq!((text(name.span), text(value.span)), ("c", "{d: $a}"));
+        match &value.node {
+            Value::Object(fields) => assert_eq!(text(fields["d"].span), "$a"),
+            _ => panic!("unexpected argument value"),
+        }
+        assert_eq!(text(field.selection_set.span), "{ ...F }");
+        match &field.selection_set.items[0] {
+            Selection::FragmentSpread(spread) => assert_eq!(text(spread.span), "...F"),
+            _ => panic!("unexpected selection"),
+        }
+        assert_eq!(text(inline.span), "... on T { f }");
+        assert_eq!(
+            inline.span.start,
+            Pos {
+                line: 3,
+                column: 3,
+                offset: 77
+            }
+        );
+    }
+
+    #[test]
+    fn equality_ignores_positions() {
+        let source =
+            "query Q($a: [Int] = [1]) @d(x: 1) { a: b(c: {d: $a}) { ...F } ... on T { f } }";
+        let moved = "\n\nquery Q(\n  $a: [Int] = [1]\n) @d(x: 1) {\n  a: b(c: {d: $a}) {\n    ...F\n  }\n  ... on T {\n    f\n  }\n}";
+        assert_eq!(ast(source), ast(moved));
+        assert_ne!(ast(source), ast(&source.replace("[1]", "[2]")));
+    }
+
     #[test]
     fn one_field_roundtrip() {
         assert_eq!(ast("{ a }").to_string(), "{\n  a\n}\n");
@@ -255,4 +390,149 @@ mod test {
     fn large_integer() {
         ast("{ a(x: 10000000000000000000000000000 }");
     }
+
+    #[test]
+    fn limits() {
+        let options = |max_depth, max_tokens, max_document_size| ParserOptions {
+            max_depth,
+            max_tokens,
+            max_document_size,
+        };
+        let kind = |s: &str, options: &ParserOptions| {
+            parse_query_with_options(s, options)
+                .map(|_| ())
+                .map_err(|e| e.kind())
+        };
+
+        let query = "query($a: [[Int]]) { a { b(c: [[1]]) { d } } }";
+        assert!(kind(query, &options(Some(5), Some(29), Some(query.len()))).is_ok());
+        assert_eq!(
+            kind(query, &options(None, None, Some(query.len() - 1))),
+            Err(ParseErrorKind::DocumentTooLarge)
+        );
+        assert_eq!(
+            kind(query, &options(None, Some(28), None)),
+            Err(ParseErrorKind::TooManyTokens)
+        );
+        assert_eq!(
+            kind("{ a { b } }", &options(Some(1), None, None)),
+            Err(ParseErrorKind::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            kind("{ a(b: [[1]]) }", &options(Some(3), None, None)),
+            Err(ParseErrorKind::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            kind("query($a: [[Int]]) { a }", &options(Some(2), None, None)),
+            Err(ParseErrorKind::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            kind("{ a ", &options(Some(3), Some(100), Some(100))),
+            Err(ParseErrorKind::Syntax)
+        );
+    }
+
+    #[test]
+    fn deep_nesting_does_not_overflow() {
+        let query = format!("{}{}", "{ a ".repeat(100_000), "}".repeat(100_000));
+        // Kept low as each level of nesting takes several stack frames, and tests run on
+        // threads with a small stack.
+        let options = ParserOptions {
+            max_depth: Some(32),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_query_with_options(&query, &options)
+                .unwrap_err()
+                .kind(),
+            ParseErrorKind::RecursionLimitExceeded
+        );
+
+        let value = format!("{{ a(b: {}{}) }}", "[".repeat(100_000), "]".repeat(100_000));
+        for query in &[query, value] {
+            assert_eq!(
+                parse_query(query).unwrap_err().kind(),
+                ParseErrorKind::RecursionLimitExceeded
+            );
+        }
+    }
+
+    #[test]
+    fn structured_errors() {
+        let err = parse_query("query {\n  a(b: 1 {\n}").unwrap_err();
+        assert_eq!(err.kind(), ParseErrorKind::Syntax);
+        assert_eq!(err.position(), Some(Pos { line: 2, column: 10, offset: 17 }));
+        assert_eq!(err.unexpected(), Some("{"));
+        assert_eq!(err.expected(), &[")", "Name"]);
+        assert_eq!(err.snippet(), Some("  a(b: 1 {"));
+        assert_eq!(
+            err.to_string(),
+            "parse error: Parse error at 2:10\nUnexpected `{`\nExpected `)` or `Name`\n"
+        );
+
+        let err = parse_query("{ a").unwrap_err();
+        assert_eq!(err.unexpected(), Some("end of input"));
+        assert_eq!(err.snippet(), Some("{ a"));
+
+        let options = ParserOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let err = parse_query_with_options("{\n  a { b }\n}", &options).unwrap_err();
+        assert_eq!(err.position(), Some(Pos { line: 2, column: 5, offset: 6 }));
+        assert_eq!(err.snippet(), Some("  a { b }"));
+    }
+
+    #[test]
+    fn recovering() {
+        let (doc, errors) = parse_query_recovering(
+            "query A { a(b: ) }\nquery B { b }\n{ c { d }\nfragment F on T { % f }\nquery E { e }",
+        );
+        let names: Vec<_> = doc
+            .definitions
+            .iter()
+            .map(|d| match d {
+                Definition::Operation(op) => op.name.unwrap(),
+                _ => "?",
+            })
+            .collect();
+        assert_eq!(names, vec!["A", "B", "E"]);
+        // `{ c { d }` is never closed, which is only found out at the end of the document.
+        let lines: Vec<_> = errors.iter().map(|e| e.position().unwrap().line).collect();
+        assert_eq!(lines, vec![1, 4, 5]);
+        assert_eq!(errors[1].unexpected(), Some("unexpected character '%'"));
+
+        let (doc, errors) = parse_query_recovering("{ a { b(c: ) d } e }\n{ ...F g: }");
+        assert_eq!(
+            doc.to_string(),
+            "{\n  a {\n    d\n  }\n  e\n}\n\n{\n  ...F\n}\n"
+        );
+        let positions: Vec<_> = errors
+            .iter()
+            .map(|e| e.position().map(|pos| (pos.line, pos.column)))
+            .collect();
+        assert_eq!(positions, vec![Some((1, 12)), Some((2, 11))]);
+
+        let (doc, errors) = parse_query_recovering("{ a } { b }");
+        assert_eq!(doc.definitions.len(), 2);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn owned_and_serde() {
+        let source = String::from("query Q($v: [Int!] = [1]) { a(b: {c: $v}) @d { ...F } }");
+        let doc = ast(&source);
+        let owned: crate::query::owned::Document = doc.clone().into_static();
+        drop(source);
+        assert_eq!(
+            owned.to_string(),
+            "query Q($v: [Int!] = [1]) {\n  a(b: {c: $v}) @d {\n    ...F\n  }\n}\n"
+        );
+
+        let json = serde_json::to_string(&owned).unwrap();
+        let back: crate::query::owned::Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, owned);
+        let borrowed: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(borrowed.into_static(), owned);
+    }
 }
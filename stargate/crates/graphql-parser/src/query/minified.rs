@@ -1,5 +1,6 @@
 use crate::query::refs::*;
 use crate::query::*;
+use crate::Spanned;
 
 #[derive(Debug)]
 pub struct MinifiedFormatter {
@@ -14,6 +15,30 @@ impl Default for MinifiedFormatter {
     }
 }
 
+impl MinifiedFormatter {
+    pub(crate) fn write(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    /// Writes `s` as an escaped string, never as a block string.
+    pub(crate) fn write_string(&mut self, s: &str) {
+        use std::fmt::Write;
+        self.buf.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.buf.push_str("\\\""),
+                '\\' => self.buf.push_str(r"\\"),
+                '\n' => self.buf.push_str(r"\n"),
+                '\r' => self.buf.push_str(r"\r"),
+                '\t' => self.buf.push_str(r"\t"),
+                '\u{0000}'..='\u{001F}' => write!(&mut self.buf, "\\u{:04x}", c as u32).unwrap(),
+                _ => self.buf.push(c),
+            }
+        }
+        self.buf.push('"');
+    }
+}
+
 pub trait MinifiedString {
     /// writes the minified string representation of this type, returns true if whitespace is needed after it.
     fn minify(&self, f: &mut MinifiedFormatter) -> bool;
@@ -78,22 +103,7 @@ macro_rules! minify_enum {
     };
 }
 
-macro_rules! write_escaped {
-    ($f:ident, $e:expr) => { write!(escaped $f, $e); };
-}
-
 macro_rules! write {
-    (escaped $f:ident, $e:expr) => {
-        if $e.contains('\\') || $e.contains('"') {
-            for c in $e.chars() {
-                if c == '"' { $f.buf.push_str("\\\""); }
-                else if c == '\\' { $f.buf.push_str(r"\\"); }
-                else { $f.buf.push(c); }
-            }
-        } else {
-            write!($f, $e);
-        }
-    };
     ($f:ident, $($e:expr,)*) => {
         $( if $e.len() == 1 { $f.buf.push($e.chars().next().unwrap()) } else { $f.buf.push_str($e) } )*
     };
@@ -169,6 +179,7 @@ impl<'a> MinifiedString for VariableDefinition<'a> {
             write!(f, "=");
             default.minify(f);
         };
+        minify_each!(f, self.directives);
         true
     }
 }
@@ -282,9 +293,7 @@ impl<'a> MinifiedString for Value<'a> {
                 true
             }
             Value::String(ref val) => {
-                write!(f, "\"");
-                write_escaped!(f, val);
-                write!(f, "\"");
+                f.write_string(&val.to_str());
                 true
             }
             Value::Boolean(true) => {
@@ -319,7 +328,7 @@ impl<'a> MinifiedString for Value<'a> {
     }
 }
 
-impl<'a> MinifiedString for (Txt<'a>, Value<'a>) {
+impl<'a> MinifiedString for Argument<'a> {
     fn minify(&self, f: &mut MinifiedFormatter) -> bool {
         let (a, b) = self;
         write!(f, a, ":");
@@ -328,7 +337,7 @@ impl<'a> MinifiedString for (Txt<'a>, Value<'a>) {
     }
 }
 
-impl<'a> MinifiedString for (&Txt<'a>, &Value<'a>) {
+impl<'a> MinifiedString for (&Txt<'a>, &Spanned<Value<'a>>) {
     fn minify(&self, f: &mut MinifiedFormatter) -> bool {
         let (a, b) = self;
         write!(f, a, ":");
@@ -377,6 +386,8 @@ mod tests {
             "query($arg1:String$representations:[_Any!]!){_entities(arg:$arg1 representations:$representations){...on User{reviews{body}numberOfReviews}}}",
             "{vehicle(id:\"{\\\"make\\\":\\\"Toyota\\\",\\\"model\\\":\\\"Rav4\\\",\\\"trim\\\":\\\"Limited\\\"}\")}",
             "{vehicle(id:\"this is a \\\\ string with a slash \\\\ \")}",
+            "{vehicle(id:\"two\\nlines\")}",
+            "query($a:Int=1@deprecated$b:String@c(d:true)){f}",
         ];
         for query in queries {
             let parsed = parse_query(query).unwrap();
@@ -1,16 +1,22 @@
 //! Query language AST and parsing utilities
 //!
 mod ast;
+pub mod fold;
 mod format;
+pub mod generic;
 mod grammar;
 pub(crate) mod minified;
+pub mod owned;
 pub mod refs;
+pub mod signature;
 
 pub use self::ast::*;
+pub use self::fold::Fold;
 
 mod visit;
 pub use self::visit::*;
 
 mod name;
-pub use self::grammar::{fragment_definition, operation_definition, parse_query};
+pub(crate) use self::grammar::{fragment_definition, operation_definition};
+pub use self::grammar::{parse_query, parse_query_recovering, parse_query_with_options};
 pub use self::name::*;
@@ -13,6 +13,24 @@ impl<'a> Name<'a> for Definition<'a> {
     }
 }
 
+impl<'a> Name<'a> for OperationDefinition<'a> {
+    fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+}
+
+impl<'a> Name<'a> for FragmentDefinition<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for VariableDefinition<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
 impl<'a> Name<'a> for Field<'a> {
     fn name(&self) -> Option<&'a str> {
         Some(self.name)
@@ -42,3 +60,9 @@ impl<'a> Name<'a> for InlineFragment<'a> {
         self.type_condition
     }
 }
+
+impl<'a> Name<'a> for Directive<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
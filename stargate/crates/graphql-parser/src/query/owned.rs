@@ -0,0 +1,25 @@
+//! Query language AST owning its names
+//!
+//! These nodes can outlive the source they were parsed from, e.g. to be cached
+//! or sent to another thread. `Document::into_static` converts a parsed
+//! document into an owned one.
+//!
+pub use crate::query::ast::Operation;
+use crate::query::generic;
+
+pub type Argument = generic::Argument<String>;
+pub type Directive = generic::Directive<String>;
+pub type Value = generic::Value<String>;
+pub type StringValue = generic::StringValue<String>;
+pub type Type = generic::Type<String>;
+
+pub type Document = generic::Document<String>;
+pub type Definition = generic::Definition<String>;
+pub type FragmentDefinition = generic::FragmentDefinition<String>;
+pub type OperationDefinition = generic::OperationDefinition<String>;
+pub type SelectionSet = generic::SelectionSet<String>;
+pub type VariableDefinition = generic::VariableDefinition<String>;
+pub type Selection = generic::Selection<String>;
+pub type Field = generic::Field<String>;
+pub type FragmentSpread = generic::FragmentSpread<String>;
+pub type InlineFragment = generic::InlineFragment<String>;
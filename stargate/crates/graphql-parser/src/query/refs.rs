@@ -1,8 +1,8 @@
-use crate::common::{Directive, Txt, Value};
 use crate::query::Node as QueryNode;
+use crate::query::{Argument, Directive, Txt};
 use crate::query::{Field, SelectionSet};
 use crate::{node_trait, visit, visit_each};
-use crate::{query, Pos};
+use crate::{query, Pos, Span};
 
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum SelectionRef<'a> {
@@ -44,7 +44,7 @@ impl<'a> SelectionRef<'a> {
             SelectionRef::FieldRef(f) => Some(f.selection_set),
             SelectionRef::Ref(query::Selection::Field(f)) | SelectionRef::Field(f) => {
                 Some(SelectionSetRef {
-                    span: (Pos { line: 0, column: 0 }, Pos { line: 0, column: 0 }),
+                    span: Span::default(),
                     items: f
                         .selection_set
                         .items
@@ -72,11 +72,11 @@ pub struct FragmentSpreadRef {
     pub name: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Default, Derivative)]
-#[derivative(Hash)]
+#[derive(Debug, Clone, Default, Derivative)]
+#[derivative(PartialEq, Hash)]
 pub struct SelectionSetRef<'a> {
-    #[derivative(Hash = "ignore")]
-    pub span: (Pos, Pos),
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    pub span: Span,
     pub items: Vec<SelectionRef<'a>>,
 }
 
@@ -89,14 +89,14 @@ impl<'a> From<&'a SelectionSet<'a>> for SelectionSetRef<'a> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
+#[derive(Debug, Clone, Derivative)]
+#[derivative(PartialEq, Hash)]
 pub struct FieldRef<'a> {
-    #[derivative(Hash = "ignore")]
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     pub position: Pos,
     pub alias: Option<Txt<'a>>,
     pub name: Txt<'a>,
-    pub arguments: &'a Vec<(Txt<'a>, Value<'a>)>,
+    pub arguments: &'a Vec<Argument<'a>>,
     pub directives: &'a Vec<Directive<'a>>,
     pub selection_set: SelectionSetRef<'a>,
 }
@@ -107,10 +107,10 @@ impl<'a> FieldRef<'a> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Derivative)]
-#[derivative(Hash)]
+#[derive(Debug, Clone, Derivative)]
+#[derivative(PartialEq, Hash)]
 pub struct InlineFragmentRef<'a> {
-    #[derivative(Hash = "ignore")]
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     pub position: Pos,
     pub type_condition: Option<Txt<'a>>,
     pub directives: &'a Vec<Directive<'a>>,
@@ -0,0 +1,252 @@
+//! Operation signatures: a canonical form of an operation, so operations which only differ
+//! by literals, aliases, ordering or whitespace are identified as the same operation.
+//!
+//! The normalization matches the default signature of Apollo's usage reporting:
+//! - the executed operation is kept, along with the fragments it uses (transitively);
+//! - literals are replaced with empty values (`0`, `""`, `[]` and `{}`),
+//!   booleans, enums, null and variables are kept;
+//! - aliases are removed;
+//! - definitions, selections, arguments, directives and variable definitions are sorted;
+//! - the document is printed minified.
+
+use crate::query::*;
+use crate::DisplayMinified;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Returns the normalized document of the operation named `operation_name`
+/// (or the first operation if `None`), or `None` if there is no such operation.
+pub fn normalize<'a>(
+    document: &Document<'a>,
+    operation_name: Option<&str>,
+) -> Option<Document<'a>> {
+    let operation = document
+        .definitions
+        .iter()
+        .find(|definition| match (definition, operation_name) {
+            (Definition::Fragment(_), _) => false,
+            (_, None) => true,
+            (Definition::Operation(operation), Some(name)) => operation.name == Some(name),
+            (Definition::SelectionSet(_), Some(_)) => false,
+        })?
+        .clone();
+
+    let used_fragments = used_fragments(document, &operation);
+    let mut fragments: Vec<Definition<'a>> = document
+        .definitions
+        .iter()
+        .filter(|definition| match definition {
+            Definition::Fragment(fragment) => used_fragments.contains(fragment.name),
+            _ => false,
+        })
+        .cloned()
+        .collect();
+    fragments.sort_by(|a, b| match (a, b) {
+        (Definition::Fragment(a), Definition::Fragment(b)) => a.name.cmp(b.name),
+        _ => unreachable!("only fragments are left"),
+    });
+
+    let mut definitions = vec![operation];
+    definitions.extend(fragments);
+    for definition in definitions.iter_mut() {
+        normalize_definition(definition);
+    }
+
+    Some(Document { definitions })
+}
+
+/// Returns the signature of the operation named `operation_name`
+/// (or the first operation if `None`), or `None` if there is no such operation.
+pub fn signature(document: &Document, operation_name: Option<&str>) -> Option<String> {
+    normalize(document, operation_name).map(|document| document.minified())
+}
+
+/// Returns a stable hash of a signature: the hex encoded SHA-256 of the signature.
+/// Unlike `std::hash`, it is the same across processes, platforms and rust versions.
+pub fn signature_hash(signature: &str) -> String {
+    format!("{:x}", Sha256::digest(signature.as_bytes()))
+}
+
+/// Collects the names of fragment spreads while visiting a selection set.
+struct FragmentSpreads {
+    names: Vec<String>,
+}
+
+impl Visitor for FragmentSpreads {
+    fn enter_sel(&mut self, sel: &Selection) {
+        if let Selection::FragmentSpread(spread) = sel {
+            self.names.push(String::from(spread.fragment_name));
+        }
+    }
+}
+
+fn used_fragments<'a>(document: &Document<'a>, operation: &Definition<'a>) -> HashSet<&'a str> {
+    let mut used: HashSet<&'a str> = HashSet::new();
+    let mut spreads = FragmentSpreads { names: vec![] };
+    operation.accept(&mut spreads);
+
+    while let Some(name) = spreads.names.pop() {
+        if used.contains(name.as_str()) {
+            continue;
+        }
+        let fragment = document.definitions.iter().find_map(|d| match d {
+            Definition::Fragment(fragment) if fragment.name == name => Some(fragment),
+            _ => None,
+        });
+        if let Some(fragment) = fragment {
+            used.insert(fragment.name);
+            fragment.selection_set.accept(&mut spreads);
+        }
+    }
+
+    used
+}
+
+fn normalize_definition(definition: &mut Definition) {
+    match definition {
+        Definition::SelectionSet(selection_set) => normalize_selection_set(selection_set),
+        Definition::Operation(operation) => {
+            for variable in operation.variable_definitions.iter_mut() {
+                if let Some(ref mut default_value) = variable.default_value {
+                    hide_literal(default_value);
+                }
+            }
+            operation
+                .variable_definitions
+                .sort_by(|a, b| a.name.cmp(b.name));
+            normalize_directives(&mut operation.directives);
+            normalize_selection_set(&mut operation.selection_set);
+        }
+        Definition::Fragment(fragment) => {
+            normalize_directives(&mut fragment.directives);
+            normalize_selection_set(&mut fragment.selection_set);
+        }
+    }
+}
+
+fn normalize_selection_set(selection_set: &mut SelectionSet) {
+    for selection in selection_set.items.iter_mut() {
+        match selection {
+            Selection::Field(field) => {
+                field.alias = None;
+                normalize_arguments(&mut field.arguments);
+                normalize_directives(&mut field.directives);
+                normalize_selection_set(&mut field.selection_set);
+            }
+            Selection::FragmentSpread(spread) => normalize_directives(&mut spread.directives),
+            Selection::InlineFragment(inline) => {
+                normalize_directives(&mut inline.directives);
+                normalize_selection_set(&mut inline.selection_set);
+            }
+        }
+    }
+    selection_set.items.sort_by(compare_selections);
+}
+
+fn compare_selections(a: &Selection, b: &Selection) -> Ordering {
+    fn key<'a>(selection: &Selection<'a>) -> (u8, &'a str) {
+        match selection {
+            Selection::Field(field) => (0, field.name),
+            Selection::FragmentSpread(spread) => (1, spread.fragment_name),
+            Selection::InlineFragment(inline) => (2, inline.type_condition.unwrap_or("")),
+        }
+    }
+    key(a).cmp(&key(b))
+}
+
+fn normalize_directives(directives: &mut Vec<Directive>) {
+    for directive in directives.iter_mut() {
+        normalize_arguments(&mut directive.arguments);
+    }
+    directives.sort_by(|a, b| a.name.cmp(b.name));
+}
+
+fn normalize_arguments(arguments: &mut Vec<Argument>) {
+    for (_, value) in arguments.iter_mut() {
+        hide_literal(value);
+    }
+    arguments.sort_by_key(|(name, _)| name.node);
+}
+
+fn hide_literal(value: &mut Value) {
+    match value {
+        Value::Int(_) | Value::Float(_) => *value = Value::Int(0),
+        Value::String(_) => *value = Value::String(StringValue::Source("")),
+        Value::List(_) => *value = Value::List(vec![]),
+        Value::Object(_) => *value = Value::Object(Default::default()),
+        Value::Variable(_) | Value::Boolean(_) | Value::Null | Value::Enum(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{signature, signature_hash};
+    use crate::parse_query;
+
+    fn sig(query: &str, operation_name: Option<&str>) -> String {
+        signature(&parse_query(query).unwrap(), operation_name).unwrap()
+    }
+
+    #[test]
+    fn it_should_hide_literals_and_aliases() {
+        assert_eq!(
+            sig(
+                r#"query Me($id: ID, $first: Int = 10) { me: user(name: "me", id: $id, first: 10) { reviews(filter: {stars: 4}, ids: [1, 2], sort: ASC, all: true) { body } } }"#,
+                Some("Me")
+            ),
+            r#"query Me($first:Int=0$id:ID){user(first:0 id:$id name:""){reviews(all:true filter:{} ids:[] sort:ASC){body}}}"#
+        );
+    }
+
+    #[test]
+    fn it_should_sort_fields_fragments_and_directives() {
+        assert_eq!(
+            sig(
+                "{ b ...on B { y x } ...F a @skip(if: false) @include(if: true) } fragment F on T { d c }",
+                None
+            ),
+            "{a@include(if:true) @skip(if:false) b ...F ...on B{x y}}fragment F on T{c d}"
+        );
+    }
+
+    #[test]
+    fn it_should_only_keep_the_operation_and_used_fragments() {
+        let query = r#"
+            query A { ...B }
+            query B { ...C }
+            fragment C on Query { ...D }
+            fragment D on Query { d }
+            fragment B on Query { b }
+        "#;
+        assert_eq!(
+            sig(query, Some("B")),
+            "query B{...C}fragment C on Query{...D}fragment D on Query{d}"
+        );
+        assert_eq!(sig(query, None), "query A{...B}fragment B on Query{b}");
+        assert_eq!(signature(&parse_query(query).unwrap(), Some("E")), None);
+    }
+
+    #[test]
+    fn it_should_give_the_same_signature_to_equivalent_operations() {
+        let a = sig(r#"query Q { user(id: 1) { name, id } }"#, Some("Q"));
+        let b = sig(
+            r#"
+            query Q {
+              u: user(id: 2) {
+                id
+                name
+              }
+            }
+            fragment Unused on User { id }
+            "#,
+            Some("Q"),
+        );
+        assert_eq!(a, b);
+        assert_eq!(signature_hash(&a), signature_hash(&b));
+        assert_eq!(
+            signature_hash("{a}"),
+            "460c3a93211614ac783c0f1d1bbbcb45a6da87d6421b5c0771772588f1015ff8"
+        );
+    }
+}
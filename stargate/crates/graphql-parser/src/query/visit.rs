@@ -1,23 +1,101 @@
-use super::{Definition, Document, Selection, SelectionSet};
+use super::{
+    Argument, Definition, Directive, Document, Field, FragmentDefinition, FragmentSpread,
+    InlineFragment, OperationDefinition, Selection, SelectionSet, Value, VariableDefinition,
+};
 use crate::{node_trait, visit, visit_each};
 
 #[allow(unused_variables)]
 pub trait Visitor {
     fn enter_query(&mut self, doc: &Document) {}
     fn enter_query_def(&mut self, def: &Definition) {}
+    fn enter_operation(&mut self, op: &OperationDefinition) {}
+    fn enter_fragment_def(&mut self, frag: &FragmentDefinition) {}
+    fn enter_variable_def(&mut self, var: &VariableDefinition) {}
     fn enter_sel_set(&mut self, sel_set: &SelectionSet) {}
     fn enter_sel(&mut self, sel: &Selection) {}
+    fn enter_query_field(&mut self, field: &Field) {}
+    fn enter_fragment_spread(&mut self, spread: &FragmentSpread) {}
+    fn enter_inline_fragment(&mut self, inline: &InlineFragment) {}
+    fn enter_directive(&mut self, dir: &Directive) {}
+    fn enter_argument(&mut self, name: &str, value: &Value) {}
+    fn enter_value(&mut self, value: &Value) {}
+    fn leave_value(&mut self, value: &Value) {}
+    fn leave_argument(&mut self, name: &str, value: &Value) {}
+    fn leave_directive(&mut self, dir: &Directive) {}
+    fn leave_inline_fragment(&mut self, inline: &InlineFragment) {}
+    fn leave_fragment_spread(&mut self, spread: &FragmentSpread) {}
+    fn leave_query_field(&mut self, field: &Field) {}
     fn leave_sel(&mut self, sel: &Selection) {}
     fn leave_sel_set(&mut self, sel_set: &SelectionSet) {}
+    fn leave_variable_def(&mut self, var: &VariableDefinition) {}
+    fn leave_fragment_def(&mut self, frag: &FragmentDefinition) {}
+    fn leave_operation(&mut self, op: &OperationDefinition) {}
     fn leave_query_def(&mut self, def: &Definition) {}
     fn leave_query(&mut self, doc: &Document) {}
 }
 
+/// Projections of the nodes other than documents, definitions, selection sets
+/// and selections are optional: when one returns `None`, the node has no
+/// output node and the output nodes of its children are merged into its parent's.
+#[allow(unused_variables)]
 pub trait Map: visit::Map {
     fn query(&mut self, doc: &Document, stack: &[Self::Output]) -> Self::Output;
     fn query_def(&mut self, def: &Definition, stack: &[Self::Output]) -> Self::Output;
     fn sel_set(&mut self, sel_set: &SelectionSet, stack: &[Self::Output]) -> Self::Output;
     fn sel(&mut self, sel: &Selection, stack: &[Self::Output]) -> Self::Output;
+
+    fn operation(
+        &mut self,
+        op: &OperationDefinition,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn fragment_def(
+        &mut self,
+        frag: &FragmentDefinition,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn variable_def(
+        &mut self,
+        var: &VariableDefinition,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn query_field(&mut self, field: &Field, stack: &[Self::Output]) -> Option<Self::Output> {
+        None
+    }
+    fn fragment_spread(
+        &mut self,
+        spread: &FragmentSpread,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn inline_fragment(
+        &mut self,
+        inline: &InlineFragment,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn directive(&mut self, dir: &Directive, stack: &[Self::Output]) -> Option<Self::Output> {
+        None
+    }
+    fn argument(
+        &mut self,
+        name: &str,
+        value: &Value,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn value(&mut self, value: &Value, stack: &[Self::Output]) -> Option<Self::Output> {
+        None
+    }
 }
 
 impl<M: Map> Visitor for visit::Fold<M> {
@@ -27,18 +105,81 @@ impl<M: Map> Visitor for visit::Fold<M> {
     fn enter_query_def(&mut self, def: &Definition) {
         self.stack.push(self.map.query_def(def, &self.stack));
     }
+    fn enter_operation(&mut self, op: &OperationDefinition) {
+        let output = self.map.operation(op, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_fragment_def(&mut self, frag: &FragmentDefinition) {
+        let output = self.map.fragment_def(frag, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_variable_def(&mut self, var: &VariableDefinition) {
+        let output = self.map.variable_def(var, &self.stack);
+        self.push_optional(output);
+    }
     fn enter_sel_set(&mut self, sel_set: &SelectionSet) {
         self.stack.push(self.map.sel_set(sel_set, &self.stack));
     }
     fn enter_sel(&mut self, sel: &Selection) {
         self.stack.push(self.map.sel(&sel, &self.stack));
     }
+    fn enter_query_field(&mut self, field: &Field) {
+        let output = self.map.query_field(field, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_fragment_spread(&mut self, spread: &FragmentSpread) {
+        let output = self.map.fragment_spread(spread, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_inline_fragment(&mut self, inline: &InlineFragment) {
+        let output = self.map.inline_fragment(inline, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_directive(&mut self, dir: &Directive) {
+        let output = self.map.directive(dir, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_argument(&mut self, name: &str, value: &Value) {
+        let output = self.map.argument(name, value, &self.stack);
+        self.push_optional(output);
+    }
+    fn enter_value(&mut self, value: &Value) {
+        let output = self.map.value(value, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_value(&mut self, _value: &Value) {
+        self.pop_optional();
+    }
+    fn leave_argument(&mut self, _name: &str, _value: &Value) {
+        self.pop_optional();
+    }
+    fn leave_directive(&mut self, _dir: &Directive) {
+        self.pop_optional();
+    }
+    fn leave_inline_fragment(&mut self, _inline: &InlineFragment) {
+        self.pop_optional();
+    }
+    fn leave_fragment_spread(&mut self, _spread: &FragmentSpread) {
+        self.pop_optional();
+    }
+    fn leave_query_field(&mut self, _field: &Field) {
+        self.pop_optional();
+    }
     fn leave_sel(&mut self, _sel: &Selection) {
         self.pop();
     }
     fn leave_sel_set(&mut self, _sel_set: &SelectionSet) {
         self.pop();
     }
+    fn leave_variable_def(&mut self, _var: &VariableDefinition) {
+        self.pop_optional();
+    }
+    fn leave_fragment_def(&mut self, _frag: &FragmentDefinition) {
+        self.pop_optional();
+    }
+    fn leave_operation(&mut self, _op: &OperationDefinition) {
+        self.pop_optional();
+    }
     fn leave_query_def(&mut self, _def: &Definition) {
         self.pop();
     }
@@ -49,6 +190,16 @@ impl<M: Map> Visitor for visit::Fold<M> {
 
 node_trait!(Visitor, Map);
 
+/// Visits each of `arguments` and its value.
+pub(crate) fn visit_arguments<V: Visitor>(arguments: &[Argument], visitor: &mut V) {
+    for (name, value) in arguments {
+        let (name, value) = (&name.node, &value.node);
+        visitor.enter_argument(name, value);
+        value.accept(visitor);
+        visitor.leave_argument(name, value);
+    }
+}
+
 impl<'a> Node for Document<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_query(self);
@@ -60,16 +211,46 @@ impl<'a> Node for Document<'a> {
 impl<'a> Node for Definition<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_query_def(self);
-        use Definition::*;
+        use crate::query::generic::Definition::*;
         match self {
             SelectionSet(sel_set) => sel_set.accept(visitor),
-            Operation(op) => op.selection_set.accept(visitor),
-            Fragment(frag) => frag.selection_set.accept(visitor),
+            Operation(op) => op.accept(visitor),
+            Fragment(frag) => frag.accept(visitor),
         }
         visitor.leave_query_def(self);
     }
 }
 
+impl<'a> Node for OperationDefinition<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_operation(self);
+        visit_each!(visitor: self.variable_definitions);
+        visit_each!(visitor: self.directives);
+        self.selection_set.accept(visitor);
+        visitor.leave_operation(self);
+    }
+}
+
+impl<'a> Node for FragmentDefinition<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_fragment_def(self);
+        visit_each!(visitor: self.directives);
+        self.selection_set.accept(visitor);
+        visitor.leave_fragment_def(self);
+    }
+}
+
+impl<'a> Node for VariableDefinition<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_variable_def(self);
+        if let Some(value) = &self.default_value {
+            value.accept(visitor);
+        }
+        visit_each!(visitor: self.directives);
+        visitor.leave_variable_def(self);
+    }
+}
+
 impl<'a> Node for SelectionSet<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_sel_set(self);
@@ -81,16 +262,67 @@ impl<'a> Node for SelectionSet<'a> {
 impl<'a> Node for Selection<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_sel(self);
-        use Selection::*;
+        use crate::query::generic::Selection::*;
         match self {
-            Field(field) => field.selection_set.accept(visitor),
-            FragmentSpread(_) => {}
-            InlineFragment(inline) => inline.selection_set.accept(visitor),
+            Field(field) => field.accept(visitor),
+            FragmentSpread(spread) => spread.accept(visitor),
+            InlineFragment(inline) => inline.accept(visitor),
         }
         visitor.leave_sel(self);
     }
 }
 
+impl<'a> Node for Field<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_query_field(self);
+        visit_arguments(&self.arguments, visitor);
+        visit_each!(visitor: self.directives);
+        self.selection_set.accept(visitor);
+        visitor.leave_query_field(self);
+    }
+}
+
+impl<'a> Node for FragmentSpread<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_fragment_spread(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_fragment_spread(self);
+    }
+}
+
+impl<'a> Node for InlineFragment<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_inline_fragment(self);
+        visit_each!(visitor: self.directives);
+        self.selection_set.accept(visitor);
+        visitor.leave_inline_fragment(self);
+    }
+}
+
+impl<'a> Node for Directive<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_directive(self);
+        visit_arguments(&self.arguments, visitor);
+        visitor.leave_directive(self);
+    }
+}
+
+impl<'a> Node for Value<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_value(self);
+        match self {
+            Value::List(items) => visit_each!(visitor: items),
+            Value::Object(fields) => {
+                for value in fields.values() {
+                    value.accept(visitor);
+                }
+            }
+            _ => {}
+        }
+        visitor.leave_value(self);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{parse_query, query, query::*, visit, ParseError};
@@ -238,4 +470,60 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn maps_optional_nodes() -> Result<(), crate::ParseError> {
+        let query = crate::parse_query("{ a(x: [1, $v]) @d { b } }")?;
+        struct TestMap {}
+        impl visit::Map for TestMap {
+            type Output = String;
+            fn merge(&mut self, parent: String, child: String) -> String {
+                format!("{}\n{}", parent, child)
+            }
+        }
+        impl Map for TestMap {
+            fn query(&mut self, _: &Document, stack: &[Self::Output]) -> Self::Output {
+                format!("{}query", "  ".repeat(stack.len()))
+            }
+            fn query_def(&mut self, _: &Definition, stack: &[Self::Output]) -> Self::Output {
+                format!("{}query_def", "  ".repeat(stack.len()))
+            }
+            fn sel_set(&mut self, _: &SelectionSet, stack: &[Self::Output]) -> Self::Output {
+                format!("{}sel_set", "  ".repeat(stack.len()))
+            }
+            fn sel(&mut self, _: &Selection, stack: &[Self::Output]) -> Self::Output {
+                format!("{}sel", "  ".repeat(stack.len()))
+            }
+            fn query_field(&mut self, f: &Field, stack: &[Self::Output]) -> Option<Self::Output> {
+                Some(format!("{}field {}", "  ".repeat(stack.len()), f.name))
+            }
+            fn value(&mut self, v: &Value, stack: &[Self::Output]) -> Option<Self::Output> {
+                match v {
+                    Value::Variable(_) | Value::Int(_) => {
+                        Some(format!("{}value {}", "  ".repeat(stack.len()), v))
+                    }
+                    _ => None,
+                }
+            }
+        }
+
+        let tx = query.map(TestMap {});
+        pretty_assertions::assert_eq!(
+            tx.output,
+            Some(String::from(
+                r#"query
+  query_def
+    sel_set
+      sel
+        field a
+          value 1
+          value $v
+          sel_set
+            sel
+              field b
+                sel_set"#
+            ))
+        );
+        Ok(())
+    }
 }
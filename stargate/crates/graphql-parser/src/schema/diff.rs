@@ -0,0 +1,701 @@
+//! Comparison of two versions of a schema
+//!
+//! Changes are classified the way `findBreakingChanges` and
+//! `findDangerousChanges` of graphql-js do: a breaking change makes valid
+//! operations invalid, a dangerous change may change the result of valid
+//! operations, e.g. a new enum value clients don't handle.
+//!
+use std::fmt;
+
+use crate::schema::generic::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Criticality {
+    Breaking,
+    Dangerous,
+    Safe,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    TypeAdded,
+    TypeRemoved,
+    TypeChangedKind,
+    FieldAdded,
+    FieldRemoved,
+    FieldChangedType,
+    ArgAdded,
+    ArgRemoved,
+    ArgChangedType,
+    ArgDefaultValueChanged,
+    InputFieldAdded,
+    InputFieldRemoved,
+    InputFieldChangedType,
+    EnumValueAdded,
+    EnumValueRemoved,
+    UnionMemberAdded,
+    UnionMemberRemoved,
+    InterfaceAdded,
+    InterfaceRemoved,
+    DirectiveAdded,
+    DirectiveRemoved,
+    DirectiveLocationAdded,
+    DirectiveLocationRemoved,
+    DirectiveRepeatableAdded,
+    DirectiveRepeatableRemoved,
+}
+
+/// A difference between two versions of a schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    pub criticality: Criticality,
+    /// The changed element, e.g. `User`, `User.name`, `Query.user(id:)` or
+    /// `@auth(role:)`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Lists the changes from `old` to `new`: removals and changes in the order
+/// of `old`, then additions in the order of `new`.
+///
+/// Extensions are ignored: apply them first with `apply_extensions`.
+pub fn diff_schemas<S: Text>(old: &Document<S>, new: &Document<S>) -> Vec<SchemaChange> {
+    let mut diff = Diff { changes: vec![] };
+
+    let old_types = types(old);
+    let new_types = types(new);
+    for (name, old_type) in &old_types {
+        match new_types.iter().find(|(n, _)| n == name) {
+            Some((_, new_type)) => diff.diff_type(name, old_type, new_type),
+            None => diff.push(
+                ChangeKind::TypeRemoved,
+                Criticality::Breaking,
+                name,
+                format!("`{}` was removed", name),
+            ),
+        }
+    }
+    for (name, _) in &new_types {
+        if !old_types.iter().any(|(n, _)| n == name) {
+            diff.push(
+                ChangeKind::TypeAdded,
+                Criticality::Safe,
+                name,
+                format!("`{}` was added", name),
+            );
+        }
+    }
+
+    let old_directives = directives(old);
+    let new_directives = directives(new);
+    for old_directive in &old_directives {
+        let path = format!("@{}", old_directive.name.as_ref());
+        match new_directives.iter().find(|d| d.name == old_directive.name) {
+            Some(new_directive) => diff.diff_directive(&path, old_directive, new_directive),
+            None => diff.push(
+                ChangeKind::DirectiveRemoved,
+                Criticality::Breaking,
+                &path,
+                format!("`{}` was removed", path),
+            ),
+        }
+    }
+    for new_directive in &new_directives {
+        if !old_directives.iter().any(|d| d.name == new_directive.name) {
+            let path = format!("@{}", new_directive.name.as_ref());
+            diff.push(
+                ChangeKind::DirectiveAdded,
+                Criticality::Safe,
+                &path,
+                format!("`{}` was added", path),
+            );
+        }
+    }
+
+    diff.changes
+}
+
+struct Diff {
+    changes: Vec<SchemaChange>,
+}
+
+impl Diff {
+    fn push(&mut self, kind: ChangeKind, criticality: Criticality, path: &str, message: String) {
+        self.changes.push(SchemaChange {
+            kind,
+            criticality,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn diff_type<S: Text>(&mut self, name: &str, old: &TypeDefinition<S>, new: &TypeDefinition<S>) {
+        match (old, new) {
+            (TypeDefinition::Scalar(_), TypeDefinition::Scalar(_)) => {}
+            (TypeDefinition::Object(old), TypeDefinition::Object(new)) => {
+                self.diff_interfaces(name, &old.implements_interfaces, &new.implements_interfaces);
+                self.diff_fields(name, &old.fields, &new.fields);
+            }
+            (TypeDefinition::Interface(old), TypeDefinition::Interface(new)) => {
+                self.diff_interfaces(name, &old.implements_interfaces, &new.implements_interfaces);
+                self.diff_fields(name, &old.fields, &new.fields);
+            }
+            (TypeDefinition::Union(old), TypeDefinition::Union(new)) => {
+                for member in &old.types {
+                    if !new.types.contains(member) {
+                        self.push(
+                            ChangeKind::UnionMemberRemoved,
+                            Criticality::Breaking,
+                            name,
+                            format!("`{}` was removed from union `{}`", member.as_ref(), name),
+                        );
+                    }
+                }
+                for member in &new.types {
+                    if !old.types.contains(member) {
+                        self.push(
+                            ChangeKind::UnionMemberAdded,
+                            Criticality::Dangerous,
+                            name,
+                            format!("`{}` was added to union `{}`", member.as_ref(), name),
+                        );
+                    }
+                }
+            }
+            (TypeDefinition::Enum(old), TypeDefinition::Enum(new)) => {
+                for value in &old.values {
+                    if !new.values.iter().any(|v| v.name == value.name) {
+                        let path = format!("{}.{}", name, value.name.as_ref());
+                        self.push(
+                            ChangeKind::EnumValueRemoved,
+                            Criticality::Breaking,
+                            &path,
+                            format!("`{}` was removed from enum `{}`", value.name.as_ref(), name),
+                        );
+                    }
+                }
+                for value in &new.values {
+                    if !old.values.iter().any(|v| v.name == value.name) {
+                        let path = format!("{}.{}", name, value.name.as_ref());
+                        self.push(
+                            ChangeKind::EnumValueAdded,
+                            Criticality::Dangerous,
+                            &path,
+                            format!("`{}` was added to enum `{}`", value.name.as_ref(), name),
+                        );
+                    }
+                }
+            }
+            (TypeDefinition::InputObject(old), TypeDefinition::InputObject(new)) => {
+                self.diff_input_fields(name, &old.fields, &new.fields);
+            }
+            (old, new) => self.push(
+                ChangeKind::TypeChangedKind,
+                Criticality::Breaking,
+                name,
+                format!(
+                    "`{}` changed from {} to {}",
+                    name,
+                    kind_description(old),
+                    kind_description(new)
+                ),
+            ),
+        }
+    }
+
+    fn diff_interfaces<S: Text>(&mut self, name: &str, old: &[S], new: &[S]) {
+        for interface in old {
+            if !new.contains(interface) {
+                self.push(
+                    ChangeKind::InterfaceRemoved,
+                    Criticality::Breaking,
+                    name,
+                    format!("`{}` no longer implements `{}`", name, interface.as_ref()),
+                );
+            }
+        }
+        for interface in new {
+            if !old.contains(interface) {
+                self.push(
+                    ChangeKind::InterfaceAdded,
+                    Criticality::Dangerous,
+                    name,
+                    format!("`{}` now implements `{}`", name, interface.as_ref()),
+                );
+            }
+        }
+    }
+
+    fn diff_fields<S: Text>(&mut self, type_name: &str, old: &[Field<S>], new: &[Field<S>]) {
+        for old_field in old {
+            let path = format!("{}.{}", type_name, old_field.name.as_ref());
+            let new_field = match new.iter().find(|f| f.name == old_field.name) {
+                Some(new_field) => new_field,
+                None => {
+                    self.push(
+                        ChangeKind::FieldRemoved,
+                        Criticality::Breaking,
+                        &path,
+                        format!("`{}` was removed", path),
+                    );
+                    continue;
+                }
+            };
+            if old_field.field_type != new_field.field_type {
+                let criticality =
+                    if is_safe_output_change(&old_field.field_type, &new_field.field_type) {
+                        Criticality::Safe
+                    } else {
+                        Criticality::Breaking
+                    };
+                self.push(
+                    ChangeKind::FieldChangedType,
+                    criticality,
+                    &path,
+                    format!(
+                        "`{}` changed type from `{}` to `{}`",
+                        path, old_field.field_type, new_field.field_type
+                    ),
+                );
+            }
+            self.diff_arguments(
+                &path,
+                &old_field.arguments,
+                &new_field.arguments,
+                Criticality::Dangerous,
+            );
+        }
+        for new_field in new {
+            if !old.iter().any(|f| f.name == new_field.name) {
+                let path = format!("{}.{}", type_name, new_field.name.as_ref());
+                self.push(
+                    ChangeKind::FieldAdded,
+                    Criticality::Safe,
+                    &path,
+                    format!("`{}` was added", path),
+                );
+            }
+        }
+    }
+
+    fn diff_input_fields<S: Text>(
+        &mut self,
+        type_name: &str,
+        old: &[InputValue<S>],
+        new: &[InputValue<S>],
+    ) {
+        for old_field in old {
+            let path = format!("{}.{}", type_name, old_field.name.as_ref());
+            match new.iter().find(|f| f.name == old_field.name) {
+                Some(new_field) if old_field.value_type != new_field.value_type => {
+                    let criticality =
+                        if is_safe_input_change(&old_field.value_type, &new_field.value_type) {
+                            Criticality::Safe
+                        } else {
+                            Criticality::Breaking
+                        };
+                    self.push(
+                        ChangeKind::InputFieldChangedType,
+                        criticality,
+                        &path,
+                        format!(
+                            "`{}` changed type from `{}` to `{}`",
+                            path, old_field.value_type, new_field.value_type
+                        ),
+                    );
+                }
+                Some(_) => {}
+                None => self.push(
+                    ChangeKind::InputFieldRemoved,
+                    Criticality::Breaking,
+                    &path,
+                    format!("`{}` was removed", path),
+                ),
+            }
+        }
+        for new_field in new {
+            if !old.iter().any(|f| f.name == new_field.name) {
+                let path = format!("{}.{}", type_name, new_field.name.as_ref());
+                let (criticality, required) = if is_required(new_field) {
+                    (Criticality::Breaking, "required")
+                } else {
+                    (Criticality::Dangerous, "optional")
+                };
+                self.push(
+                    ChangeKind::InputFieldAdded,
+                    criticality,
+                    &path,
+                    format!("{} input field `{}` was added", required, path),
+                );
+            }
+        }
+    }
+
+    /// Compares the arguments of the field or directive at `path`. Adding an
+    /// optional argument is `optional_added`: dangerous on fields, where it
+    /// may change results, and safe on directives.
+    fn diff_arguments<S: Text>(
+        &mut self,
+        path: &str,
+        old: &[InputValue<S>],
+        new: &[InputValue<S>],
+        optional_added: Criticality,
+    ) {
+        for old_arg in old {
+            let arg_path = format!("{}({}:)", path, old_arg.name.as_ref());
+            let new_arg = match new.iter().find(|a| a.name == old_arg.name) {
+                Some(new_arg) => new_arg,
+                None => {
+                    self.push(
+                        ChangeKind::ArgRemoved,
+                        Criticality::Breaking,
+                        &arg_path,
+                        format!("`{}` was removed", arg_path),
+                    );
+                    continue;
+                }
+            };
+            if old_arg.value_type != new_arg.value_type {
+                let criticality = if is_safe_input_change(&old_arg.value_type, &new_arg.value_type)
+                {
+                    Criticality::Safe
+                } else {
+                    Criticality::Breaking
+                };
+                self.push(
+                    ChangeKind::ArgChangedType,
+                    criticality,
+                    &arg_path,
+                    format!(
+                        "`{}` changed type from `{}` to `{}`",
+                        arg_path, old_arg.value_type, new_arg.value_type
+                    ),
+                );
+            }
+            if let Some(old_default) = &old_arg.default_value {
+                let message = match &new_arg.default_value {
+                    None => format!("`{}` no longer has a default value", arg_path),
+                    Some(new_default) if new_default != old_default => format!(
+                        "`{}` changed default value from `{}` to `{}`",
+                        arg_path, old_default, new_default
+                    ),
+                    Some(_) => continue,
+                };
+                self.push(
+                    ChangeKind::ArgDefaultValueChanged,
+                    Criticality::Dangerous,
+                    &arg_path,
+                    message,
+                );
+            }
+        }
+        for new_arg in new {
+            if !old.iter().any(|a| a.name == new_arg.name) {
+                let arg_path = format!("{}({}:)", path, new_arg.name.as_ref());
+                let (criticality, required) = if is_required(new_arg) {
+                    (Criticality::Breaking, "required")
+                } else {
+                    (optional_added, "optional")
+                };
+                self.push(
+                    ChangeKind::ArgAdded,
+                    criticality,
+                    &arg_path,
+                    format!("{} argument `{}` was added", required, arg_path),
+                );
+            }
+        }
+    }
+
+    fn diff_directive<S: Text>(
+        &mut self,
+        path: &str,
+        old: &DirectiveDefinition<S>,
+        new: &DirectiveDefinition<S>,
+    ) {
+        self.diff_arguments(path, &old.arguments, &new.arguments, Criticality::Safe);
+        if old.repeatable && !new.repeatable {
+            self.push(
+                ChangeKind::DirectiveRepeatableRemoved,
+                Criticality::Breaking,
+                path,
+                format!("`{}` is no longer repeatable", path),
+            );
+        } else if !old.repeatable && new.repeatable {
+            self.push(
+                ChangeKind::DirectiveRepeatableAdded,
+                Criticality::Safe,
+                path,
+                format!("`{}` is now repeatable", path),
+            );
+        }
+        for location in &old.locations {
+            if !new.locations.contains(location) {
+                self.push(
+                    ChangeKind::DirectiveLocationRemoved,
+                    Criticality::Breaking,
+                    path,
+                    format!(
+                        "`{}` can no longer be used on `{}`",
+                        path,
+                        location.as_str()
+                    ),
+                );
+            }
+        }
+        for location in &new.locations {
+            if !old.locations.contains(location) {
+                self.push(
+                    ChangeKind::DirectiveLocationAdded,
+                    Criticality::Safe,
+                    path,
+                    format!("`{}` can now be used on `{}`", path, location.as_str()),
+                );
+            }
+        }
+    }
+}
+
+/// Whether values of field type `new` can be used where `old` was expected,
+/// i.e. `new` is `old` or `old` made non-null at any level.
+fn is_safe_output_change<S: Text>(old: &Type<S>, new: &Type<S>) -> bool {
+    match (old, new) {
+        (Type::NonNullType(old), Type::NonNullType(new)) => is_safe_output_change(old, new),
+        (Type::NonNullType(_), _) => false,
+        (Type::ListType(old), Type::ListType(new)) => is_safe_output_change(old, new),
+        (Type::NamedType(old), Type::NamedType(new)) => old == new,
+        (old, Type::NonNullType(new)) => is_safe_output_change(old, new),
+        _ => false,
+    }
+}
+
+/// Whether values accepted by an input of type `old` are accepted by an
+/// input of type `new`, i.e. `new` is `old` made nullable at any level.
+fn is_safe_input_change<S: Text>(old: &Type<S>, new: &Type<S>) -> bool {
+    match (old, new) {
+        (Type::NonNullType(old), Type::NonNullType(new)) => is_safe_input_change(old, new),
+        (Type::NonNullType(old), new) => is_safe_input_change(old, new),
+        (Type::ListType(old), Type::ListType(new)) => is_safe_input_change(old, new),
+        (Type::NamedType(old), Type::NamedType(new)) => old == new,
+        _ => false,
+    }
+}
+
+fn is_required<S>(value: &InputValue<S>) -> bool {
+    matches!(value.value_type.node, Type::NonNullType(_)) && value.default_value.is_none()
+}
+
+fn types<S: Text>(doc: &Document<S>) -> Vec<(&str, &TypeDefinition<S>)> {
+    doc.definitions
+        .iter()
+        .filter_map(|def| match def {
+            Definition::Type(ty) => Some((type_name(ty), ty)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn directives<S: Text>(doc: &Document<S>) -> Vec<&DirectiveDefinition<S>> {
+    doc.definitions
+        .iter()
+        .filter_map(|def| match def {
+            Definition::Directive(directive) => Some(directive),
+            _ => None,
+        })
+        .collect()
+}
+
+fn type_name<S: Text>(ty: &TypeDefinition<S>) -> &str {
+    match ty {
+        TypeDefinition::Scalar(t) => t.name.as_ref(),
+        TypeDefinition::Object(t) => t.name.as_ref(),
+        TypeDefinition::Interface(t) => t.name.as_ref(),
+        TypeDefinition::Union(t) => t.name.as_ref(),
+        TypeDefinition::Enum(t) => t.name.as_ref(),
+        TypeDefinition::InputObject(t) => t.name.as_ref(),
+    }
+}
+
+fn kind_description<S>(ty: &TypeDefinition<S>) -> &'static str {
+    match ty {
+        TypeDefinition::Scalar(_) => "a scalar",
+        TypeDefinition::Object(_) => "an object type",
+        TypeDefinition::Interface(_) => "an interface",
+        TypeDefinition::Union(_) => "a union",
+        TypeDefinition::Enum(_) => "an enum",
+        TypeDefinition::InputObject(_) => "an input object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_schema;
+
+    fn diff(old: &str, new: &str) -> Vec<(Criticality, String)> {
+        let old = parse_schema(old).unwrap();
+        let new = parse_schema(new).unwrap();
+        diff_schemas(&old, &new)
+            .into_iter()
+            .map(|change| (change.criticality, change.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn no_changes() {
+        let schema = r#"
+            type Query { user(id: ID!): User }
+            type User { id: ID! }
+        "#;
+        assert_eq!(diff(schema, schema), vec![]);
+    }
+
+    #[test]
+    fn classifies_changes() {
+        use Criticality::*;
+
+        let old = r#"
+            type Query {
+                user(id: ID!, first: Int = 10, format: String): User
+                users: [User]
+                legacy: String
+            }
+            interface Node { id: ID! }
+            type User implements Node { id: ID! name: String age: Int }
+            type Admin { id: ID! }
+            union Account = User | Admin
+            enum Role { ADMIN USER GUEST }
+            input Filter { name: String age: Int! }
+            scalar Date
+            directive @auth(role: Role) on FIELD_DEFINITION | OBJECT
+            directive @old on FIELD
+        "#;
+        let new = r#"
+            type Query {
+                user(id: ID, first: Int = 20, locale: String, strict: Boolean!): User!
+                users: [User!]
+                legacy: Int
+            }
+            interface Node { id: ID! }
+            interface Named { name: String }
+            type User implements Named { id: ID! name: String! }
+            type Admin implements Node { id: ID! }
+            union Account = User
+            enum Role { ADMIN USER OWNER }
+            input Filter { name: [String] age: Int limit: Int! offset: Int }
+            enum Date { TODAY }
+            directive @auth(role: Role, scope: String) repeatable on FIELD_DEFINITION
+            directive @new on FIELD
+        "#;
+
+        assert_eq!(
+            diff(old, new),
+            vec![
+                (
+                    Safe,
+                    "`Query.user` changed type from `User` to `User!`".to_string()
+                ),
+                (
+                    Safe,
+                    "`Query.user(id:)` changed type from `ID!` to `ID`".to_string()
+                ),
+                (
+                    Dangerous,
+                    "`Query.user(first:)` changed default value from `10` to `20`".to_string()
+                ),
+                (Breaking, "`Query.user(format:)` was removed".to_string()),
+                (
+                    Dangerous,
+                    "optional argument `Query.user(locale:)` was added".to_string()
+                ),
+                (
+                    Breaking,
+                    "required argument `Query.user(strict:)` was added".to_string()
+                ),
+                (
+                    Safe,
+                    "`Query.users` changed type from `[User]` to `[User!]`".to_string()
+                ),
+                (
+                    Breaking,
+                    "`Query.legacy` changed type from `String` to `Int`".to_string()
+                ),
+                (Breaking, "`User` no longer implements `Node`".to_string()),
+                (Dangerous, "`User` now implements `Named`".to_string()),
+                (
+                    Safe,
+                    "`User.name` changed type from `String` to `String!`".to_string()
+                ),
+                (Breaking, "`User.age` was removed".to_string()),
+                (Dangerous, "`Admin` now implements `Node`".to_string()),
+                (
+                    Breaking,
+                    "`Admin` was removed from union `Account`".to_string()
+                ),
+                (Breaking, "`GUEST` was removed from enum `Role`".to_string()),
+                (Dangerous, "`OWNER` was added to enum `Role`".to_string()),
+                (
+                    Breaking,
+                    "`Filter.name` changed type from `String` to `[String]`".to_string()
+                ),
+                (
+                    Safe,
+                    "`Filter.age` changed type from `Int!` to `Int`".to_string()
+                ),
+                (
+                    Breaking,
+                    "required input field `Filter.limit` was added".to_string()
+                ),
+                (
+                    Dangerous,
+                    "optional input field `Filter.offset` was added".to_string()
+                ),
+                (
+                    Breaking,
+                    "`Date` changed from a scalar to an enum".to_string()
+                ),
+                (Safe, "`Named` was added".to_string()),
+                (
+                    Safe,
+                    "optional argument `@auth(scope:)` was added".to_string()
+                ),
+                (Safe, "`@auth` is now repeatable".to_string()),
+                (
+                    Breaking,
+                    "`@auth` can no longer be used on `OBJECT`".to_string()
+                ),
+                (Breaking, "`@old` was removed".to_string()),
+                (Safe, "`@new` was added".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_default_value_change_alongside_type_change() {
+        use Criticality::*;
+
+        let old = "type Query { users(first: Int = 10): [String] }";
+        let new = "type Query { users(first: Int! = 20): [String] }";
+
+        assert_eq!(
+            diff(old, new),
+            vec![
+                (
+                    Breaking,
+                    "`Query.users(first:)` changed type from `Int` to `Int!`".to_string()
+                ),
+                (
+                    Dangerous,
+                    "`Query.users(first:)` changed default value from `10` to `20`".to_string()
+                ),
+            ]
+        );
+    }
+}
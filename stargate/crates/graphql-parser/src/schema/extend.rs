@@ -0,0 +1,510 @@
+//! Merging type extensions into the types they extend
+//!
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::position::{Pos, Span};
+use crate::schema::generic::*;
+
+/// Error applying a type extension
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExtensionError {
+    #[error("cannot extend unknown type `{name}`")]
+    UnknownType { name: String, position: Pos },
+    #[error("cannot extend `{definition} {name}` with `extend {extension}`")]
+    KindMismatch {
+        name: String,
+        definition: &'static str,
+        extension: &'static str,
+        position: Pos,
+    },
+    #[error("field `{name}` is already defined on `{type_name}`")]
+    DuplicateField {
+        type_name: String,
+        name: String,
+        position: Pos,
+    },
+    #[error("value `{name}` is already defined on `{type_name}`")]
+    DuplicateEnumValue {
+        type_name: String,
+        name: String,
+        position: Pos,
+    },
+    #[error("type `{name}` is already a member of `{type_name}`")]
+    DuplicateUnionMember {
+        type_name: String,
+        name: String,
+        position: Pos,
+    },
+    #[error("`{type_name}` already implements `{name}`")]
+    DuplicateInterface {
+        type_name: String,
+        name: String,
+        position: Pos,
+    },
+    #[error("the schema already has a {operation} root type")]
+    DuplicateOperation {
+        operation: &'static str,
+        position: Pos,
+    },
+}
+
+impl ExtensionError {
+    /// Where the offending extension, or member of the extension, starts.
+    pub fn position(&self) -> Pos {
+        match self {
+            ExtensionError::UnknownType { position, .. }
+            | ExtensionError::KindMismatch { position, .. }
+            | ExtensionError::DuplicateField { position, .. }
+            | ExtensionError::DuplicateEnumValue { position, .. }
+            | ExtensionError::DuplicateUnionMember { position, .. }
+            | ExtensionError::DuplicateInterface { position, .. }
+            | ExtensionError::DuplicateOperation { position, .. } => *position,
+        }
+    }
+}
+
+/// Merges every `extend` definition of `doc` into the type it extends.
+///
+/// The members and directives of an extension are appended to the extended
+/// type, which keeps its place in the document, and the extension is removed.
+/// Extensions may come before the type they extend. `extend schema` extends
+/// the schema definition, which is added first in the document when there is
+/// none, with the `Query`, `Mutation` and `Subscription` types it implies.
+/// All errors are reported, not only the first one.
+pub fn apply_extensions<S: Text>(doc: Document<S>) -> Result<Document<S>, Vec<ExtensionError>> {
+    match merge_extensions(doc) {
+        (doc, errors) if errors.is_empty() => Ok(doc),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Merges what it can of the extensions of `doc`, returning the merged document along with the
+/// errors of the extensions, or parts of them, which couldn't be merged.
+pub(crate) fn merge_extensions<S: Text>(doc: Document<S>) -> (Document<S>, Vec<ExtensionError>) {
+    let mut definitions = Vec::with_capacity(doc.definitions.len());
+    let mut extensions = vec![];
+    let mut schema_extensions = vec![];
+    for def in doc.definitions {
+        match def {
+            Definition::TypeExtension(ext) => extensions.push(ext),
+            Definition::SchemaExtension(ext) => schema_extensions.push(ext),
+            def => definitions.push(def),
+        }
+    }
+
+    let types: HashMap<String, usize> = definitions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, def)| match def {
+            Definition::Type(ty) => Some((type_name(ty).as_ref().to_owned(), i)),
+            _ => None,
+        })
+        .collect();
+
+    let mut errors = vec![];
+    for ext in extensions {
+        let (name, position) = extension_name(&ext);
+        let ty = match types.get(name.as_ref()) {
+            Some(&i) => match &mut definitions[i] {
+                Definition::Type(ty) => ty,
+                _ => unreachable!("only type definitions are indexed"),
+            },
+            None => {
+                errors.push(ExtensionError::UnknownType {
+                    name: name.as_ref().to_owned(),
+                    position,
+                });
+                continue;
+            }
+        };
+        extend(ty, ext, &mut errors);
+    }
+
+    if !schema_extensions.is_empty() {
+        let index = match definitions
+            .iter()
+            .position(|def| matches!(def, Definition::Schema(_)))
+        {
+            Some(index) => index,
+            None => {
+                let implied = |name: &str| match types.get(name).map(|&i| &definitions[i]) {
+                    Some(Definition::Type(TypeDefinition::Object(object))) => {
+                        Some(object.name.clone())
+                    }
+                    _ => None,
+                };
+                let schema = SchemaDefinition {
+                    position: Pos::default(),
+                    span: Span::default(),
+                    description: None,
+                    directives: vec![],
+                    query: implied("Query"),
+                    mutation: implied("Mutation"),
+                    subscription: implied("Subscription"),
+                };
+                definitions.insert(0, Definition::Schema(schema));
+                0
+            }
+        };
+        if let Definition::Schema(schema) = &mut definitions[index] {
+            for ext in schema_extensions {
+                extend_schema(schema, ext, &mut errors);
+            }
+        }
+    }
+
+    (Document { definitions }, errors)
+}
+
+fn extend<S: Text>(
+    ty: &mut TypeDefinition<S>,
+    ext: TypeExtension<S>,
+    errors: &mut Vec<ExtensionError>,
+) {
+    match (ty, ext) {
+        (TypeDefinition::Scalar(ty), TypeExtension::Scalar(ext)) => {
+            ty.directives.extend(ext.directives);
+        }
+        (TypeDefinition::Object(ty), TypeExtension::Object(ext)) => {
+            merge_interfaces(
+                &ty.name,
+                &mut ty.implements_interfaces,
+                ext.implements_interfaces,
+                ext.position,
+                errors,
+            );
+            ty.directives.extend(ext.directives);
+            merge_fields(&ty.name, &mut ty.fields, ext.fields, errors);
+        }
+        (TypeDefinition::Interface(ty), TypeExtension::Interface(ext)) => {
+            merge_interfaces(
+                &ty.name,
+                &mut ty.implements_interfaces,
+                ext.implements_interfaces,
+                ext.position,
+                errors,
+            );
+            ty.directives.extend(ext.directives);
+            merge_fields(&ty.name, &mut ty.fields, ext.fields, errors);
+        }
+        (TypeDefinition::Union(ty), TypeExtension::Union(ext)) => {
+            ty.directives.extend(ext.directives);
+            for member in ext.types {
+                if ty.types.contains(&member) {
+                    errors.push(ExtensionError::DuplicateUnionMember {
+                        type_name: ty.name.as_ref().to_owned(),
+                        name: member.into(),
+                        position: ext.position,
+                    });
+                } else {
+                    ty.types.push(member);
+                }
+            }
+        }
+        (TypeDefinition::Enum(ty), TypeExtension::Enum(ext)) => {
+            ty.directives.extend(ext.directives);
+            for value in ext.values {
+                if ty.values.iter().any(|v| v.name == value.name) {
+                    errors.push(ExtensionError::DuplicateEnumValue {
+                        type_name: ty.name.as_ref().to_owned(),
+                        name: value.name.into(),
+                        position: value.position,
+                    });
+                } else {
+                    ty.values.push(value);
+                }
+            }
+        }
+        (TypeDefinition::InputObject(ty), TypeExtension::InputObject(ext)) => {
+            ty.directives.extend(ext.directives);
+            for field in ext.fields {
+                if ty.fields.iter().any(|f| f.name == field.name) {
+                    errors.push(ExtensionError::DuplicateField {
+                        type_name: ty.name.as_ref().to_owned(),
+                        name: field.name.into(),
+                        position: field.position,
+                    });
+                } else {
+                    ty.fields.push(field);
+                }
+            }
+        }
+        (ty, ext) => {
+            let (name, position) = extension_name(&ext);
+            errors.push(ExtensionError::KindMismatch {
+                name: name.as_ref().to_owned(),
+                definition: type_keyword(ty),
+                extension: extension_keyword(&ext),
+                position,
+            });
+        }
+    }
+}
+
+fn extend_schema<S: Text>(
+    schema: &mut SchemaDefinition<S>,
+    ext: SchemaExtension<S>,
+    errors: &mut Vec<ExtensionError>,
+) {
+    schema.directives.extend(ext.directives);
+    let operations = vec![
+        ("query", &mut schema.query, ext.query),
+        ("mutation", &mut schema.mutation, ext.mutation),
+        ("subscription", &mut schema.subscription, ext.subscription),
+    ];
+    for (operation, root, extension) in operations {
+        if extension.is_none() {
+            continue;
+        }
+        if root.is_some() {
+            errors.push(ExtensionError::DuplicateOperation {
+                operation,
+                position: ext.position,
+            });
+        } else {
+            *root = extension;
+        }
+    }
+}
+
+fn merge_fields<S: Text>(
+    type_name: &S,
+    fields: &mut Vec<Field<S>>,
+    extension: Vec<Field<S>>,
+    errors: &mut Vec<ExtensionError>,
+) {
+    for field in extension {
+        if fields.iter().any(|f| f.name == field.name) {
+            errors.push(ExtensionError::DuplicateField {
+                type_name: type_name.as_ref().to_owned(),
+                name: field.name.into(),
+                position: field.position,
+            });
+        } else {
+            fields.push(field);
+        }
+    }
+}
+
+fn merge_interfaces<S: Text>(
+    type_name: &S,
+    interfaces: &mut Vec<S>,
+    extension: Vec<S>,
+    position: Pos,
+    errors: &mut Vec<ExtensionError>,
+) {
+    for interface in extension {
+        if interfaces.contains(&interface) {
+            errors.push(ExtensionError::DuplicateInterface {
+                type_name: type_name.as_ref().to_owned(),
+                name: interface.into(),
+                position,
+            });
+        } else {
+            interfaces.push(interface);
+        }
+    }
+}
+
+fn type_name<S>(ty: &TypeDefinition<S>) -> &S {
+    match ty {
+        TypeDefinition::Scalar(t) => &t.name,
+        TypeDefinition::Object(t) => &t.name,
+        TypeDefinition::Interface(t) => &t.name,
+        TypeDefinition::Union(t) => &t.name,
+        TypeDefinition::Enum(t) => &t.name,
+        TypeDefinition::InputObject(t) => &t.name,
+    }
+}
+
+fn extension_name<S>(ext: &TypeExtension<S>) -> (&S, Pos) {
+    match ext {
+        TypeExtension::Scalar(e) => (&e.name, e.position),
+        TypeExtension::Object(e) => (&e.name, e.position),
+        TypeExtension::Interface(e) => (&e.name, e.position),
+        TypeExtension::Union(e) => (&e.name, e.position),
+        TypeExtension::Enum(e) => (&e.name, e.position),
+        TypeExtension::InputObject(e) => (&e.name, e.position),
+    }
+}
+
+fn type_keyword<S>(ty: &TypeDefinition<S>) -> &'static str {
+    match ty {
+        TypeDefinition::Scalar(_) => "scalar",
+        TypeDefinition::Object(_) => "type",
+        TypeDefinition::Interface(_) => "interface",
+        TypeDefinition::Union(_) => "union",
+        TypeDefinition::Enum(_) => "enum",
+        TypeDefinition::InputObject(_) => "input",
+    }
+}
+
+fn extension_keyword<S>(ext: &TypeExtension<S>) -> &'static str {
+    match ext {
+        TypeExtension::Scalar(_) => "scalar",
+        TypeExtension::Object(_) => "type",
+        TypeExtension::Interface(_) => "interface",
+        TypeExtension::Union(_) => "union",
+        TypeExtension::Enum(_) => "enum",
+        TypeExtension::InputObject(_) => "input",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_schema;
+
+    #[test]
+    fn merges_extensions() {
+        let doc = parse_schema(
+            r#"
+            extend type Query @key(fields: "id") {
+                me: User
+            }
+
+            type Query {
+                users: [User]
+            }
+
+            interface Node { id: ID! }
+
+            type User { id: ID! }
+
+            extend type User implements Node {
+                name: String
+            }
+
+            scalar Date
+            extend scalar Date @specifiedBy(url: "https://example.com")
+
+            union Result = User
+            extend union Result = Query
+
+            enum Color { RED }
+            extend enum Color { GREEN }
+
+            input Filter { first: Int }
+            extend input Filter { after: String }
+
+            extend interface Node { createdAt: Date }
+            "#,
+        )
+        .unwrap();
+
+        let merged = apply_extensions(doc).unwrap();
+        assert_eq!(
+            merged.to_string(),
+            r#"type Query @key(fields: "id") {
+  users: [User]
+  me: User
+}
+
+interface Node {
+  id: ID!
+  createdAt: Date
+}
+
+type User implements Node {
+  id: ID!
+  name: String
+}
+
+scalar Date @specifiedBy(url: "https://example.com")
+
+union Result = User | Query
+
+enum Color {
+  RED
+  GREEN
+}
+
+input Filter {
+  first: Int
+  after: String
+}
+"#
+        );
+    }
+
+    #[test]
+    fn merges_schema_extensions() {
+        let doc = parse_schema(
+            r#"
+            type Query { me: String }
+            type Subscription { ticks: Int }
+            extend schema @link(url: "https://example.com") { mutation: Query }
+            extend schema @composed
+            "#,
+        )
+        .unwrap();
+
+        let merged = apply_extensions(doc).unwrap();
+        assert_eq!(
+            merged.to_string(),
+            r#"schema @link(url: "https://example.com") @composed {
+  query: Query
+  mutation: Query
+  subscription: Subscription
+}
+
+type Query {
+  me: String
+}
+
+type Subscription {
+  ticks: Int
+}
+"#
+        );
+
+        let doc = parse_schema(
+            r#"
+            schema { query: Query }
+            type Query { me: String }
+            extend schema { query: Query }
+            "#,
+        )
+        .unwrap();
+        let errors = apply_extensions(doc).unwrap_err();
+        assert_eq!(
+            errors[0].to_string(),
+            "the schema already has a query root type"
+        );
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let doc = parse_schema(
+            r#"
+            type Query { me: User }
+            enum Color { RED }
+            union Result = Query
+
+            extend type Query { me: User }
+            extend type Unknown { field: Int }
+            extend enum Query { RED }
+            extend enum Color { RED }
+            extend union Result = Query
+            "#,
+        )
+        .unwrap();
+
+        let errors = apply_extensions(doc).unwrap_err();
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "field `me` is already defined on `Query`",
+                "cannot extend unknown type `Unknown`",
+                "cannot extend `type Query` with `extend enum`",
+                "value `RED` is already defined on `Color`",
+                "type `Query` is already a member of `Result`",
+            ]
+        );
+        let position = errors[0].position();
+        assert_eq!((position.line, position.column), (6, 33));
+    }
+}
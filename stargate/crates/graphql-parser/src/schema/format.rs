@@ -1,10 +1,10 @@
 use std::fmt;
 
-use crate::format::{format_directives, Displayable, Formatter, Style};
+use crate::format::{format_directives, Displayable, Formatter, SortKey, Style};
 
-use crate::schema::ast::*;
+use crate::schema::generic::*;
 
-impl<'a> Document<'a> {
+impl<S: Text> Document<S> {
     /// Format a document according to style
     pub fn format(&self, style: &Style) -> String {
         let mut formatter = Formatter::new(style);
@@ -28,7 +28,7 @@ fn description(description: &Option<String>, f: &mut Formatter) {
     }
 }
 
-impl<'a> Displayable for Document<'a> {
+impl<S: Text> Displayable for Document<S> {
     fn display(&self, f: &mut Formatter) {
         for item in &self.definitions {
             item.display(f);
@@ -36,13 +36,17 @@ impl<'a> Displayable for Document<'a> {
     }
 }
 
-impl<'a> Displayable for Definition<'a> {
+impl<S: Text> Displayable for Definition<S> {
     fn display(&self, f: &mut Formatter) {
         match *self {
             Definition::Schema(ref s) => {
                 f.margin();
                 s.display(f)
             }
+            Definition::SchemaExtension(ref e) => {
+                f.margin();
+                e.display(f)
+            }
             Definition::Type(ref t) => {
                 f.margin();
                 t.display(f)
@@ -61,36 +65,59 @@ impl<'a> Displayable for Definition<'a> {
     }
 }
 
-impl<'a> Displayable for SchemaDefinition<'a> {
+fn format_root_operations<S: Text>(
+    query: &Option<S>,
+    mutation: &Option<S>,
+    subscription: &Option<S>,
+    f: &mut Formatter,
+) {
+    f.write(" ");
+    f.start_block();
+    if let Some(ref q) = query {
+        f.indent();
+        f.write("query: ");
+        f.write(q.as_ref());
+        f.endline();
+    }
+    if let Some(ref m) = mutation {
+        f.indent();
+        f.write("mutation: ");
+        f.write(m.as_ref());
+        f.endline();
+    }
+    if let Some(ref s) = subscription {
+        f.indent();
+        f.write("subscription: ");
+        f.write(s.as_ref());
+        f.endline();
+    }
+    f.end_block();
+}
+
+impl<S: Text> Displayable for SchemaDefinition<S> {
     fn display(&self, f: &mut Formatter) {
+        description(&self.description, f);
         f.indent();
         f.write("schema");
         format_directives(&self.directives, f);
-        f.write(" ");
-        f.start_block();
-        if let Some(ref q) = self.query {
-            f.indent();
-            f.write("query: ");
-            f.write(q.as_ref());
-            f.endline();
-        }
-        if let Some(ref m) = self.mutation {
-            f.indent();
-            f.write("mutation: ");
-            f.write(m.as_ref());
-            f.endline();
-        }
-        if let Some(ref s) = self.subscription {
-            f.indent();
-            f.write("subscription: ");
-            f.write(s.as_ref());
+        format_root_operations(&self.query, &self.mutation, &self.subscription, f);
+    }
+}
+
+impl<S: Text> Displayable for SchemaExtension<S> {
+    fn display(&self, f: &mut Formatter) {
+        f.indent();
+        f.write("extend schema");
+        format_directives(&self.directives, f);
+        if self.query.is_some() || self.mutation.is_some() || self.subscription.is_some() {
+            format_root_operations(&self.query, &self.mutation, &self.subscription, f);
+        } else {
             f.endline();
         }
-        f.end_block();
     }
 }
 
-impl<'a> Displayable for TypeDefinition<'a> {
+impl<S: Text> Displayable for TypeDefinition<S> {
     fn display(&self, f: &mut Formatter) {
         match *self {
             TypeDefinition::Scalar(ref s) => s.display(f),
@@ -103,7 +130,7 @@ impl<'a> Displayable for TypeDefinition<'a> {
     }
 }
 
-impl<'a> Displayable for ScalarType<'a> {
+impl<S: Text> Displayable for ScalarType<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -114,7 +141,7 @@ impl<'a> Displayable for ScalarType<'a> {
     }
 }
 
-impl<'a> Displayable for ScalarTypeExtension<'a> {
+impl<S: Text> Displayable for ScalarTypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("extend scalar ");
@@ -124,11 +151,11 @@ impl<'a> Displayable for ScalarTypeExtension<'a> {
     }
 }
 
-fn format_fields<'a>(fields: &[Field<'a>], f: &mut Formatter) {
+fn format_fields<S: Text>(fields: &[Field<S>], f: &mut Formatter) {
     if !fields.is_empty() {
         f.write(" ");
         f.start_block();
-        for fld in fields {
+        for fld in f.sorted(fields) {
             fld.display(f);
         }
         f.end_block();
@@ -137,7 +164,7 @@ fn format_fields<'a>(fields: &[Field<'a>], f: &mut Formatter) {
     }
 }
 
-impl<'a> Displayable for ObjectType<'a> {
+impl<S: Text> Displayable for ObjectType<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -149,7 +176,7 @@ impl<'a> Displayable for ObjectType<'a> {
     }
 }
 
-fn format_interfaces<'a>(implements_interfaces: &[Txt<'a>], f: &mut Formatter) {
+fn format_interfaces<S: Text>(implements_interfaces: &[S], f: &mut Formatter) {
     if !implements_interfaces.is_empty() {
         f.write(" implements ");
         f.write(implements_interfaces[0].as_ref());
@@ -160,7 +187,7 @@ fn format_interfaces<'a>(implements_interfaces: &[Txt<'a>], f: &mut Formatter) {
     }
 }
 
-impl<'a> Displayable for ObjectTypeExtension<'a> {
+impl<S: Text> Displayable for ObjectTypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("extend type ");
@@ -171,7 +198,7 @@ impl<'a> Displayable for ObjectTypeExtension<'a> {
     }
 }
 
-impl<'a> Displayable for InputValue<'a> {
+impl<S: Text> Displayable for InputValue<S> {
     fn display(&self, f: &mut Formatter) {
         if let Some(ref descr) = self.description {
             f.write_quoted(descr.as_ref());
@@ -188,19 +215,24 @@ impl<'a> Displayable for InputValue<'a> {
     }
 }
 
-fn format_arguments<'a>(arguments: &[InputValue<'a>], f: &mut Formatter) {
-    if !arguments.is_empty() {
-        f.write("(");
-        arguments[0].display(f);
-        for arg in &arguments[1..] {
-            f.write(", ");
-            arg.display(f);
-        }
-        f.write(")");
+impl<S: Text> SortKey for Field<S> {
+    fn sort_key(&self) -> (u8, &str) {
+        (0, self.name.as_ref())
+    }
+}
+
+impl<S: Text> SortKey for InputValue<S> {
+    fn sort_key(&self) -> (u8, &str) {
+        (0, self.name.as_ref())
     }
 }
 
-impl<'a> Displayable for Field<'a> {
+fn format_arguments<S: Text>(arguments: &[InputValue<S>], f: &mut Formatter) {
+    let arguments = f.sorted(arguments);
+    f.write_arguments(&arguments, |arg, f| arg.display(f));
+}
+
+impl<S: Text> Displayable for Field<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -213,7 +245,7 @@ impl<'a> Displayable for Field<'a> {
     }
 }
 
-impl<'a> Displayable for InterfaceType<'a> {
+impl<S: Text> Displayable for InterfaceType<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -225,17 +257,18 @@ impl<'a> Displayable for InterfaceType<'a> {
     }
 }
 
-impl<'a> Displayable for InterfaceTypeExtension<'a> {
+impl<S: Text> Displayable for InterfaceTypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("extend interface ");
         f.write(self.name.as_ref());
+        format_interfaces(&self.implements_interfaces, f);
         format_directives(&self.directives, f);
         format_fields(&self.fields, f);
     }
 }
 
-impl<'a> Displayable for UnionType<'a> {
+impl<S: Text> Displayable for UnionType<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -254,7 +287,7 @@ impl<'a> Displayable for UnionType<'a> {
     }
 }
 
-impl<'a> Displayable for UnionTypeExtension<'a> {
+impl<S: Text> Displayable for UnionTypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("extend union ");
@@ -272,7 +305,7 @@ impl<'a> Displayable for UnionTypeExtension<'a> {
     }
 }
 
-impl<'a> Displayable for EnumType<'a> {
+impl<S: Text> Displayable for EnumType<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -299,7 +332,7 @@ impl<'a> Displayable for EnumType<'a> {
     }
 }
 
-impl<'a> Displayable for EnumTypeExtension<'a> {
+impl<S: Text> Displayable for EnumTypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("extend enum ");
@@ -325,11 +358,11 @@ impl<'a> Displayable for EnumTypeExtension<'a> {
     }
 }
 
-fn format_inputs<'a>(fields: &[InputValue<'a>], f: &mut Formatter) {
+fn format_inputs<S: Text>(fields: &[InputValue<S>], f: &mut Formatter) {
     if !fields.is_empty() {
         f.write(" ");
         f.start_block();
-        for fld in fields {
+        for fld in f.sorted(fields) {
             f.indent();
             fld.display(f);
             f.endline();
@@ -340,7 +373,7 @@ fn format_inputs<'a>(fields: &[InputValue<'a>], f: &mut Formatter) {
     }
 }
 
-impl<'a> Displayable for InputObjectType<'a> {
+impl<S: Text> Displayable for InputObjectType<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
@@ -351,7 +384,7 @@ impl<'a> Displayable for InputObjectType<'a> {
     }
 }
 
-impl<'a> Displayable for InputObjectTypeExtension<'a> {
+impl<S: Text> Displayable for InputObjectTypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         f.indent();
         f.write("extend input ");
@@ -361,7 +394,7 @@ impl<'a> Displayable for InputObjectTypeExtension<'a> {
     }
 }
 
-impl<'a> Displayable for TypeExtension<'a> {
+impl<S: Text> Displayable for TypeExtension<S> {
     fn display(&self, f: &mut Formatter) {
         match *self {
             TypeExtension::Scalar(ref s) => s.display(f),
@@ -374,13 +407,16 @@ impl<'a> Displayable for TypeExtension<'a> {
     }
 }
 
-impl<'a> Displayable for DirectiveDefinition<'a> {
+impl<S: Text> Displayable for DirectiveDefinition<S> {
     fn display(&self, f: &mut Formatter) {
         description(&self.description, f);
         f.indent();
         f.write("directive @");
         f.write(self.name.as_ref());
         format_arguments(&self.arguments, f);
+        if self.repeatable {
+            f.write(" repeatable");
+        }
         if !self.locations.is_empty() {
             f.write(" on ");
             let mut first = true;
@@ -398,10 +434,11 @@ impl<'a> Displayable for DirectiveDefinition<'a> {
 }
 
 impl_display!(
-    'a
+    S
     Document,
     Definition,
     SchemaDefinition,
+    SchemaExtension,
     TypeDefinition,
     TypeExtension,
     ScalarType,
@@ -0,0 +1,887 @@
+//! Schema definition language AST generic over the text of its names
+//!
+//! `S` is `&str` for the nodes of `schema::ast`, which borrow their names
+//! from the parsed source, and `String` for the nodes of `schema::owned`.
+//!
+pub use crate::common::{Argument, Directive, StringValue, Text, Type, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::common::directives_into_static;
+use crate::position::{Pos, Span, Spanned};
+use crate::query::generic::{FragmentDefinition, OperationDefinition};
+use crate::schema::ast::DirectiveLocation;
+
+#[derive(Clone, Default, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct Document<S> {
+    pub definitions: Vec<Definition<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub enum Definition<S> {
+    Schema(SchemaDefinition<S>),
+    SchemaExtension(SchemaExtension<S>),
+    Type(TypeDefinition<S>),
+    TypeExtension(TypeExtension<S>),
+    Directive(DirectiveDefinition<S>),
+    Operation(OperationDefinition<S>),
+    Fragment(FragmentDefinition<S>),
+}
+
+#[derive(Clone, Default, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct SchemaDefinition<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub directives: Vec<Directive<S>>,
+    pub query: Option<S>,
+    pub mutation: Option<S>,
+    pub subscription: Option<S>,
+}
+
+#[derive(Clone, Default, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct SchemaExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub directives: Vec<Directive<S>>,
+    pub query: Option<S>,
+    pub mutation: Option<S>,
+    pub subscription: Option<S>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub enum TypeDefinition<S> {
+    Scalar(ScalarType<S>),
+    Object(ObjectType<S>),
+    Interface(InterfaceType<S>),
+    Union(UnionType<S>),
+    Enum(EnumType<S>),
+    InputObject(InputObjectType<S>),
+}
+
+impl<S> TypeDefinition<S> {
+    pub fn is_composite_type(&self) -> bool {
+        matches!(self, TypeDefinition::Object(_) | TypeDefinition::Interface(_) | TypeDefinition::Union(_))
+    }
+
+    pub fn is_abstract_type(&self) -> bool {
+        matches!(self, TypeDefinition::Interface(_) | TypeDefinition::Union(_))
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub enum TypeExtension<S> {
+    Scalar(ScalarTypeExtension<S>),
+    Object(ObjectTypeExtension<S>),
+    Interface(InterfaceTypeExtension<S>),
+    Union(UnionTypeExtension<S>),
+    Enum(EnumTypeExtension<S>),
+    InputObject(InputObjectTypeExtension<S>),
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct ScalarType<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+}
+
+impl<S> ScalarType<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            directives: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct ScalarTypeExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+}
+
+impl<S> ScalarTypeExtension<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            name,
+            directives: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct ObjectType<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub implements_interfaces: Vec<S>,
+    pub directives: Vec<Directive<S>>,
+    pub fields: Vec<Field<S>>,
+}
+
+impl<S> ObjectType<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            implements_interfaces: vec![],
+            directives: vec![],
+            fields: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct ObjectTypeExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub implements_interfaces: Vec<S>,
+    pub directives: Vec<Directive<S>>,
+    pub fields: Vec<Field<S>>,
+}
+
+impl<S> ObjectTypeExtension<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            name,
+            implements_interfaces: vec![],
+            directives: vec![],
+            fields: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct Field<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub arguments: Vec<InputValue<S>>,
+    pub field_type: Spanned<Type<S>>,
+    pub directives: Vec<Directive<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct InputValue<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub value_type: Spanned<Type<S>>,
+    pub default_value: Option<Spanned<Value<S>>>,
+    pub directives: Vec<Directive<S>>,
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct InterfaceType<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub implements_interfaces: Vec<S>,
+    pub directives: Vec<Directive<S>>,
+    pub fields: Vec<Field<S>>,
+}
+
+impl<S> InterfaceType<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            implements_interfaces: vec![],
+            directives: vec![],
+            fields: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct InterfaceTypeExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub implements_interfaces: Vec<S>,
+    pub directives: Vec<Directive<S>>,
+    pub fields: Vec<Field<S>>,
+}
+
+impl<S> InterfaceTypeExtension<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            name,
+            implements_interfaces: vec![],
+            directives: vec![],
+            fields: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct UnionType<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+    pub types: Vec<S>,
+}
+
+impl<S> UnionType<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            directives: vec![],
+            types: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct UnionTypeExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+    pub types: Vec<S>,
+}
+
+impl<S> UnionTypeExtension<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            name,
+            directives: vec![],
+            types: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct EnumType<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+    pub values: Vec<EnumValue<S>>,
+}
+
+impl<S> EnumType<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            directives: vec![],
+            values: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct EnumValue<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+}
+
+impl<S> EnumValue<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            directives: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct EnumTypeExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+    pub values: Vec<EnumValue<S>>,
+}
+
+impl<S> EnumTypeExtension<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            name,
+            directives: vec![],
+            values: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct InputObjectType<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+    pub fields: Vec<InputValue<S>>,
+}
+
+impl<S> InputObjectType<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            directives: vec![],
+            fields: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct InputObjectTypeExtension<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub name: S,
+    pub directives: Vec<Directive<S>>,
+    pub fields: Vec<InputValue<S>>,
+}
+
+impl<S> InputObjectTypeExtension<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            name,
+            directives: vec![],
+            fields: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Derivative, Serialize, Deserialize)]
+#[derivative(Debug(bound = "S: Text"), PartialEq(bound = "S: Text"))]
+#[serde(bound(
+    serialize = "S: Text + Serialize",
+    deserialize = "S: Text + Deserialize<'de>"
+))]
+pub struct DirectiveDefinition<S> {
+    #[derivative(PartialEq = "ignore")]
+    pub position: Pos,
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+    pub description: Option<String>,
+    pub name: S,
+    pub arguments: Vec<InputValue<S>>,
+    pub repeatable: bool,
+    pub locations: Vec<DirectiveLocation>,
+}
+
+impl<S> DirectiveDefinition<S> {
+    pub fn new(name: S) -> Self {
+        Self {
+            position: Pos::default(),
+            span: Span::default(),
+            description: None,
+            name,
+            arguments: vec![],
+            repeatable: false,
+            locations: vec![],
+        }
+    }
+}
+
+impl<S: Text> Document<S> {
+    /// Converts the document into one owning its names, which no longer
+    /// borrows from the source it was parsed from.
+    pub fn into_static(self) -> Document<String> {
+        Document {
+            definitions: self
+                .definitions
+                .into_iter()
+                .map(Definition::into_static)
+                .collect(),
+        }
+    }
+}
+
+impl<S: Text> Definition<S> {
+    /// Converts the definition into one owning its names.
+    pub fn into_static(self) -> Definition<String> {
+        match self {
+            Definition::Schema(s) => Definition::Schema(s.into_static()),
+            Definition::SchemaExtension(e) => Definition::SchemaExtension(e.into_static()),
+            Definition::Type(t) => Definition::Type(t.into_static()),
+            Definition::TypeExtension(e) => Definition::TypeExtension(e.into_static()),
+            Definition::Directive(d) => Definition::Directive(d.into_static()),
+            Definition::Operation(o) => Definition::Operation(o.into_static()),
+            Definition::Fragment(f) => Definition::Fragment(f.into_static()),
+        }
+    }
+}
+
+impl<S: Text> SchemaDefinition<S> {
+    /// Converts the schema definition into one owning its names.
+    pub fn into_static(self) -> SchemaDefinition<String> {
+        SchemaDefinition {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            directives: directives_into_static(self.directives),
+            query: self.query.map(Into::into),
+            mutation: self.mutation.map(Into::into),
+            subscription: self.subscription.map(Into::into),
+        }
+    }
+}
+
+impl<S: Text> SchemaExtension<S> {
+    /// Converts the schema extension into one owning its names.
+    pub fn into_static(self) -> SchemaExtension<String> {
+        SchemaExtension {
+            position: self.position,
+            span: self.span,
+            directives: directives_into_static(self.directives),
+            query: self.query.map(Into::into),
+            mutation: self.mutation.map(Into::into),
+            subscription: self.subscription.map(Into::into),
+        }
+    }
+}
+
+impl<S: Text> TypeDefinition<S> {
+    /// Converts the type definition into one owning its names.
+    pub fn into_static(self) -> TypeDefinition<String> {
+        match self {
+            TypeDefinition::Scalar(t) => TypeDefinition::Scalar(t.into_static()),
+            TypeDefinition::Object(t) => TypeDefinition::Object(t.into_static()),
+            TypeDefinition::Interface(t) => TypeDefinition::Interface(t.into_static()),
+            TypeDefinition::Union(t) => TypeDefinition::Union(t.into_static()),
+            TypeDefinition::Enum(t) => TypeDefinition::Enum(t.into_static()),
+            TypeDefinition::InputObject(t) => TypeDefinition::InputObject(t.into_static()),
+        }
+    }
+}
+
+impl<S: Text> TypeExtension<S> {
+    /// Converts the type extension into one owning its names.
+    pub fn into_static(self) -> TypeExtension<String> {
+        match self {
+            TypeExtension::Scalar(e) => TypeExtension::Scalar(e.into_static()),
+            TypeExtension::Object(e) => TypeExtension::Object(e.into_static()),
+            TypeExtension::Interface(e) => TypeExtension::Interface(e.into_static()),
+            TypeExtension::Union(e) => TypeExtension::Union(e.into_static()),
+            TypeExtension::Enum(e) => TypeExtension::Enum(e.into_static()),
+            TypeExtension::InputObject(e) => TypeExtension::InputObject(e.into_static()),
+        }
+    }
+}
+
+fn names_into_static<S: Text>(names: Vec<S>) -> Vec<String> {
+    names.into_iter().map(Into::into).collect()
+}
+
+fn fields_into_static<S: Text>(fields: Vec<Field<S>>) -> Vec<Field<String>> {
+    fields.into_iter().map(Field::into_static).collect()
+}
+
+fn input_values_into_static<S: Text>(values: Vec<InputValue<S>>) -> Vec<InputValue<String>> {
+    values.into_iter().map(InputValue::into_static).collect()
+}
+
+fn enum_values_into_static<S: Text>(values: Vec<EnumValue<S>>) -> Vec<EnumValue<String>> {
+    values.into_iter().map(EnumValue::into_static).collect()
+}
+
+impl<S: Text> ScalarType<S> {
+    /// Converts the scalar type into one owning its names.
+    pub fn into_static(self) -> ScalarType<String> {
+        ScalarType {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> ScalarTypeExtension<S> {
+    /// Converts the scalar type extension into one owning its names.
+    pub fn into_static(self) -> ScalarTypeExtension<String> {
+        ScalarTypeExtension {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> ObjectType<S> {
+    /// Converts the object type into one owning its names.
+    pub fn into_static(self) -> ObjectType<String> {
+        ObjectType {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            implements_interfaces: names_into_static(self.implements_interfaces),
+            directives: directives_into_static(self.directives),
+            fields: fields_into_static(self.fields),
+        }
+    }
+}
+
+impl<S: Text> ObjectTypeExtension<S> {
+    /// Converts the object type extension into one owning its names.
+    pub fn into_static(self) -> ObjectTypeExtension<String> {
+        ObjectTypeExtension {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            implements_interfaces: names_into_static(self.implements_interfaces),
+            directives: directives_into_static(self.directives),
+            fields: fields_into_static(self.fields),
+        }
+    }
+}
+
+impl<S: Text> Field<S> {
+    /// Converts the field into one owning its names.
+    pub fn into_static(self) -> Field<String> {
+        Field {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            arguments: input_values_into_static(self.arguments),
+            field_type: self.field_type.map(Type::into_static),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> InputValue<S> {
+    /// Converts the input value into one owning its names.
+    pub fn into_static(self) -> InputValue<String> {
+        InputValue {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            value_type: self.value_type.map(Type::into_static),
+            default_value: self
+                .default_value
+                .map(|value| value.map(Value::into_static)),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> InterfaceType<S> {
+    /// Converts the interface type into one owning its names.
+    pub fn into_static(self) -> InterfaceType<String> {
+        InterfaceType {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            implements_interfaces: names_into_static(self.implements_interfaces),
+            directives: directives_into_static(self.directives),
+            fields: fields_into_static(self.fields),
+        }
+    }
+}
+
+impl<S: Text> InterfaceTypeExtension<S> {
+    /// Converts the interface type extension into one owning its names.
+    pub fn into_static(self) -> InterfaceTypeExtension<String> {
+        InterfaceTypeExtension {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            implements_interfaces: names_into_static(self.implements_interfaces),
+            directives: directives_into_static(self.directives),
+            fields: fields_into_static(self.fields),
+        }
+    }
+}
+
+impl<S: Text> UnionType<S> {
+    /// Converts the union type into one owning its names.
+    pub fn into_static(self) -> UnionType<String> {
+        UnionType {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+            types: names_into_static(self.types),
+        }
+    }
+}
+
+impl<S: Text> UnionTypeExtension<S> {
+    /// Converts the union type extension into one owning its names.
+    pub fn into_static(self) -> UnionTypeExtension<String> {
+        UnionTypeExtension {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+            types: names_into_static(self.types),
+        }
+    }
+}
+
+impl<S: Text> EnumType<S> {
+    /// Converts the enum type into one owning its names.
+    pub fn into_static(self) -> EnumType<String> {
+        EnumType {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+            values: enum_values_into_static(self.values),
+        }
+    }
+}
+
+impl<S: Text> EnumValue<S> {
+    /// Converts the enum value into one owning its names.
+    pub fn into_static(self) -> EnumValue<String> {
+        EnumValue {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+        }
+    }
+}
+
+impl<S: Text> EnumTypeExtension<S> {
+    /// Converts the enum type extension into one owning its names.
+    pub fn into_static(self) -> EnumTypeExtension<String> {
+        EnumTypeExtension {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+            values: enum_values_into_static(self.values),
+        }
+    }
+}
+
+impl<S: Text> InputObjectType<S> {
+    /// Converts the input object type into one owning its names.
+    pub fn into_static(self) -> InputObjectType<String> {
+        InputObjectType {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+            fields: input_values_into_static(self.fields),
+        }
+    }
+}
+
+impl<S: Text> InputObjectTypeExtension<S> {
+    /// Converts the input object type extension into one owning its names.
+    pub fn into_static(self) -> InputObjectTypeExtension<String> {
+        InputObjectTypeExtension {
+            position: self.position,
+            span: self.span,
+            name: self.name.into(),
+            directives: directives_into_static(self.directives),
+            fields: input_values_into_static(self.fields),
+        }
+    }
+}
+
+impl<S: Text> DirectiveDefinition<S> {
+    /// Converts the directive definition into one owning its names.
+    pub fn into_static(self) -> DirectiveDefinition<String> {
+        DirectiveDefinition {
+            position: self.position,
+            span: self.span,
+            description: self.description,
+            name: self.name.into(),
+            arguments: input_values_into_static(self.arguments),
+            repeatable: self.repeatable,
+            locations: self.locations,
+        }
+    }
+}
@@ -1,601 +1,633 @@
-use combine::easy::{Error, Errors};
-use combine::error::StreamError;
-use combine::parser::choice::{choice, optional};
-use combine::parser::item::{eof, position};
-use combine::parser::repeat::{many, many1, sep_by1};
-use combine::{parser, ParseResult, Parser};
-
 use crate::common::{default_value, directives, parse_type, string};
-use crate::helpers::{ident, kind, name, punct};
+use crate::helpers::{parse_recovering, ParseResult, Parser};
+use crate::position::Span;
 use crate::query::{fragment_definition, operation_definition};
 use crate::schema::ast::*;
-use crate::tokenizer::{Kind as T, Token, TokenStream};
-use crate::ParseError;
-
-pub fn schema<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<SchemaDefinition<'a>, TokenStream<'a>> {
-    (
-        position().skip(ident("schema")),
-        parser(directives),
-        punct("{")
-            .with(many((kind(T::Name).skip(punct(":")), name::<'a>())))
-            .skip(punct("}")),
-    )
-        .flat_map(
-            |(position, directives, operations): (_, _, Vec<(Token, _)>)| {
-                let mut query = None;
-                let mut mutation = None;
-                let mut subscription = None;
-                let mut err = Errors::empty(position);
-                for (oper, type_name) in operations {
-                    match oper.value {
-                        "query" if query.is_some() => {
-                            err.add_error(Error::unexpected_static_message(
-                                "duplicate `query` operation",
-                            ));
-                        }
-                        "query" => {
-                            query = Some(type_name);
-                        }
-                        "mutation" if mutation.is_some() => {
-                            err.add_error(Error::unexpected_static_message(
-                                "duplicate `mutation` operation",
-                            ));
-                        }
-                        "mutation" => {
-                            mutation = Some(type_name);
-                        }
-                        "subscription" if subscription.is_some() => {
-                            err.add_error(Error::unexpected_static_message(
-                                "duplicate `subscription` operation",
-                            ));
-                        }
-                        "subscription" => {
-                            subscription = Some(type_name);
-                        }
-                        _ => {
-                            err.add_error(Error::unexpected_token(oper));
-                            err.add_error(Error::expected_static_message("query"));
-                            err.add_error(Error::expected_static_message("mutation"));
-                            err.add_error(Error::expected_static_message("subscription"));
-                        }
-                    }
-                }
-                if !err.errors.is_empty() {
-                    return Err(err);
-                }
-                Ok(SchemaDefinition {
-                    position,
-                    directives,
-                    query,
-                    mutation,
-                    subscription,
-                })
-            },
-        )
-        .parse_stream(input)
-}
-
-pub fn scalar_type<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<ScalarType<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("scalar").with(name::<'a>()),
-        parser(directives),
-    )
-        .map(|(position, name, directives)| ScalarType {
+use crate::tokenizer::{Kind as T, Token};
+use crate::{ParseError, ParserOptions};
+
+/// The `query`, `mutation` and `subscription` root types of a schema
+/// definition or extension.
+type RootOperations<'a> = (Option<Txt<'a>>, Option<Txt<'a>>, Option<Txt<'a>>);
+
+pub fn root_operations<'a>(input: &mut Parser<'a>) -> ParseResult<RootOperations<'a>> {
+    input.punct("{")?;
+    let mut query = None;
+    let mut mutation = None;
+    let mut subscription = None;
+    while !input.eat_punct("}")? {
+        let position = input.position();
+        let (operation, root) = if input.eat_ident("query")? {
+            ("query", &mut query)
+        } else if input.eat_ident("mutation")? {
+            ("mutation", &mut mutation)
+        } else if input.eat_ident("subscription")? {
+            ("subscription", &mut subscription)
+        } else {
+            return Err(input.unexpected());
+        };
+        input.punct(":")?;
+        let name = input.name()?;
+        if root.is_some() {
+            return Err(input.invalid(position, format!("duplicate `{}` operation", operation)));
+        }
+        *root = Some(name);
+    }
+    Ok((query, mutation, subscription))
+}
+
+pub fn schema<'a>(input: &mut Parser<'a>) -> ParseResult<SchemaDefinition<'a>> {
+    let position = input.position();
+    input.ident("schema")?;
+    let directives = directives(input)?;
+    let (query, mutation, subscription) = root_operations(input)?;
+    Ok(SchemaDefinition {
+        position,
+        span: Span::new(position, input.end_position()),
+        description: None, // is filled in described_definition
+        directives,
+        query,
+        mutation,
+        subscription,
+    })
+}
+
+pub fn schema_extension<'a>(input: &mut Parser<'a>) -> ParseResult<SchemaExtension<'a>> {
+    let position = input.position();
+    input.ident("schema")?;
+    let directives = directives(input)?;
+    let operations = if input.is_punct("{") {
+        Some(root_operations(input)?)
+    } else {
+        None
+    };
+    if directives.is_empty() && operations.is_none() {
+        return Err(input.invalid(
             position,
-            description: None,
-            name,
-            directives,
-        })
-        .parse_stream(input)
-}
-
-pub fn scalar_type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<ScalarTypeExtension<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("scalar").with(name::<'a>()),
-        parser(directives),
-    )
-        .flat_map(|(position, name, directives)| {
-            if directives.is_empty() {
-                let mut e = Errors::empty(position);
-                e.add_error(Error::expected_static_message(
-                    "Scalar type extension should contain at least \
-                 one directive.",
-                ));
-                return Err(e);
-            }
-            Ok(ScalarTypeExtension {
-                position,
-                name,
-                directives,
-            })
-        })
-        .parse_stream(input)
-}
-
-pub fn implements_interfaces<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<Txt<'a>>, TokenStream<'a>> {
-    optional(
-        ident("implements")
-            .skip(optional(punct("&")))
-            .with(sep_by1(name(), punct("&"))),
-    )
-    .map(|opt| opt.unwrap_or_else(Vec::new))
-    .parse_stream(input)
-}
-
-pub fn input_value<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<InputValue<'a>, TokenStream<'a>> {
-    (
-        position(),
-        optional(parser(string)),
-        name::<'a>(),
-        punct(":").with(parser(parse_type)),
-        optional(punct("=").with(parser(default_value))),
-        parser(directives),
-    )
-        .map(
-            |(position, description, name, value_type, default_value, directives)| InputValue {
-                position,
-                description,
-                name,
-                value_type,
-                default_value,
-                directives,
-            },
-        )
-        .parse_stream(input)
-}
-
-pub fn arguments_definition<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<InputValue<'a>>, TokenStream<'a>> {
-    optional(punct("(").with(many1(parser(input_value))).skip(punct(")")))
-        .map(|v| v.unwrap_or_else(Vec::new))
-        .parse_stream(input)
-}
-
-pub fn field<'a>(input: &mut TokenStream<'a>) -> ParseResult<Field<'a>, TokenStream<'a>> {
-    (
-        position(),
-        optional(parser(string)),
-        name::<'a>(),
-        parser(arguments_definition),
-        punct(":").with(parser(parse_type)),
-        parser(directives),
-    )
-        .map(
-            |(position, description, name, arguments, field_type, directives)| Field {
-                position,
-                description,
-                name,
-                arguments,
-                field_type,
-                directives,
-            },
-        )
-        .parse_stream(input)
-}
-
-pub fn fields<'a>(input: &mut TokenStream<'a>) -> ParseResult<Vec<Field<'a>>, TokenStream<'a>> {
-    optional(punct("{").with(many1(parser(field))).skip(punct("}")))
-        .map(|v| v.unwrap_or_else(Vec::new))
-        .parse_stream(input)
-}
-
-pub fn object_type<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<ObjectType<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("type").with(name::<'a>()),
-        parser(implements_interfaces),
-        parser(directives),
-        parser(fields),
-    )
-        .map(|(position, name, interfaces, directives, fields)| {
-            ObjectType {
-                position,
-                name,
-                directives,
-                fields,
-                implements_interfaces: interfaces,
-                description: None, // is filled in described_definition
+            "Schema extension should contain at least one directive or operation.".to_string(),
+        ));
+    }
+    let (query, mutation, subscription) = operations.unwrap_or_default();
+    Ok(SchemaExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        directives,
+        query,
+        mutation,
+        subscription,
+    })
+}
+
+pub fn scalar_type<'a>(input: &mut Parser<'a>) -> ParseResult<ScalarType<'a>> {
+    let position = input.position();
+    input.ident("scalar")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    Ok(ScalarType {
+        position,
+        span: Span::new(position, input.end_position()),
+        description: None,
+        name,
+        directives,
+    })
+}
+
+pub fn scalar_type_extension<'a>(input: &mut Parser<'a>) -> ParseResult<ScalarTypeExtension<'a>> {
+    let position = input.position();
+    input.ident("scalar")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    if directives.is_empty() {
+        return Err(input.invalid(
+            position,
+            "Scalar type extension should contain at least one directive.".to_string(),
+        ));
+    }
+    Ok(ScalarTypeExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+    })
+}
+
+pub fn implements_interfaces<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<Txt<'a>>> {
+    let mut interfaces = vec![];
+    if input.eat_ident("implements")? {
+        input.eat_punct("&")?;
+        loop {
+            interfaces.push(input.name()?);
+            if !input.eat_punct("&")? {
+                break;
             }
-        })
-        .parse_stream(input)
-}
-
-pub fn object_type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<ObjectTypeExtension<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("type").with(name::<'a>()),
-        parser(implements_interfaces),
-        parser(directives),
-        parser(fields),
-    )
-        .flat_map(|(position, name, interfaces, directives, fields)| {
-            if interfaces.is_empty() && directives.is_empty() && fields.is_empty() {
-                let mut e = Errors::empty(position);
-                e.add_error(Error::expected_static_message(
-                    "Object type extension should contain at least \
-                     one interface, directive or field.",
-                ));
-                return Err(e);
+        }
+    }
+    Ok(interfaces)
+}
+
+pub fn input_value<'a>(input: &mut Parser<'a>) -> ParseResult<InputValue<'a>> {
+    let position = input.position();
+    let description = string(input)?;
+    let name = input.name()?;
+    input.punct(":")?;
+    let value_type = input.spanned(parse_type)?;
+    let default_value = if input.eat_punct("=")? {
+        Some(default_value(input)?)
+    } else {
+        None
+    };
+    let directives = directives(input)?;
+    Ok(InputValue {
+        position,
+        span: Span::new(position, input.end_position()),
+        description,
+        name,
+        value_type,
+        default_value,
+        directives,
+    })
+}
+
+/// Parses `open`, one or more input values and `close`, if the next token is `open`.
+fn input_values<'a>(
+    input: &mut Parser<'a>,
+    open: &'static str,
+    close: &'static str,
+) -> ParseResult<Vec<InputValue<'a>>> {
+    let mut values = vec![];
+    if input.eat_punct(open)? {
+        loop {
+            values.push(input_value(input)?);
+            if input.eat_punct(close)? {
+                break;
             }
-            Ok(ObjectTypeExtension {
-                position,
-                name,
-                directives,
-                fields,
-                implements_interfaces: interfaces,
-            })
-        })
-        .parse_stream(input)
-}
-
-pub fn interface_type<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<InterfaceType<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("interface").with(name::<'a>()),
-        parser(implements_interfaces),
-        parser(directives),
-        parser(fields),
-    )
-        .map(|(position, name, interfaces, directives, fields)| {
-            InterfaceType {
-                position,
-                name,
-                implements_interfaces: interfaces,
-                directives,
-                fields,
-                description: None, // is filled in described_definition
+        }
+    }
+    Ok(values)
+}
+
+pub fn arguments_definition<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<InputValue<'a>>> {
+    input_values(input, "(", ")")
+}
+
+pub fn field<'a>(input: &mut Parser<'a>) -> ParseResult<Field<'a>> {
+    let position = input.position();
+    let description = string(input)?;
+    let name = input.name()?;
+    let arguments = arguments_definition(input)?;
+    input.punct(":")?;
+    let field_type = input.spanned(parse_type)?;
+    let directives = directives(input)?;
+    Ok(Field {
+        position,
+        span: Span::new(position, input.end_position()),
+        description,
+        name,
+        arguments,
+        field_type,
+        directives,
+    })
+}
+
+pub fn fields<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<Field<'a>>> {
+    let mut fields = vec![];
+    if input.eat_punct("{")? {
+        loop {
+            fields.push(field(input)?);
+            if input.eat_punct("}")? {
+                break;
             }
-        })
-        .parse_stream(input)
+        }
+    }
+    Ok(fields)
+}
+
+pub fn object_type<'a>(input: &mut Parser<'a>) -> ParseResult<ObjectType<'a>> {
+    let position = input.position();
+    input.ident("type")?;
+    let name = input.name()?;
+    let implements_interfaces = implements_interfaces(input)?;
+    let directives = directives(input)?;
+    let fields = fields(input)?;
+    Ok(ObjectType {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        fields,
+        implements_interfaces,
+        description: None, // is filled in described_definition
+    })
+}
+
+pub fn object_type_extension<'a>(input: &mut Parser<'a>) -> ParseResult<ObjectTypeExtension<'a>> {
+    let position = input.position();
+    input.ident("type")?;
+    let name = input.name()?;
+    let implements_interfaces = implements_interfaces(input)?;
+    let directives = directives(input)?;
+    let fields = fields(input)?;
+    if implements_interfaces.is_empty() && directives.is_empty() && fields.is_empty() {
+        return Err(input.invalid(
+            position,
+            "Object type extension should contain at least one interface, directive or field."
+                .to_string(),
+        ));
+    }
+    Ok(ObjectTypeExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        fields,
+        implements_interfaces,
+    })
+}
+
+pub fn interface_type<'a>(input: &mut Parser<'a>) -> ParseResult<InterfaceType<'a>> {
+    let position = input.position();
+    input.ident("interface")?;
+    let name = input.name()?;
+    let implements_interfaces = implements_interfaces(input)?;
+    let directives = directives(input)?;
+    let fields = fields(input)?;
+    Ok(InterfaceType {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        implements_interfaces,
+        directives,
+        fields,
+        description: None, // is filled in described_definition
+    })
 }
 
 pub fn interface_type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<InterfaceTypeExtension<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("interface").with(name::<'a>()),
-        parser(directives),
-        parser(fields),
-    )
-        .flat_map(|(position, name, directives, fields)| {
-            if directives.is_empty() && fields.is_empty() {
-                let mut e = Errors::empty(position);
-                e.add_error(Error::expected_static_message(
-                    "Interface type extension should contain at least \
-                     one directive or field.",
-                ));
-                return Err(e);
-            }
-            Ok(InterfaceTypeExtension {
-                position,
-                name,
-                directives,
-                fields,
-            })
-        })
-        .parse_stream(input)
-}
-
-pub fn union_members<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<Txt<'a>>, TokenStream<'a>> {
-    optional(punct("|"))
-        .with(sep_by1(name::<'a>(), punct("|")))
-        .parse_stream(input)
-}
-
-pub fn union_type<'a>(input: &mut TokenStream<'a>) -> ParseResult<UnionType<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("union").with(name::<'a>()),
-        parser(directives),
-        optional(punct("=").with(parser(union_members))),
-    )
-        .map(|(position, name, directives, types)| {
-            UnionType {
-                position,
-                name,
-                directives,
-                types: types.unwrap_or_else(Vec::new),
-                description: None, // is filled in described_definition
-            }
-        })
-        .parse_stream(input)
-}
-
-pub fn union_type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<UnionTypeExtension<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("union").with(name::<'a>()),
-        parser(directives),
-        optional(punct("=").with(parser(union_members))),
-    )
-        .flat_map(|(position, name, directives, types)| {
-            if directives.is_empty() && types.is_none() {
-                let mut e = Errors::empty(position);
-                e.add_error(Error::expected_static_message(
-                    "Union type extension should contain at least \
-                 one directive or type.",
-                ));
-                return Err(e);
-            }
-            Ok(UnionTypeExtension {
-                position,
-                name,
-                directives,
-                types: types.unwrap_or_else(Vec::new),
-            })
-        })
-        .parse_stream(input)
-}
-
-pub fn enum_values<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<EnumValue<'a>>, TokenStream<'a>> {
-    punct("{")
-        .with(many1(
-            (
-                position(),
-                optional(parser(string)),
-                name::<'a>(),
-                parser(directives),
-            )
-                .map(|(position, description, name, directives)| EnumValue {
-                    position,
-                    description,
-                    name,
-                    directives,
-                }),
-        ))
-        .skip(punct("}"))
-        .parse_stream(input)
-}
-
-pub fn enum_type<'a>(input: &mut TokenStream<'a>) -> ParseResult<EnumType<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("enum").with(name::<'a>()),
-        parser(directives),
-        optional(parser(enum_values)),
-    )
-        .map(|(position, name, directives, values)| {
-            EnumType {
-                position,
-                name,
-                directives,
-                values: values.unwrap_or_else(Vec::new),
-                description: None, // is filled in described_definition
-            }
-        })
-        .parse_stream(input)
-}
-
-pub fn enum_type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<EnumTypeExtension<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("enum").with(name::<'a>()),
-        parser(directives),
-        optional(parser(enum_values)),
-    )
-        .flat_map(|(position, name, directives, values)| {
-            if directives.is_empty() && values.is_none() {
-                let mut e = Errors::empty(position);
-                e.add_error(Error::expected_static_message(
-                    "Enum type extension should contain at least \
-                 one directive or value.",
-                ));
-                return Err(e);
-            }
-            Ok(EnumTypeExtension {
-                position,
-                name,
-                directives,
-                values: values.unwrap_or_else(Vec::new),
-            })
-        })
-        .parse_stream(input)
-}
-
-pub fn input_fields<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<InputValue<'a>>, TokenStream<'a>> {
-    optional(punct("{").with(many1(parser(input_value))).skip(punct("}")))
-        .map(|v| v.unwrap_or_else(Vec::new))
-        .parse_stream(input)
-}
-
-pub fn input_object_type<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<InputObjectType<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("input").with(name::<'a>()),
-        parser(directives),
-        parser(input_fields),
-    )
-        .map(|(position, name, directives, fields)| {
-            InputObjectType {
-                position,
-                name,
-                directives,
-                fields,
-                description: None, // is filled in described_definition
-            }
-        })
-        .parse_stream(input)
+    input: &mut Parser<'a>,
+) -> ParseResult<InterfaceTypeExtension<'a>> {
+    let position = input.position();
+    input.ident("interface")?;
+    let name = input.name()?;
+    let implements_interfaces = implements_interfaces(input)?;
+    let directives = directives(input)?;
+    let fields = fields(input)?;
+    if directives.is_empty() && fields.is_empty() && implements_interfaces.is_empty() {
+        return Err(input.invalid(
+            position,
+            "Interface type extension should contain at least one interface, directive or field."
+                .to_string(),
+        ));
+    }
+    Ok(InterfaceTypeExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        implements_interfaces,
+        directives,
+        fields,
+    })
+}
+
+pub fn union_members<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<Txt<'a>>> {
+    input.eat_punct("|")?;
+    let mut members = vec![];
+    loop {
+        members.push(input.name()?);
+        if !input.eat_punct("|")? {
+            return Ok(members);
+        }
+    }
+}
+
+pub fn union_type<'a>(input: &mut Parser<'a>) -> ParseResult<UnionType<'a>> {
+    let position = input.position();
+    input.ident("union")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    let types = if input.eat_punct("=")? {
+        union_members(input)?
+    } else {
+        Vec::new()
+    };
+    Ok(UnionType {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        types,
+        description: None, // is filled in described_definition
+    })
+}
+
+pub fn union_type_extension<'a>(input: &mut Parser<'a>) -> ParseResult<UnionTypeExtension<'a>> {
+    let position = input.position();
+    input.ident("union")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    let types = if input.eat_punct("=")? {
+        Some(union_members(input)?)
+    } else {
+        None
+    };
+    if directives.is_empty() && types.is_none() {
+        return Err(input.invalid(
+            position,
+            "Union type extension should contain at least one directive or type.".to_string(),
+        ));
+    }
+    Ok(UnionTypeExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        types: types.unwrap_or_else(Vec::new),
+    })
+}
+
+pub fn enum_values<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<EnumValue<'a>>> {
+    input.punct("{")?;
+    let mut values = vec![];
+    loop {
+        let position = input.position();
+        let description = string(input)?;
+        let name = input.name()?;
+        let directives = directives(input)?;
+        values.push(EnumValue {
+            position,
+            span: Span::new(position, input.end_position()),
+            description,
+            name,
+            directives,
+        });
+        if input.eat_punct("}")? {
+            return Ok(values);
+        }
+    }
+}
+
+pub fn enum_type<'a>(input: &mut Parser<'a>) -> ParseResult<EnumType<'a>> {
+    let position = input.position();
+    input.ident("enum")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    let values = if input.is_punct("{") {
+        enum_values(input)?
+    } else {
+        Vec::new()
+    };
+    Ok(EnumType {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        values,
+        description: None, // is filled in described_definition
+    })
+}
+
+pub fn enum_type_extension<'a>(input: &mut Parser<'a>) -> ParseResult<EnumTypeExtension<'a>> {
+    let position = input.position();
+    input.ident("enum")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    let values = if input.is_punct("{") {
+        Some(enum_values(input)?)
+    } else {
+        None
+    };
+    if directives.is_empty() && values.is_none() {
+        return Err(input.invalid(
+            position,
+            "Enum type extension should contain at least one directive or value.".to_string(),
+        ));
+    }
+    Ok(EnumTypeExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        values: values.unwrap_or_else(Vec::new),
+    })
+}
+
+pub fn input_fields<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<InputValue<'a>>> {
+    input_values(input, "{", "}")
+}
+
+pub fn input_object_type<'a>(input: &mut Parser<'a>) -> ParseResult<InputObjectType<'a>> {
+    let position = input.position();
+    input.ident("input")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    let fields = input_fields(input)?;
+    Ok(InputObjectType {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        fields,
+        description: None, // is filled in described_definition
+    })
 }
 
 pub fn input_object_type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<InputObjectTypeExtension<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("input").with(name::<'a>()),
-        parser(directives),
-        parser(input_fields),
-    )
-        .flat_map(|(position, name, directives, fields)| {
-            if directives.is_empty() && fields.is_empty() {
-                let mut e = Errors::empty(position);
-                e.add_error(Error::expected_static_message(
-                    "Input object type extension should contain at least \
-                     one directive or field.",
-                ));
-                return Err(e);
-            }
-            Ok(InputObjectTypeExtension {
-                position,
-                name,
-                directives,
-                fields,
-            })
-        })
-        .parse_stream(input)
-}
-
-pub fn directive_locations<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Vec<DirectiveLocation>, TokenStream<'a>> {
-    optional(optional(punct("|")).with(sep_by1(
-        kind(T::Name).and_then(|tok| tok.value.parse::<DirectiveLocation>()),
-        punct("|"),
-    )))
-    .map(|opt| opt.unwrap_or_else(Vec::new))
-    .parse_stream(input)
-}
-
-pub fn directive_definition<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<DirectiveDefinition<'a>, TokenStream<'a>> {
-    (
-        position(),
-        ident("directive").and(punct("@")).with(name::<'a>()),
-        parser(arguments_definition),
-        ident("on").with(parser(directive_locations)),
-    )
-        .map(|(position, name, arguments, locations)| {
-            DirectiveDefinition {
-                position,
-                name,
-                arguments,
-                locations,
-                description: None, // is filled in described_definition
+    input: &mut Parser<'a>,
+) -> ParseResult<InputObjectTypeExtension<'a>> {
+    let position = input.position();
+    input.ident("input")?;
+    let name = input.name()?;
+    let directives = directives(input)?;
+    let fields = input_fields(input)?;
+    if directives.is_empty() && fields.is_empty() {
+        return Err(input.invalid(
+            position,
+            "Input object type extension should contain at least one directive or field."
+                .to_string(),
+        ));
+    }
+    Ok(InputObjectTypeExtension {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        directives,
+        fields,
+    })
+}
+
+pub fn directive_locations<'a>(input: &mut Parser<'a>) -> ParseResult<Vec<DirectiveLocation>> {
+    let mut locations = vec![];
+    if input.eat_punct("|")? || input.is_name() {
+        loop {
+            let position = input.position();
+            let location = input.name()?;
+            match location.parse() {
+                Ok(location) => locations.push(location),
+                Err(e) => return Err(input.invalid(position, format!("{}", e))),
             }
-        })
-        .parse_stream(input)
-}
-
-pub fn described_definition<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<Definition<'a>, TokenStream<'a>> {
-    use self::TypeDefinition::*;
-    (
-        optional(parser(string)),
-        choice((
-            choice((
-                parser(scalar_type).map(Scalar),
-                parser(object_type).map(Object),
-                parser(interface_type).map(Interface),
-                parser(union_type).map(Union),
-                parser(enum_type).map(Enum),
-                parser(input_object_type).map(InputObject),
-            ))
-            .map(Definition::Type),
-            parser(operation_definition).map(Definition::Operation),
-            parser(fragment_definition).map(Definition::Fragment),
-            parser(directive_definition).map(Definition::Directive),
-        )),
-    )
-        // We can't set description inside type definition parser, because
-        // that means parser will need to backtrace, and that in turn
-        // means that error reporting is bad (along with performance)
-        .map(|(descr, mut def)| {
-            use crate::schema::ast::Definition::Type as T;
-            use crate::schema::ast::Definition::*;
-            use crate::schema::ast::TypeDefinition::*;
-            match def {
-                T(Scalar(ref mut s)) => s.description = descr,
-                T(Object(ref mut o)) => o.description = descr,
-                T(Interface(ref mut i)) => i.description = descr,
-                T(Union(ref mut u)) => u.description = descr,
-                T(Enum(ref mut e)) => e.description = descr,
-                T(InputObject(ref mut o)) => o.description = descr,
-                Directive(ref mut d) => d.description = descr,
-                Operation(ref mut op) => op.description = descr,
-                Fragment(ref mut f) => f.description = descr,
-                Schema(_) => unreachable!(),
-                TypeExtension(_) => unreachable!(),
+            if !input.eat_punct("|")? {
+                break;
             }
-            def
-        })
-        .parse_stream(input)
-}
-
-pub fn type_extension<'a>(
-    input: &mut TokenStream<'a>,
-) -> ParseResult<TypeExtension<'a>, TokenStream<'a>> {
-    ident("extend")
-        .with(choice((
-            parser(scalar_type_extension).map(TypeExtension::Scalar),
-            parser(object_type_extension).map(TypeExtension::Object),
-            parser(interface_type_extension).map(TypeExtension::Interface),
-            parser(union_type_extension).map(TypeExtension::Union),
-            parser(enum_type_extension).map(TypeExtension::Enum),
-            parser(input_object_type_extension).map(TypeExtension::InputObject),
-        )))
-        .parse_stream(input)
-}
-
-pub fn definition<'a>(input: &mut TokenStream<'a>) -> ParseResult<Definition<'a>, TokenStream<'a>> {
-    choice((
-        parser(schema).map(Definition::Schema),
-        parser(type_extension).map(Definition::TypeExtension),
-        parser(described_definition),
-    ))
-    .parse_stream(input)
-}
-
-/// Parses a piece of schema language and returns an AST
+        }
+    }
+    Ok(locations)
+}
+
+pub fn directive_definition<'a>(input: &mut Parser<'a>) -> ParseResult<DirectiveDefinition<'a>> {
+    let position = input.position();
+    input.ident("directive")?;
+    input.punct("@")?;
+    let name = input.name()?;
+    let arguments = arguments_definition(input)?;
+    let repeatable = input.eat_ident("repeatable")?;
+    input.ident("on")?;
+    let locations = directive_locations(input)?;
+    Ok(DirectiveDefinition {
+        position,
+        span: Span::new(position, input.end_position()),
+        name,
+        arguments,
+        repeatable,
+        locations,
+        description: None, // is filled in described_definition
+    })
+}
+
+pub fn described_definition<'a>(input: &mut Parser<'a>) -> ParseResult<Definition<'a>> {
+    use crate::schema::generic::TypeDefinition::*;
+    let description = string(input)?;
+    let mut definition = if input.is_ident("schema") {
+        Definition::Schema(schema(input)?)
+    } else if input.is_ident("scalar") {
+        Definition::Type(Scalar(scalar_type(input)?))
+    } else if input.is_ident("type") {
+        Definition::Type(Object(object_type(input)?))
+    } else if input.is_ident("interface") {
+        Definition::Type(Interface(interface_type(input)?))
+    } else if input.is_ident("union") {
+        Definition::Type(Union(union_type(input)?))
+    } else if input.is_ident("enum") {
+        Definition::Type(Enum(enum_type(input)?))
+    } else if input.is_ident("input") {
+        Definition::Type(InputObject(input_object_type(input)?))
+    } else if input.is_ident("query")
+        || input.is_ident("mutation")
+        || input.is_ident("subscription")
+    {
+        Definition::Operation(operation_definition(input)?)
+    } else if input.is_ident("fragment") {
+        Definition::Fragment(fragment_definition(input)?)
+    } else if input.is_ident("directive") {
+        Definition::Directive(directive_definition(input)?)
+    } else {
+        return Err(input.unexpected());
+    };
+    {
+        use crate::schema::generic::Definition::Type as T;
+        use crate::schema::generic::Definition::*;
+        match definition {
+            Schema(ref mut s) => s.description = description,
+            T(Scalar(ref mut s)) => s.description = description,
+            T(Object(ref mut o)) => o.description = description,
+            T(Interface(ref mut i)) => i.description = description,
+            T(Union(ref mut u)) => u.description = description,
+            T(Enum(ref mut e)) => e.description = description,
+            T(InputObject(ref mut o)) => o.description = description,
+            Directive(ref mut d) => d.description = description,
+            Operation(ref mut op) => op.description = description,
+            Fragment(ref mut f) => f.description = description,
+            SchemaExtension(_) | TypeExtension(_) => unreachable!(),
+        }
+    }
+    Ok(definition)
+}
+
+pub fn extension<'a>(input: &mut Parser<'a>) -> ParseResult<Definition<'a>> {
+    input.ident("extend")?;
+    if input.is_ident("schema") {
+        return schema_extension(input).map(Definition::SchemaExtension);
+    }
+    let extension = if input.is_ident("scalar") {
+        TypeExtension::Scalar(scalar_type_extension(input)?)
+    } else if input.is_ident("type") {
+        TypeExtension::Object(object_type_extension(input)?)
+    } else if input.is_ident("interface") {
+        TypeExtension::Interface(interface_type_extension(input)?)
+    } else if input.is_ident("union") {
+        TypeExtension::Union(union_type_extension(input)?)
+    } else if input.is_ident("enum") {
+        TypeExtension::Enum(enum_type_extension(input)?)
+    } else if input.is_ident("input") {
+        TypeExtension::InputObject(input_object_type_extension(input)?)
+    } else {
+        return Err(input.unexpected());
+    };
+    Ok(Definition::TypeExtension(extension))
+}
+
+pub fn definition<'a>(input: &mut Parser<'a>) -> ParseResult<Definition<'a>> {
+    if input.is_ident("extend") {
+        extension(input)
+    } else {
+        described_definition(input)
+    }
+}
+
+/// Parses a piece of schema language and returns an AST, with the default `ParserOptions`
 pub fn parse_schema(s: &str) -> Result<Document, ParseError> {
-    let mut tokens = TokenStream::new(s);
-    let (doc, _) = many1(parser(definition))
-        .map(|d| Document { definitions: d })
-        .skip(eof())
-        .parse_stream(&mut tokens)
-        .map_err(|e| e.into_inner().error)?;
+    parse_schema_with_options(s, &ParserOptions::default())
+}
 
-    Ok(doc)
+/// Parses a piece of schema language, failing as soon as one of the limits of `options` is
+/// exceeded.
+pub fn parse_schema_with_options<'a>(
+    s: &'a str,
+    options: &ParserOptions,
+) -> Result<Document<'a>, ParseError> {
+    let mut input = Parser::new(s, options)?;
+    let mut definitions = vec![];
+    loop {
+        definitions.push(definition(&mut input)?);
+        if input.is_end() {
+            return Ok(Document { definitions });
+        }
+    }
+}
+
+/// Parses a piece of schema language, carrying on after syntax errors, and returns the
+/// definitions which could be parsed along with all the errors found.
+///
+/// After an error, parsing resumes at the next definition keyword or description
+/// outside of the definition that failed.
+pub fn parse_schema_recovering(s: &str) -> (Document, Vec<ParseError>) {
+    let (definitions, errors) = parse_recovering(s, definition, |token: Token| match token.kind {
+        T::Name => matches!(
+            token.value,
+            "schema"
+                | "extend"
+                | "scalar"
+                | "type"
+                | "interface"
+                | "union"
+                | "enum"
+                | "input"
+                | "directive"
+                | "query"
+                | "mutation"
+                | "subscription"
+                | "fragment"
+        ),
+        T::StringValue | T::BlockString => true,
+        _ => false,
+    });
+    (Document { definitions }, errors)
 }
 
 #[cfg(test)]
 mod test {
-    use super::parse_schema;
-    use super::{Definition, Document, SchemaDefinition};
-    use crate::position::Pos;
+    use super::{parse_schema, parse_schema_recovering, parse_schema_with_options};
+    use super::{Definition, Document, SchemaDefinition, TypeDefinition};
+    use crate::position::{Pos, Span};
+    use crate::{Name, ParseErrorKind, ParserOptions};
 
     fn ast(s: &str) -> Document {
         parse_schema(&s).unwrap()
@@ -607,7 +639,24 @@ mod test {
             ast("schema { query: Query }"),
             Document {
                 definitions: vec![Definition::Schema(SchemaDefinition {
-                    position: Pos { line: 1, column: 1 },
+                    position: Pos {
+                        line: 1,
+                        column: 1,
+                        offset: 0
+                    },
+                    span: Span::new(
+                        Pos {
+                            line: 1,
+                            column: 1,
+                            offset: 0
+                        },
+                        Pos {
+                            line: 1,
+                            column: 24,
+                            offset: 23
+                        }
+                    ),
+                    description: None,
                     directives: vec![],
                     query: Some("Query"),
                     mutation: None,
@@ -616,4 +665,149 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn recovering() {
+        let (doc, errors) = parse_schema_recovering(
+            r#"
+type A {
+  a: Int
+  b(: Int
+}
+
+"""B"""
+type B {
+  b: String
+}
+
+type C {
+  c: [Int
+}
+
+union D = | | A
+enum E { E }
+"#,
+        );
+        let names: Vec<_> = doc
+            .definitions
+            .iter()
+            .map(|d| match d {
+                Definition::Type(t) => t.name().unwrap(),
+                _ => "?",
+            })
+            .collect();
+        assert_eq!(names, vec!["B", "E"]);
+        let lines: Vec<_> = errors.iter().map(|e| e.position().unwrap().line).collect();
+        assert_eq!(lines, vec![4, 14, 16]);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |s| parse_schema(s).unwrap_err();
+
+        let err = error("type T { a: Int b }");
+        assert_eq!(err.unexpected(), Some("}"));
+        assert_eq!(err.expected(), &["(", ":"]);
+
+        let err = error("schema { query: Q query: R }");
+        assert_eq!(err.unexpected(), Some("duplicate `query` operation"));
+        assert_eq!(err.position().unwrap().column, 19);
+
+        let err = error("schema { root: Q }");
+        assert_eq!(err.expected(), &["}", "query", "mutation", "subscription"]);
+
+        let err = error("directive @a on FIELD | FIELDS");
+        assert_eq!(err.position().unwrap().column, 25);
+
+        let err = error("extend type T");
+        assert_eq!(
+            err.unexpected(),
+            Some(
+                "Object type extension should contain at least one interface, directive or field."
+            )
+        );
+        assert_eq!(err.position().unwrap().column, 8);
+
+        let err = error("scalar S\nunion U = A |");
+        assert_eq!(err.unexpected(), Some("end of input"));
+        assert_eq!(err.expected(), &["Name"]);
+    }
+
+    #[test]
+    fn limits() {
+        let options = |max_depth, max_document_size| ParserOptions {
+            max_depth,
+            max_document_size,
+            ..ParserOptions::default()
+        };
+        let kind = |s: &str, options: &ParserOptions| {
+            parse_schema_with_options(s, options)
+                .map(|_| ())
+                .map_err(|e| e.kind())
+        };
+
+        let schema = "type Query { a(b: [[Int]] = [[1]]): [[Int]] }";
+        assert!(kind(schema, &options(Some(3), Some(schema.len()))).is_ok());
+        assert_eq!(
+            kind(schema, &options(Some(2), None)),
+            Err(ParseErrorKind::RecursionLimitExceeded)
+        );
+        assert_eq!(
+            kind(schema, &options(None, Some(schema.len() - 1))),
+            Err(ParseErrorKind::DocumentTooLarge)
+        );
+
+        let deep = format!(
+            "type Query {{ a: {}Int{} }}",
+            "[".repeat(200),
+            "]".repeat(200)
+        );
+        assert_eq!(
+            kind(&deep, &ParserOptions::default()),
+            Err(ParseErrorKind::RecursionLimitExceeded)
+        );
+        assert!(kind(&deep, &options(None, None)).is_ok());
+    }
+
+    #[test]
+    fn spans() {
+        let source =
+            "\"\"\"doc\"\"\"\ntype T @k(fields: \"id\") {\n  f(a: [Int!] = [1] @d): String!\n}\n";
+        let doc = ast(source);
+        let text = |span: Span| span.slice(source);
+        let object = match &doc.definitions[0] {
+            Definition::Type(TypeDefinition::Object(object)) => object,
+            _ => panic!("unexpected definition"),
+        };
+        assert_eq!(
+            text(object.span),
+            "type T @k(fields: \"id\") {\n  f(a: [Int!] = [1] @d): String!\n}"
+        );
+        assert_eq!(text(object.directives[0].span), "@k(fields: \"id\")");
+
+        let field = &object.fields[0];
+        assert_eq!(text(field.span), "f(a: [Int!] = [1] @d): String!");
+        assert_eq!(text(field.field_type.span), "String!");
+        let argument = &field.arguments[0];
+        assert_eq!(text(argument.span), "a: [Int!] = [1] @d");
+        assert_eq!(text(argument.value_type.span), "[Int!]");
+        assert_eq!(text(argument.default_value.as_ref().unwrap().span), "[1]");
+    }
+
+    #[test]
+    fn owned_and_serde() {
+        let source = String::from(
+            "schema { query: Q }\ntype Q implements I @k(fields: \"id\") {\n  f(a: E = V): [String!]\n}\n",
+        );
+        let doc = ast(&source);
+        let formatted = doc.to_string();
+        let json = serde_json::to_string(&doc).unwrap();
+        let owned: crate::schema::owned::Document = doc.into_static();
+        drop(source);
+        assert_eq!(owned.to_string(), formatted);
+        assert_eq!(serde_json::to_string(&owned).unwrap(), json);
+
+        let back: crate::schema::owned::Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, owned);
+    }
 }
@@ -0,0 +1,941 @@
+//! Conversion between introspection results and schema documents
+//!
+//! `IntrospectionSchema` is the `__schema` field of the result of the
+//! standard introspection query. It is built from a schema document to
+//! answer introspection queries, and converted to a schema document to use
+//! services which only expose introspection.
+//!
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::common::default_value;
+use crate::helpers::Parser;
+use crate::position::{Pos, Span, Spanned};
+use crate::schema::ast::DirectiveLocation;
+use crate::schema::generic::*;
+use crate::schema::owned;
+use crate::{ParseError, ParserOptions};
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+const BUILTIN_DIRECTIVES: &[&str] = &["skip", "include", "deprecated", "specifiedBy"];
+const DEFAULT_DEPRECATION_REASON: &str = "No longer supported";
+
+/// The kind of each type of the schema, by name.
+type Kinds<'t> = HashMap<&'t str, TypeKind>;
+
+/// The types and directives of the spec which every schema implicitly defines.
+const INTROSPECTION_SDL: &str = r#"
+type __Schema {
+  description: String
+  types: [__Type!]!
+  queryType: __Type!
+  mutationType: __Type
+  subscriptionType: __Type
+  directives: [__Directive!]!
+}
+
+type __Type {
+  kind: __TypeKind!
+  name: String
+  description: String
+  specifiedByURL: String
+  fields(includeDeprecated: Boolean = false): [__Field!]
+  interfaces: [__Type!]
+  possibleTypes: [__Type!]
+  enumValues(includeDeprecated: Boolean = false): [__EnumValue!]
+  inputFields: [__InputValue!]
+  ofType: __Type
+}
+
+enum __TypeKind {
+  SCALAR
+  OBJECT
+  INTERFACE
+  UNION
+  ENUM
+  INPUT_OBJECT
+  LIST
+  NON_NULL
+}
+
+type __Field {
+  name: String!
+  description: String
+  args: [__InputValue!]!
+  type: __Type!
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __InputValue {
+  name: String!
+  description: String
+  type: __Type!
+  defaultValue: String
+}
+
+type __EnumValue {
+  name: String!
+  description: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+type __Directive {
+  name: String!
+  description: String
+  isRepeatable: Boolean!
+  locations: [__DirectiveLocation!]!
+  args: [__InputValue!]!
+}
+
+enum __DirectiveLocation {
+  QUERY
+  MUTATION
+  SUBSCRIPTION
+  FIELD
+  FRAGMENT_DEFINITION
+  FRAGMENT_SPREAD
+  INLINE_FRAGMENT
+  VARIABLE_DEFINITION
+  SCHEMA
+  SCALAR
+  OBJECT
+  FIELD_DEFINITION
+  ARGUMENT_DEFINITION
+  INTERFACE
+  UNION
+  ENUM
+  ENUM_VALUE
+  INPUT_OBJECT
+  INPUT_FIELD_DEFINITION
+}
+
+directive @skip(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+
+directive @include(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+
+directive @deprecated(reason: String = "No longer supported") on FIELD_DEFINITION | ENUM_VALUE
+
+directive @specifiedBy(url: String!) on SCALAR
+"#;
+
+/// Error converting an introspection result
+#[derive(Error, Debug)]
+pub enum IntrospectionError {
+    #[error("invalid introspection JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("introspection result has no `__schema`")]
+    MissingSchema,
+    #[error("invalid type reference: {0}")]
+    InvalidTypeRef(String),
+    #[error("invalid default value `{value}` of `{name}`: {error}")]
+    InvalidDefaultValue {
+        name: String,
+        value: String,
+        error: ParseError,
+    },
+    #[error("unknown directive location `{0}`")]
+    InvalidDirectiveLocation(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionSchema {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub query_type: IntrospectionTypeName,
+    pub mutation_type: Option<IntrospectionTypeName>,
+    pub subscription_type: Option<IntrospectionTypeName>,
+    pub types: Vec<IntrospectionType>,
+    #[serde(default)]
+    pub directives: Vec<IntrospectionDirective>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntrospectionTypeName {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionType {
+    pub kind: TypeKind,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "specifiedByURL", alias = "specifiedByUrl")]
+    pub specified_by_url: Option<String>,
+    #[serde(default)]
+    pub fields: Option<Vec<IntrospectionField>>,
+    #[serde(default)]
+    pub interfaces: Option<Vec<IntrospectionTypeRef>>,
+    #[serde(default)]
+    pub possible_types: Option<Vec<IntrospectionTypeRef>>,
+    #[serde(default)]
+    pub enum_values: Option<Vec<IntrospectionEnumValue>>,
+    #[serde(default)]
+    pub input_fields: Option<Vec<IntrospectionInputValue>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionField {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub args: Vec<IntrospectionInputValue>,
+    #[serde(rename = "type")]
+    pub field_type: IntrospectionTypeRef,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    #[serde(default)]
+    pub deprecation_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionInputValue {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub value_type: IntrospectionTypeRef,
+    /// The default value in GraphQL syntax, e.g. `"\"admin\""` for a string.
+    #[serde(default)]
+    pub default_value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionEnumValue {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    #[serde(default)]
+    pub deprecation_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionTypeRef {
+    pub kind: TypeKind,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub of_type: Option<Box<IntrospectionTypeRef>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntrospectionDirective {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_repeatable: bool,
+    pub locations: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<IntrospectionInputValue>,
+}
+
+/// Builds the schema document of an introspection result.
+///
+/// `json` is either a whole response, `{"data": {"__schema": ...}}`, its
+/// data, `{"__schema": ...}`, or the `__schema` object itself.
+pub fn schema_from_introspection(json: &str) -> Result<owned::Document, IntrospectionError> {
+    let mut json: serde_json::Value = serde_json::from_str(json)?;
+    if let Some(data) = json.get_mut("data") {
+        json = data.take();
+    }
+    if let Some(schema) = json.get_mut("__schema") {
+        json = schema.take();
+    }
+    if !json.is_object() {
+        return Err(IntrospectionError::MissingSchema);
+    }
+    let schema: IntrospectionSchema = serde_json::from_value(json)?;
+    schema.to_document()
+}
+
+/// Builds the introspection result of `doc`, `{"__schema": ...}`, as returned
+/// in the `data` of a response to the introspection query.
+///
+/// Extensions are ignored: apply them first with `apply_extensions`.
+pub fn introspection_from_schema<S: Text>(doc: &Document<S>) -> serde_json::Value {
+    let schema = IntrospectionSchema::from_document(doc);
+    serde_json::json!({ "__schema": schema })
+}
+
+impl IntrospectionSchema {
+    /// Builds the introspection of `doc`, including the built-in scalars,
+    /// the introspection types and the directives of the spec.
+    pub fn from_document<S: Text>(doc: &Document<S>) -> IntrospectionSchema {
+        let builtins = crate::parse_schema(INTROSPECTION_SDL)
+            .expect("introspection types are valid")
+            .into_static();
+
+        let mut schema_def = None;
+        let mut types: Vec<TypeDefinition<String>> = vec![];
+        let mut directives: Vec<DirectiveDefinition<String>> = vec![];
+        for def in &doc.definitions {
+            match def {
+                Definition::Schema(schema) => schema_def = Some(schema),
+                Definition::Type(ty) => types.push(ty.clone().into_static()),
+                Definition::Directive(directive) => {
+                    directives.push(directive.clone().into_static())
+                }
+                _ => {}
+            }
+        }
+
+        for name in BUILTIN_SCALARS {
+            if !types.iter().any(|ty| type_name(ty) == *name) {
+                types.push(TypeDefinition::Scalar(ScalarType::new(name.to_string())));
+            }
+        }
+        for def in builtins.definitions {
+            match def {
+                Definition::Type(ty) => types.push(ty),
+                Definition::Directive(directive)
+                    if !directives.iter().any(|d| d.name == directive.name) =>
+                {
+                    directives.push(directive)
+                }
+                _ => {}
+            }
+        }
+
+        let kinds: Kinds = types
+            .iter()
+            .map(|ty| (type_name(ty), kind_of(ty)))
+            .collect();
+        let root = |operation: Option<&S>, default: &str| match schema_def {
+            Some(_) => operation.map(|name| name.as_ref().to_string()),
+            None => types
+                .iter()
+                .find(|ty| type_name(ty) == default)
+                .map(|_| default.to_string()),
+        };
+        let query_type = root(schema_def.and_then(|s| s.query.as_ref()), "Query");
+        let mutation_type = root(schema_def.and_then(|s| s.mutation.as_ref()), "Mutation");
+        let subscription_type = root(
+            schema_def.and_then(|s| s.subscription.as_ref()),
+            "Subscription",
+        );
+
+        IntrospectionSchema {
+            description: schema_def.and_then(|s| s.description.clone()),
+            query_type: IntrospectionTypeName {
+                name: query_type.unwrap_or_else(|| "Query".to_string()),
+            },
+            mutation_type: mutation_type.map(|name| IntrospectionTypeName { name }),
+            subscription_type: subscription_type.map(|name| IntrospectionTypeName { name }),
+            types: types
+                .iter()
+                .map(|ty| introspect_type(ty, &types, &kinds))
+                .collect(),
+            directives: directives
+                .iter()
+                .map(|directive| introspect_directive(directive, &kinds))
+                .collect(),
+        }
+    }
+
+    /// Builds the schema document of this introspection, leaving out the
+    /// built-in scalars, the introspection types and the directives of the
+    /// spec.
+    pub fn to_document(&self) -> Result<owned::Document, IntrospectionError> {
+        let mut definitions = vec![];
+
+        let query = &self.query_type.name;
+        let mutation = self.mutation_type.as_ref().map(|t| &t.name);
+        let subscription = self.subscription_type.as_ref().map(|t| &t.name);
+        if query != "Query"
+            || matches!(mutation, Some(name) if name != "Mutation")
+            || matches!(subscription, Some(name) if name != "Subscription")
+            || self.description.is_some()
+        {
+            definitions.push(Definition::Schema(SchemaDefinition {
+                description: self.description.clone(),
+                query: Some(query.clone()),
+                mutation: mutation.cloned(),
+                subscription: subscription.cloned(),
+                ..SchemaDefinition::default()
+            }));
+        }
+
+        for directive in &self.directives {
+            if BUILTIN_DIRECTIVES.contains(&directive.name.as_str()) {
+                continue;
+            }
+            let locations = directive
+                .locations
+                .iter()
+                .map(|location| {
+                    location
+                        .parse::<DirectiveLocation>()
+                        .map_err(|_| IntrospectionError::InvalidDirectiveLocation(location.clone()))
+                })
+                .collect::<Result<_, _>>()?;
+            definitions.push(Definition::Directive(DirectiveDefinition {
+                description: directive.description.clone(),
+                arguments: input_values(&directive.args)?,
+                repeatable: directive.is_repeatable,
+                locations,
+                ..DirectiveDefinition::new(directive.name.clone())
+            }));
+        }
+
+        for ty in &self.types {
+            if ty.name.starts_with("__") || BUILTIN_SCALARS.contains(&ty.name.as_str()) {
+                continue;
+            }
+            let name = ty.name.clone();
+            let description = ty.description.clone();
+            let def = match ty.kind {
+                TypeKind::Scalar => TypeDefinition::Scalar(ScalarType {
+                    description,
+                    directives: ty
+                        .specified_by_url
+                        .iter()
+                        .map(|url| directive("specifiedBy", "url", url))
+                        .collect(),
+                    ..ScalarType::new(name)
+                }),
+                TypeKind::Object => TypeDefinition::Object(ObjectType {
+                    description,
+                    implements_interfaces: type_names(&ty.interfaces)?,
+                    fields: fields(&ty.fields)?,
+                    ..ObjectType::new(name)
+                }),
+                TypeKind::Interface => TypeDefinition::Interface(InterfaceType {
+                    description,
+                    implements_interfaces: type_names(&ty.interfaces)?,
+                    fields: fields(&ty.fields)?,
+                    ..InterfaceType::new(name)
+                }),
+                TypeKind::Union => TypeDefinition::Union(UnionType {
+                    description,
+                    types: type_names(&ty.possible_types)?,
+                    ..UnionType::new(name)
+                }),
+                TypeKind::Enum => TypeDefinition::Enum(EnumType {
+                    description,
+                    values: ty
+                        .enum_values
+                        .iter()
+                        .flatten()
+                        .map(|value| EnumValue {
+                            description: value.description.clone(),
+                            directives: deprecation(value.is_deprecated, &value.deprecation_reason),
+                            ..EnumValue::new(value.name.clone())
+                        })
+                        .collect(),
+                    ..EnumType::new(name)
+                }),
+                TypeKind::InputObject => TypeDefinition::InputObject(InputObjectType {
+                    description,
+                    fields: input_values(ty.input_fields.as_deref().unwrap_or(&[]))?,
+                    ..InputObjectType::new(name)
+                }),
+                TypeKind::List | TypeKind::NonNull => {
+                    return Err(IntrospectionError::InvalidTypeRef(format!(
+                        "`{}` is defined as a wrapping type",
+                        ty.name
+                    )))
+                }
+            };
+            definitions.push(Definition::Type(def));
+        }
+
+        Ok(Document { definitions })
+    }
+}
+
+fn introspect_type(
+    ty: &TypeDefinition<String>,
+    types: &[TypeDefinition<String>],
+    kinds: &Kinds,
+) -> IntrospectionType {
+    let mut result = IntrospectionType {
+        kind: kind_of(ty),
+        name: type_name(ty).to_string(),
+        description: None,
+        specified_by_url: None,
+        fields: None,
+        interfaces: None,
+        possible_types: None,
+        enum_values: None,
+        input_fields: None,
+    };
+    match ty {
+        TypeDefinition::Scalar(scalar) => {
+            result.description = scalar.description.clone();
+            result.specified_by_url = scalar
+                .directives
+                .iter()
+                .find(|d| d.name == "specifiedBy")
+                .and_then(|d| string_argument(d, "url"));
+        }
+        TypeDefinition::Object(object) => {
+            result.description = object.description.clone();
+            result.fields = Some(
+                object
+                    .fields
+                    .iter()
+                    .map(|field| introspect_field(field, kinds))
+                    .collect(),
+            );
+            result.interfaces = Some(
+                object
+                    .implements_interfaces
+                    .iter()
+                    .map(|name| named_ref(name, kinds))
+                    .collect(),
+            );
+        }
+        TypeDefinition::Interface(interface) => {
+            result.description = interface.description.clone();
+            result.fields = Some(
+                interface
+                    .fields
+                    .iter()
+                    .map(|field| introspect_field(field, kinds))
+                    .collect(),
+            );
+            result.interfaces = Some(
+                interface
+                    .implements_interfaces
+                    .iter()
+                    .map(|name| named_ref(name, kinds))
+                    .collect(),
+            );
+            result.possible_types = Some(
+                types
+                    .iter()
+                    .filter_map(|ty| match ty {
+                        TypeDefinition::Object(object)
+                            if object.implements_interfaces.contains(&interface.name) =>
+                        {
+                            Some(named_ref(&object.name, kinds))
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+            );
+        }
+        TypeDefinition::Union(union) => {
+            result.description = union.description.clone();
+            result.possible_types = Some(
+                union
+                    .types
+                    .iter()
+                    .map(|name| named_ref(name, kinds))
+                    .collect(),
+            );
+        }
+        TypeDefinition::Enum(enum_type) => {
+            result.description = enum_type.description.clone();
+            result.enum_values = Some(
+                enum_type
+                    .values
+                    .iter()
+                    .map(|value| {
+                        let deprecation_reason = deprecation_reason(&value.directives);
+                        IntrospectionEnumValue {
+                            name: value.name.clone(),
+                            description: value.description.clone(),
+                            is_deprecated: deprecation_reason.is_some(),
+                            deprecation_reason,
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        TypeDefinition::InputObject(input) => {
+            result.description = input.description.clone();
+            result.input_fields = Some(
+                input
+                    .fields
+                    .iter()
+                    .map(|value| introspect_input_value(value, kinds))
+                    .collect(),
+            );
+        }
+    }
+    result
+}
+
+fn introspect_field(field: &Field<String>, kinds: &Kinds) -> IntrospectionField {
+    let deprecation_reason = deprecation_reason(&field.directives);
+    IntrospectionField {
+        name: field.name.clone(),
+        description: field.description.clone(),
+        args: field
+            .arguments
+            .iter()
+            .map(|value| introspect_input_value(value, kinds))
+            .collect(),
+        field_type: type_ref(&field.field_type, kinds),
+        is_deprecated: deprecation_reason.is_some(),
+        deprecation_reason,
+    }
+}
+
+fn introspect_input_value(value: &InputValue<String>, kinds: &Kinds) -> IntrospectionInputValue {
+    IntrospectionInputValue {
+        name: value.name.clone(),
+        description: value.description.clone(),
+        value_type: type_ref(&value.value_type, kinds),
+        default_value: value.default_value.as_ref().map(ToString::to_string),
+    }
+}
+
+fn introspect_directive(
+    directive: &DirectiveDefinition<String>,
+    kinds: &Kinds,
+) -> IntrospectionDirective {
+    IntrospectionDirective {
+        name: directive.name.clone(),
+        description: directive.description.clone(),
+        is_repeatable: directive.repeatable,
+        locations: directive
+            .locations
+            .iter()
+            .map(|location| location.as_str().to_string())
+            .collect(),
+        args: directive
+            .arguments
+            .iter()
+            .map(|value| introspect_input_value(value, kinds))
+            .collect(),
+    }
+}
+
+fn type_ref(ty: &Type<String>, kinds: &Kinds) -> IntrospectionTypeRef {
+    match ty {
+        Type::NamedType(name) => named_ref(name, kinds),
+        Type::ListType(inner) => IntrospectionTypeRef {
+            kind: TypeKind::List,
+            name: None,
+            of_type: Some(Box::new(type_ref(inner, kinds))),
+        },
+        Type::NonNullType(inner) => IntrospectionTypeRef {
+            kind: TypeKind::NonNull,
+            name: None,
+            of_type: Some(Box::new(type_ref(inner, kinds))),
+        },
+    }
+}
+
+/// A reference to a named type. Its kind is left as `SCALAR` if the type
+/// is not defined.
+fn named_ref(name: &str, kinds: &Kinds) -> IntrospectionTypeRef {
+    IntrospectionTypeRef {
+        kind: kinds.get(name).copied().unwrap_or(TypeKind::Scalar),
+        name: Some(name.to_string()),
+        of_type: None,
+    }
+}
+
+fn deprecation_reason(directives: &[Directive<String>]) -> Option<String> {
+    directives.iter().find(|d| d.name == "deprecated").map(|d| {
+        string_argument(d, "reason").unwrap_or_else(|| DEFAULT_DEPRECATION_REASON.to_string())
+    })
+}
+
+fn string_argument(directive: &Directive<String>, name: &str) -> Option<String> {
+    directive
+        .arguments
+        .iter()
+        .find_map(|(arg, value)| match &value.node {
+            Value::String(value) if arg.node == name => Some(value.to_str().into_owned()),
+            _ => None,
+        })
+}
+
+fn kind_of(ty: &TypeDefinition<String>) -> TypeKind {
+    match ty {
+        TypeDefinition::Scalar(_) => TypeKind::Scalar,
+        TypeDefinition::Object(_) => TypeKind::Object,
+        TypeDefinition::Interface(_) => TypeKind::Interface,
+        TypeDefinition::Union(_) => TypeKind::Union,
+        TypeDefinition::Enum(_) => TypeKind::Enum,
+        TypeDefinition::InputObject(_) => TypeKind::InputObject,
+    }
+}
+
+fn type_name(ty: &TypeDefinition<String>) -> &str {
+    match ty {
+        TypeDefinition::Scalar(t) => &t.name,
+        TypeDefinition::Object(t) => &t.name,
+        TypeDefinition::Interface(t) => &t.name,
+        TypeDefinition::Union(t) => &t.name,
+        TypeDefinition::Enum(t) => &t.name,
+        TypeDefinition::InputObject(t) => &t.name,
+    }
+}
+
+fn fields(
+    fields: &Option<Vec<IntrospectionField>>,
+) -> Result<Vec<Field<String>>, IntrospectionError> {
+    fields
+        .iter()
+        .flatten()
+        .map(|field| {
+            Ok(Field {
+                position: Pos::default(),
+                span: Span::default(),
+                description: field.description.clone(),
+                name: field.name.clone(),
+                arguments: input_values(&field.args)?,
+                field_type: schema_type(&field.field_type)?.into(),
+                directives: deprecation(field.is_deprecated, &field.deprecation_reason),
+            })
+        })
+        .collect()
+}
+
+fn input_values(
+    values: &[IntrospectionInputValue],
+) -> Result<Vec<InputValue<String>>, IntrospectionError> {
+    values
+        .iter()
+        .map(|value| {
+            let default_value = match &value.default_value {
+                Some(default) => Some(parse_default_value(default).map_err(|error| {
+                    IntrospectionError::InvalidDefaultValue {
+                        name: value.name.clone(),
+                        value: default.clone(),
+                        error,
+                    }
+                })?),
+                None => None,
+            };
+            Ok(InputValue {
+                position: Pos::default(),
+                span: Span::default(),
+                description: value.description.clone(),
+                name: value.name.clone(),
+                value_type: schema_type(&value.value_type)?.into(),
+                default_value,
+                directives: vec![],
+            })
+        })
+        .collect()
+}
+
+fn type_names(refs: &Option<Vec<IntrospectionTypeRef>>) -> Result<Vec<String>, IntrospectionError> {
+    refs.iter()
+        .flatten()
+        .map(|type_ref| {
+            type_ref.name.clone().ok_or_else(|| {
+                IntrospectionError::InvalidTypeRef("named type without a `name`".to_string())
+            })
+        })
+        .collect()
+}
+
+fn schema_type(type_ref: &IntrospectionTypeRef) -> Result<Type<String>, IntrospectionError> {
+    let of_type = || {
+        type_ref.of_type.as_deref().ok_or_else(|| {
+            IntrospectionError::InvalidTypeRef("wrapping type without an `ofType`".to_string())
+        })
+    };
+    Ok(match type_ref.kind {
+        TypeKind::NonNull => Type::NonNullType(Box::new(schema_type(of_type()?)?)),
+        TypeKind::List => Type::ListType(Box::new(schema_type(of_type()?)?)),
+        _ => Type::NamedType(type_ref.name.clone().ok_or_else(|| {
+            IntrospectionError::InvalidTypeRef("named type without a `name`".to_string())
+        })?),
+    })
+}
+
+fn deprecation(is_deprecated: bool, reason: &Option<String>) -> Vec<Directive<String>> {
+    match reason {
+        _ if !is_deprecated => vec![],
+        Some(reason) if reason != DEFAULT_DEPRECATION_REASON => {
+            vec![directive("deprecated", "reason", reason)]
+        }
+        _ => vec![directive("deprecated", "", "")],
+    }
+}
+
+/// Builds `@name(argument: "value")`, or `@name` if `argument` is empty.
+fn directive(name: &str, argument: &str, value: &str) -> Directive<String> {
+    let arguments = if argument.is_empty() {
+        vec![]
+    } else {
+        vec![(
+            argument.to_string().into(),
+            Value::String(value.to_string().into()).into(),
+        )]
+    };
+    Directive {
+        position: Pos::default(),
+        span: Span::default(),
+        name: name.to_string(),
+        arguments,
+    }
+}
+
+fn parse_default_value(s: &str) -> Result<Spanned<Value<String>>, ParseError> {
+    let mut input = Parser::new(s, &ParserOptions::default())?;
+    let value = default_value(&mut input)?;
+    if !input.is_end() {
+        return Err(input.unexpected());
+    }
+    Ok(value.map(Value::into_static))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_schema;
+
+    const SCHEMA: &str = r#""The example schema"
+schema {
+  query: Root
+  mutation: Mutation
+}
+
+"Something with an id"
+interface Node {
+  id: ID!
+}
+
+type Root {
+  node(id: ID!): Node
+  search(filter: Filter = {first: 10, tags: ["a"]}, mode: Mode = FAST): [Result!]!
+}
+
+type Mutation {
+  noop: Boolean @deprecated
+}
+
+type User implements Node {
+  id: ID!
+  "The full name"
+  name: String @deprecated(reason: "Use `fullName`")
+  born: Date
+}
+
+union Result = User
+
+enum Mode {
+  FAST
+  SLOW @deprecated
+}
+
+input Filter {
+  first: Int = 10
+  tags: [String!]
+}
+
+scalar Date @specifiedBy(url: "https://tools.ietf.org/html/rfc3339")
+
+directive @auth(role: String = "admin") repeatable on FIELD_DEFINITION | OBJECT
+"#;
+
+    #[test]
+    fn round_trips() {
+        let doc = parse_schema(SCHEMA).unwrap();
+        let json = introspection_from_schema(&doc);
+        let response = serde_json::json!({ "data": json }).to_string();
+
+        let rebuilt = schema_from_introspection(&response).unwrap();
+        let directive = rebuilt
+            .definitions
+            .iter()
+            .position(|d| matches!(d, Definition::Directive(_)))
+            .unwrap();
+        let mut definitions = rebuilt.definitions;
+        let directive = definitions.remove(directive);
+        definitions.push(directive);
+        assert_eq!(Document { definitions }.to_string(), SCHEMA);
+    }
+
+    #[test]
+    fn introspects_a_schema() {
+        let doc = parse_schema(SCHEMA).unwrap();
+        let schema = IntrospectionSchema::from_document(&doc);
+
+        assert_eq!(schema.query_type.name, "Root");
+        assert_eq!(schema.mutation_type.as_ref().unwrap().name, "Mutation");
+        assert!(schema.subscription_type.is_none());
+        assert_eq!(schema.description.as_deref(), Some("The example schema"));
+        assert!(schema.directives[0].is_repeatable);
+        let names: Vec<&str> = schema.directives.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["auth", "skip", "include", "deprecated", "specifiedBy"]
+        );
+
+        let find = |name: &str| schema.types.iter().find(|ty| ty.name == name).unwrap();
+        assert!(schema.types.iter().any(|ty| ty.name == "__Schema"));
+        assert_eq!(find("String").kind, TypeKind::Scalar);
+        assert_eq!(
+            find("Node").possible_types.as_ref().unwrap()[0]
+                .name
+                .as_deref(),
+            Some("User")
+        );
+        assert_eq!(
+            find("Date").specified_by_url.as_deref(),
+            Some("https://tools.ietf.org/html/rfc3339")
+        );
+
+        let user = find("User");
+        let name = &user.fields.as_ref().unwrap()[1];
+        assert!(name.is_deprecated);
+        assert_eq!(name.deprecation_reason.as_deref(), Some("Use `fullName`"));
+
+        let root = serde_json::to_value(find("Root")).unwrap();
+        assert_eq!(
+            root["fields"][1]["args"][0]["defaultValue"],
+            r#"{first: 10, tags: ["a"]}"#
+        );
+        assert_eq!(root["fields"][1]["type"]["kind"], "NON_NULL");
+        assert_eq!(root["fields"][1]["type"]["ofType"]["kind"], "LIST");
+        assert_eq!(root["fields"][0]["type"]["kind"], "INTERFACE");
+        assert_eq!(root["fields"][1]["args"][0]["type"]["kind"], "INPUT_OBJECT");
+    }
+
+    #[test]
+    fn reports_invalid_introspection() {
+        let error = schema_from_introspection(r#"{"data": null}"#).unwrap_err();
+        assert_eq!(error.to_string(), "introspection result has no `__schema`");
+
+        let error = schema_from_introspection(
+            r#"{"__schema": {"queryType": {"name": "Query"}, "types": [
+                {"kind": "OBJECT", "name": "Query", "fields": [
+                    {"name": "a", "args": [], "type": {"kind": "NON_NULL"}}
+                ]}
+            ]}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid type reference: wrapping type without an `ofType`"
+        );
+    }
+}
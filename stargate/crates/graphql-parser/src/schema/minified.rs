@@ -0,0 +1,311 @@
+use crate::query::minified::{MinifiedFormatter, MinifiedString};
+use crate::schema::*;
+
+/// Writes `items`, separated by a space where the previous one needs it, and
+/// returns whether the last one needs whitespace after it.
+fn minify_each<T: MinifiedString>(items: &[T], f: &mut MinifiedFormatter) -> bool {
+    let mut space = false;
+    for item in items {
+        if space {
+            f.write(" ");
+        }
+        space = item.minify(f);
+    }
+    space
+}
+
+fn description(description: &Option<String>, f: &mut MinifiedFormatter) {
+    if let Some(description) = description {
+        f.write_string(description);
+    }
+}
+
+/// Writes `name` after a keyword, then the directives.
+fn header(keyword: &str, name: &str, directives: &[Directive], f: &mut MinifiedFormatter) -> bool {
+    f.write(keyword);
+    f.write(name);
+    directives.is_empty() || minify_each(directives, f)
+}
+
+fn interfaces(interfaces: &[&str], f: &mut MinifiedFormatter) {
+    if !interfaces.is_empty() {
+        f.write(" implements ");
+        f.write(&interfaces.join("&"));
+    }
+}
+
+/// Writes `items` in braces, if any, and returns whether whitespace is needed after them.
+fn block<T: MinifiedString>(items: &[T], space: bool, f: &mut MinifiedFormatter) -> bool {
+    if items.is_empty() {
+        return space;
+    }
+    f.write("{");
+    minify_each(items, f);
+    f.write("}");
+    false
+}
+
+fn members(types: &[&str], f: &mut MinifiedFormatter) -> bool {
+    if !types.is_empty() {
+        f.write("=");
+        f.write(&types.join("|"));
+    }
+    true
+}
+
+fn arguments(arguments: &[InputValue], f: &mut MinifiedFormatter) {
+    if !arguments.is_empty() {
+        f.write("(");
+        minify_each(arguments, f);
+        f.write(")");
+    }
+}
+
+impl<'a> MinifiedString for Document<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        minify_each(&self.definitions, f);
+        false
+    }
+}
+
+impl<'a> MinifiedString for Definition<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        match self {
+            Definition::Schema(schema) => schema.minify(f),
+            Definition::SchemaExtension(ext) => ext.minify(f),
+            Definition::Type(ty) => ty.minify(f),
+            Definition::TypeExtension(ext) => ext.minify(f),
+            Definition::Directive(directive) => directive.minify(f),
+            Definition::Operation(op) => op.minify(f),
+            Definition::Fragment(frag) => frag.minify(f),
+        }
+    }
+}
+
+fn root_operations(
+    query: Option<&str>,
+    mutation: Option<&str>,
+    subscription: Option<&str>,
+    f: &mut MinifiedFormatter,
+) {
+    f.write("{");
+    let operations = [
+        ("query:", query),
+        ("mutation:", mutation),
+        ("subscription:", subscription),
+    ];
+    let mut space = false;
+    for (operation, name) in operations.iter() {
+        if let Some(name) = name {
+            if space {
+                f.write(" ");
+            }
+            f.write(operation);
+            f.write(name);
+            space = true;
+        }
+    }
+    f.write("}");
+}
+
+impl<'a> MinifiedString for SchemaDefinition<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        description(&self.description, f);
+        f.write("schema");
+        minify_each(&self.directives, f);
+        root_operations(self.query, self.mutation, self.subscription, f);
+        false
+    }
+}
+
+impl<'a> MinifiedString for SchemaExtension<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        let space = header("extend schema", "", &self.directives, f);
+        if self.query.is_none() && self.mutation.is_none() && self.subscription.is_none() {
+            return space;
+        }
+        root_operations(self.query, self.mutation, self.subscription, f);
+        false
+    }
+}
+
+impl<'a> MinifiedString for TypeDefinition<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        match self {
+            TypeDefinition::Scalar(t) => {
+                description(&t.description, f);
+                header("scalar ", t.name, &t.directives, f)
+            }
+            TypeDefinition::Object(t) => {
+                description(&t.description, f);
+                f.write("type ");
+                f.write(t.name);
+                interfaces(&t.implements_interfaces, f);
+                let space = t.directives.is_empty() || minify_each(&t.directives, f);
+                block(&t.fields, space, f)
+            }
+            TypeDefinition::Interface(t) => {
+                description(&t.description, f);
+                f.write("interface ");
+                f.write(t.name);
+                interfaces(&t.implements_interfaces, f);
+                let space = t.directives.is_empty() || minify_each(&t.directives, f);
+                block(&t.fields, space, f)
+            }
+            TypeDefinition::Union(t) => {
+                description(&t.description, f);
+                header("union ", t.name, &t.directives, f);
+                members(&t.types, f)
+            }
+            TypeDefinition::Enum(t) => {
+                description(&t.description, f);
+                let space = header("enum ", t.name, &t.directives, f);
+                block(&t.values, space, f)
+            }
+            TypeDefinition::InputObject(t) => {
+                description(&t.description, f);
+                let space = header("input ", t.name, &t.directives, f);
+                block(&t.fields, space, f)
+            }
+        }
+    }
+}
+
+impl<'a> MinifiedString for TypeExtension<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        match self {
+            TypeExtension::Scalar(t) => header("extend scalar ", t.name, &t.directives, f),
+            TypeExtension::Object(t) => {
+                f.write("extend type ");
+                f.write(t.name);
+                interfaces(&t.implements_interfaces, f);
+                let space = t.directives.is_empty() || minify_each(&t.directives, f);
+                block(&t.fields, space, f)
+            }
+            TypeExtension::Interface(t) => {
+                f.write("extend interface ");
+                f.write(t.name);
+                interfaces(&t.implements_interfaces, f);
+                let space = t.directives.is_empty() || minify_each(&t.directives, f);
+                block(&t.fields, space, f)
+            }
+            TypeExtension::Union(t) => {
+                header("extend union ", t.name, &t.directives, f);
+                members(&t.types, f)
+            }
+            TypeExtension::Enum(t) => {
+                let space = header("extend enum ", t.name, &t.directives, f);
+                block(&t.values, space, f)
+            }
+            TypeExtension::InputObject(t) => {
+                let space = header("extend input ", t.name, &t.directives, f);
+                block(&t.fields, space, f)
+            }
+        }
+    }
+}
+
+impl<'a> MinifiedString for Field<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        description(&self.description, f);
+        f.write(self.name);
+        arguments(&self.arguments, f);
+        f.write(":");
+        let space = self.field_type.minify(f);
+        if self.directives.is_empty() {
+            space
+        } else {
+            minify_each(&self.directives, f)
+        }
+    }
+}
+
+impl<'a> MinifiedString for InputValue<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        description(&self.description, f);
+        f.write(self.name);
+        f.write(":");
+        let mut space = self.value_type.minify(f);
+        if let Some(default) = &self.default_value {
+            f.write("=");
+            space = default.minify(f);
+        }
+        if self.directives.is_empty() {
+            space
+        } else {
+            minify_each(&self.directives, f)
+        }
+    }
+}
+
+impl<'a> MinifiedString for EnumValue<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        description(&self.description, f);
+        header("", self.name, &self.directives, f)
+    }
+}
+
+impl<'a> MinifiedString for DirectiveDefinition<'a> {
+    fn minify(&self, f: &mut MinifiedFormatter) -> bool {
+        description(&self.description, f);
+        f.write("directive @");
+        f.write(self.name);
+        arguments(&self.arguments, f);
+        if self.arguments.is_empty() {
+            f.write(" ");
+        }
+        if self.repeatable {
+            f.write("repeatable ");
+        }
+        f.write("on ");
+        let locations: Vec<&str> = self.locations.iter().map(|l| l.as_str()).collect();
+        f.write(&locations.join("|"));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_schema, DisplayMinified};
+
+    #[test]
+    fn minify() {
+        let schemas: Vec<&str> = vec![
+            "schema{query:Query mutation:Mutation}",
+            "type Query{a:Int b(x:Int=1 y:[String!]!):String@deprecated c:Int!}",
+            "\"described\"type User implements Node&Named@key(fields:\"id\"){id:ID!}",
+            "interface Node{id:ID!}type A{a:[A]}scalar Date",
+            "union U@a=A|B enum E{A \"two\\nlines\"B@deprecated C}input I{a:Int=1@a b:E=A}",
+            "extend type Query@key(fields:\"id\") extend union U=C extend scalar S@a",
+            "directive @auth(role:String=\"admin\")on FIELD_DEFINITION|OBJECT",
+            "directive @key(fields:String!)repeatable on OBJECT directive @a repeatable on SCHEMA",
+            "\"described\"schema{query:Q}extend schema@link(url:\"x\"){subscription:S}extend schema@a",
+            "interface I implements N{a:Int}extend interface I implements M",
+        ];
+        for schema in schemas {
+            let parsed = parse_schema(schema).unwrap();
+            assert_eq!(schema, parsed.minified())
+        }
+    }
+
+    #[test]
+    fn minify_round_trips() {
+        let schema = r#"
+            """
+            The root
+            """
+            type Query {
+              user(id: ID!, "Defaults to 10" first: Int = 10): User @cost(weight: 2)
+              node: Node
+            }
+
+            enum Role { ADMIN "Read only" GUEST }
+        "#;
+        let parsed = parse_schema(schema).unwrap();
+        let minified = parsed.minified();
+        assert_eq!(
+            parse_schema(&minified).unwrap().to_string(),
+            parsed.to_string()
+        );
+    }
+}
@@ -1,12 +1,25 @@
 //! Schema definition language AST and utility
 //!
 mod ast;
+mod diff;
+mod extend;
 mod format;
+pub mod generic;
 mod grammar;
+mod minified;
+pub mod introspection;
 mod name;
+pub mod owned;
+mod validate;
 mod visit;
 
 pub use self::ast::*;
-pub use self::grammar::parse_schema;
+pub use self::diff::{diff_schemas, ChangeKind, Criticality, SchemaChange};
+pub use self::extend::{apply_extensions, ExtensionError};
+pub use self::grammar::{parse_schema, parse_schema_recovering, parse_schema_with_options};
+pub use self::introspection::{
+    introspection_from_schema, schema_from_introspection, IntrospectionError,
+};
 pub use self::name::*;
+pub use self::validate::{validate_schema, ValidationError};
 pub use self::visit::*;
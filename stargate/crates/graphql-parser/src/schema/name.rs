@@ -6,7 +6,7 @@ impl<'a> Name<'a> for Document<'a> {}
 impl<'a> Name<'a> for Definition<'a> {
     fn name(&self) -> Option<&'a str> {
         match self {
-            Definition::Schema(_) => None,
+            Definition::Schema(_) | Definition::SchemaExtension(_) => None,
             Definition::Type(t) => t.name(),
             Definition::TypeExtension(te) => te.name(),
             Definition::Directive(d) => Some(d.name),
@@ -42,6 +42,87 @@ impl<'a> Name<'a> for TypeExtension<'a> {
     }
 }
 
+impl<'a> Name<'a> for SchemaDefinition<'a> {}
+impl<'a> Name<'a> for SchemaExtension<'a> {}
+
+impl<'a> Name<'a> for ScalarType<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for ObjectType<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for InterfaceType<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for UnionType<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for EnumType<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for InputObjectType<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for ScalarTypeExtension<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for ObjectTypeExtension<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for InterfaceTypeExtension<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for UnionTypeExtension<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for EnumTypeExtension<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for InputObjectTypeExtension<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
+impl<'a> Name<'a> for DirectiveDefinition<'a> {
+    fn name(&self) -> Option<&'a str> {
+        Some(self.name)
+    }
+}
+
 impl<'a> Name<'a> for Field<'a> {
     fn name(&self) -> Option<&'a str> {
         Some(self.name)
@@ -0,0 +1,34 @@
+//! Schema definition language AST owning its names
+//!
+//! These nodes can outlive the source they were parsed from, e.g. to be cached
+//! or sent to another thread. `Document::into_static` converts a parsed
+//! document into an owned one.
+//!
+pub use crate::query::owned::{
+    Argument, Directive, FragmentDefinition, OperationDefinition, StringValue, Type, Value,
+};
+pub use crate::schema::ast::DirectiveLocation;
+use crate::schema::generic;
+
+pub type Document = generic::Document<String>;
+pub type Definition = generic::Definition<String>;
+pub type SchemaDefinition = generic::SchemaDefinition<String>;
+pub type SchemaExtension = generic::SchemaExtension<String>;
+pub type TypeDefinition = generic::TypeDefinition<String>;
+pub type TypeExtension = generic::TypeExtension<String>;
+pub type ScalarType = generic::ScalarType<String>;
+pub type ScalarTypeExtension = generic::ScalarTypeExtension<String>;
+pub type ObjectType = generic::ObjectType<String>;
+pub type ObjectTypeExtension = generic::ObjectTypeExtension<String>;
+pub type Field = generic::Field<String>;
+pub type InputValue = generic::InputValue<String>;
+pub type InterfaceType = generic::InterfaceType<String>;
+pub type InterfaceTypeExtension = generic::InterfaceTypeExtension<String>;
+pub type UnionType = generic::UnionType<String>;
+pub type UnionTypeExtension = generic::UnionTypeExtension<String>;
+pub type EnumType = generic::EnumType<String>;
+pub type EnumValue = generic::EnumValue<String>;
+pub type EnumTypeExtension = generic::EnumTypeExtension<String>;
+pub type InputObjectType = generic::InputObjectType<String>;
+pub type InputObjectTypeExtension = generic::InputObjectTypeExtension<String>;
+pub type DirectiveDefinition = generic::DirectiveDefinition<String>;
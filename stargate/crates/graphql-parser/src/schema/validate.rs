@@ -0,0 +1,846 @@
+//! Validation of schemas against the type system rules of the GraphQL spec
+//!
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::position::Pos;
+use crate::schema::ast::DirectiveLocation;
+use crate::schema::extend::{merge_extensions, ExtensionError};
+use crate::schema::generic::*;
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+
+/// Schema validation error
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{message}")]
+pub struct ValidationError {
+    message: String,
+    position: Pos,
+}
+
+impl ValidationError {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Where the offending definition starts.
+    pub fn position(&self) -> Pos {
+        self.position
+    }
+}
+
+impl From<ExtensionError> for ValidationError {
+    fn from(error: ExtensionError) -> Self {
+        ValidationError {
+            position: error.position(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Checks `doc` against the type system rules of the GraphQL spec.
+///
+/// Extensions are applied first, so a document of `extend type`s is
+/// validated as the schema it builds. Built-in scalars are implicitly
+/// defined. All errors are reported, in the order of the definitions,
+/// followed by the errors of extensions which couldn't be applied, whose
+/// definitions are validated without them.
+pub fn validate_schema<S: Text>(doc: &Document<S>) -> Result<(), Vec<ValidationError>> {
+    let has_extensions = doc.definitions.iter().any(|def| {
+        matches!(
+            def,
+            Definition::TypeExtension(_) | Definition::SchemaExtension(_)
+        )
+    });
+    let (merged, extension_errors) = if has_extensions {
+        let (merged, errors) = merge_extensions(doc.clone());
+        (Some(merged), errors)
+    } else {
+        (None, vec![])
+    };
+    let doc = merged.as_ref().unwrap_or(doc);
+
+    let mut validator = Validator::new(doc);
+    validator.validate(doc);
+    validator
+        .errors
+        .extend(extension_errors.into_iter().map(ValidationError::from));
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+struct Validator<'d, S> {
+    types: HashMap<&'d str, &'d TypeDefinition<S>>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'d, S: Text> Validator<'d, S> {
+    fn new(doc: &'d Document<S>) -> Self {
+        let mut validator = Validator {
+            types: HashMap::new(),
+            errors: vec![],
+        };
+        for def in &doc.definitions {
+            if let Definition::Type(ty) = def {
+                let (name, position) = type_name(ty);
+                if validator.types.insert(name, ty).is_some() {
+                    validator.error(
+                        position,
+                        format!("type `{}` is defined more than once", name),
+                    );
+                }
+            }
+        }
+        validator
+    }
+
+    fn error(&mut self, position: Pos, message: String) {
+        self.errors.push(ValidationError { message, position });
+    }
+
+    fn validate(&mut self, doc: &'d Document<S>) {
+        let mut schemas = doc.definitions.iter().filter_map(|def| match def {
+            Definition::Schema(schema) => Some(schema),
+            _ => None,
+        });
+        match schemas.next() {
+            Some(schema) => self.validate_schema_definition(schema),
+            None => {
+                if !matches!(self.types.get("Query"), Some(TypeDefinition::Object(_))) {
+                    self.error(
+                        Pos::default(),
+                        "schema has no query root operation type".to_string(),
+                    );
+                }
+            }
+        }
+        for schema in schemas {
+            self.error(
+                schema.position,
+                "schema is defined more than once".to_string(),
+            );
+        }
+
+        let mut directives = HashSet::new();
+        for def in &doc.definitions {
+            match def {
+                Definition::Type(ty) => self.validate_type(ty),
+                Definition::Directive(directive) => {
+                    if !directives.insert(directive.name.as_ref()) {
+                        self.error(
+                            directive.position,
+                            format!(
+                                "directive `@{}` is defined more than once",
+                                directive.name.as_ref()
+                            ),
+                        );
+                    }
+                    self.validate_directive(directive);
+                }
+                _ => {}
+            }
+        }
+
+        self.validate_input_cycles(doc);
+    }
+
+    fn validate_schema_definition(&mut self, schema: &'d SchemaDefinition<S>) {
+        if schema.query.is_none() {
+            self.error(
+                schema.position,
+                "schema has no query root operation type".to_string(),
+            );
+        }
+        let roots = [
+            ("query", &schema.query),
+            ("mutation", &schema.mutation),
+            ("subscription", &schema.subscription),
+        ];
+        for (operation, root) in roots.iter() {
+            if let Some(root) = root {
+                let root = root.as_ref();
+                match self.types.get(root) {
+                    Some(TypeDefinition::Object(_)) => {}
+                    Some(_) => self.error(
+                        schema.position,
+                        format!("{} root type `{}` must be an object type", operation, root),
+                    ),
+                    None => self.error(
+                        schema.position,
+                        format!("{} root type `{}` is not defined", operation, root),
+                    ),
+                }
+            }
+        }
+    }
+
+    fn validate_type(&mut self, ty: &'d TypeDefinition<S>) {
+        let (name, position) = type_name(ty);
+        self.validate_name(name, position, || format!("type `{}`", name));
+        match ty {
+            TypeDefinition::Scalar(_) => {}
+            TypeDefinition::Object(object) => {
+                self.validate_fields(name, &object.fields);
+                self.validate_implementations(
+                    name,
+                    position,
+                    &object.implements_interfaces,
+                    &object.fields,
+                );
+            }
+            TypeDefinition::Interface(interface) => {
+                self.validate_fields(name, &interface.fields);
+                self.validate_implementations(
+                    name,
+                    position,
+                    &interface.implements_interfaces,
+                    &interface.fields,
+                );
+            }
+            TypeDefinition::Union(union) => {
+                let mut members = HashSet::new();
+                for member in &union.types {
+                    let member = member.as_ref();
+                    if !members.insert(member) {
+                        self.error(
+                            position,
+                            format!("union `{}` includes `{}` more than once", name, member),
+                        );
+                    }
+                    match self.types.get(member) {
+                        Some(TypeDefinition::Object(_)) => {}
+                        Some(_) => self.error(
+                            position,
+                            format!(
+                                "member `{}` of union `{}` must be an object type",
+                                member, name
+                            ),
+                        ),
+                        None => self.error(
+                            position,
+                            format!("member `{}` of union `{}` is not defined", member, name),
+                        ),
+                    }
+                }
+            }
+            TypeDefinition::Enum(enum_type) => {
+                let mut values = HashSet::new();
+                for value in &enum_type.values {
+                    let value_name = value.name.as_ref();
+                    let what = || format!("enum value `{}.{}`", name, value_name);
+                    self.validate_name(value_name, value.position, what);
+                    if !values.insert(value_name) {
+                        self.error(
+                            value.position,
+                            format!("{} is defined more than once", what()),
+                        );
+                    }
+                    if ["true", "false", "null"].contains(&value_name) {
+                        self.error(
+                            value.position,
+                            format!("{} cannot be named `{}`", what(), value_name),
+                        );
+                    }
+                }
+            }
+            TypeDefinition::InputObject(input) => {
+                self.validate_input_values(&input.fields, |field| {
+                    format!("input field `{}.{}`", name, field)
+                });
+            }
+        }
+    }
+
+    fn validate_directive(&mut self, directive: &'d DirectiveDefinition<S>) {
+        let name = directive.name.as_ref();
+        self.validate_name(name, directive.position, || {
+            format!("directive `@{}`", name)
+        });
+        self.validate_input_values(&directive.arguments, |arg| {
+            format!("argument `@{}({}:)`", name, arg)
+        });
+
+        for arg in &directive.arguments {
+            if arg.directives.iter().any(|d| d.name.as_ref() == name) {
+                self.error(
+                    arg.position,
+                    format!(
+                        "directive `@{}` cannot reference itself on argument `{}`",
+                        name,
+                        arg.name.as_ref()
+                    ),
+                );
+            }
+        }
+
+        let mut locations: HashSet<&DirectiveLocation> = HashSet::new();
+        for location in &directive.locations {
+            if !locations.insert(location) {
+                self.error(
+                    directive.position,
+                    format!(
+                        "directive `@{}` lists location `{}` more than once",
+                        name,
+                        location.as_str()
+                    ),
+                );
+            }
+        }
+    }
+
+    fn validate_fields(&mut self, type_name: &str, fields: &'d [Field<S>]) {
+        let mut names = HashSet::new();
+        for field in fields {
+            let name = field.name.as_ref();
+            let what = || format!("field `{}.{}`", type_name, name);
+            self.validate_name(name, field.position, what);
+            if !names.insert(name) {
+                self.error(
+                    field.position,
+                    format!("{} is defined more than once", what()),
+                );
+            }
+            self.validate_type_ref(&field.field_type, false, field.position, what);
+            self.validate_input_values(&field.arguments, |arg| {
+                format!("argument `{}.{}({}:)`", type_name, name, arg)
+            });
+        }
+    }
+
+    fn validate_input_values(
+        &mut self,
+        values: &'d [InputValue<S>],
+        what: impl Fn(&str) -> String,
+    ) {
+        let mut names = HashSet::new();
+        for value in values {
+            let name = value.name.as_ref();
+            self.validate_name(name, value.position, || what(name));
+            if !names.insert(name) {
+                self.error(
+                    value.position,
+                    format!("{} is defined more than once", what(name)),
+                );
+            }
+            self.validate_type_ref(&value.value_type, true, value.position, || what(name));
+        }
+    }
+
+    /// Checks that the named type of `ty` exists and is an input type if
+    /// `input`, or an output type otherwise.
+    fn validate_type_ref(
+        &mut self,
+        ty: &Type<S>,
+        input: bool,
+        position: Pos,
+        what: impl Fn() -> String,
+    ) {
+        let name = named_type(ty);
+        match self.types.get(name) {
+            Some(TypeDefinition::InputObject(_)) if !input => self.error(
+                position,
+                format!("{} cannot have input type `{}`", what(), name),
+            ),
+            Some(TypeDefinition::Object(_))
+            | Some(TypeDefinition::Interface(_))
+            | Some(TypeDefinition::Union(_))
+                if input =>
+            {
+                self.error(
+                    position,
+                    format!("{} cannot have output type `{}`", what(), name),
+                )
+            }
+            Some(_) => {}
+            None if BUILTIN_SCALARS.contains(&name) => {}
+            None => self.error(position, format!("{} has unknown type `{}`", what(), name)),
+        }
+    }
+
+    fn validate_name(&mut self, name: &str, position: Pos, what: impl Fn() -> String) {
+        if name.starts_with("__") {
+            self.error(
+                position,
+                format!(
+                    "{} cannot start with `__`, which is reserved for introspection",
+                    what()
+                ),
+            );
+        }
+    }
+
+    /// Checks that `type_name` correctly implements each of `interfaces`.
+    fn validate_implementations(
+        &mut self,
+        type_name: &str,
+        position: Pos,
+        interfaces: &'d [S],
+        fields: &'d [Field<S>],
+    ) {
+        let mut implemented = HashSet::new();
+        for interface_name in interfaces {
+            let interface_name = interface_name.as_ref();
+            if !implemented.insert(interface_name) {
+                self.error(
+                    position,
+                    format!(
+                        "`{}` implements `{}` more than once",
+                        type_name, interface_name
+                    ),
+                );
+                continue;
+            }
+            if interface_name == type_name {
+                self.error(position, format!("`{}` cannot implement itself", type_name));
+                continue;
+            }
+            let interface = match self.types.get(interface_name).copied() {
+                Some(TypeDefinition::Interface(interface)) => interface,
+                Some(_) => {
+                    self.error(
+                        position,
+                        format!(
+                            "`{}` cannot implement `{}`, which is not an interface",
+                            type_name, interface_name
+                        ),
+                    );
+                    continue;
+                }
+                None => {
+                    self.error(
+                        position,
+                        format!(
+                            "`{}` implements unknown interface `{}`",
+                            type_name, interface_name
+                        ),
+                    );
+                    continue;
+                }
+            };
+
+            for transitive in &interface.implements_interfaces {
+                if !interfaces.contains(transitive) {
+                    self.error(
+                        position,
+                        format!(
+                            "`{}` must implement `{}`, which is implemented by `{}`",
+                            type_name,
+                            transitive.as_ref(),
+                            interface_name
+                        ),
+                    );
+                }
+            }
+
+            for interface_field in &interface.fields {
+                let field_name = interface_field.name.as_ref();
+                let field = match fields.iter().find(|f| f.name.as_ref() == field_name) {
+                    Some(field) => field,
+                    None => {
+                        self.error(
+                            position,
+                            format!(
+                                "`{}` must define field `{}`, which is required by `{}`",
+                                type_name, field_name, interface_name
+                            ),
+                        );
+                        continue;
+                    }
+                };
+                self.validate_implementation_field(
+                    type_name,
+                    interface_name,
+                    field,
+                    interface_field,
+                );
+            }
+        }
+    }
+
+    fn validate_implementation_field(
+        &mut self,
+        type_name: &str,
+        interface_name: &str,
+        field: &'d Field<S>,
+        interface_field: &'d Field<S>,
+    ) {
+        let field_name = field.name.as_ref();
+        if !self.is_valid_implementation_type(&field.field_type, &interface_field.field_type) {
+            self.error(
+                field.position,
+                format!(
+                    "field `{}.{}` of type `{}` is not compatible with `{}.{}` of type `{}`",
+                    type_name,
+                    field_name,
+                    field.field_type,
+                    interface_name,
+                    field_name,
+                    interface_field.field_type
+                ),
+            );
+        }
+
+        for interface_arg in &interface_field.arguments {
+            let arg_name = interface_arg.name.as_ref();
+            match field.arguments.iter().find(|arg| arg.name.as_ref() == arg_name) {
+                Some(arg) if arg.value_type != interface_arg.value_type => self.error(
+                    arg.position,
+                    format!(
+                        "argument `{}.{}({}:)` of type `{}` must have the type `{}` of `{}.{}({}:)`",
+                        type_name,
+                        field_name,
+                        arg_name,
+                        arg.value_type,
+                        interface_arg.value_type,
+                        interface_name,
+                        field_name,
+                        arg_name
+                    ),
+                ),
+                Some(_) => {}
+                None => self.error(
+                    field.position,
+                    format!(
+                        "field `{}.{}` must define argument `{}`, which is required by `{}`",
+                        type_name, field_name, arg_name, interface_name
+                    ),
+                ),
+            }
+        }
+
+        for arg in &field.arguments {
+            let arg_name = arg.name.as_ref();
+            let required =
+                matches!(arg.value_type.node, Type::NonNullType(_)) && arg.default_value.is_none();
+            if required
+                && !interface_field
+                    .arguments
+                    .iter()
+                    .any(|a| a.name.as_ref() == arg_name)
+            {
+                self.error(
+                    arg.position,
+                    format!(
+                        "argument `{}.{}({}:)` must be optional, as `{}.{}` does not define it",
+                        type_name, field_name, arg_name, interface_name, field_name
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Whether a field of type `field_type` can implement an interface field
+    /// of type `implemented`: it may be non-null where the interface field is
+    /// nullable, and its named type may be a subtype of the interface's.
+    fn is_valid_implementation_type(&self, field_type: &Type<S>, implemented: &Type<S>) -> bool {
+        match (field_type, implemented) {
+            (Type::NonNullType(field_type), Type::NonNullType(implemented)) => {
+                self.is_valid_implementation_type(field_type, implemented)
+            }
+            (Type::NonNullType(field_type), implemented) => {
+                self.is_valid_implementation_type(field_type, implemented)
+            }
+            (_, Type::NonNullType(_)) => false,
+            (Type::ListType(field_type), Type::ListType(implemented)) => {
+                self.is_valid_implementation_type(field_type, implemented)
+            }
+            (Type::ListType(_), _) | (_, Type::ListType(_)) => false,
+            (Type::NamedType(field_type), Type::NamedType(implemented)) => {
+                field_type == implemented
+                    || self.is_subtype(field_type.as_ref(), implemented.as_ref())
+            }
+        }
+    }
+
+    fn is_subtype(&self, subtype: &str, supertype: &str) -> bool {
+        match self.types.get(supertype) {
+            Some(TypeDefinition::Union(union)) => union.types.iter().any(|t| t.as_ref() == subtype),
+            Some(TypeDefinition::Interface(_)) => {
+                let interfaces = match self.types.get(subtype) {
+                    Some(TypeDefinition::Object(object)) => &object.implements_interfaces,
+                    Some(TypeDefinition::Interface(interface)) => &interface.implements_interfaces,
+                    _ => return false,
+                };
+                interfaces.iter().any(|i| i.as_ref() == supertype)
+            }
+            _ => false,
+        }
+    }
+
+    /// Reports input objects referencing themselves through non-null fields,
+    /// which no finite value could satisfy.
+    fn validate_input_cycles(&mut self, doc: &'d Document<S>) {
+        let mut visited = HashSet::new();
+        let mut path = vec![];
+        let mut path_index = HashMap::new();
+        for def in &doc.definitions {
+            if let Definition::Type(TypeDefinition::InputObject(input)) = def {
+                self.find_input_cycles(input, &mut visited, &mut path, &mut path_index);
+            }
+        }
+    }
+
+    fn find_input_cycles(
+        &mut self,
+        input: &'d InputObjectType<S>,
+        visited: &mut HashSet<&'d str>,
+        path: &mut Vec<&'d InputValue<S>>,
+        path_index: &mut HashMap<&'d str, usize>,
+    ) {
+        let name = input.name.as_ref();
+        if !visited.insert(name) {
+            return;
+        }
+        path_index.insert(name, path.len());
+        for field in &input.fields {
+            let next = match &field.value_type.node {
+                Type::NonNullType(inner) => match &**inner {
+                    Type::NamedType(next) => next.as_ref(),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let next_input = match self.types.get(next).copied() {
+                Some(TypeDefinition::InputObject(next_input)) => next_input,
+                _ => continue,
+            };
+            path.push(field);
+            match path_index.get(next) {
+                Some(&start) => {
+                    let fields: Vec<String> = path[start..]
+                        .iter()
+                        .map(|field| format!("`{}`", field.name.as_ref()))
+                        .collect();
+                    self.error(
+                        field.position,
+                        format!(
+                            "input object `{}` cannot reference itself through non-null fields {}",
+                            next,
+                            fields.join(", ")
+                        ),
+                    );
+                }
+                None => self.find_input_cycles(next_input, visited, path, path_index),
+            }
+            path.pop();
+        }
+        path_index.remove(name);
+    }
+}
+
+fn type_name<S: Text>(ty: &TypeDefinition<S>) -> (&str, Pos) {
+    match ty {
+        TypeDefinition::Scalar(t) => (t.name.as_ref(), t.position),
+        TypeDefinition::Object(t) => (t.name.as_ref(), t.position),
+        TypeDefinition::Interface(t) => (t.name.as_ref(), t.position),
+        TypeDefinition::Union(t) => (t.name.as_ref(), t.position),
+        TypeDefinition::Enum(t) => (t.name.as_ref(), t.position),
+        TypeDefinition::InputObject(t) => (t.name.as_ref(), t.position),
+    }
+}
+
+fn named_type<S: Text>(ty: &Type<S>) -> &str {
+    match ty {
+        Type::NamedType(name) => name.as_ref(),
+        Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_schema;
+
+    fn errors(schema: &str) -> Vec<String> {
+        let doc = parse_schema(schema).unwrap();
+        match validate_schema(&doc) {
+            Ok(()) => vec![],
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn valid_schema() {
+        assert_eq!(
+            errors(
+                r#"
+                schema { query: Root }
+                type Root { node(id: ID!): Node search(text: String): [Result!] }
+                interface Node { id: ID! }
+                interface Named implements Node { id: ID! name(upper: Boolean): String }
+                type User implements Named & Node {
+                    id: ID!
+                    name(upper: Boolean, locale: String): String!
+                    friends: [User]
+                }
+                union Result = User
+                input Filter { next: Filter, list: [Filter!]! }
+                enum Color { RED GREEN }
+                directive @auth(role: String = "admin") on FIELD_DEFINITION | OBJECT
+                "#
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn applies_extensions() {
+        assert_eq!(
+            errors(
+                r#"
+                type Query { me: User }
+                type User { id: ID! }
+                extend type User { name: String }
+                "#
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            errors("extend type Query { me: Int }"),
+            vec![
+                "schema has no query root operation type",
+                "cannot extend unknown type `Query`"
+            ]
+        );
+        assert_eq!(
+            errors(
+                r#"
+                type Query { me: User }
+                type User { id: ID! friend: Friend }
+                extend type User { id: ID! }
+                "#
+            ),
+            vec![
+                "field `User.friend` has unknown type `Friend`",
+                "field `id` is already defined on `User`"
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_names() {
+        assert_eq!(
+            errors(
+                r#"
+                type Query { a: Int a: Int b(x: Int, x: Int): Int }
+                type Query { c: Int }
+                enum E { A A __B }
+                directive @d on FIELD | FIELD
+                directive @d on FIELD
+                "#
+            ),
+            vec![
+                "type `Query` is defined more than once",
+                "field `Query.a` is defined more than once",
+                "argument `Query.b(x:)` is defined more than once",
+                "enum value `E.A` is defined more than once",
+                "enum value `E.__B` cannot start with `__`, which is reserved for introspection",
+                "directive `@d` lists location `FIELD` more than once",
+                "directive `@d` is defined more than once",
+            ]
+        );
+    }
+
+    #[test]
+    fn interface_implementations() {
+        assert_eq!(
+            errors(
+                r#"
+                type Query { a: A }
+                interface Node { id: ID! }
+                interface Named implements Node { id: ID! name(upper: Boolean): String friend: Named }
+                type A implements Named {
+                    id: ID
+                    name(upper: Int, locale: String!): String
+                }
+                type B implements Node & Node & Query & Missing { id: ID! }
+                type C implements Named & Node {
+                    id: ID!
+                    name(upper: Boolean): String!
+                    friend: C
+                }
+                "#
+            ),
+            vec![
+                "`A` must implement `Node`, which is implemented by `Named`",
+                "field `A.id` of type `ID` is not compatible with `Named.id` of type `ID!`",
+                "argument `A.name(upper:)` of type `Int` must have the type `Boolean` of `Named.name(upper:)`",
+                "argument `A.name(locale:)` must be optional, as `Named.name` does not define it",
+                "`A` must define field `friend`, which is required by `Named`",
+                "`B` implements `Node` more than once",
+                "`B` cannot implement `Query`, which is not an interface",
+                "`B` implements unknown interface `Missing`",
+            ]
+        );
+    }
+
+    #[test]
+    fn type_positions() {
+        assert_eq!(
+            errors(
+                r#"
+                type Query { a(filter: Query): Filter b: [Missing] }
+                union U = Query | Color | Nope
+                input Filter { q: Query, c: Color }
+                enum Color { RED }
+                "#
+            ),
+            vec![
+                "field `Query.a` cannot have input type `Filter`",
+                "argument `Query.a(filter:)` cannot have output type `Query`",
+                "field `Query.b` has unknown type `Missing`",
+                "member `Color` of union `U` must be an object type",
+                "member `Nope` of union `U` is not defined",
+                "input field `Filter.q` cannot have output type `Query`",
+            ]
+        );
+    }
+
+    #[test]
+    fn input_cycles() {
+        assert_eq!(
+            errors(
+                r#"
+                type Query { a: Int }
+                input A { b: B! }
+                input B { a: A!, self: B, list: [B!]! }
+                input C { c: C! }
+                "#
+            ),
+            vec![
+                "input object `A` cannot reference itself through non-null fields `b`, `a`",
+                "input object `C` cannot reference itself through non-null fields `c`",
+            ]
+        );
+    }
+
+    #[test]
+    fn directives_and_roots() {
+        assert_eq!(
+            errors(
+                r#"
+                schema { query: Query mutation: Color subscription: Missing }
+                schema { query: Query }
+                type Query { a: Int }
+                enum Color { RED }
+                directive @d(arg: Query, other: Int @d) on FIELD
+                "#
+            ),
+            vec![
+                "mutation root type `Color` must be an object type",
+                "subscription root type `Missing` is not defined",
+                "schema is defined more than once",
+                "argument `@d(arg:)` cannot have output type `Query`",
+                "directive `@d` cannot reference itself on argument `other`",
+            ]
+        );
+        assert_eq!(
+            errors("type Mutation { a: Int }"),
+            vec!["schema has no query root operation type"]
+        );
+    }
+}
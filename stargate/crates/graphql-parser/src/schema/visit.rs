@@ -7,14 +7,57 @@ use crate::{node_trait, visit, visit_each};
 pub trait Visitor: query::Visitor {
     fn enter_schema<'a>(&mut self, doc: &Document<'a>) {}
     fn enter_schema_def<'a>(&mut self, def: &Definition<'a>) {}
+    fn enter_schema_definition<'a>(&mut self, schema_def: &SchemaDefinition<'a>) {}
+    fn enter_schema_extension<'a>(&mut self, schema_ext: &SchemaExtension<'a>) {}
+    fn enter_scalar_type<'a>(&mut self, scalar: &ScalarType<'a>) {}
+    fn enter_object_type<'a>(&mut self, object: &ObjectType<'a>) {}
+    fn enter_interface_type<'a>(&mut self, interface: &InterfaceType<'a>) {}
+    fn enter_union_type<'a>(&mut self, union: &UnionType<'a>) {}
+    fn enter_enum_type<'a>(&mut self, enum_type: &EnumType<'a>) {}
+    fn enter_input_object_type<'a>(&mut self, input_object: &InputObjectType<'a>) {}
+    fn enter_scalar_type_extension<'a>(&mut self, scalar: &ScalarTypeExtension<'a>) {}
+    fn enter_object_type_extension<'a>(&mut self, object: &ObjectTypeExtension<'a>) {}
+    fn enter_interface_type_extension<'a>(&mut self, interface: &InterfaceTypeExtension<'a>) {}
+    fn enter_union_type_extension<'a>(&mut self, union: &UnionTypeExtension<'a>) {}
+    fn enter_enum_type_extension<'a>(&mut self, enum_type: &EnumTypeExtension<'a>) {}
+    fn enter_input_object_type_extension<'a>(
+        &mut self,
+        input_object: &InputObjectTypeExtension<'a>,
+    ) {
+    }
+    fn enter_enum_value<'a>(&mut self, enum_value: &EnumValue<'a>) {}
+    fn enter_directive_def<'a>(&mut self, directive_def: &DirectiveDefinition<'a>) {}
     fn enter_field<'a>(&mut self, field: &Field<'a>) {}
     fn leave_field<'a>(&mut self, field: &Field<'a>) {}
     fn enter_input_value<'a>(&mut self, input_value: &InputValue<'a>) {}
     fn leave_input_value<'a>(&mut self, input_value: &InputValue<'a>) {}
+    fn leave_directive_def<'a>(&mut self, directive_def: &DirectiveDefinition<'a>) {}
+    fn leave_enum_value<'a>(&mut self, enum_value: &EnumValue<'a>) {}
+    fn leave_input_object_type_extension<'a>(
+        &mut self,
+        input_object: &InputObjectTypeExtension<'a>,
+    ) {
+    }
+    fn leave_enum_type_extension<'a>(&mut self, enum_type: &EnumTypeExtension<'a>) {}
+    fn leave_union_type_extension<'a>(&mut self, union: &UnionTypeExtension<'a>) {}
+    fn leave_interface_type_extension<'a>(&mut self, interface: &InterfaceTypeExtension<'a>) {}
+    fn leave_object_type_extension<'a>(&mut self, object: &ObjectTypeExtension<'a>) {}
+    fn leave_scalar_type_extension<'a>(&mut self, scalar: &ScalarTypeExtension<'a>) {}
+    fn leave_input_object_type<'a>(&mut self, input_object: &InputObjectType<'a>) {}
+    fn leave_enum_type<'a>(&mut self, enum_type: &EnumType<'a>) {}
+    fn leave_union_type<'a>(&mut self, union: &UnionType<'a>) {}
+    fn leave_interface_type<'a>(&mut self, interface: &InterfaceType<'a>) {}
+    fn leave_object_type<'a>(&mut self, object: &ObjectType<'a>) {}
+    fn leave_scalar_type<'a>(&mut self, scalar: &ScalarType<'a>) {}
+    fn leave_schema_extension<'a>(&mut self, schema_ext: &SchemaExtension<'a>) {}
+    fn leave_schema_definition<'a>(&mut self, schema_def: &SchemaDefinition<'a>) {}
     fn leave_schema_def<'a>(&mut self, def: &Definition<'a>) {}
     fn leave_schema<'a>(&mut self, doc: &Document<'a>) {}
 }
 
+/// As with `query::Map`, the projections of the nodes other than documents,
+/// definitions, fields and input values are optional.
+#[allow(unused_variables)]
 pub trait Map: query::Map {
     fn schema<'a>(&mut self, doc: &Document<'a>, stack: &[Self::Output]) -> Self::Output;
     fn schema_def<'a>(&mut self, def: &Definition<'a>, stack: &[Self::Output]) -> Self::Output;
@@ -24,6 +67,119 @@ pub trait Map: query::Map {
         input_value: &InputValue<'a>,
         stack: &[Self::Output],
     ) -> Self::Output;
+
+    fn schema_definition<'a>(
+        &mut self,
+        schema_def: &SchemaDefinition<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn schema_extension<'a>(
+        &mut self,
+        schema_ext: &SchemaExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn scalar_type<'a>(
+        &mut self,
+        scalar: &ScalarType<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn object_type<'a>(
+        &mut self,
+        object: &ObjectType<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn interface_type<'a>(
+        &mut self,
+        interface: &InterfaceType<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn union_type<'a>(
+        &mut self,
+        union: &UnionType<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn enum_type<'a>(
+        &mut self,
+        enum_type: &EnumType<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn input_object_type<'a>(
+        &mut self,
+        input_object: &InputObjectType<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn scalar_type_extension<'a>(
+        &mut self,
+        scalar: &ScalarTypeExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn object_type_extension<'a>(
+        &mut self,
+        object: &ObjectTypeExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn interface_type_extension<'a>(
+        &mut self,
+        interface: &InterfaceTypeExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn union_type_extension<'a>(
+        &mut self,
+        union: &UnionTypeExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn enum_type_extension<'a>(
+        &mut self,
+        enum_type: &EnumTypeExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn input_object_type_extension<'a>(
+        &mut self,
+        input_object: &InputObjectTypeExtension<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn enum_value<'a>(
+        &mut self,
+        enum_value: &EnumValue<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
+    fn directive_def<'a>(
+        &mut self,
+        directive_def: &DirectiveDefinition<'a>,
+        stack: &[Self::Output],
+    ) -> Option<Self::Output> {
+        None
+    }
 }
 
 impl<M: Map> Visitor for visit::Fold<M> {
@@ -46,6 +202,123 @@ impl<M: Map> Visitor for visit::Fold<M> {
     fn leave_input_value<'a>(&mut self, _: &InputValue<'a>) {
         self.pop();
     }
+    fn enter_schema_definition<'a>(&mut self, schema_def: &SchemaDefinition<'a>) {
+        let output = self.map.schema_definition(schema_def, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_schema_definition<'a>(&mut self, _: &SchemaDefinition<'a>) {
+        self.pop_optional();
+    }
+    fn enter_schema_extension<'a>(&mut self, schema_ext: &SchemaExtension<'a>) {
+        let output = self.map.schema_extension(schema_ext, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_schema_extension<'a>(&mut self, _: &SchemaExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_scalar_type<'a>(&mut self, scalar: &ScalarType<'a>) {
+        let output = self.map.scalar_type(scalar, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_scalar_type<'a>(&mut self, _: &ScalarType<'a>) {
+        self.pop_optional();
+    }
+    fn enter_object_type<'a>(&mut self, object: &ObjectType<'a>) {
+        let output = self.map.object_type(object, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_object_type<'a>(&mut self, _: &ObjectType<'a>) {
+        self.pop_optional();
+    }
+    fn enter_interface_type<'a>(&mut self, interface: &InterfaceType<'a>) {
+        let output = self.map.interface_type(interface, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_interface_type<'a>(&mut self, _: &InterfaceType<'a>) {
+        self.pop_optional();
+    }
+    fn enter_union_type<'a>(&mut self, union: &UnionType<'a>) {
+        let output = self.map.union_type(union, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_union_type<'a>(&mut self, _: &UnionType<'a>) {
+        self.pop_optional();
+    }
+    fn enter_enum_type<'a>(&mut self, enum_type: &EnumType<'a>) {
+        let output = self.map.enum_type(enum_type, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_enum_type<'a>(&mut self, _: &EnumType<'a>) {
+        self.pop_optional();
+    }
+    fn enter_input_object_type<'a>(&mut self, input_object: &InputObjectType<'a>) {
+        let output = self.map.input_object_type(input_object, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_input_object_type<'a>(&mut self, _: &InputObjectType<'a>) {
+        self.pop_optional();
+    }
+    fn enter_scalar_type_extension<'a>(&mut self, scalar: &ScalarTypeExtension<'a>) {
+        let output = self.map.scalar_type_extension(scalar, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_scalar_type_extension<'a>(&mut self, _: &ScalarTypeExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_object_type_extension<'a>(&mut self, object: &ObjectTypeExtension<'a>) {
+        let output = self.map.object_type_extension(object, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_object_type_extension<'a>(&mut self, _: &ObjectTypeExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_interface_type_extension<'a>(&mut self, interface: &InterfaceTypeExtension<'a>) {
+        let output = self.map.interface_type_extension(interface, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_interface_type_extension<'a>(&mut self, _: &InterfaceTypeExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_union_type_extension<'a>(&mut self, union: &UnionTypeExtension<'a>) {
+        let output = self.map.union_type_extension(union, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_union_type_extension<'a>(&mut self, _: &UnionTypeExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_enum_type_extension<'a>(&mut self, enum_type: &EnumTypeExtension<'a>) {
+        let output = self.map.enum_type_extension(enum_type, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_enum_type_extension<'a>(&mut self, _: &EnumTypeExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_input_object_type_extension<'a>(
+        &mut self,
+        input_object: &InputObjectTypeExtension<'a>,
+    ) {
+        let output = self
+            .map
+            .input_object_type_extension(input_object, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_input_object_type_extension<'a>(&mut self, _: &InputObjectTypeExtension<'a>) {
+        self.pop_optional();
+    }
+    fn enter_enum_value<'a>(&mut self, enum_value: &EnumValue<'a>) {
+        let output = self.map.enum_value(enum_value, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_enum_value<'a>(&mut self, _: &EnumValue<'a>) {
+        self.pop_optional();
+    }
+    fn enter_directive_def<'a>(&mut self, directive_def: &DirectiveDefinition<'a>) {
+        let output = self.map.directive_def(directive_def, &self.stack);
+        self.push_optional(output);
+    }
+    fn leave_directive_def<'a>(&mut self, _: &DirectiveDefinition<'a>) {
+        self.pop_optional();
+    }
     fn leave_schema_def<'a>(&mut self, _: &Definition<'a>) {
         self.pop();
     }
@@ -68,12 +341,13 @@ impl<'a> Node for Definition<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_schema_def(self);
         match self {
-            Definition::Schema(_) => {}
+            Definition::Schema(s) => s.accept(visitor),
+            Definition::SchemaExtension(se) => se.accept(visitor),
             Definition::Type(t) => t.accept(visitor),
             Definition::TypeExtension(tx) => tx.accept(visitor),
-            Definition::Directive(_) => {}
-            Definition::Operation(o) => o.selection_set.accept(visitor),
-            Definition::Fragment(f) => f.selection_set.accept(visitor),
+            Definition::Directive(d) => d.accept(visitor),
+            Definition::Operation(o) => o.accept(visitor),
+            Definition::Fragment(f) => f.accept(visitor),
         }
         visitor.leave_schema_def(self);
     }
@@ -82,12 +356,12 @@ impl<'a> Node for Definition<'a> {
 impl<'a> Node for TypeDefinition<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         match self {
-            TypeDefinition::Scalar(_) => {}
-            TypeDefinition::Object(o) => visit_each!(visitor: o.fields),
-            TypeDefinition::Interface(i) => visit_each!(visitor: i.fields),
-            TypeDefinition::Union(_) => {}
-            TypeDefinition::Enum(_) => {}
-            TypeDefinition::InputObject(io) => visit_each!(visitor: io.fields),
+            TypeDefinition::Scalar(s) => s.accept(visitor),
+            TypeDefinition::Object(o) => o.accept(visitor),
+            TypeDefinition::Interface(i) => i.accept(visitor),
+            TypeDefinition::Union(u) => u.accept(visitor),
+            TypeDefinition::Enum(e) => e.accept(visitor),
+            TypeDefinition::InputObject(io) => io.accept(visitor),
         }
     }
 }
@@ -95,19 +369,157 @@ impl<'a> Node for TypeDefinition<'a> {
 impl<'a> Node for TypeExtension<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         match self {
-            TypeExtension::Scalar(_) => {}
-            TypeExtension::Object(o) => visit_each!(visitor: o.fields),
-            TypeExtension::Interface(i) => visit_each!(visitor: i.fields),
-            TypeExtension::Union(_) => {}
-            TypeExtension::Enum(_) => {}
-            TypeExtension::InputObject(io) => visit_each!(visitor: io.fields),
+            TypeExtension::Scalar(s) => s.accept(visitor),
+            TypeExtension::Object(o) => o.accept(visitor),
+            TypeExtension::Interface(i) => i.accept(visitor),
+            TypeExtension::Union(u) => u.accept(visitor),
+            TypeExtension::Enum(e) => e.accept(visitor),
+            TypeExtension::InputObject(io) => io.accept(visitor),
         }
     }
 }
 
+impl<'a> Node for SchemaDefinition<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_schema_definition(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_schema_definition(self);
+    }
+}
+
+impl<'a> Node for SchemaExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_schema_extension(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_schema_extension(self);
+    }
+}
+
+impl<'a> Node for ScalarType<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_scalar_type(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_scalar_type(self);
+    }
+}
+
+impl<'a> Node for ObjectType<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_object_type(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.fields);
+        visitor.leave_object_type(self);
+    }
+}
+
+impl<'a> Node for InterfaceType<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_interface_type(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.fields);
+        visitor.leave_interface_type(self);
+    }
+}
+
+impl<'a> Node for UnionType<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_union_type(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_union_type(self);
+    }
+}
+
+impl<'a> Node for EnumType<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_enum_type(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.values);
+        visitor.leave_enum_type(self);
+    }
+}
+
+impl<'a> Node for InputObjectType<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_input_object_type(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.fields);
+        visitor.leave_input_object_type(self);
+    }
+}
+
+impl<'a> Node for ScalarTypeExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_scalar_type_extension(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_scalar_type_extension(self);
+    }
+}
+
+impl<'a> Node for ObjectTypeExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_object_type_extension(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.fields);
+        visitor.leave_object_type_extension(self);
+    }
+}
+
+impl<'a> Node for InterfaceTypeExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_interface_type_extension(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.fields);
+        visitor.leave_interface_type_extension(self);
+    }
+}
+
+impl<'a> Node for UnionTypeExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_union_type_extension(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_union_type_extension(self);
+    }
+}
+
+impl<'a> Node for EnumTypeExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_enum_type_extension(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.values);
+        visitor.leave_enum_type_extension(self);
+    }
+}
+
+impl<'a> Node for InputObjectTypeExtension<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_input_object_type_extension(self);
+        visit_each!(visitor: self.directives);
+        visit_each!(visitor: self.fields);
+        visitor.leave_input_object_type_extension(self);
+    }
+}
+
+impl<'a> Node for EnumValue<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_enum_value(self);
+        visit_each!(visitor: self.directives);
+        visitor.leave_enum_value(self);
+    }
+}
+
+impl<'a> Node for DirectiveDefinition<'a> {
+    fn accept<V: Visitor>(&self, visitor: &mut V) {
+        visitor.enter_directive_def(self);
+        visit_each!(visitor: self.arguments);
+        visitor.leave_directive_def(self);
+    }
+}
+
 impl<'a> Node for Field<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_field(self);
+        visit_each!(visitor: self.arguments);
+        visit_each!(visitor: self.directives);
         visitor.leave_field(self);
     }
 }
@@ -115,6 +527,10 @@ impl<'a> Node for Field<'a> {
 impl<'a> Node for InputValue<'a> {
     fn accept<V: Visitor>(&self, visitor: &mut V) {
         visitor.enter_input_value(self);
+        if let Some(value) = &self.default_value {
+            value.accept(visitor);
+        }
+        visit_each!(visitor: self.directives);
         visitor.leave_input_value(self);
     }
 }
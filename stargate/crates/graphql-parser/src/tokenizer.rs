@@ -1,10 +1,6 @@
 use std::fmt;
 
 use crate::position::Pos;
-use combine::easy::{Error, Errors};
-use combine::error::StreamError;
-use combine::stream::Resetable;
-use combine::{Positioned, StreamOnce};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Kind {
@@ -22,62 +18,15 @@ pub struct Token<'a> {
     pub value: &'a str,
 }
 
-#[derive(Debug, PartialEq)]
+/// Splits a document into tokens, skipping the whitespace, commas and comments
+/// between them.
+#[derive(Debug, Clone, PartialEq)]
 pub struct TokenStream<'a> {
     buf: &'a str,
     position: Pos,
     off: usize,
-    next_state: Option<(usize, Token<'a>, usize, Pos)>,
-}
-
-#[derive(Clone, Debug, PartialEq)]
-pub struct Checkpoint {
-    position: Pos,
-    off: usize,
-}
-
-impl<'a> StreamOnce for TokenStream<'a> {
-    type Item = Token<'a>;
-    type Range = Token<'a>;
-    type Position = Pos;
-    type Error = Errors<Token<'a>, Token<'a>, Pos>;
-
-    fn uncons(&mut self) -> Result<Self::Item, Error<Token<'a>, Token<'a>>> {
-        if let Some((at, tok, off, pos)) = self.next_state {
-            if at == self.off {
-                self.off = off;
-                self.position = pos;
-                return Ok(tok);
-            }
-        }
-        let old_pos = self.off;
-        let (kind, len) = self.peek_token()?;
-        let value = &self.buf[self.off - len..self.off];
-        self.skip_whitespace();
-        let token = Token { kind, value };
-        self.next_state = Some((old_pos, token, self.off, self.position));
-        Ok(token)
-    }
-}
-
-impl<'a> Positioned for TokenStream<'a> {
-    fn position(&self) -> Self::Position {
-        self.position
-    }
-}
-
-impl<'a> Resetable for TokenStream<'a> {
-    type Checkpoint = Checkpoint;
-    fn checkpoint(&self) -> Self::Checkpoint {
-        Checkpoint {
-            position: self.position,
-            off: self.off,
-        }
-    }
-    fn reset(&mut self, checkpoint: Checkpoint) {
-        self.position = checkpoint.position;
-        self.off = checkpoint.off;
-    }
+    /// End of the last token read.
+    end: Pos,
 }
 
 // NOTE: we expect that first character is always digit or minus, as returned
@@ -123,107 +72,122 @@ impl<'a> TokenStream<'a> {
     pub fn new(s: &str) -> TokenStream {
         let mut me = TokenStream {
             buf: s,
-            position: Pos { line: 1, column: 1 },
+            position: Pos {
+                line: 1,
+                column: 1,
+                offset: 0,
+            },
             off: 0,
-            next_state: None,
+            end: Pos::default(),
         };
         me.skip_whitespace();
         me
     }
 
-    fn peek_token(&mut self) -> Result<(Kind, usize), Error<Token<'a>, Token<'a>>> {
-        use self::Kind::*;
-        let mut iter = self.buf[self.off..].char_indices();
-        let cur_char = match iter.next() {
-            Some((_, x)) => x,
-            None => return Err(Error::end_of_input()),
-        };
+    /// Returns the position of the next token, which is where whitespace before it ends.
+    pub fn position(&self) -> Pos {
+        Pos {
+            offset: self.off,
+            ..self.position
+        }
+    }
+
+    /// Returns the position where the last token read ends.
+    pub fn end_position(&self) -> Pos {
+        self.end
+    }
+
+    /// Reads the next token, or returns `None` at the end of input. On error, the
+    /// stream stays before the characters which couldn't be read.
+    pub fn next_token(&mut self) -> Result<Option<Token<'a>>, String> {
+        if self.off == self.buf.len() {
+            return Ok(None);
+        }
+        let (kind, len) = self.lex_token()?;
+        let value = &self.buf[self.off - len..self.off];
+        self.end = self.position();
+        self.skip_whitespace();
+        Ok(Some(Token { kind, value }))
+    }
 
-        match cur_char {
-            '!' | '$' | ':' | '=' | '@' | '|' | '(' | ')' | '[' | ']' | '{' | '}' | '&' => {
+    /// Skips the character the tokenizer failed on, to carry on after a syntax error.
+    pub(crate) fn skip_char(&mut self) {
+        if let Some(c) = self.buf[self.off..].chars().next() {
+            self.off += c.len_utf8();
+            self.position.column += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn lex_token(&mut self) -> Result<(Kind, usize), String> {
+        use self::Kind::*;
+        let rest = &self.buf.as_bytes()[self.off..];
+        match rest[0] {
+            b'!' | b'$' | b':' | b'=' | b'@' | b'|' | b'(' | b')' | b'[' | b']' | b'{' | b'}'
+            | b'&' => {
                 self.position.column += 1;
                 self.off += 1;
 
                 Ok((Punctuator, 1))
             }
-            '.' => {
-                if iter.as_str().starts_with("..") {
+            b'.' => {
+                if rest.starts_with(b"...") {
                     self.position.column += 3;
                     self.off += 3;
 
                     Ok((Punctuator, 3))
                 } else {
-                    Err(Error::unexpected_message(format_args!(
-                        "bare dot {:?} is not supported, \
-                            only \"...\"",
-                        cur_char
-                    )))
+                    Err(String::from("bare dot '.' is not supported, only \"...\""))
                 }
             }
-            '_' | 'a'..='z' | 'A'..='Z' => {
-                while let Some((idx, cur_char)) = iter.next() {
-                    match cur_char {
-                        '_' | 'a'..='z' | 'A'..='Z' | '0'..='9' => continue,
-                        _ => {
-                            self.position.column += idx;
-                            self.off += idx;
-                            return Ok((Name, idx));
-                        }
-                    }
-                }
-                let len = self.buf.len() - self.off;
+            b'_' | b'a'..=b'z' | b'A'..=b'Z' => {
+                let len = rest
+                    .iter()
+                    .position(|b| !matches!(b, b'_' | b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'))
+                    .unwrap_or(rest.len());
                 self.position.column += len;
                 self.off += len;
 
                 Ok((Name, len))
             }
-            '-' | '0'..='9' => {
+            b'-' | b'0'..=b'9' => {
                 let mut exponent = None;
                 let mut real = None;
-                let len = loop {
-                    let (idx, cur_char) = match iter.next() {
-                        Some(pair) => pair,
-                        None => break self.buf.len() - self.off,
-                    };
-                    match cur_char {
+                let mut len = rest.len();
+                for (idx, b) in rest.iter().enumerate().skip(1) {
+                    match b {
                         // just scan for now, will validate later on
-                        ' ' | '\n' | '\r' | '\t' | ',' | '#' | '!' | '$' | ':' | '=' | '@'
-                        | '|' | '&' | '(' | ')' | '[' | ']' | '{' | '}' => break idx,
-                        '.' => real = Some(idx),
-                        'e' | 'E' => exponent = Some(idx),
+                        b' ' | b'\n' | b'\r' | b'\t' | b',' | b'#' | b'!' | b'$' | b':' | b'='
+                        | b'@' | b'|' | b'&' | b'(' | b')' | b'[' | b']' | b'{' | b'}' => {
+                            len = idx;
+                            break;
+                        }
+                        b'.' => real = Some(idx),
+                        b'e' | b'E' => exponent = Some(idx),
                         _ => {}
                     }
-                };
+                }
 
-                if exponent.is_some() || real.is_some() {
-                    let value = &self.buf[self.off..][..len];
+                let value = &self.buf[self.off..][..len];
+                let kind = if exponent.is_some() || real.is_some() {
                     if !check_float(value, exponent, real) {
-                        return Err(Error::unexpected_message(format_args!(
-                            "unsupported float {:?}",
-                            value
-                        )));
+                        return Err(format!("unsupported float {:?}", value));
                     }
-                    self.position.column += len;
-                    self.off += len;
-
-                    Ok((FloatValue, len))
+                    FloatValue
                 } else {
-                    let value = &self.buf[self.off..][..len];
                     if !check_int(value) {
-                        return Err(Error::unexpected_message(format_args!(
-                            "unsupported integer {:?}",
-                            value
-                        )));
+                        return Err(format!("unsupported integer {:?}", value));
                     }
-                    self.position.column += len;
-                    self.off += len;
+                    IntValue
+                };
+                self.position.column += len;
+                self.off += len;
 
-                    Ok((IntValue, len))
-                }
+                Ok((kind, len))
             }
-            '"' => {
-                if iter.as_str().starts_with("\"\"") {
-                    let tail = &iter.as_str()[2..];
+            b'"' => {
+                if rest.starts_with(b"\"\"\"") {
+                    let tail = &self.buf[self.off + 3..];
                     for (end_idx, _) in tail.match_indices("\"\"\"") {
                         if !tail[..end_idx].ends_with('\\') {
                             self.update_position(end_idx + 6);
@@ -231,75 +195,69 @@ impl<'a> TokenStream<'a> {
                         }
                     }
 
-                    Err(Error::unexpected_message("unterminated block string value"))
+                    Err(String::from("unterminated block string value"))
                 } else {
                     let mut nchars = 1;
                     let mut escaped = false;
-                    for (idx, cur_char) in iter {
-                        nchars += 1;
-                        match cur_char {
-                            '"' if escaped => {}
-                            '"' => {
+                    for (idx, &b) in rest.iter().enumerate().skip(1) {
+                        // count characters, not the continuation bytes of multibyte ones
+                        if b & 0xC0 != 0x80 {
+                            nchars += 1;
+                        }
+                        match b {
+                            b'"' if escaped => {}
+                            b'"' => {
                                 self.position.column += nchars;
                                 self.off += idx + 1;
                                 return Ok((StringValue, idx + 1));
                             }
-                            '\n' => {
-                                return Err(Error::unexpected_message("unterminated string value"));
-                            }
-
+                            b'\n' => return Err(String::from("unterminated string value")),
                             _ => {}
                         }
 
                         // if we aren't escaped and the current char is a \, we are now escaped
-                        escaped = !escaped && cur_char == '\\';
+                        escaped = !escaped && b == b'\\';
                     }
-                    Err(Error::unexpected_message("unterminated string value"))
+                    Err(String::from("unterminated string value"))
                 }
             }
-            _ => Err(Error::unexpected_message(format_args!(
+            _ => Err(format!(
                 "unexpected character {:?}",
-                cur_char
-            ))),
+                self.buf[self.off..].chars().next().unwrap()
+            )),
         }
     }
 
     fn skip_whitespace(&mut self) {
-        let mut iter = self.buf[self.off..].char_indices();
-        let idx = loop {
-            let (idx, cur_char) = match iter.next() {
-                Some(pair) => pair,
-                None => break self.buf.len() - self.off,
-            };
-            match cur_char {
-                '\u{feff}' | '\r' => continue,
-                '\t' => self.position.column += 8,
-                '\n' => {
+        let bytes = self.buf.as_bytes();
+        while let Some(&b) = bytes.get(self.off) {
+            match b {
+                b'\r' => {}
+                b'\t' => self.position.column += 8,
+                b'\n' => {
                     self.position.column = 1;
                     self.position.line += 1;
                 }
                 // comma is also entirely ignored in spec
-                ' ' | ',' => {
-                    self.position.column += 1;
+                b' ' | b',' => self.position.column += 1,
+                // comment, up to the line terminator, which is skipped as whitespace
+                b'#' => {
+                    // TODO(tailhook) ensure SourceCharacter
+                    self.off += bytes[self.off..]
+                        .iter()
+                        .position(|&b| b == b'\r' || b == b'\n')
+                        .unwrap_or(bytes.len() - self.off);
                     continue;
                 }
-                //comment
-                '#' => {
-                    #[allow(clippy::while_let_on_iterator)]
-                    while let Some((_, cur_char)) = iter.next() {
-                        // TODO(tailhook) ensure SourceCharacter
-                        if cur_char == '\r' || cur_char == '\n' {
-                            self.position.column = 1;
-                            self.position.line += 1;
-                            break;
-                        }
-                    }
+                // byte order mark
+                0xEF if bytes[self.off..].starts_with("\u{feff}".as_bytes()) => {
+                    self.off += 3;
                     continue;
                 }
-                _ => break idx,
+                _ => return,
             }
-        };
-        self.off += idx;
+            self.off += 1;
+        }
     }
 
     fn update_position(&mut self, len: usize) {
@@ -328,17 +286,14 @@ impl<'a> fmt::Display for Token<'a> {
 mod test {
     use super::Kind::*;
     use super::{Kind, TokenStream};
-    use combine::easy::Error;
-
-    use combine::{Positioned, StreamOnce};
 
     fn tok_str(s: &str) -> Vec<&str> {
         let mut r = Vec::new();
         let mut s = TokenStream::new(s);
         loop {
-            match s.uncons() {
-                Ok(x) => r.push(x.value),
-                Err(ref e) if e == &Error::end_of_input() => break,
+            match s.next_token() {
+                Ok(Some(x)) => r.push(x.value),
+                Ok(None) => break,
                 Err(e) => panic!("Parse error at {}: {}", s.position(), e),
             }
         }
@@ -348,9 +303,9 @@ mod test {
         let mut r = Vec::new();
         let mut s = TokenStream::new(s);
         loop {
-            match s.uncons() {
-                Ok(x) => r.push(x.kind),
-                Err(ref e) if e == &Error::end_of_input() => break,
+            match s.next_token() {
+                Ok(Some(x)) => r.push(x.kind),
+                Ok(None) => break,
                 Err(e) => panic!("Parse error at {}: {}", s.position(), e),
             }
         }
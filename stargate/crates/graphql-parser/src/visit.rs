@@ -27,9 +27,21 @@ pub struct Fold<M: Map> {
 
     /// The root output node.
     pub output: Option<M::Output>,
+
+    /// Whether each node entered with an optional projection has an output node on the stack.
+    optional: Vec<bool>,
 }
 
 impl<M: Map> Fold<M> {
+    pub fn new(map: M) -> Self {
+        Fold {
+            stack: vec![],
+            map,
+            output: None,
+            optional: vec![],
+        }
+    }
+
     pub fn pop(&mut self) {
         let output = self.stack.pop();
         if self.stack.is_empty() || output.is_none() {
@@ -40,6 +52,21 @@ impl<M: Map> Fold<M> {
             self.stack.push(self.map.merge(parent, child));
         }
     }
+
+    /// Pushes the output node of an optional projection, if any.
+    pub fn push_optional(&mut self, output: Option<M::Output>) {
+        self.optional.push(output.is_some());
+        if let Some(output) = output {
+            self.stack.push(output);
+        }
+    }
+
+    /// Pops the output node pushed by the matching `push_optional`, if any.
+    pub fn pop_optional(&mut self) {
+        if self.optional.pop() == Some(true) {
+            self.pop();
+        }
+    }
 }
 
 #[macro_export]
@@ -58,11 +85,7 @@ macro_rules! node_trait {
             fn accept<V: $visitor>(&self, visitor: &mut V);
 
             fn map<M: $map>(&self, map: M) -> crate::visit::Fold<M> {
-                let mut mapping = crate::visit::Fold {
-                    stack: vec![],
-                    map,
-                    output: None,
-                };
+                let mut mapping = crate::visit::Fold::new(map);
                 self.accept(&mut mapping);
                 mapping
             }
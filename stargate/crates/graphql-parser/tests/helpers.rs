@@ -12,6 +12,9 @@ macro_rules! tests_for_parser {
                     format!("{}\n---\n{:#?}", &input, &result));
                 if let Ok(ast) = result {
                     assert_eq!(ast.to_string(), expected);
+                    // the syntax tree keeps the source as is
+                    assert_eq!(graphql_parser::cst::$parser(&input).unwrap().to_string(), input);
+                    [<serde _ $parser>](&ast);
                     assert_snapshot!(
                         stringify!([<$name __ visit _ $parser __ win>]),
                         format!("{}\n---\n{:#?}", &input, [<visit _ $parser>](&ast)));
@@ -30,6 +33,8 @@ macro_rules! tests_for_parser {
                     format!("{}\n---\n{:#?}", &$input.replace("\r\n", "\n"), &result));
                 if let Ok(ast) = result {
                     assert_eq!(ast.to_string(), expected);
+                    // the syntax tree keeps the source as is
+                    assert_eq!(graphql_parser::cst::$parser(&input).unwrap().to_string(), input);
                     assert_snapshot!(
                         stringify!([<$name __ visit _ $parser __ win>]),
                         format!("{}\n---\n{:#?}", &$input.replace("\r\n", "\n"), &[<visit _ $parser>](&ast)));
@@ -49,6 +54,32 @@ macro_rules! test {
 
 use graphql_parser::{query, query::Node as QueryNode, schema, schema::Node as SchemaNode, Name};
 
+/// Round-trips the document and its owned copy through serde, which must give back equal
+/// documents and the same JSON.
+pub fn serde_parse_query(doc: &query::Document) {
+    let json = serde_json::to_string(doc).unwrap();
+    let borrowed: query::Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(&borrowed, doc);
+
+    let owned: query::owned::Document = doc.clone().into_static();
+    assert_eq!(owned.to_string(), doc.to_string());
+    assert_eq!(serde_json::to_string(&owned).unwrap(), json);
+    let owned_back: query::owned::Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(owned_back, owned);
+}
+
+pub fn serde_parse_schema(doc: &schema::Document) {
+    let json = serde_json::to_string(doc).unwrap();
+    let borrowed: schema::Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(&borrowed, doc);
+
+    let owned: schema::owned::Document = doc.clone().into_static();
+    assert_eq!(owned.to_string(), doc.to_string());
+    assert_eq!(serde_json::to_string(&owned).unwrap(), json);
+    let owned_back: schema::owned::Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(owned_back, owned);
+}
+
 pub fn visit_parse_query<'a>(doc: &query::Document<'a>) -> Print {
     let mut p = Print::default();
     doc.accept(&mut p);
@@ -83,22 +114,92 @@ macro_rules! print {
     };
 }
 
+impl Print {
+    fn push(&mut self, event: &str, name: String) {
+        self.output.push(Visit {
+            event: String::from(event),
+            name: Some(name),
+        })
+    }
+}
+
 impl query::Visitor for Print {
     print!(enter_query query::Document);
     print!(leave_query query::Document);
     print!(enter_query_def query::Definition);
     print!(leave_query_def query::Definition);
+    print!(enter_operation query::OperationDefinition);
+    print!(leave_operation query::OperationDefinition);
+    print!(enter_fragment_def query::FragmentDefinition);
+    print!(leave_fragment_def query::FragmentDefinition);
+    print!(enter_variable_def query::VariableDefinition);
+    print!(leave_variable_def query::VariableDefinition);
     print!(enter_sel_set query::SelectionSet);
     print!(leave_sel_set query::SelectionSet);
     print!(enter_sel query::Selection);
     print!(leave_sel query::Selection);
+    print!(enter_query_field query::Field);
+    print!(leave_query_field query::Field);
+    print!(enter_fragment_spread query::FragmentSpread);
+    print!(leave_fragment_spread query::FragmentSpread);
+    print!(enter_inline_fragment query::InlineFragment);
+    print!(leave_inline_fragment query::InlineFragment);
+    print!(enter_directive query::Directive);
+    print!(leave_directive query::Directive);
+
+    fn enter_argument(&mut self, name: &str, _: &query::Value) {
+        self.push("enter_argument", String::from(name));
+    }
+    fn leave_argument(&mut self, name: &str, _: &query::Value) {
+        self.push("leave_argument", String::from(name));
+    }
+    fn enter_value(&mut self, value: &query::Value) {
+        self.push("enter_value", value.to_string());
+    }
+    fn leave_value(&mut self, value: &query::Value) {
+        self.push("leave_value", value.to_string());
+    }
 }
 
 impl schema::Visitor for Print {
     print!(enter_schema schema::Document);
     print!(enter_schema_def schema::Definition);
+    print!(enter_schema_definition schema::SchemaDefinition);
+    print!(enter_schema_extension schema::SchemaExtension);
+    print!(enter_scalar_type schema::ScalarType);
+    print!(enter_object_type schema::ObjectType);
+    print!(enter_interface_type schema::InterfaceType);
+    print!(enter_union_type schema::UnionType);
+    print!(enter_enum_type schema::EnumType);
+    print!(enter_input_object_type schema::InputObjectType);
+    print!(enter_scalar_type_extension schema::ScalarTypeExtension);
+    print!(enter_object_type_extension schema::ObjectTypeExtension);
+    print!(enter_interface_type_extension schema::InterfaceTypeExtension);
+    print!(enter_union_type_extension schema::UnionTypeExtension);
+    print!(enter_enum_type_extension schema::EnumTypeExtension);
+    print!(enter_input_object_type_extension schema::InputObjectTypeExtension);
+    print!(enter_enum_value schema::EnumValue);
+    print!(enter_directive_def schema::DirectiveDefinition);
     print!(enter_field schema::Field);
+    print!(enter_input_value schema::InputValue);
+    print!(leave_input_value schema::InputValue);
     print!(leave_field schema::Field);
+    print!(leave_directive_def schema::DirectiveDefinition);
+    print!(leave_enum_value schema::EnumValue);
+    print!(leave_input_object_type_extension schema::InputObjectTypeExtension);
+    print!(leave_enum_type_extension schema::EnumTypeExtension);
+    print!(leave_union_type_extension schema::UnionTypeExtension);
+    print!(leave_interface_type_extension schema::InterfaceTypeExtension);
+    print!(leave_object_type_extension schema::ObjectTypeExtension);
+    print!(leave_scalar_type_extension schema::ScalarTypeExtension);
+    print!(leave_input_object_type schema::InputObjectType);
+    print!(leave_enum_type schema::EnumType);
+    print!(leave_union_type schema::UnionType);
+    print!(leave_interface_type schema::InterfaceType);
+    print!(leave_object_type schema::ObjectType);
+    print!(leave_scalar_type schema::ScalarType);
+    print!(leave_schema_extension schema::SchemaExtension);
+    print!(leave_schema_definition schema::SchemaDefinition);
     print!(leave_schema_def schema::Definition);
     print!(leave_schema schema::Document);
 }
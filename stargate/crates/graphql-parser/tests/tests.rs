@@ -65,6 +65,11 @@ test!(
     include_str!("extend_scalar.graphql"),
     include_str!("extend_scalar.graphql")
 );
+test!(
+    extend_schema,
+    include_str!("extend_schema.graphql"),
+    include_str!("extend_schema.graphql")
+);
 test!(
     fail_bad_args,
     include_str!("fail_bad_args.graphql"),
@@ -120,6 +125,11 @@ test!(
     include_str!("interface.graphql"),
     include_str!("interface.graphql")
 );
+test!(
+    interface_implements,
+    include_str!("interface_implements.graphql"),
+    include_str!("interface_implements.graphql")
+);
 test!(
     minimal_mutation,
     include_str!("minimal_mutation.graphql"),
@@ -210,16 +220,31 @@ test!(
     include_str!("query_var_defaults.graphql"),
     include_str!("query_var_defaults.graphql")
 );
+test!(
+    query_var_directives,
+    include_str!("query_var_directives.graphql"),
+    include_str!("query_var_directives.graphql")
+);
 test!(
     query_vars,
     include_str!("query_vars.graphql"),
     include_str!("query_vars.graphql")
 );
+test!(
+    repeatable_directive,
+    include_str!("repeatable_directive.graphql"),
+    include_str!("repeatable_directive.graphql")
+);
 test!(
     scalar_type,
     include_str!("scalar_type.graphql"),
     include_str!("scalar_type.graphql")
 );
+test!(
+    schema_description,
+    include_str!("schema_description.graphql"),
+    include_str!("schema_description.graphql")
+);
 test!(
     schema_kitchen_sink,
     include_str!("schema_kitchen_sink.graphql"),
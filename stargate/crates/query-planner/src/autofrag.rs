@@ -1,6 +1,7 @@
 use crate::builder::get_field_def_from_type;
 use crate::consts::{MUTATION_TYPE_NAME, QUERY_TYPE_NAME};
 use crate::context::QueryPlanningContext;
+use crate::{QueryPlanError, Result};
 use graphql_parser::query::refs::{
     FragmentDefinitionRef, FragmentSpreadRef, SelectionRef, SelectionSetRef,
 };
@@ -13,14 +14,14 @@ use std::hash::{Hash, Hasher};
 
 macro_rules! autofrag_field {
     ($field:ident, $parent:ident, $context:ident, $frags:ident, $counter:ident, $ss:expr, $else:expr) => {{
-        let field_return_type = get_field_def_from_type($parent, $field.name)
+        let field_return_type = get_field_def_from_type($parent, $field.name, $field.position)?
             .field_type
             .as_name();
 
         if let Some(new_parent) = $context.names_to_types.get(field_return_type) {
             let new_field = field_ref!(
                 $field,
-                auto_frag_selection_set($context, $frags, $counter, *new_parent, $ss)
+                auto_frag_selection_set($context, $frags, $counter, *new_parent, $ss)?
             );
             SelectionRef::FieldRef(new_field)
         } else {
@@ -32,10 +33,10 @@ macro_rules! autofrag_field {
 macro_rules! autofrag_inline {
     ($inline:ident, $context:ident, $frags:ident, $counter:ident, $ss:expr, $else:expr) => {{
         if let Some(tc) = $inline.type_condition {
-            let new_parent = $context.names_to_types[tc];
+            let new_parent = $context.type_condition(tc, $inline.position)?;
             SelectionRef::InlineFragmentRef(inline_fragment_ref!(
                 $inline,
-                auto_frag_selection_set($context, $frags, $counter, new_parent, $ss)
+                auto_frag_selection_set($context, $frags, $counter, new_parent, $ss)?
             ))
         } else {
             SelectionRef::InlineFragmentRef($else)
@@ -46,12 +47,20 @@ macro_rules! autofrag_inline {
 pub(crate) fn auto_fragmentization<'q>(
     context: &'q QueryPlanningContext<'q>,
     selection_set: SelectionSetRef<'q>,
-) -> (Vec<FragmentDefinitionRef<'q>>, SelectionSetRef<'q>) {
-    let root_parent = if let Operation::Query = &context.operation.kind {
-        context.names_to_types[QUERY_TYPE_NAME]
+) -> Result<(Vec<FragmentDefinitionRef<'q>>, SelectionSetRef<'q>)> {
+    let root_type_name = if let Operation::Query = &context.operation.kind {
+        QUERY_TYPE_NAME
     } else {
-        context.names_to_types[MUTATION_TYPE_NAME]
+        MUTATION_TYPE_NAME
     };
+    let root_parent = context
+        .names_to_types
+        .get(root_type_name)
+        .copied()
+        .ok_or_else(|| QueryPlanError::UnknownType {
+            type_name: root_type_name.to_string(),
+            position: context.operation.position,
+        })?;
 
     fn auto_frag_selection_set<'a, 'q>(
         context: &'q QueryPlanningContext<'q>,
@@ -59,14 +68,14 @@ pub(crate) fn auto_fragmentization<'q>(
         counter: &'a mut Counter,
         parent: &'q TypeDefinition<'q>,
         selection_set: SelectionSetRef<'q>,
-    ) -> SelectionSetRef<'q> {
+    ) -> Result<SelectionSetRef<'q>> {
         let mut new_ss = SelectionSetRef {
             span: selection_set.span,
             items: vec![],
         };
 
         for sel in selection_set.items.into_iter() {
-            let new_sel = auto_frag_selection(context, frags, counter, parent, sel);
+            let new_sel = auto_frag_selection(context, frags, counter, parent, sel)?;
             new_ss.items.push(new_sel)
         }
 
@@ -81,12 +90,12 @@ pub(crate) fn auto_fragmentization<'q>(
                 .name
                 .clone();
 
-            SelectionSetRef {
+            Ok(SelectionSetRef {
                 span: selection_set.span,
                 items: vec![SelectionRef::FragmentSpreadRef(FragmentSpreadRef { name })],
-            }
+            })
         } else {
-            new_ss
+            Ok(new_ss)
         }
     }
 
@@ -96,8 +105,8 @@ pub(crate) fn auto_fragmentization<'q>(
         counter: &'a mut Counter,
         parent: &'q TypeDefinition<'q>,
         selection: SelectionRef<'q>,
-    ) -> SelectionRef<'q> {
-        match selection {
+    ) -> Result<SelectionRef<'q>> {
+        let selection = match selection {
             SelectionRef::Ref(sel) => match sel {
                 Selection::Field(field) => autofrag_field!(
                     field,
@@ -149,7 +158,8 @@ pub(crate) fn auto_fragmentization<'q>(
             SelectionRef::FragmentSpreadRef(_) => {
                 unreachable!("Fragment spreads is only used at the end of query planning")
             }
-        }
+        };
+        Ok(selection)
     }
 
     let mut frags: HashMap<u64, FragmentDefinitionRef<'q>> = HashMap::new();
@@ -160,14 +170,14 @@ pub(crate) fn auto_fragmentization<'q>(
     };
 
     for sel in selection_set.items.into_iter() {
-        let new_sel = auto_frag_selection(context, &mut frags, &mut counter, root_parent, sel);
+        let new_sel = auto_frag_selection(context, &mut frags, &mut counter, root_parent, sel)?;
         new_ss.items.push(new_sel)
     }
 
     let mut values: Vec<FragmentDefinitionRef<'q>> = values!(frags);
     values.sort_by(|a, b| a.name.cmp(&b.name));
 
-    (values, new_ss)
+    Ok((values, new_ss))
 }
 
 fn calculate_hash<T: Hash, R: Hash>(t: &T, parent_type: R) -> u64 {
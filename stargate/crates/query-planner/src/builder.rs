@@ -16,7 +16,7 @@ use crate::{context, model, QueryPlanError, QueryPlanningOptions, Result};
 use graphql_parser::query::refs::{FieldRef, InlineFragmentRef, SelectionRef, SelectionSetRef};
 use graphql_parser::query::*;
 use graphql_parser::schema::TypeDefinition;
-use graphql_parser::{query, schema, DisplayMinified, Name};
+use graphql_parser::{query, schema, DisplayMinified, Name, Pos};
 use linked_hash_map::LinkedHashMap;
 use std::collections::HashSet;
 use std::rc::Rc;
@@ -30,28 +30,33 @@ pub(crate) fn build_query_plan(
 ) -> Result<QueryPlan> {
     let mut ops = get_operations(query);
 
-    if ops.is_empty() {
-        return Ok(QueryPlan { node: None });
-    }
-
     if ops.len() > 1 {
-        return Err(QueryPlanError::InvalidQuery(
-            "multiple operations are not supported",
-        ));
+        return Err(QueryPlanError::InvalidQuery {
+            message: String::from("multiple operations are not supported"),
+            position: ops[1].position,
+        });
     }
 
-    if let Operation::Subscription = ops[0].kind {
-        return Err(QueryPlanError::InvalidQuery(
-            "subscriptions are not supported",
-        ));
+    let operation = match ops.pop() {
+        Some(operation) => operation,
+        None => return Ok(QueryPlan { node: None }),
+    };
+
+    if let Operation::Subscription = operation.kind {
+        return Err(QueryPlanError::InvalidQuery {
+            message: String::from("subscriptions are not supported"),
+            position: operation.position,
+        });
     }
 
+    check_fragment_cycles(query)?;
+
     let types = names_to_types(schema);
 
     // TODO(ran)(p2)(#114) see if we can optimize and memoize the stuff we build only using the schema.
     let context = QueryPlanningContext {
         schema,
-        operation: ops.pop().unwrap(),
+        operation,
         fragments: query
             .definitions
             .iter()
@@ -60,37 +65,46 @@ pub(crate) fn build_query_plan(
                 _ => None,
             })
             .collect(),
-        possible_types: build_possible_types(schema, &types),
+        possible_types: build_possible_types(schema, &types)?,
         variable_name_to_def: variable_name_to_def(query),
-        federation: Federation::new(schema),
+        federation: Federation::new(schema)?,
         names_to_types: types,
         options,
     };
 
     let is_mutation = context.operation.kind.as_str() == "mutation";
 
-    let root_type = if is_mutation {
-        context.names_to_types[MUTATION_TYPE_NAME]
+    let root_type_name = if is_mutation {
+        MUTATION_TYPE_NAME
     } else {
-        context.names_to_types[QUERY_TYPE_NAME]
+        QUERY_TYPE_NAME
+    };
+    let root_type = match context.names_to_types.get(root_type_name) {
+        Some(&root_type) if matches!(root_type, TypeDefinition::Object(_)) => root_type,
+        _ => {
+            return Err(QueryPlanError::InvalidQuery {
+                message: format!("the schema has no `{}` object type", root_type_name),
+                position: context.operation.position,
+            })
+        }
     };
 
     let fields = collect_fields(
         &context,
         context.new_scope(root_type, None),
         SelectionSetRef::from(context.operation.selection_set),
-    );
+    )?;
 
     let groups = if is_mutation {
-        split_root_fields_serially(&context, fields)
+        split_root_fields_serially(&context, fields)?
     } else {
-        split_root_fields(&context, fields)
+        split_root_fields(&context, fields)?
     };
 
     let nodes: Vec<PlanNode> = groups
         .into_iter()
         .map(|group| execution_node_for_group(&context, group, Some(root_type)))
-        .collect();
+        .collect::<Result<_>>()?;
 
     let node = if nodes.is_empty() {
         None
@@ -107,17 +121,17 @@ pub(crate) fn collect_fields<'q>(
     context: &'q QueryPlanningContext<'q>,
     scope: Rc<Scope<'q>>,
     selection_set: SelectionSetRef<'q>,
-) -> FieldSet<'q> {
+) -> Result<FieldSet<'q>> {
     if selection_set.items.is_empty() {
-        return vec![];
+        return Ok(vec![]);
     }
 
     macro_rules! collect_inline_fragment {
         ($inline:ident, $selection_set:expr, $context:ident, $scope:ident, $visited_fragment_names:ident, $fields:ident, $directives:expr) => {
-            let fragment_condition = $inline
-                .type_condition
-                .map(|tc| $context.names_to_types[tc])
-                .unwrap_or_else(|| $scope.parent_type);
+            let fragment_condition = match $inline.type_condition {
+                Some(tc) => $context.type_condition(tc, $inline.position)?,
+                None => $scope.parent_type,
+            };
             let new_scope = $context.new_scope_with_directives(
                 fragment_condition,
                 Some($scope.clone()),
@@ -130,7 +144,7 @@ pub(crate) fn collect_fields<'q>(
                     $selection_set,
                     $visited_fragment_names,
                     $fields,
-                )
+                )?
             }
         };
     }
@@ -141,7 +155,7 @@ pub(crate) fn collect_fields<'q>(
         selection_set: SelectionSetRef<'q>,
         visited_fragment_names: &'a mut HashSet<&'q str>,
         fields: &'a mut FieldSet<'q>,
-    ) {
+    ) -> Result<()> {
         let selections_without_introspection = selection_set
             .items
             .into_iter()
@@ -150,18 +164,23 @@ pub(crate) fn collect_fields<'q>(
         for selection in selections_without_introspection {
             match selection {
                 SelectionRef::FieldRef(field) => {
-                    let name = field.name;
+                    let field_def =
+                        get_field_def_from_type(&scope.parent_type, field.name, field.position)?;
                     fields.push(context::Field {
                         scope: scope.clone(),
                         field_node: field,
-                        field_def: get_field_def_from_type(&scope.parent_type, name),
+                        field_def,
                     })
                 }
                 SelectionRef::Field(field) | SelectionRef::Ref(Selection::Field(field)) => fields
                     .push(context::Field {
                         scope: scope.clone(),
                         field_node: field_ref!(field),
-                        field_def: get_field_def_from_type(&scope.parent_type, field.name),
+                        field_def: get_field_def_from_type(
+                            &scope.parent_type,
+                            field.name,
+                            field.position,
+                        )?,
                     }),
                 SelectionRef::Ref(Selection::InlineFragment(inline)) => {
                     collect_inline_fragment!(
@@ -186,10 +205,16 @@ pub(crate) fn collect_fields<'q>(
                     );
                 }
                 SelectionRef::Ref(Selection::FragmentSpread(spread)) => {
-                    let fragment = context.fragments[spread.fragment_name];
+                    let fragment =
+                        context.fragments.get(spread.fragment_name).ok_or_else(|| {
+                            QueryPlanError::UnknownFragment {
+                                fragment_name: spread.fragment_name.to_string(),
+                                position: spread.position,
+                            }
+                        })?;
                     if !visited_fragment_names.contains(spread.fragment_name) {
                         let new_scope = context.new_scope(
-                            context.names_to_types[fragment.type_condition],
+                            context.type_condition(fragment.type_condition, fragment.position)?,
                             Some(scope.clone()),
                         );
                         if !new_scope.possible_types.is_empty() {
@@ -200,7 +225,7 @@ pub(crate) fn collect_fields<'q>(
                                 SelectionSetRef::from(&fragment.selection_set),
                                 visited_fragment_names,
                                 fields,
-                            );
+                            )?;
                         }
                     }
                 }
@@ -209,6 +234,7 @@ pub(crate) fn collect_fields<'q>(
                 }
             }
         }
+        Ok(())
     }
 
     let mut visited_fragment_names: HashSet<&str> = HashSet::new();
@@ -219,30 +245,30 @@ pub(crate) fn collect_fields<'q>(
         selection_set,
         &mut visited_fragment_names,
         &mut fields,
-    );
-    fields
+    )?;
+    Ok(fields)
 }
 
 fn split_root_fields<'q>(
     context: &'q QueryPlanningContext<'q>,
     fields: FieldSet<'q>,
-) -> Vec<FetchGroup<'q>> {
+) -> Result<Vec<FetchGroup<'q>>> {
     let mut group_for_service = ParallelGroupForField::new(context);
 
-    split_fields(context, vec![], fields, &mut group_for_service);
+    split_fields(context, vec![], fields, &mut group_for_service)?;
 
-    group_for_service.into_groups()
+    Ok(group_for_service.into_groups())
 }
 
 fn split_root_fields_serially<'q>(
     context: &'q QueryPlanningContext<'q>,
     fields: FieldSet<'q>,
-) -> Vec<FetchGroup<'q>> {
+) -> Result<Vec<FetchGroup<'q>>> {
     let mut serial_group_for_field = SerialGroupForField::new(context);
 
-    split_fields(context, vec![], fields, &mut serial_group_for_field);
+    split_fields(context, vec![], fields, &mut serial_group_for_field)?;
 
-    serial_group_for_field.into_groups()
+    Ok(serial_group_for_field.into_groups())
 }
 
 fn split_fields<'a, 'q: 'a>(
@@ -250,7 +276,7 @@ fn split_fields<'a, 'q: 'a>(
     path: ResponsePath,
     fields: FieldSet<'q>,
     grouper: &'a mut dyn GroupForField<'q>,
-) {
+) -> Result<()> {
     let grouped = group_by(fields, |f| f.field_node.response_name());
     let fields_for_response_names: Vec<FieldSet> = values!(grouped);
 
@@ -270,50 +296,58 @@ fn split_fields<'a, 'q: 'a>(
             }
 
             let can_find_group = matches!(
-                context.names_to_types[parent_type],
+                scope.parent_type,
                 schema::TypeDefinition::Object(obj) if scope.possible_types.contains(&obj)
             );
 
             if can_find_group {
-                let group = grouper.group_for_field(scope.parent_type, field_def);
+                let group = grouper.group_for_field(scope.parent_type, field_def)?;
                 complete_field(
                     context,
                     scope.clone(),
                     group,
                     path.clone(),
                     fields_for_parent_type,
-                )
+                )?
             } else {
-                let has_no_extending_field_defs = scope
+                let runtime_field_defs = scope
                     .possible_types
                     .iter()
-                    .map(|runtime_type| get_field_def!(runtime_type, field.field_node.name))
-                    .all(|field_def| {
-                        context
-                            .federation
-                            .service_name_for_field(field_def)
-                            .is_none()
-                    });
+                    .map(|runtime_type| {
+                        get_field_def!(
+                            runtime_type,
+                            field.field_node.name,
+                            field.field_node.position
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let has_no_extending_field_defs = runtime_field_defs.iter().all(|field_def| {
+                    context
+                        .federation
+                        .service_name_for_field(field_def)
+                        .is_none()
+                });
 
                 if has_no_extending_field_defs {
-                    let group = grouper.group_for_field(scope.parent_type, field_def);
+                    let group = grouper.group_for_field(scope.parent_type, field_def)?;
                     complete_field(
                         context,
                         scope.clone(),
                         group,
                         path.clone(),
                         fields_for_parent_type,
-                    );
+                    )?;
                     continue;
                 }
 
-                for runtime_parent_obj_type in scope.possible_types.iter() {
-                    let field_def = get_field_def!(runtime_parent_obj_type, field.field_node.name);
+                for (runtime_parent_obj_type, field_def) in
+                    scope.possible_types.iter().zip(runtime_field_defs)
+                {
                     let new_scope = context.new_scope(
                         context.type_def_for_object(runtime_parent_obj_type),
                         Some(scope.clone()),
                     );
-                    let group = grouper.group_for_field(new_scope.parent_type, field_def);
+                    let group = grouper.group_for_field(new_scope.parent_type, field_def)?;
 
                     let fields_with_runtime_parent_type = fields_for_parent_type
                         .iter()
@@ -330,24 +364,31 @@ fn split_fields<'a, 'q: 'a>(
                         group,
                         path.clone(),
                         fields_with_runtime_parent_type,
-                    );
+                    )?;
                 }
             }
         }
     }
+    Ok(())
 }
 
+/// Returns the definition of the field `name` of `td`, selected at `position` in the query.
 pub(crate) fn get_field_def_from_type<'q>(
     td: &'q TypeDefinition<'q>,
     name: &'q str,
-) -> &'q schema::Field<'q> {
+    position: Pos,
+) -> Result<&'q schema::Field<'q>> {
     if name == TYPENAME_FIELD_NAME {
-        typename_field_def()
+        Ok(typename_field_def())
     } else {
         match td {
-            TypeDefinition::Object(obj) => get_field_def!(obj, name),
-            TypeDefinition::Interface(iface) => get_field_def!(iface, name),
-            _ => unreachable!(),
+            TypeDefinition::Object(obj) => get_field_def!(obj, name, position),
+            TypeDefinition::Interface(iface) => get_field_def!(iface, name, position),
+            _ => Err(QueryPlanError::UnknownField {
+                type_name: td.as_name().to_string(),
+                field_name: name.to_string(),
+                position,
+            }),
         }
     }
 }
@@ -358,21 +399,24 @@ fn complete_field<'a, 'q: 'a>(
     parent_group: &'a mut FetchGroup<'q>,
     path: ResponsePath,
     fields: FieldSet<'q>,
-) {
+) -> Result<()> {
+    let (head, mut tail) = match fields.head() {
+        Some(head_and_tail) => head_and_tail,
+        None => return Ok(()),
+    };
+
     let field: context::Field = {
-        let type_name = fields[0].field_def.field_type.as_name();
+        let type_name = head.field_def.field_type.as_name();
         // the type_name could be a primitive type which is not in our names_to_types map.
         let return_type = context.names_to_types.get(type_name);
 
         if return_type.is_none() || !return_type.unwrap().is_composite_type() {
-            let mut fields = fields;
             context::Field {
                 scope,
-                ..fields.pop().unwrap()
+                ..tail.pop().unwrap_or(head)
             }
         } else {
             let return_type = return_type.expect("Already checked this is not None");
-            let (head, tail) = fields.head();
 
             let field_path = add_path(
                 path,
@@ -382,7 +426,7 @@ fn complete_field<'a, 'q: 'a>(
             let mut sub_group = FetchGroup::new(
                 parent_group.service_name.clone(),
                 field_path.clone(),
-                context.get_provided_fields(head.field_def, &parent_group.service_name),
+                context.get_provided_fields(head.field_def, &parent_group.service_name)?,
             );
 
             if return_type.is_abstract_type() {
@@ -400,8 +444,8 @@ fn complete_field<'a, 'q: 'a>(
             };
 
             let fields: FieldSet = vec![head].into_iter().chain(tail).collect();
-            let sub_fields = collect_sub_fields(context, return_type, fields);
-            let sub_group = split_sub_fields(context, field_path, sub_fields, sub_group);
+            let sub_fields = collect_sub_fields(context, return_type, fields)?;
+            let sub_group = split_sub_fields(context, field_path, sub_fields, sub_group)?;
 
             let selection_set_ref =
                 selection_set_from_field_set(sub_group.fields, Some(return_type), context);
@@ -421,6 +465,7 @@ fn complete_field<'a, 'q: 'a>(
         }
     };
     parent_group.fields.push(field);
+    Ok(())
 }
 
 fn add_path(mut path: ResponsePath, response_name: &str, typ: &Type) -> ResponsePath {
@@ -444,17 +489,17 @@ fn collect_sub_fields<'q>(
     context: &'q QueryPlanningContext<'q>,
     return_type: &'q TypeDefinition<'q>,
     fields: FieldSet<'q>,
-) -> FieldSet<'q> {
-    fields
-        .into_iter()
-        .flat_map(|field| {
-            collect_fields(
-                context,
-                context.new_scope(return_type, None),
-                field.field_node.selection_set,
-            )
-        })
-        .collect()
+) -> Result<FieldSet<'q>> {
+    let mut sub_fields = vec![];
+    for field in fields {
+        let mut fields = collect_fields(
+            context,
+            context.new_scope(return_type, None),
+            field.field_node.selection_set,
+        )?;
+        sub_fields.append(&mut fields);
+    }
+    Ok(sub_fields)
 }
 
 fn split_sub_fields<'q>(
@@ -462,17 +507,17 @@ fn split_sub_fields<'q>(
     field_path: ResponsePath,
     sub_fields: FieldSet<'q>,
     parent_group: FetchGroup<'q>,
-) -> FetchGroup<'q> {
+) -> Result<FetchGroup<'q>> {
     let mut grouper = GroupForSubField::new(context, parent_group);
-    split_fields(context, field_path, sub_fields, &mut grouper);
-    grouper.into_groups().pop().unwrap()
+    split_fields(context, field_path, sub_fields, &mut grouper)?;
+    Ok(grouper.into_parent_group())
 }
 
 fn execution_node_for_group(
     context: &QueryPlanningContext,
     group: FetchGroup,
     parent_type: Option<&TypeDefinition>,
-) -> PlanNode {
+) -> Result<PlanNode> {
     let FetchGroup {
         service_name,
         fields,
@@ -495,7 +540,7 @@ fn execution_node_for_group(
         None
     };
 
-    let (variable_names, variable_defs) = context.get_variable_usages(&selection_set);
+    let (variable_names, variable_defs) = context.get_variable_usages(&selection_set)?;
 
     let operation = if requires.is_some() {
         operation_for_entities_fetch(selection_set, variable_defs)
@@ -505,7 +550,7 @@ fn execution_node_for_group(
             selection_set,
             variable_defs,
             context.operation.kind,
-        )
+        )?
     };
 
     let fetch_node = PlanNode::Fetch(FetchNode {
@@ -528,17 +573,17 @@ fn execution_node_for_group(
         let dependent_nodes = values!(iter dependent_groups_by_service)
             .chain(other_dependent_groups.into_iter())
             .map(|group| execution_node_for_group(context, group, None))
-            .collect();
+            .collect::<Result<_>>()?;
 
-        flat_wrap(
+        Ok(flat_wrap(
             NodeCollectionKind::Sequence,
             vec![
                 plan_node,
                 flat_wrap(NodeCollectionKind::Parallel, dependent_nodes),
             ],
-        )
+        ))
     } else {
-        plan_node
+        Ok(plan_node)
     }
 }
 
@@ -576,8 +621,7 @@ fn selection_set_from_field_set<'q>(
             let name = fields_with_same_reponse_name[0]
                 .field_def
                 .field_type
-                .name()
-                .unwrap();
+                .as_name();
 
             // NB: we don't have specified types (i.e. primitives) in our map.
             // They are not composite types.
@@ -657,7 +701,7 @@ fn operation_for_root_fetch<'q>(
     selection_set: SelectionSetRef<'q>,
     variable_definitions: Vec<&'q VariableDefinition<'q>>,
     op_kind: Operation,
-) -> GraphQLDocument {
+) -> Result<GraphQLDocument> {
     let vars = if variable_definitions.is_empty() {
         String::from("")
     } else {
@@ -670,14 +714,14 @@ fn operation_for_root_fetch<'q>(
         )
     };
 
-    let (frags, selection_set) = maybe_auto_fragmentization(context, selection_set);
+    let (frags, selection_set) = maybe_auto_fragmentization(context, selection_set)?;
 
     let op_kind = match op_kind {
         Operation::Query if vars.is_empty() => "",
         _ => op_kind.as_str(),
     };
 
-    format!("{}{}{}{}", op_kind, vars, selection_set, frags)
+    Ok(format!("{}{}{}{}", op_kind, vars, selection_set, frags))
 }
 
 fn field_into_model_selection(field: &query::Field) -> ModelSelection {
@@ -779,15 +823,15 @@ fn flat_wrap(kind: NodeCollectionKind, mut nodes: Vec<PlanNode>) -> PlanNode {
 fn maybe_auto_fragmentization<'q>(
     context: &'q QueryPlanningContext<'q>,
     selection_set: SelectionSetRef<'q>,
-) -> (String, String) {
+) -> Result<(String, String)> {
     if context.options.auto_fragmentization {
-        let (frags, selection_set) = auto_fragmentization(context, selection_set);
+        let (frags, selection_set) = auto_fragmentization(context, selection_set)?;
         let frags = frags
             .into_iter()
             .map(|fd| fd.minified())
             .collect::<String>();
-        (frags, selection_set.minified())
+        Ok((frags, selection_set.minified()))
     } else {
-        (String::from(""), selection_set.minified())
+        Ok((String::from(""), selection_set.minified()))
     }
 }
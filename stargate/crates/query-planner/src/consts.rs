@@ -1,6 +1,6 @@
 use crate::helpers::{pos, span};
 use graphql_parser::query::refs::{FieldRef, SelectionSetRef};
-use graphql_parser::query::{Directive, Txt, Value};
+use graphql_parser::query::{Argument, Directive};
 use graphql_parser::{schema, schema::Type};
 
 lazy_static! {
@@ -17,10 +17,11 @@ lazy_static! {
     };
     static ref TYPENAME_SCHEMA_FIELD: schema::Field<'static> = schema::Field {
         position: pos(),
+        span: span(),
         description: None,
         name: TYPENAME_FIELD_NAME,
         arguments: vec![],
-        field_type: Type::NonNullType(Box::new(Type::NamedType("String"))),
+        field_type: Type::NonNullType(Box::new(Type::NamedType("String"))).into(),
         directives: vec![]
     };
 }
@@ -30,7 +31,7 @@ pub static INTROSPECTION_SCHEMA_FIELD_NAME: &str = "__schema";
 pub static INTROSPECTION_TYPE_FIELD_NAME: &str = "__type";
 pub static QUERY_TYPE_NAME: &str = "Query";
 pub static MUTATION_TYPE_NAME: &str = "Mutation";
-pub static EMPTY_ARGS: Vec<(Txt<'static>, Value<'static>)> = vec![];
+pub static EMPTY_ARGS: Vec<Argument<'static>> = vec![];
 pub static EMPTY_DIRECTIVES: Vec<Directive<'static>> = vec![];
 
 pub(crate) fn typename_field_def<'a>() -> &'a schema::Field<'a> {
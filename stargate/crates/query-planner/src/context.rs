@@ -1,13 +1,14 @@
 use crate::builder::collect_fields;
 use crate::consts::{typename_field_def, typename_field_node};
 use crate::federation::Federation;
+use crate::groups::FetchGroup;
 use crate::helpers::Op;
 use crate::visitors::VariableUsagesMap;
-use crate::QueryPlanningOptions;
+use crate::{QueryPlanError, QueryPlanningOptions, Result};
 use graphql_parser::query::refs::{FieldRef, Node, SelectionRef, SelectionSetRef};
 use graphql_parser::query::*;
 use graphql_parser::schema::TypeDefinition;
-use graphql_parser::{schema, Name};
+use graphql_parser::{schema, Name, Pos};
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
@@ -58,20 +59,44 @@ impl<'q> QueryPlanningContext<'q> {
         self.new_scope_with_directives(parent_type, enclosing_scope, None)
     }
 
-    fn get_possible_types(&self, td: &'q TypeDefinition<'q>) -> &Vec<&'q schema::ObjectType<'q>> {
-        &self.possible_types[td.as_name()]
+    fn get_possible_types(&self, td: &'q TypeDefinition<'q>) -> &[&'q schema::ObjectType<'q>] {
+        self.possible_types
+            .get(td.as_name())
+            .map_or(&[], |types| types.as_slice())
+    }
+
+    /// Returns the type named by the type condition of a fragment at `position`, which must
+    /// be an object, interface or union type.
+    pub(crate) fn type_condition(
+        &self,
+        name: &str,
+        position: Pos,
+    ) -> Result<&'q TypeDefinition<'q>> {
+        match self.names_to_types.get(name) {
+            Some(td) if td.is_composite_type() => Ok(td),
+            Some(_) => Err(QueryPlanError::InvalidQuery {
+                message: format!("fragment cannot condition on non composite type `{}`", name),
+                position,
+            }),
+            None => Err(QueryPlanError::UnknownType {
+                type_name: name.to_string(),
+                position,
+            }),
+        }
     }
 
     pub(crate) fn get_variable_usages(
         &self,
         selection_set: &SelectionSetRef,
-    ) -> (Vec<String>, Vec<&VariableDefinition>) {
-        selection_set
-            .map(VariableUsagesMap::new(&self.variable_name_to_def))
-            .output
-            .unwrap()
-            .into_iter()
-            .unzip()
+    ) -> Result<(Vec<String>, Vec<&VariableDefinition>)> {
+        let fold = selection_set.map(VariableUsagesMap::new(&self.variable_name_to_def));
+        if let Some((variable_name, position)) = fold.map.undefined_variable {
+            return Err(QueryPlanError::UnknownVariable {
+                variable_name,
+                position,
+            });
+        }
+        Ok(fold.output.unwrap_or_default().into_iter().unzip())
     }
 
     pub(crate) fn type_def_for_object(
@@ -82,29 +107,40 @@ impl<'q> QueryPlanningContext<'q> {
     }
 
     // TODO(ran)(p2)(#114) we may be able to change this return type to &str
-    pub(crate) fn get_base_service(&self, parent_type: &schema::ObjectType) -> String {
+    pub(crate) fn get_base_service(
+        &self,
+        parent_type: &'q TypeDefinition<'q>,
+        field_def: &schema::Field,
+    ) -> Result<String> {
         self.federation
             .service_name_for_type(parent_type)
-            .expect("Cannot find federation metadata")
+            .ok_or_else(|| QueryPlanError::NoOwningService {
+                type_name: parent_type.as_name().to_string(),
+                field_name: field_def.name.to_string(),
+            })
     }
 
     pub(crate) fn get_owning_service(
         &self,
-        parent_type: &schema::ObjectType,
+        parent_type: &'q TypeDefinition<'q>,
         field_def: &schema::Field,
-    ) -> String {
-        self.federation
-            .service_name_for_field(field_def)
-            .unwrap_or_else(|| self.get_base_service(parent_type))
+    ) -> Result<String> {
+        match self.federation.service_name_for_field(field_def) {
+            Some(service_name) => Ok(service_name),
+            None => self.get_base_service(parent_type, field_def),
+        }
     }
 
     // TODO(ran)(p2)(#114) for get_X_fields, we can calculate it once from the schema and put it in some maps or something.
+    /// Returns `__typename` and the key fields for `service_name` of the possible types of
+    /// `parent_type`: all their keys without a `fetching_group`, otherwise a single key per
+    /// type, the cheapest for `fetching_group` to fetch.
     pub(crate) fn get_key_fields<'a>(
         &'q self,
         parent_type: &'q TypeDefinition<'q>,
         service_name: &'a str,
-        fetch_all: bool,
-    ) -> FieldSet<'q> {
+        fetching_group: Option<&FetchGroup<'q>>,
+    ) -> Result<FieldSet<'q>> {
         let mut key_fields = vec![];
 
         key_fields.push(Field {
@@ -120,32 +156,31 @@ impl<'q> QueryPlanningContext<'q> {
             match keys {
                 None => continue,
                 Some(keys) if keys.is_empty() => continue,
-                Some(mut keys) => {
+                Some(keys) => {
                     let possible_type: &'q TypeDefinition<'q> =
                         self.type_def_for_object(possible_type);
                     let new_scope = self.new_scope(possible_type, None);
 
-                    if fetch_all {
-                        let collected_fields = keys.into_iter().flat_map(|key_selection_set| {
-                            collect_fields(self, new_scope.clone(), key_selection_set)
-                        });
-                        key_fields.extend(collected_fields);
-                    } else {
-                        if keys.len() > 1 {
-                            panic!("We think this is not possible; get_key_fields should be \
-                            called with fetch_all = false on cases where there's only one key for the service_name. \
-                            Only for the extending service case. \
-                            parent: {}, service_name: {}", parent_type.as_name(), service_name);
+                    match fetching_group {
+                        None => {
+                            for key_selection_set in keys {
+                                let mut fields =
+                                    collect_fields(self, new_scope.clone(), key_selection_set)?;
+                                key_fields.append(&mut fields);
+                            }
+                        }
+                        Some(group) => {
+                            if let Some(key) = cheapest_key(keys, group) {
+                                let mut fields = collect_fields(self, new_scope.clone(), key)?;
+                                key_fields.append(&mut fields)
+                            }
                         }
-                        let mut fields =
-                            collect_fields(self, new_scope.clone(), keys.pop().unwrap());
-                        key_fields.append(&mut fields)
                     }
                 }
             }
         }
 
-        key_fields
+        Ok(key_fields)
     }
 
     pub(crate) fn get_required_fields<'a>(
@@ -153,22 +188,24 @@ impl<'q> QueryPlanningContext<'q> {
         parent_type: &'q TypeDefinition<'q>,
         field_def: &'q schema::Field<'q>,
         service_name: &'a str,
-    ) -> FieldSet<'q> {
-        let mut required_fields = self.get_key_fields(parent_type, service_name, false);
+        fetching_group: &FetchGroup<'q>,
+    ) -> Result<FieldSet<'q>> {
+        let mut required_fields =
+            self.get_key_fields(parent_type, service_name, Some(fetching_group))?;
 
         if let Some(requires) = self.federation.requires(field_def) {
-            let mut fields = collect_fields(self, self.new_scope(parent_type, None), requires);
+            let mut fields = collect_fields(self, self.new_scope(parent_type, None), requires)?;
             required_fields.append(&mut fields);
         }
 
-        required_fields
+        Ok(required_fields)
     }
 
     pub(crate) fn get_provided_fields<'a>(
         &'q self,
         field_def: &'q schema::Field<'q>,
         service_name: &'a str,
-    ) -> Vec<&'q str> {
+    ) -> Result<Vec<&'q str>> {
         // N.B. this is similar to collect_fields, except we don't care about creating
         //  redundant field nodes and scopes and stuff, we just want field names.
         fn collect_fields_names<'a, 'q>(
@@ -176,7 +213,7 @@ impl<'q> QueryPlanningContext<'q> {
             fragments: &'a HashMap<&'q str, &'q FragmentDefinition<'q>>,
             res: &'a mut Vec<&'q str>,
             visited_fragment_names: &'a mut HashSet<&'q str>,
-        ) {
+        ) -> Result<()> {
             for selection in selection_set.items.into_iter() {
                 match selection {
                     SelectionRef::FieldRef(field) => res.push(field.name),
@@ -188,22 +225,27 @@ impl<'q> QueryPlanningContext<'q> {
                         fragments,
                         res,
                         visited_fragment_names,
-                    ),
+                    )?,
                     SelectionRef::InlineFragmentRef(inline_ref) => collect_fields_names(
                         inline_ref.selection_set,
                         fragments,
                         res,
                         visited_fragment_names,
-                    ),
+                    )?,
                     SelectionRef::Ref(Selection::FragmentSpread(spread)) => {
-                        let fragment = fragments[spread.fragment_name];
+                        let fragment = fragments.get(spread.fragment_name).ok_or_else(|| {
+                            QueryPlanError::UnknownFragment {
+                                fragment_name: spread.fragment_name.to_string(),
+                                position: spread.position,
+                            }
+                        })?;
                         if !visited_fragment_names.contains(spread.fragment_name) {
                             collect_fields_names(
                                 SelectionSetRef::from(&fragment.selection_set),
                                 fragments,
                                 res,
                                 visited_fragment_names,
-                            )
+                            )?
                         }
                     }
                     SelectionRef::FragmentSpreadRef(_) => {
@@ -211,20 +253,16 @@ impl<'q> QueryPlanningContext<'q> {
                     }
                 }
             }
+            Ok(())
         }
 
-        let return_type = self.names_to_types.get(field_def.field_type.as_name());
-        let field_type_is_not_composite =
-            return_type.is_none() || !return_type.unwrap().is_composite_type();
-
-        if field_type_is_not_composite {
-            return vec![];
-        }
-
-        let return_type = return_type.unwrap();
+        let return_type = match self.names_to_types.get(field_def.field_type.as_name()) {
+            Some(return_type) if return_type.is_composite_type() => return_type,
+            _ => return Ok(vec![]),
+        };
 
         let provided_fields = self
-            .get_key_fields(return_type, service_name, true)
+            .get_key_fields(return_type, service_name, None)?
             .into_iter()
             .map(|f| f.field_def.name);
 
@@ -237,16 +275,43 @@ impl<'q> QueryPlanningContext<'q> {
                     &self.fragments,
                     &mut res,
                     &mut visited_fragment_names,
-                );
+                )?;
                 res
             };
-            provided_fields.chain(fields).collect()
+            Ok(provided_fields.chain(fields).collect())
         } else {
-            provided_fields.collect()
+            Ok(provided_fields.collect())
         }
     }
 }
 
+/// Picks the key which adds the fewest fields `group` can't provide to its fetch, then the one
+/// which adds the fewest fields, then the one with the fewest fields, then the first declared.
+fn cheapest_key<'a>(
+    keys: Vec<SelectionSetRef<'a>>,
+    group: &FetchGroup,
+) -> Option<SelectionSetRef<'a>> {
+    keys.into_iter().min_by_key(|key| {
+        let added: Vec<&str> = key
+            .items
+            .iter()
+            .filter_map(|selection| match *selection {
+                SelectionRef::FieldRef(ref field) => Some(field.name),
+                SelectionRef::Field(field) | SelectionRef::Ref(Selection::Field(field)) => {
+                    Some(field.name)
+                }
+                _ => None,
+            })
+            .filter(|name| !group.fields.iter().any(|f| f.field_node.name == *name))
+            .collect();
+        let not_provided = added
+            .iter()
+            .filter(|name| !group.provided_fields.contains(name))
+            .count();
+        (not_provided, added.len(), key.items.len())
+    })
+}
+
 #[derive(Debug, PartialEq)]
 pub(crate) struct Scope<'q> {
     pub parent_type: &'q TypeDefinition<'q>,
@@ -0,0 +1,141 @@
+use graphql_parser::{ParseError, Pos};
+use std::fmt::{Display, Formatter};
+
+/// An error planning a query.
+///
+/// Errors caused by the query carry the position in the query of the operation, selection or
+/// fragment at fault. Errors caused by the schema carry the names of the types, fields and
+/// services involved, and the position in the schema where there is one.
+#[derive(Debug)]
+pub enum QueryPlanError {
+    FailedParsingSchema(ParseError),
+    FailedParsingQuery(ParseError),
+    /// The query can't be planned, e.g. it has several operations or is a subscription.
+    InvalidQuery {
+        message: String,
+        position: Pos,
+    },
+    UnknownType {
+        type_name: String,
+        position: Pos,
+    },
+    UnknownField {
+        type_name: String,
+        field_name: String,
+        position: Pos,
+    },
+    UnknownFragment {
+        fragment_name: String,
+        position: Pos,
+    },
+    UnknownVariable {
+        variable_name: String,
+        position: Pos,
+    },
+    /// The schema is inconsistent, e.g. a union has a member which is not an object type.
+    /// `position` is in the schema.
+    InvalidSchema {
+        message: String,
+        position: Pos,
+    },
+    /// A federation directive of the schema is malformed. `location` is the type or field the
+    /// directive is on, and `position` where the directive is in the schema.
+    InvalidDirective {
+        directive: String,
+        location: String,
+        message: String,
+        position: Pos,
+    },
+    /// Neither the field nor its type is owned by a service.
+    NoOwningService {
+        type_name: String,
+        field_name: String,
+    },
+}
+
+impl QueryPlanError {
+    /// Where in the query the error is, e.g. to return `locations` in GraphQL errors. `None` for
+    /// errors caused by the schema, whose positions are in the schema.
+    pub fn position(&self) -> Option<Pos> {
+        match self {
+            QueryPlanError::FailedParsingQuery(error) => error.position(),
+            QueryPlanError::InvalidQuery { position, .. }
+            | QueryPlanError::UnknownType { position, .. }
+            | QueryPlanError::UnknownField { position, .. }
+            | QueryPlanError::UnknownFragment { position, .. }
+            | QueryPlanError::UnknownVariable { position, .. } => Some(*position),
+            QueryPlanError::FailedParsingSchema(_)
+            | QueryPlanError::InvalidSchema { .. }
+            | QueryPlanError::InvalidDirective { .. }
+            | QueryPlanError::NoOwningService { .. } => None,
+        }
+    }
+}
+
+impl Display for QueryPlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryPlanError::FailedParsingSchema(error) => write!(f, "schema {}", error),
+            QueryPlanError::FailedParsingQuery(error) => write!(f, "query {}", error),
+            QueryPlanError::InvalidQuery { message, position } => {
+                write!(f, "{} at {}", message, position)
+            }
+            QueryPlanError::UnknownType {
+                type_name,
+                position,
+            } => write!(f, "unknown type `{}` at {}", type_name, position),
+            QueryPlanError::UnknownField {
+                type_name,
+                field_name,
+                position,
+            } => write!(
+                f,
+                "cannot query field `{}` on type `{}` at {}",
+                field_name, type_name, position
+            ),
+            QueryPlanError::UnknownFragment {
+                fragment_name,
+                position,
+            } => write!(f, "unknown fragment `{}` at {}", fragment_name, position),
+            QueryPlanError::UnknownVariable {
+                variable_name,
+                position,
+            } => write!(
+                f,
+                "variable `${}` is not defined by the operation, used at {}",
+                variable_name, position
+            ),
+            QueryPlanError::InvalidSchema { message, position } => {
+                write!(f, "invalid schema: {} at {}", message, position)
+            }
+            QueryPlanError::InvalidDirective {
+                directive,
+                location,
+                message,
+                position,
+            } => write!(
+                f,
+                "invalid `@{}` directive on `{}` at {}: {}",
+                directive, location, position, message
+            ),
+            QueryPlanError::NoOwningService {
+                type_name,
+                field_name,
+            } => write!(
+                f,
+                "no service owns field `{}.{}` or its type",
+                type_name, field_name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryPlanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QueryPlanError::FailedParsingSchema(error)
+            | QueryPlanError::FailedParsingQuery(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -4,7 +4,8 @@ use graphql_parser::schema::*;
 use graphql_parser::{parse_query, Pos};
 use std::collections::HashMap;
 
-use crate::helpers::directive_args_as_map;
+use crate::helpers::string_argument;
+use crate::{QueryPlanError, Result};
 
 #[derive(Debug, PartialEq)]
 struct FederationTypeMetadata<'q> {
@@ -47,9 +48,40 @@ pub(crate) struct Federation<'q> {
 }
 
 impl<'q> Federation<'q> {
-    pub(crate) fn new(schema: &'q Document<'q>) -> Federation<'q> {
-        let mut types = FederationTypeMetadata::new();
-        let mut fields = FederationFieldMetadata::new();
+    /// Reads the federation metadata of the object and interface types of `schema`.
+    ///
+    /// An object type inherits the `@owner` and `@key`s of the interfaces it implements, and its
+    /// fields the `@resolve`, `@requires` and `@provides` of the same fields on those interfaces,
+    /// for what it doesn't declare itself. The first interface declaring something wins.
+    pub(crate) fn new(schema: &'q Document<'q>) -> Result<Federation<'q>> {
+        let mut federation = Federation {
+            types: FederationTypeMetadata::new(),
+            fields: FederationFieldMetadata::new(),
+        };
+
+        let interfaces: Vec<&'q InterfaceType<'q>> = schema
+            .definitions
+            .iter()
+            .filter_map(|d| match d {
+                Definition::Type(TypeDefinition::Interface(iface)) => Some(iface),
+                _ => None,
+            })
+            .collect();
+
+        // Interfaces go first, so that the object types implementing them can inherit from them.
+        for iface in interfaces.iter() {
+            federation.read_directives(
+                iface.position,
+                iface.name,
+                &iface.directives,
+                &iface.fields,
+            )?;
+        }
+
+        let interfaces_by_name: HashMap<&str, &'q InterfaceType<'q>> = interfaces
+            .iter()
+            .map(|iface| (iface.name, *iface))
+            .collect();
 
         let obj_types = schema.definitions.iter().filter_map(|d| {
             if let Definition::Type(TypeDefinition::Object(obj)) = d {
@@ -60,76 +92,119 @@ impl<'q> Federation<'q> {
         });
 
         for obj_type in obj_types {
-            // Populate type metadata
+            federation.read_directives(
+                obj_type.position,
+                obj_type.name,
+                &obj_type.directives,
+                &obj_type.fields,
+            )?;
+
+            for iface in obj_type
+                .implements_interfaces
+                .iter()
+                .filter_map(|name| interfaces_by_name.get(name))
             {
-                // value type / owner
-                match get_directive!(obj_type.directives, "owner").next() {
-                    Some(owner_directive) => {
-                        types.is_value_type.insert(obj_type.position, false);
-                        let graph = letp!((_, Value::String(graph)) = &owner_directive.arguments[0] => graph.clone());
-                        types.owner.insert(obj_type.position, graph);
-                    }
-                    None => {
-                        types.is_value_type.insert(obj_type.position, true);
-                    }
-                }
+                federation.inherit(obj_type, iface);
+            }
 
-                // keys
-                let mut keys_for_obj: HashMap<String, Vec<query::SelectionSet<'q>>> =
-                    HashMap::new();
-
-                let graph_to_key_tuples = get_directive!(obj_type.directives, "key")
-                    .map(|key_dir| directive_args_as_map(&key_dir.arguments))
-                    .map(|args| {
-                        (
-                            String::from(args["graph"]),
-                            as_selection_set_ref(args["fields"]),
-                        )
-                    });
-
-                for (graph, key) in graph_to_key_tuples {
-                    keys_for_obj.entry(graph).or_insert_with(Vec::new).push(key)
+            // For serivce_name, fallback to owner of type if it's there.
+            if let Some(graph) = federation.types.owner.get(&obj_type.position) {
+                for field in obj_type.fields.iter() {
+                    federation
+                        .fields
+                        .service_name
+                        .entry(field.position)
+                        .or_insert_with(|| graph.clone());
                 }
+            }
+        }
+
+        Ok(federation)
+    }
 
-                types.keys.insert(obj_type.position, keys_for_obj);
+    /// Records what an object or interface type and its fields declare with directives.
+    fn read_directives(
+        &mut self,
+        position: Pos,
+        type_name: &str,
+        directives: &'q [Directive<'q>],
+        type_fields: &'q [Field<'q>],
+    ) -> Result<()> {
+        // value type / owner
+        match get_directive!(directives, "owner").next() {
+            Some(owner_directive) => {
+                self.types.is_value_type.insert(position, false);
+                let graph = string_arg(owner_directive, "graph", type_name, None)?;
+                self.types.owner.insert(position, graph.to_string());
             }
+            None => {
+                self.types.is_value_type.insert(position, true);
+            }
+        }
 
-            // Populate field metadata
-            {
-                for field in obj_type.fields.iter() {
-                    for d in field.directives.iter() {
-                        match d.name {
-                            "requires" => {
-                                let requires = letp!((_, Value::String(requires)) = &d.arguments[0] => requires);
-                                fields
-                                    .requires
-                                    .insert(field.position, as_selection_set_ref(requires));
-                            }
-                            "provides" => {
-                                let provides = letp!((_, Value::String(provides)) = &d.arguments[0] => provides);
-                                fields
-                                    .provides
-                                    .insert(field.position, as_selection_set_ref(provides));
-                            }
-                            "resolve" => {
-                                let graph = letp!((_, Value::String(graph)) = &d.arguments[0] => graph.clone());
-                                fields.service_name.insert(field.position, graph);
-                            }
-                            _ => (),
-                        }
-                    }
+        // keys
+        let mut keys_for_type: HashMap<String, Vec<query::SelectionSet<'q>>> = HashMap::new();
+
+        for key_dir in get_directive!(directives, "key") {
+            let graph = string_arg(key_dir, "graph", type_name, None)?;
+            let key = field_set_arg(key_dir, type_name, None)?;
+            keys_for_type
+                .entry(String::from(graph))
+                .or_insert_with(Vec::new)
+                .push(key)
+        }
+
+        self.types.keys.insert(position, keys_for_type);
 
-                    // For serivce_name, fallback to owner of type if it's there.
-                    if !fields.service_name.contains_key(&field.position) {
-                        if let Some(graph) = types.owner.get(&obj_type.position) {
-                            fields.service_name.insert(field.position, graph.clone());
-                        }
+        for field in type_fields.iter() {
+            for d in field.directives.iter() {
+                match d.name {
+                    "requires" => {
+                        let requires = field_set_arg(d, type_name, Some(field.name))?;
+                        self.fields.requires.insert(field.position, requires);
+                    }
+                    "provides" => {
+                        let provides = field_set_arg(d, type_name, Some(field.name))?;
+                        self.fields.provides.insert(field.position, provides);
                     }
+                    "resolve" => {
+                        let graph = string_arg(d, "graph", type_name, Some(field.name))?;
+                        self.fields
+                            .service_name
+                            .insert(field.position, graph.to_string());
+                    }
+                    _ => (),
                 }
             }
         }
 
-        Federation { types, fields }
+        Ok(())
+    }
+
+    /// Completes the metadata of `obj_type` and its fields with that of `iface`, for what they
+    /// don't declare or haven't inherited yet. Keys are inherited per service.
+    fn inherit(&mut self, obj_type: &'q ObjectType<'q>, iface: &'q InterfaceType<'q>) {
+        if !self.types.owner.contains_key(&obj_type.position) {
+            if let Some(graph) = self.types.owner.get(&iface.position).cloned() {
+                self.types.is_value_type.insert(obj_type.position, false);
+                self.types.owner.insert(obj_type.position, graph);
+            }
+        }
+
+        if let Some(iface_keys) = self.types.keys.get(&iface.position).cloned() {
+            let keys = self.types.keys.entry(obj_type.position).or_default();
+            for (graph, key) in iface_keys {
+                keys.entry(graph).or_insert(key);
+            }
+        }
+
+        for field in obj_type.fields.iter() {
+            if let Some(iface_field) = iface.fields.iter().find(|f| f.name == field.name) {
+                inherit_entry(&mut self.fields.service_name, field, iface_field);
+                inherit_entry(&mut self.fields.requires, field, iface_field);
+                inherit_entry(&mut self.fields.provides, field, iface_field);
+            }
+        }
     }
 
     pub(crate) fn service_name_for_field<'a>(&'a self, field_def: &'q Field<'q>) -> Option<String> {
@@ -152,9 +227,9 @@ impl<'q> Federation<'q> {
 
     pub(crate) fn service_name_for_type<'a>(
         &'a self,
-        object_type: &'q ObjectType<'q>,
+        parent_type: &'q TypeDefinition<'q>,
     ) -> Option<String> {
-        self.types.owner.get(&object_type.position).cloned()
+        type_position(parent_type).and_then(|position| self.types.owner.get(&position).cloned())
     }
 
     pub(crate) fn key<'a>(
@@ -173,19 +248,85 @@ impl<'q> Federation<'q> {
     }
 
     pub(crate) fn is_value_type<'a>(&'a self, parent_type: &'q TypeDefinition<'q>) -> bool {
-        if let TypeDefinition::Object(object_type) = parent_type {
-            self.types.is_value_type[&object_type.position]
-        } else {
-            true
+        type_position(parent_type)
+            .and_then(|position| self.types.is_value_type.get(&position).copied())
+            .unwrap_or(true)
+    }
+}
+
+/// Returns the position of an object or interface type, which keys its metadata.
+fn type_position(td: &TypeDefinition) -> Option<Pos> {
+    match td {
+        TypeDefinition::Object(obj) => Some(obj.position),
+        TypeDefinition::Interface(iface) => Some(iface.position),
+        _ => None,
+    }
+}
+
+/// Copies the metadata of `from`, a field of an interface, to `field` unless it has its own.
+fn inherit_entry<V: Clone>(map: &mut HashMap<Pos, V>, field: &Field, from: &Field) {
+    if !map.contains_key(&field.position) {
+        if let Some(value) = map.get(&from.position).cloned() {
+            map.insert(field.position, value);
         }
     }
 }
 
-fn as_selection_set_ref(value: &str) -> query::SelectionSet {
-    let ss = parse_query(value)
-        .expect("failed parsing directive value as selection set")
-        .definitions
-        .pop()
-        .unwrap();
-    letp!(query::Definition::SelectionSet(ss) = ss => ss)
+/// Returns the string argument `name` of `directive`, which is on the type `type_name` or on
+/// its field `field_name`.
+fn string_arg<'q>(
+    directive: &'q Directive<'q>,
+    name: &str,
+    type_name: &str,
+    field_name: Option<&str>,
+) -> Result<&'q str> {
+    directive
+        .arguments
+        .iter()
+        .find(|(arg, _)| arg.node == name)
+        .and_then(|(_, value)| string_argument(value))
+        .ok_or_else(|| {
+            invalid_directive(
+                directive,
+                type_name,
+                field_name,
+                format!("expected a string `{}` argument", name),
+            )
+        })
+}
+
+/// Parses the `fields` argument of `directive`, e.g. `"{ id }"`, as a selection set.
+fn field_set_arg<'q>(
+    directive: &'q Directive<'q>,
+    type_name: &str,
+    field_name: Option<&str>,
+) -> Result<query::SelectionSet<'q>> {
+    let fields = string_arg(directive, "fields", type_name, field_name)?;
+    let invalid = |message| invalid_directive(directive, type_name, field_name, message);
+    let mut document = parse_query(fields)
+        .map_err(|e| invalid(format!("failed parsing field set `{}`: {}", fields, e)))?;
+    match document.definitions.pop() {
+        Some(query::Definition::SelectionSet(ss)) if document.definitions.is_empty() => Ok(ss),
+        _ => Err(invalid(format!(
+            "expected a single selection set, got `{}`",
+            fields
+        ))),
+    }
+}
+
+fn invalid_directive(
+    directive: &Directive,
+    type_name: &str,
+    field_name: Option<&str>,
+    message: String,
+) -> QueryPlanError {
+    QueryPlanError::InvalidDirective {
+        directive: directive.name.to_string(),
+        location: match field_name {
+            Some(field_name) => format!("{}.{}", type_name, field_name),
+            None => type_name.to_string(),
+        },
+        message,
+        position: directive.position,
+    }
 }
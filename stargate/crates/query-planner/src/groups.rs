@@ -1,6 +1,7 @@
 use crate::consts::TYPENAME_FIELD_NAME;
 use crate::context::{FieldSet, QueryPlanningContext};
 use crate::model::ResponsePath;
+use crate::Result;
 use graphql_parser::query::FragmentDefinition;
 use graphql_parser::schema;
 use graphql_parser::schema::TypeDefinition;
@@ -71,7 +72,7 @@ pub(crate) trait GroupForField<'q> {
         &'a mut self,
         parent_type: &'q TypeDefinition<'q>,
         field_def: &'q schema::Field<'q>,
-    ) -> &'a mut FetchGroup<'q>;
+    ) -> Result<&'a mut FetchGroup<'q>>;
 
     fn into_groups(self) -> Vec<FetchGroup<'q>>;
 }
@@ -96,20 +97,13 @@ impl<'q> GroupForField<'q> for ParallelGroupForField<'q> {
         &'a mut self,
         parent_type: &'q TypeDefinition<'q>,
         field_def: &'q schema::Field<'q>,
-    ) -> &'a mut FetchGroup<'q> {
-        let parent_type = match parent_type {
-            TypeDefinition::Object(obj) => obj,
-            _ => unreachable!(
-                "Based on the .ts implementation, it's impossible to call this \
-                function with a parent_type that is not an ObjectType"
-            ),
-        };
-
-        let service_name = self.context.get_owning_service(parent_type, field_def);
+    ) -> Result<&'a mut FetchGroup<'q>> {
+        let service_name = self.context.get_owning_service(parent_type, field_def)?;
 
-        self.groups_map
+        Ok(self
+            .groups_map
             .entry(service_name.clone())
-            .or_insert_with(|| FetchGroup::init(service_name))
+            .or_insert_with(|| FetchGroup::init(service_name)))
     }
 
     fn into_groups(self) -> Vec<FetchGroup<'q>> {
@@ -137,23 +131,15 @@ impl<'q> GroupForField<'q> for SerialGroupForField<'q> {
         &'a mut self,
         parent_type: &'q TypeDefinition<'q>,
         field_def: &'q schema::Field<'q>,
-    ) -> &'a mut FetchGroup<'q> {
-        let parent_type = match parent_type {
-            TypeDefinition::Object(obj) => obj,
-            _ => unreachable!(
-                "Based on the .ts implementation, it's impossible to call this \
-                function with a parent_type that is not an ObjectType"
-            ),
-        };
-
-        let service_name = self.context.get_owning_service(parent_type, field_def);
+    ) -> Result<&'a mut FetchGroup<'q>> {
+        let service_name = self.context.get_owning_service(parent_type, field_def)?;
 
         match self.groups.last() {
             Some(group) if group.service_name == service_name => (),
             _ => self.groups.push(FetchGroup::init(service_name)),
         }
 
-        self.groups.last_mut().unwrap()
+        Ok(self.groups.last_mut().unwrap())
     }
 
     fn into_groups(self) -> Vec<FetchGroup<'q>> {
@@ -174,6 +160,10 @@ impl<'q> GroupForSubField<'q> {
             parent_group,
         }
     }
+
+    pub(crate) fn into_parent_group(self) -> FetchGroup<'q> {
+        self.parent_group
+    }
 }
 
 impl<'q> GroupForField<'q> for GroupForSubField<'q> {
@@ -181,9 +171,9 @@ impl<'q> GroupForField<'q> for GroupForSubField<'q> {
         &'a mut self,
         parent_type: &'q TypeDefinition<'q>,
         field_def: &'q schema::Field<'q>,
-    ) -> &'a mut FetchGroup<'q> {
+    ) -> Result<&'a mut FetchGroup<'q>> {
         if field_def.name == TYPENAME_FIELD_NAME {
-            return &mut self.parent_group;
+            return Ok(&mut self.parent_group);
         }
 
         let (base_service, owning_service) = if self.context.federation.is_value_type(parent_type) {
@@ -192,24 +182,14 @@ impl<'q> GroupForField<'q> for GroupForSubField<'q> {
                 self.parent_group.service_name.clone(),
             )
         } else {
-            let obj_type = match parent_type {
-                TypeDefinition::Object(obj) => obj,
-                _ => unreachable!(format!(
-                    "Based on the .ts implementation, it's impossible to call this \
-                    function with a parent_type that is not an ObjectType, \
-                    for fields other than __typename, parent_type: {:?}; field: {}",
-                    parent_type, field_def.name
-                )),
-            };
-
             (
-                self.context.get_base_service(obj_type),
-                self.context.get_owning_service(obj_type, field_def),
+                self.context.get_base_service(parent_type, field_def)?,
+                self.context.get_owning_service(parent_type, field_def)?,
             )
         };
 
         // Is the field defined on the base service?
-        if owning_service == base_service {
+        let group = if owning_service == base_service {
             // Can we fetch the field from the parent group?
             if owning_service == self.parent_group.service_name
                 || self
@@ -225,16 +205,19 @@ impl<'q> GroupForField<'q> for GroupForSubField<'q> {
                 let key_fields = self.context.get_key_fields(
                     parent_type,
                     &self.parent_group.service_name,
-                    false,
-                );
+                    Some(&self.parent_group),
+                )?;
                 let key_fields = if key_fields.len() == 1
                     && key_fields[0].field_def.name == TYPENAME_FIELD_NAME
                 {
                     // Only __typename key found.
                     // In some cases, the parent group does not have any @key directives.
                     // Fall back to owning group's keys
-                    self.context
-                        .get_key_fields(parent_type, &owning_service, false)
+                    self.context.get_key_fields(
+                        parent_type,
+                        &owning_service,
+                        Some(&self.parent_group),
+                    )?
                 } else {
                     key_fields
                 };
@@ -244,9 +227,12 @@ impl<'q> GroupForField<'q> for GroupForSubField<'q> {
             }
         } else {
             // It's an extension field, so we need to fetch the required fields first.
-            let required_fields =
-                self.context
-                    .get_required_fields(parent_type, field_def, &owning_service);
+            let required_fields = self.context.get_required_fields(
+                parent_type,
+                field_def,
+                &owning_service,
+                &self.parent_group,
+            )?;
 
             // Can we fetch the required fields from the parent group?
             let all_required_fields_are_provided = required_fields.iter().all(|required_field| {
@@ -269,14 +255,15 @@ impl<'q> GroupForField<'q> for GroupForSubField<'q> {
                 let key_fields = self.context.get_key_fields(
                     parent_type,
                     &self.parent_group.service_name,
-                    false,
-                );
+                    Some(&self.parent_group),
+                )?;
 
                 self.parent_group
                     .dependent_group_for_service(base_service, key_fields)
                     .dependent_group_for_service(owning_service, required_fields)
             }
-        }
+        };
+        Ok(group)
     }
 
     fn into_groups(self) -> Vec<FetchGroup<'q>> {
@@ -1,10 +1,11 @@
 use crate::consts::{INTROSPECTION_SCHEMA_FIELD_NAME, INTROSPECTION_TYPE_FIELD_NAME};
+use crate::{QueryPlanError, Result};
 use graphql_parser::query::refs::{FieldRef, SelectionRef, SelectionSetRef};
 use graphql_parser::query::*;
 use graphql_parser::schema::TypeDefinition;
-use graphql_parser::{query, schema, Name, Pos};
+use graphql_parser::{query, schema, Name, Pos, Span};
 use linked_hash_map::LinkedHashMap;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 use std::iter::FromIterator;
 
@@ -16,10 +17,12 @@ pub(crate) fn get_operations<'q>(query: &'q Document<'q>) -> Vec<Op<'q>> {
             Definition::Operation(op) => Some(Op {
                 kind: op.kind,
                 selection_set: &op.selection_set,
+                position: op.position,
             }),
             Definition::SelectionSet(ss) => Some(Op {
                 kind: query::Operation::Query,
                 selection_set: ss,
+                position: ss.span.start,
             }),
             _ => None,
         })
@@ -29,7 +32,7 @@ pub(crate) fn get_operations<'q>(query: &'q Document<'q>) -> Vec<Op<'q>> {
 pub(crate) fn build_possible_types<'a, 'q>(
     schema: &'q schema::Document<'q>,
     types: &'a HashMap<&'q str, &'q schema::TypeDefinition<'q>>,
-) -> HashMap<&'q str, Vec<&'q schema::ObjectType<'q>>> {
+) -> Result<HashMap<&'q str, Vec<&'q schema::ObjectType<'q>>>> {
     let mut implementing_types: HashMap<&'q str, Vec<&'q schema::ObjectType<'q>>> = HashMap::new();
 
     // N.B(ran) when switching `types` to LinkedHashMap for consistent ordering,
@@ -52,8 +55,17 @@ pub(crate) fn build_possible_types<'a, 'q>(
                 let objects_for_union = union
                     .types
                     .iter()
-                    .map(|o_name| letp!(TypeDefinition::Object(obj) = types[*o_name] => obj))
-                    .collect();
+                    .map(|o_name| match types.get(o_name) {
+                        Some(TypeDefinition::Object(obj)) => Ok(obj),
+                        _ => Err(QueryPlanError::InvalidSchema {
+                            message: format!(
+                                "member `{}` of union `{}` is not an object type",
+                                o_name, union.name
+                            ),
+                            position: union.position,
+                        }),
+                    })
+                    .collect::<Result<_>>()?;
                 implementing_types.insert(union.name, objects_for_union);
             }
             TypeDefinition::Object(ref obj) => {
@@ -73,13 +85,23 @@ pub(crate) fn build_possible_types<'a, 'q>(
                             .or_insert_with(Vec::new)
                             .push(obj);
 
-                        letp!(
-                            TypeDefinition::Interface(iface) = types[iface] =>
+                        match types.get(iface) {
+                            Some(TypeDefinition::Interface(iface)) => {
                                 for &iface in &iface.implements_interfaces {
                                     // add them to the queue.
                                     queue.push_back(iface);
                                 }
-                        );
+                            }
+                            _ => {
+                                return Err(QueryPlanError::InvalidSchema {
+                                    message: format!(
+                                        "`{}` implemented by `{}` is not an interface",
+                                        iface, obj.name
+                                    ),
+                                    position: obj.position,
+                                })
+                            }
+                        }
                     }
                 }
             }
@@ -87,12 +109,12 @@ pub(crate) fn build_possible_types<'a, 'q>(
         }
     }
 
-    implementing_types
+    Ok(implementing_types)
 }
 
-pub(crate) fn names_to_types<'q>(
-    schema: &'q schema::Document<'q>,
-) -> HashMap<&'q str, &'q TypeDefinition<'q>> {
+pub fn names_to_types<'a, 'q>(
+    schema: &'a schema::Document<'q>,
+) -> HashMap<&'q str, &'a TypeDefinition<'q>> {
     schema
         .definitions
         .iter()
@@ -107,25 +129,98 @@ pub(crate) fn names_to_types<'q>(
 pub(crate) fn variable_name_to_def<'q>(
     query: &'q query::Document<'q>,
 ) -> HashMap<&'q str, &'q VariableDefinition<'q>> {
-    match query
+    match query.definitions.iter().find_map(|d| match d {
+        Definition::Operation(op) => Some(op),
+        _ => None,
+    }) {
+        Some(op) => op
+            .variable_definitions
+            .iter()
+            .map(|vd| (vd.name, vd))
+            .collect(),
+        None => HashMap::new(),
+    }
+}
+
+/// Rejects fragments which spread themselves, directly or through other fragments: collecting
+/// their fields would never end. The error is at the spread closing the cycle.
+pub(crate) fn check_fragment_cycles<'q>(query: &'q Document<'q>) -> Result<()> {
+    let fragments: HashMap<&str, &FragmentDefinition> = query
         .definitions
         .iter()
-        .find(|d| matches!(d, Definition::Operation(_)))
-    {
-        Some(op) => {
-            let defs = letp!(Definition::Operation(op) = op => &op.variable_definitions);
-            defs.iter().map(|vd| (vd.name, vd)).collect()
+        .filter_map(|d| match d {
+            Definition::Fragment(frag) => Some((frag.name, frag)),
+            _ => None,
+        })
+        .collect();
+
+    fn visit<'q>(
+        fragment: &'q FragmentDefinition<'q>,
+        fragments: &HashMap<&'q str, &'q FragmentDefinition<'q>>,
+        path: &mut Vec<&'q str>,
+        checked: &mut HashSet<&'q str>,
+    ) -> Result<()> {
+        path.push(fragment.name);
+        let mut spreads = vec![];
+        fragment_spreads(&fragment.selection_set, &mut spreads);
+        for spread in spreads {
+            let name = spread.fragment_name;
+            if let Some(i) = path.iter().position(|&n| n == name) {
+                let via = if i + 1 < path.len() {
+                    let via: Vec<String> =
+                        path[i + 1..].iter().map(|n| format!("`{}`", n)).collect();
+                    format!(" via {}", via.join(", "))
+                } else {
+                    String::new()
+                };
+                return Err(QueryPlanError::InvalidQuery {
+                    message: format!("cannot spread fragment `{}` within itself{}", name, via),
+                    position: spread.position,
+                });
+            }
+            if checked.contains(name) {
+                continue;
+            }
+            // Unknown fragments are reported when planning.
+            if let Some(&spread_fragment) = fragments.get(name) {
+                visit(spread_fragment, fragments, path, checked)?;
+            }
+        }
+        path.pop();
+        checked.insert(fragment.name);
+        Ok(())
+    }
+
+    let mut checked = HashSet::new();
+    for d in &query.definitions {
+        if let Definition::Fragment(fragment) = d {
+            if !checked.contains(fragment.name) {
+                visit(fragment, &fragments, &mut vec![], &mut checked)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn fragment_spreads<'q>(
+    selection_set: &'q SelectionSet<'q>,
+    spreads: &mut Vec<&'q FragmentSpread<'q>>,
+) {
+    for selection in &selection_set.items {
+        match selection {
+            Selection::Field(field) => fragment_spreads(&field.selection_set, spreads),
+            Selection::FragmentSpread(spread) => spreads.push(spread),
+            Selection::InlineFragment(inline) => fragment_spreads(&inline.selection_set, spreads),
         }
-        None => HashMap::new(),
     }
 }
 
 pub(crate) fn pos() -> Pos {
-    Pos { line: 0, column: 0 }
+    Pos::default()
 }
 
-pub(crate) fn span() -> (Pos, Pos) {
-    (pos(), pos())
+pub(crate) fn span() -> Span {
+    Span::default()
 }
 
 pub(crate) fn merge_selection_sets<'q>(fields: Vec<FieldRef<'q>>) -> SelectionSetRef<'q> {
@@ -143,12 +238,13 @@ pub(crate) fn merge_selection_sets<'q>(fields: Vec<FieldRef<'q>>) -> SelectionSe
             _ => unreachable!(),
         });
 
-        let merged_field_nodes = values!(iter nodes_by_same_name).map(|nodes_with_same_name| {
-            let nothing_to_do = nodes_with_same_name.len() == 1
-                || nodes_with_same_name[0].no_or_empty_selection_set();
+        let merged_field_nodes =
+            values!(iter nodes_by_same_name).filter_map(|nodes_with_same_name: Vec<_>| {
+                let (head, tail) = nodes_with_same_name.head()?;
 
-            if !nothing_to_do {
-                let (head, tail) = nodes_with_same_name.head();
+                if tail.is_empty() || head.no_or_empty_selection_set() {
+                    return Some(head);
+                }
 
                 let mut field_ref = match head {
                     SelectionRef::FieldRef(f) => f,
@@ -172,11 +268,8 @@ pub(crate) fn merge_selection_sets<'q>(fields: Vec<FieldRef<'q>>) -> SelectionSe
 
                 field_ref.selection_set.items = items;
 
-                SelectionRef::FieldRef(field_ref)
-            } else {
-                nodes_with_same_name.head().0
-            }
-        });
+                Some(SelectionRef::FieldRef(field_ref))
+            });
 
         merged_field_nodes
             .chain(aliased_field_nodes)
@@ -236,28 +329,36 @@ pub(crate) fn is_not_introspection_field(selection: &SelectionRef) -> bool {
     }
 }
 
-pub fn directive_args_as_map<'q>(args: &'q [(Txt<'q>, Value<'q>)]) -> HashMap<Txt<'q>, Txt<'q>> {
+/// Returns the string arguments of a directive by name. Arguments which are not strings are
+/// left out.
+pub fn directive_args_as_map<'q>(args: &'q [Argument<'q>]) -> HashMap<Txt<'q>, Txt<'q>> {
     args.iter()
-        .map(|(k, v)| {
-            let str = letp!(Value::String(str) = v => str);
-            (*k, str.as_str())
-        })
+        .filter_map(|(k, v)| string_argument(v).map(|v| (k.node, v)))
         .collect()
 }
 
+/// Returns the text of a string argument, without copying it, or `None` if
+/// the value is not a string. Block strings are not dedented: indentation
+/// doesn't matter to the field sets and graph names the federation directives take.
+pub fn string_argument<'q>(value: &'q Value<'q>) -> Option<Txt<'q>> {
+    match value {
+        Value::String(StringValue::Source(str)) | Value::String(StringValue::Block(str)) => {
+            Some(str)
+        }
+        Value::String(StringValue::Owned(str)) => Some(str),
+        _ => None,
+    }
+}
+
 pub trait Head<T> {
-    /// gets the head and tail of a vector
-    fn head(self) -> (T, Vec<T>);
+    /// gets the head and tail of a vector, or `None` if it is empty
+    fn head(self) -> Option<(T, Vec<T>)>;
 }
 
 impl<T> Head<T> for Vec<T> {
-    fn head(self) -> (T, Vec<T>) {
-        if self.is_empty() {
-            panic!("head must be called on a non empty Vec")
-        } else {
-            let mut iter = self.into_iter();
-            (iter.next().unwrap(), iter.collect())
-        }
+    fn head(self) -> Option<(T, Vec<T>)> {
+        let mut iter = self.into_iter();
+        iter.next().map(|head| (head, iter.collect()))
     }
 }
 
@@ -265,6 +366,8 @@ impl<T> Head<T> for Vec<T> {
 pub(crate) struct Op<'q> {
     pub selection_set: &'q SelectionSet<'q>,
     pub kind: query::Operation,
+    /// Where the operation starts in the query.
+    pub position: Pos,
 }
 
 pub enum NodeCollectionKind {
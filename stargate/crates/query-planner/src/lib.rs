@@ -6,7 +6,7 @@ extern crate derive_builder;
 
 use crate::builder::build_query_plan;
 use crate::model::QueryPlan;
-use graphql_parser::{parse_query, parse_schema, schema, ParseError};
+use graphql_parser::{parse_query, parse_schema, query, schema};
 use serde::{Deserialize, Serialize};
 
 #[macro_use]
@@ -15,18 +15,14 @@ mod autofrag;
 mod builder;
 mod consts;
 mod context;
+mod error;
 mod federation;
 mod groups;
 pub mod helpers;
 pub mod model;
 mod visitors;
 
-#[derive(Debug)]
-pub enum QueryPlanError {
-    FailedParsingSchema(ParseError),
-    FailedParsingQuery(ParseError),
-    InvalidQuery(&'static str),
-}
+pub use crate::error::QueryPlanError;
 
 pub type Result<T> = std::result::Result<T, QueryPlanError>;
 
@@ -36,15 +32,24 @@ pub struct QueryPlanner<'s> {
 }
 
 impl<'s> QueryPlanner<'s> {
-    pub fn new(schema: &'s str) -> QueryPlanner<'s> {
-        let schema = parse_schema(schema).expect("failed parsing schema");
-        QueryPlanner { schema }
+    pub fn new(schema: &'s str) -> Result<QueryPlanner<'s>> {
+        let schema = parse_schema(schema).map_err(QueryPlanError::FailedParsingSchema)?;
+        Ok(QueryPlanner { schema })
     }
 
     // TODO(ran) FIXME: make options a field on the planner.
     pub fn plan(&self, query: &str, options: QueryPlanningOptions) -> Result<QueryPlan> {
-        let query = parse_query(query).expect("failed parsing query");
-        build_query_plan(&self.schema, &query, options)
+        let query = parse_query(query).map_err(QueryPlanError::FailedParsingQuery)?;
+        self.plan_document(&query, options)
+    }
+
+    /// Plans a query that was already parsed, e.g. with the caller's `ParserOptions`.
+    pub fn plan_document(
+        &self,
+        query: &query::Document,
+        options: QueryPlanningOptions,
+    ) -> Result<QueryPlan> {
+        build_query_plan(&self.schema, query, options)
     }
 }
 
@@ -61,7 +66,7 @@ pub struct QueryPlanningOptions {
 #[cfg(test)]
 mod tests {
     use crate::model::QueryPlan;
-    use crate::{QueryPlanner, QueryPlanningOptionsBuilder};
+    use crate::{QueryPlanError, QueryPlanner, QueryPlanningOptionsBuilder};
     use gherkin_rust::Feature;
     use gherkin_rust::StepType;
     use std::fs::{read_dir, read_to_string};
@@ -95,7 +100,7 @@ mod tests {
 
         for dir in dirs {
             let schema = read_to_string(dir.join("csdl.graphql")).unwrap();
-            let planner = QueryPlanner::new(&schema);
+            let planner = QueryPlanner::new(&schema).unwrap();
             let feature_paths = read_dir(dir)
                 .unwrap()
                 .map(|res| res.map(|e| e.path()).unwrap())
@@ -135,6 +140,114 @@ mod tests {
         }
     }
 
+    fn plan_error(schema: &str, query: &str) -> String {
+        let planner = QueryPlanner::new(schema).unwrap();
+        let options = QueryPlanningOptionsBuilder::default().build().unwrap();
+        planner.plan(query, options).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn query_errors() {
+        let schema = read_to_string(
+            PathBuf::from("tests")
+                .join("features")
+                .join("basic")
+                .join("csdl.graphql"),
+        )
+        .unwrap();
+        let cases = [
+            ("{ me { nope } }", "cannot query field `nope` on type `User` at 1:8"),
+            (
+                "{ body { ... on Image { nope } } }",
+                "cannot query field `nope` on type `Image` at 1:25",
+            ),
+            ("{ me { ... on Nope { id } } }", "unknown type `Nope` at 1:12"),
+            ("{ me { ...Nope } }", "unknown fragment `Nope` at 1:11"),
+            (
+                "query { user(id: $id) { id } }",
+                "variable `$id` is not defined by the operation, used at 1:9",
+            ),
+            (
+                "{ me { id } } { me { name } }",
+                "multiple operations are not supported at 1:15",
+            ),
+            (
+                "subscription { me { id } }",
+                "subscriptions are not supported at 1:1",
+            ),
+            (
+                "query { me { ...A } } fragment A on User { reviews { author { ...A } } }",
+                "cannot spread fragment `A` within itself at 1:66",
+            ),
+            (
+                "{ me { ...A } } fragment A on User { ...B } fragment B on User { ... on User { ...A } }",
+                "cannot spread fragment `A` within itself via `B` at 1:83",
+            ),
+            (
+                "{ me { id }",
+                "query parse error: Parse error at 1:12\nUnexpected end of input\nExpected `}`, `Name` or `...`\n",
+            ),
+        ];
+        for (query, expected) in cases.iter() {
+            assert_eq!(plan_error(&schema, query), *expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn schema_errors() {
+        let user = r#"type User @owner(graph: "accounts") @key(fields: "{ id }", graph: "accounts") {
+            id: ID!
+        }"#;
+        let me = r#"type Query { me: User @resolve(graph: "accounts") }"#;
+        let cases = [
+            (
+                format!("type Query {{ me: User }} {}", user),
+                "no service owns field `Query.me` or its type",
+            ),
+            (
+                format!("type Mutation {{ me: User }} {}", user),
+                "the schema has no `Query` object type at 1:1",
+            ),
+            (
+                format!("{} {}", me, user.replace("{ id }", "{ id")),
+                "invalid `@key` directive on `User` at 1:89: failed parsing field set `{ id`: \
+                 parse error: Parse error at 1:5\nUnexpected end of input\n\
+                 Expected `:`, `(`, `@`, `{`, `}`, `Name` or `...`\n",
+            ),
+            (
+                format!("{} {}", me, user.replace(r#", graph: "accounts""#, "")),
+                "invalid `@key` directive on `User` at 1:89: expected a string `graph` argument",
+            ),
+            (
+                format!(
+                    "{} {}",
+                    me,
+                    user.replace(r#"graph: "accounts")"#, "graph: 1)")
+                ),
+                "invalid `@owner` directive on `User` at 1:63: expected a string `graph` argument",
+            ),
+            (
+                format!(
+                    r#"type Query {{ me: Node @resolve(graph: "accounts") }} union Node = User | ID {}"#,
+                    user
+                ),
+                "invalid schema: member `ID` of union `Node` is not an object type at 1:53",
+            ),
+        ];
+        for (schema, expected) in cases.iter() {
+            assert_eq!(
+                plan_error(schema, "{ me { reviews } }"),
+                *expected,
+                "{}",
+                schema
+            );
+        }
+        assert!(matches!(
+            QueryPlanner::new("type Query {"),
+            Err(QueryPlanError::FailedParsingSchema(_))
+        ));
+    }
+
     #[test]
     fn query_planning_options_initialization() {
         let options = QueryPlanningOptionsBuilder::default().build().unwrap();
@@ -6,8 +6,8 @@ macro_rules! get_directive {
     ($directives:expr , $name:literal , $arg:literal == $arg_val:expr) => {
         $directives.iter().filter(|d| d.name == $name).filter(|d| {
             d.arguments.iter().any(|(k, v)| {
-                if let graphql_parser::query::Value::String(v) = v {
-                    k == &$arg && v == $arg_val
+                if let graphql_parser::query::Value::String(v) = &v.node {
+                    k.node == $arg && v == $arg_val
                 } else {
                     false
                 }
@@ -16,25 +16,18 @@ macro_rules! get_directive {
     };
 }
 
-macro_rules! letp {
-    ($pat:pat = $expr:expr => $stmt:stmt ) => {
-        if let $pat = $expr {
-            $stmt
-        } else {
-            unreachable!()
-        }
-    };
-}
-
 macro_rules! get_field_def {
-    ($obj:ident, $name:expr) => {
+    ($obj:ident, $name:expr, $position:expr) => {
         if $name == crate::consts::TYPENAME_FIELD_NAME {
-            crate::consts::typename_field_def()
+            Ok(crate::consts::typename_field_def())
         } else {
-            $obj.fields
-                .iter()
-                .find(|f| f.name == $name)
-                .unwrap_or_else(|| panic!("Cannot query field `{}` on type `{}`", $name, $obj.name))
+            $obj.fields.iter().find(|f| f.name == $name).ok_or_else(|| {
+                crate::QueryPlanError::UnknownField {
+                    type_name: $obj.name.to_string(),
+                    field_name: $name.to_string(),
+                    position: $position,
+                }
+            })
         }
     };
 }
@@ -5,9 +5,12 @@ use linked_hash_map::LinkedHashMap;
 use graphql_parser::query;
 use graphql_parser::query::refs::{SelectionRef, SelectionSetRef};
 use graphql_parser::query::*;
+use graphql_parser::Pos;
 
 pub(crate) struct VariableUsagesMap<'q> {
     variable_definitions: &'q HashMap<&'q str, &'q VariableDefinition<'q>>,
+    /// The first variable used which the operation doesn't define, and where it is used.
+    pub undefined_variable: Option<(String, Pos)>,
 }
 
 impl<'q> VariableUsagesMap<'q> {
@@ -16,6 +19,7 @@ impl<'q> VariableUsagesMap<'q> {
     ) -> Self {
         Self {
             variable_definitions,
+            undefined_variable: None,
         }
     }
 }
@@ -29,14 +33,21 @@ impl<'q> graphql_parser::Map for VariableUsagesMap<'q> {
 }
 
 macro_rules! build_variables_map {
-    (as_map $iter:expr, $self:ident) => {
-        $iter
-            .map(|name| {
-                let td = $self.variable_definitions[name.as_str()];
-                (name, td)
-            })
-            .collect::<LinkedHashMap<String, &'q VariableDefinition<'q>>>()
-    };
+    (as_map $iter:expr, $position:expr, $self:ident) => {{
+        let variable_definitions = $self.variable_definitions;
+        let mut map: LinkedHashMap<String, &'q VariableDefinition<'q>> = LinkedHashMap::new();
+        for name in $iter {
+            match variable_definitions.get(name.as_str()) {
+                Some(&td) => {
+                    map.insert(name, td);
+                }
+                None => {
+                    $self.undefined_variable.get_or_insert((name, $position));
+                }
+            }
+        }
+        map
+    }};
     (args: $args:ident, $self:ident) => {
         $args
             .arguments
@@ -50,14 +61,14 @@ macro_rules! build_variables_map {
                 .iter()
                 .flat_map(|d| &d.arguments)
                 .flat_map(|(_, v)| variable_usage_from_value(&v)),
-        ), $self)
+        ), $field.position, $self)
     };
     ($args:ident, $self:ident) => {
         build_variables_map!(as_map $args
             .directives
             .iter()
             .flat_map(|d| &d.arguments)
-            .flat_map(|(_, v)| variable_usage_from_value(&v)), $self)
+            .flat_map(|(_, v)| variable_usage_from_value(&v)), $args.position, $self)
     };
 }
 
@@ -104,8 +115,14 @@ impl<'q> refs::Map for VariableUsagesMap<'q> {
 fn variable_usage_from_value(value: &Value) -> Vec<String> {
     match value {
         Value::Variable(str) => vec![String::from(*str)],
-        Value::List(values) => values.iter().flat_map(variable_usage_from_value).collect(),
-        Value::Object(map) => map.values().flat_map(variable_usage_from_value).collect(),
+        Value::List(values) => values
+            .iter()
+            .flat_map(|value| variable_usage_from_value(value))
+            .collect(),
+        Value::Object(map) => map
+            .values()
+            .flat_map(|value| variable_usage_from_value(value))
+            .collect(),
         _ => vec![],
     }
 }
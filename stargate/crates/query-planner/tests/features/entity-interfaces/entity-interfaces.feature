@@ -0,0 +1,158 @@
+Feature: Query Planning > Entity interfaces
+
+  Scenario: dispatches the fields of an entity interface to the services of its implementations
+    Given query
+      """
+      query {
+        topProducts {
+          upc
+          name
+          reviews {
+            body
+          }
+          ... on Book {
+            pages
+          }
+          ... on Movie {
+            duration
+          }
+        }
+      }
+      """
+    Then query plan
+      """
+      {
+        "kind": "QueryPlan",
+        "node": {
+          "kind": "Sequence",
+          "nodes": [
+            {
+              "kind": "Fetch",
+              "serviceName": "products",
+              "variableUsages": [],
+              "operation": "{topProducts{__typename ...on Book{upc name __typename}...on Movie{upc __typename}}}"
+            },
+            {
+              "kind": "Parallel",
+              "nodes": [
+                {
+                  "kind": "Flatten",
+                  "path": ["topProducts", "@"],
+                  "node": {
+                    "kind": "Fetch",
+                    "serviceName": "movies",
+                    "requires": [
+                      {
+                        "kind": "InlineFragment",
+                        "typeCondition": "Movie",
+                        "selections": [
+                          { "kind": "Field", "name": "__typename" },
+                          { "kind": "Field", "name": "upc" }
+                        ]
+                      }
+                    ],
+                    "variableUsages": [],
+                    "operation": "query($representations:[_Any!]!){_entities(representations:$representations){...on Movie{name duration}}}"
+                  }
+                },
+                {
+                  "kind": "Flatten",
+                  "path": ["topProducts", "@"],
+                  "node": {
+                    "kind": "Fetch",
+                    "serviceName": "reviews",
+                    "requires": [
+                      {
+                        "kind": "InlineFragment",
+                        "typeCondition": "Book",
+                        "selections": [
+                          { "kind": "Field", "name": "__typename" },
+                          { "kind": "Field", "name": "upc" }
+                        ]
+                      },
+                      {
+                        "kind": "InlineFragment",
+                        "typeCondition": "Movie",
+                        "selections": [
+                          { "kind": "Field", "name": "__typename" },
+                          { "kind": "Field", "name": "upc" }
+                        ]
+                      }
+                    ],
+                    "variableUsages": [],
+                    "operation": "query($representations:[_Any!]!){_entities(representations:$representations){...on Book{reviews{body}}...on Movie{reviews{body}}}}"
+                  }
+                },
+                {
+                  "kind": "Flatten",
+                  "path": ["topProducts", "@"],
+                  "node": {
+                    "kind": "Fetch",
+                    "serviceName": "books",
+                    "requires": [
+                      {
+                        "kind": "InlineFragment",
+                        "typeCondition": "Book",
+                        "selections": [
+                          { "kind": "Field", "name": "__typename" },
+                          { "kind": "Field", "name": "upc" }
+                        ]
+                      }
+                    ],
+                    "variableUsages": [],
+                    "operation": "query($representations:[_Any!]!){_entities(representations:$representations){...on Book{pages}}}"
+                  }
+                }
+              ]
+            }
+          ]
+        }
+      }
+      """
+
+  Scenario: inherits the owner and keys of the interface
+    Given query
+      """
+      query {
+        topProducts {
+          name
+        }
+      }
+      """
+    Then query plan
+      """
+      {
+        "kind": "QueryPlan",
+        "node": {
+          "kind": "Sequence",
+          "nodes": [
+            {
+              "kind": "Fetch",
+              "serviceName": "products",
+              "variableUsages": [],
+              "operation": "{topProducts{__typename ...on Book{name}...on Movie{__typename upc}}}"
+            },
+            {
+              "kind": "Flatten",
+              "path": ["topProducts", "@"],
+              "node": {
+                "kind": "Fetch",
+                "serviceName": "movies",
+                "requires": [
+                  {
+                    "kind": "InlineFragment",
+                    "typeCondition": "Movie",
+                    "selections": [
+                      { "kind": "Field", "name": "__typename" },
+                      { "kind": "Field", "name": "upc" }
+                    ]
+                  }
+                ],
+                "variableUsages": [],
+                "operation": "query($representations:[_Any!]!){_entities(representations:$representations){...on Movie{name}}}"
+              }
+            }
+          ]
+        }
+      }
+      """
@@ -70,3 +70,100 @@ Feature: Query Planning > Multiple keys
         }
       }
       """
+
+  Scenario: Picks the key the parent fetch provides
+    Given query
+      """
+        query {
+          reviews {
+            author {
+              balance
+            }
+          }
+        }
+      """
+    Then query plan
+      """
+      {
+        "kind": "QueryPlan",
+        "node": {
+          "kind": "Sequence",
+          "nodes": [
+            {
+              "kind": "Fetch",
+              "serviceName": "reviews",
+              "variableUsages": [],
+              "operation": "{reviews{author{__typename id}}}"
+            },
+            {
+              "kind": "Flatten",
+              "path": ["reviews", "@", "author"],
+              "node": {
+                "kind": "Fetch",
+                "serviceName": "billing",
+                "requires": [
+                  {
+                    "kind": "InlineFragment",
+                    "typeCondition": "User",
+                    "selections": [
+                      { "kind": "Field", "name": "__typename" },
+                      { "kind": "Field", "name": "id" }
+                    ]
+                  }
+                ],
+                "variableUsages": [],
+                "operation": "query($representations:[_Any!]!){_entities(representations:$representations){...on User{balance}}}"
+              }
+            }
+          ]
+        }
+      }
+      """
+
+  Scenario: Picks the key the parent fetch already selects
+    Given query
+      """
+        query {
+          users {
+            id
+            balance
+          }
+        }
+      """
+    Then query plan
+      """
+      {
+        "kind": "QueryPlan",
+        "node": {
+          "kind": "Sequence",
+          "nodes": [
+            {
+              "kind": "Fetch",
+              "serviceName": "users",
+              "variableUsages": [],
+              "operation": "{users{id __typename}}"
+            },
+            {
+              "kind": "Flatten",
+              "path": ["users", "@"],
+              "node": {
+                "kind": "Fetch",
+                "serviceName": "billing",
+                "requires": [
+                  {
+                    "kind": "InlineFragment",
+                    "typeCondition": "User",
+                    "selections": [
+                      { "kind": "Field", "name": "__typename" },
+                      { "kind": "Field", "name": "id" }
+                    ]
+                  }
+                ],
+                "variableUsages": [],
+                "operation": "query($representations:[_Any!]!){_entities(representations:$representations){...on User{balance}}}"
+              }
+            }
+          ]
+        }
+      }
+      """
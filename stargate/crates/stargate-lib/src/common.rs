@@ -1,6 +1,10 @@
+use crate::request_pipeline::query_limits::QueryLimits;
+use crate::usage_reporting::UsageReportingConfig;
 use std::fmt::{Display, Formatter};
+use std::num::NonZeroU64;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 use structopt::StructOpt;
 use url::Url;
 
@@ -22,11 +26,46 @@ pub struct Opt {
     #[structopt(short, long)]
     pub structured_logging: bool,
 
-    /// Endpoint url to send traces to (Jaeger format).
+    /// Endpoint url to send traces to.
     /// Setting this argument enables tracing.
-    /// Accepts [http|udp]://host:port/path (path is optional)
+    /// Accepts [http|https|udp]://host:port/path or unix:///path/to/socket for Jaeger,
+    /// and otlp+[grpc|http|https]://host:port/path for OTLP (path is optional)
     #[structopt(short, long)]
     pub tracing_endpoint: Option<TracingConfig>,
+
+    /// Sampler to use when tracing is enabled.
+    /// Accepts always_on, always_off, ratio:<0.0..1.0> or parent:<sampler>,
+    /// where parent follows the sampling decision of the incoming trace context if there is one.
+    #[structopt(long, default_value = "always_on")]
+    pub tracing_sampler: SamplerConfig,
+
+    /// API key used to send usage reports.
+    /// Setting this argument enables usage reporting.
+    #[structopt(long, env = "APOLLO_KEY", hide_env_values = true)]
+    pub apollo_key: Option<String>,
+
+    /// Graph and variant usage reports are for, e.g. mygraph@current
+    #[structopt(long, env = "APOLLO_GRAPH_REF")]
+    pub apollo_graph_ref: Option<String>,
+
+    /// Endpoint url to send usage reports to.
+    #[structopt(
+        long,
+        default_value = "https://usage-reporting.api.apollographql.com/api/ingress/traces"
+    )]
+    pub usage_reporting_endpoint: String,
+
+    /// Interval in seconds between usage reports, which can't be 0.
+    #[structopt(long, default_value = "20")]
+    pub usage_reporting_interval: NonZeroU64,
+
+    /// Request ftv1 traces from subgraphs for every operation, to report per-field statistics
+    /// of all operations. Otherwise they only come from operations whose client asked for a trace.
+    #[structopt(long)]
+    pub usage_reporting_field_stats: bool,
+
+    #[structopt(flatten)]
+    pub query_limits: QueryLimits,
 }
 
 impl Opt {
@@ -47,32 +86,80 @@ impl Opt {
         if let Some(ref tracing_endpoint) = self.tracing_endpoint {
             buf.push_str("tracing: ");
             buf.push_str(tracing_endpoint.to_string().as_str());
+            buf.push('\n');
+
+            buf.push_str("tracing_sampler: ");
+            buf.push_str(self.tracing_sampler.to_string().as_str());
         } else {
             buf.push_str("tracing: disabled");
         }
+        buf.push('\n');
+
+        if self.apollo_key.is_some() {
+            buf.push_str("usage_reporting: ");
+            buf.push_str(&self.usage_reporting_endpoint);
+            buf.push_str(&format!(" every {}s", self.usage_reporting_interval));
+            if self.usage_reporting_field_stats {
+                buf.push_str(" with field statistics");
+            }
+        } else {
+            buf.push_str("usage_reporting: disabled");
+        }
+        buf.push('\n');
+
+        if self.query_limits != QueryLimits::default() {
+            buf.push_str(&format!("query_limits: {:?}", self.query_limits));
+        } else {
+            buf.push_str("query_limits: disabled");
+        }
 
         buf
     }
+
+    /// Usage reporting is enabled when an API key is given.
+    pub fn usage_reporting_config(&self) -> Option<UsageReportingConfig> {
+        self.apollo_key
+            .as_ref()
+            .map(|api_key| UsageReportingConfig {
+                endpoint: self.usage_reporting_endpoint.clone(),
+                api_key: Some(api_key.clone()),
+                graph_ref: self.apollo_graph_ref.clone(),
+                send_interval: Duration::from_secs(self.usage_reporting_interval.get()),
+                field_stats: self.usage_reporting_field_stats,
+            })
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct TracingConfig {
+    pub exporter: TracingExporter,
     pub protocol: TracingProtocol,
     pub host_port_path: String,
 }
 
 impl Display for TracingConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&format!("{:?}://{}", self.protocol, self.host_port_path).to_lowercase())
+        let scheme = match self.exporter {
+            TracingExporter::Jaeger => format!("{:?}", self.protocol),
+            TracingExporter::Otlp => format!("{:?}+{:?}", self.exporter, self.protocol),
+        };
+        f.write_str(&format!("{}://{}", scheme, self.host_port_path).to_lowercase())
     }
 }
 
-// TODO(ran) support HTTPS
-// TODO(ran) support UDS ?
+#[derive(Debug, PartialEq)]
+pub enum TracingExporter {
+    Jaeger,
+    Otlp,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum TracingProtocol {
     HTTP,
+    HTTPS,
     UDP,
+    UNIX,
+    GRPC,
 }
 
 impl FromStr for TracingConfig {
@@ -80,33 +167,99 @@ impl FromStr for TracingConfig {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let err = || {
-            String::from("endpoint must be in the format of [http|udp]://[host]:[port]/path (path is optional)")
+            String::from("endpoint must be in the format of [http|https|udp|otlp+grpc|otlp+http|otlp+https]://[host]:[port]/path (path is optional) or unix:///path/to/socket")
         };
-        match Url::parse(s) {
-            Err(e) => Err(e.to_string()),
-            Ok(url) => {
-                if (url.scheme() != "http" && url.scheme() != "udp")
-                    || url.port().is_none()
-                    || url.host_str().is_none()
-                {
-                    Err(err())
-                } else {
-                    let protocol = if url.scheme() == "udp" {
-                        TracingProtocol::UDP
-                    } else {
-                        TracingProtocol::HTTP
-                    };
-
-                    let mut host_port_path =
-                        format!("{}:{}", url.host_str().unwrap(), url.port().unwrap());
-                    if url.path() != "/" {
-                        host_port_path.push_str(url.path());
-                    }
+        let url = Url::parse(s).map_err(|e| e.to_string())?;
+
+        let (exporter, protocol) = match url.scheme() {
+            "http" => (TracingExporter::Jaeger, TracingProtocol::HTTP),
+            "https" => (TracingExporter::Jaeger, TracingProtocol::HTTPS),
+            "udp" => (TracingExporter::Jaeger, TracingProtocol::UDP),
+            "unix" => (TracingExporter::Jaeger, TracingProtocol::UNIX),
+            "otlp+grpc" => (TracingExporter::Otlp, TracingProtocol::GRPC),
+            "otlp+http" => (TracingExporter::Otlp, TracingProtocol::HTTP),
+            "otlp+https" => (TracingExporter::Otlp, TracingProtocol::HTTPS),
+            _ => return Err(err()),
+        };
+
+        if protocol == TracingProtocol::UNIX {
+            // unix:///tmp/jaeger.sock has no host, the socket path is the url path.
+            if url.host_str().is_some() || url.path().is_empty() || url.path() == "/" {
+                return Err(err());
+            }
+            return Ok(TracingConfig {
+                exporter,
+                protocol,
+                host_port_path: String::from(url.path()),
+            });
+        }
 
-                    Ok(TracingConfig {
-                        protocol,
-                        host_port_path,
-                    })
+        match (url.host_str(), url.port_or_known_default()) {
+            (Some(host), Some(port)) => {
+                let mut host_port_path = format!("{}:{}", host, port);
+                if url.path() != "/" && !url.path().is_empty() {
+                    host_port_path.push_str(url.path());
+                }
+
+                Ok(TracingConfig {
+                    exporter,
+                    protocol,
+                    host_port_path,
+                })
+            }
+            _ => Err(err()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SamplerConfig {
+    AlwaysOn,
+    AlwaysOff,
+    /// Samples the given fraction of traces, based on the trace id.
+    Ratio(f64),
+    /// Follows the sampling decision of the parent span, and uses the inner sampler for root spans.
+    ParentBased(Box<SamplerConfig>),
+}
+
+impl Display for SamplerConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SamplerConfig::AlwaysOn => f.write_str("always_on"),
+            SamplerConfig::AlwaysOff => f.write_str("always_off"),
+            SamplerConfig::Ratio(ratio) => write!(f, "ratio:{}", ratio),
+            SamplerConfig::ParentBased(root) => write!(f, "parent:{}", root),
+        }
+    }
+}
+
+impl FromStr for SamplerConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            String::from(
+                "sampler must be one of always_on, always_off, ratio:<0.0..1.0> or parent:<sampler>",
+            )
+        };
+        match s {
+            "always_on" => Ok(SamplerConfig::AlwaysOn),
+            "always_off" => Ok(SamplerConfig::AlwaysOff),
+            _ => {
+                if let Some(ratio) = s.strip_prefix("ratio:") {
+                    match ratio.parse::<f64>() {
+                        Ok(ratio) if (0.0..=1.0).contains(&ratio) => {
+                            Ok(SamplerConfig::Ratio(ratio))
+                        }
+                        _ => Err(err()),
+                    }
+                } else if let Some(root) = s.strip_prefix("parent:") {
+                    match SamplerConfig::from_str(root)? {
+                        SamplerConfig::ParentBased(_) => Err(err()),
+                        root => Ok(SamplerConfig::ParentBased(Box::new(root))),
+                    }
+                } else {
+                    Err(err())
                 }
             }
         }
@@ -121,10 +274,17 @@ impl Default for Opt {
 
 #[cfg(test)]
 mod tests {
-    use crate::common::{Opt, TracingConfig, TracingProtocol};
+    use crate::common::{Opt, SamplerConfig, TracingConfig, TracingExporter, TracingProtocol};
+    use crate::request_pipeline::query_limits::QueryLimits;
+    use crate::usage_reporting::UsageReportingConfig;
+    use std::num::NonZeroU64;
     use std::path::PathBuf;
+    use std::time::Duration;
     use structopt::StructOpt;
 
+    const DEFAULT_USAGE_REPORTING_ENDPOINT: &str =
+        "https://usage-reporting.api.apollographql.com/api/ingress/traces";
+
     #[test]
     fn test_good_opt() {
         assert_eq!(
@@ -133,7 +293,14 @@ mod tests {
                 manifest: PathBuf::from("foo.graphql"),
                 structured_logging: false,
                 port: 8080,
-                tracing_endpoint: None
+                tracing_endpoint: None,
+                tracing_sampler: SamplerConfig::AlwaysOn,
+                apollo_key: None,
+                apollo_graph_ref: None,
+                usage_reporting_endpoint: String::from(DEFAULT_USAGE_REPORTING_ENDPOINT),
+                usage_reporting_interval: NonZeroU64::new(20).unwrap(),
+                usage_reporting_field_stats: false,
+                query_limits: QueryLimits::default(),
             }
         );
 
@@ -145,7 +312,14 @@ mod tests {
                 manifest: PathBuf::from("foo.graphql"),
                 structured_logging: true,
                 port: 8181,
-                tracing_endpoint: None
+                tracing_endpoint: None,
+                tracing_sampler: SamplerConfig::AlwaysOn,
+                apollo_key: None,
+                apollo_graph_ref: None,
+                usage_reporting_endpoint: String::from(DEFAULT_USAGE_REPORTING_ENDPOINT),
+                usage_reporting_interval: NonZeroU64::new(20).unwrap(),
+                usage_reporting_field_stats: false,
+                query_limits: QueryLimits::default(),
             }
         );
 
@@ -158,9 +332,17 @@ mod tests {
                 structured_logging: false,
                 port: 8080,
                 tracing_endpoint: Some(TracingConfig {
+                    exporter: TracingExporter::Jaeger,
                     protocol: TracingProtocol::UDP,
                     host_port_path: String::from("localhost:6831")
-                })
+                }),
+                tracing_sampler: SamplerConfig::AlwaysOn,
+                apollo_key: None,
+                apollo_graph_ref: None,
+                usage_reporting_endpoint: String::from(DEFAULT_USAGE_REPORTING_ENDPOINT),
+                usage_reporting_interval: NonZeroU64::new(20).unwrap(),
+                usage_reporting_field_stats: false,
+                query_limits: QueryLimits::default(),
             }
         );
 
@@ -173,9 +355,17 @@ mod tests {
                 structured_logging: false,
                 port: 8080,
                 tracing_endpoint: Some(TracingConfig {
+                    exporter: TracingExporter::Jaeger,
                     protocol: TracingProtocol::HTTP,
                     host_port_path: String::from("localhost:6831")
-                })
+                }),
+                tracing_sampler: SamplerConfig::AlwaysOn,
+                apollo_key: None,
+                apollo_graph_ref: None,
+                usage_reporting_endpoint: String::from(DEFAULT_USAGE_REPORTING_ENDPOINT),
+                usage_reporting_interval: NonZeroU64::new(20).unwrap(),
+                usage_reporting_field_stats: false,
+                query_limits: QueryLimits::default(),
             }
         );
 
@@ -189,9 +379,145 @@ mod tests {
                 structured_logging: false,
                 port: 8080,
                 tracing_endpoint: Some(TracingConfig {
+                    exporter: TracingExporter::Jaeger,
                     protocol: TracingProtocol::HTTP,
                     host_port_path: String::from("localhost:14268/api/traces")
-                })
+                }),
+                tracing_sampler: SamplerConfig::AlwaysOn,
+                apollo_key: None,
+                apollo_graph_ref: None,
+                usage_reporting_endpoint: String::from(DEFAULT_USAGE_REPORTING_ENDPOINT),
+                usage_reporting_interval: NonZeroU64::new(20).unwrap(),
+                usage_reporting_field_stats: false,
+                query_limits: QueryLimits::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tracing_endpoints() {
+        let cases = vec![
+            (
+                "https://localhost:14268/api/traces",
+                TracingExporter::Jaeger,
+                TracingProtocol::HTTPS,
+                "localhost:14268/api/traces",
+            ),
+            (
+                "https://jaeger.example.com/api/traces",
+                TracingExporter::Jaeger,
+                TracingProtocol::HTTPS,
+                "jaeger.example.com:443/api/traces",
+            ),
+            (
+                "unix:///var/run/jaeger.sock",
+                TracingExporter::Jaeger,
+                TracingProtocol::UNIX,
+                "/var/run/jaeger.sock",
+            ),
+            (
+                "otlp+grpc://localhost:4317",
+                TracingExporter::Otlp,
+                TracingProtocol::GRPC,
+                "localhost:4317",
+            ),
+            (
+                "otlp+http://localhost:4318/v1/traces",
+                TracingExporter::Otlp,
+                TracingProtocol::HTTP,
+                "localhost:4318/v1/traces",
+            ),
+        ];
+
+        for (endpoint, exporter, protocol, host_port_path) in cases {
+            let config: TracingConfig = endpoint.parse().unwrap();
+            assert_eq!(
+                config,
+                TracingConfig {
+                    exporter,
+                    protocol,
+                    host_port_path: String::from(host_port_path),
+                }
+            );
+            assert_eq!(config.to_string().parse::<TracingConfig>().unwrap(), config);
+        }
+
+        assert_eq!(
+            "otlp+https://collector:4318/v1/traces"
+                .parse::<TracingConfig>()
+                .unwrap()
+                .to_string(),
+            "otlp+https://collector:4318/v1/traces"
+        );
+    }
+
+    #[test]
+    fn test_tracing_sampler() {
+        assert_eq!(
+            Opt::from_iter(
+                "test --manifest foo.graphql --tracing-endpoint udp://localhost:6831 --tracing-sampler parent:ratio:0.25"
+                    .split(' ')
+            )
+            .tracing_sampler,
+            SamplerConfig::ParentBased(Box::new(SamplerConfig::Ratio(0.25)))
+        );
+        assert_eq!(
+            "always_off".parse::<SamplerConfig>(),
+            Ok(SamplerConfig::AlwaysOff)
+        );
+        assert_eq!(
+            "parent:always_on"
+                .parse::<SamplerConfig>()
+                .unwrap()
+                .to_string(),
+            "parent:always_on"
+        );
+
+        assert!("ratio:1.5".parse::<SamplerConfig>().is_err());
+        assert!("ratio:abc".parse::<SamplerConfig>().is_err());
+        assert!("parent:parent:always_on".parse::<SamplerConfig>().is_err());
+        assert!("sometimes".parse::<SamplerConfig>().is_err());
+    }
+
+    #[test]
+    fn test_usage_reporting() {
+        let opt = Opt::from_iter("test --manifest foo.graphql".split(' '));
+        assert!(opt.usage_reporting_config().is_none());
+
+        let opt = Opt::from_iter(
+            "test --manifest foo.graphql --apollo-key service:a:b --apollo-graph-ref a@current --usage-reporting-endpoint http://localhost:8081 --usage-reporting-interval 5 --usage-reporting-field-stats"
+                .split(' '),
+        );
+        assert_eq!(
+            opt.usage_reporting_config(),
+            Some(UsageReportingConfig {
+                endpoint: String::from("http://localhost:8081"),
+                api_key: Some(String::from("service:a:b")),
+                graph_ref: Some(String::from("a@current")),
+                send_interval: Duration::from_secs(5),
+                field_stats: true,
+            })
+        );
+
+        assert!(Opt::from_iter_safe(
+            "test --manifest foo.graphql --usage-reporting-interval 0".split(' ')
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_query_limits() {
+        assert_eq!(
+            Opt::from_iter(
+                "test --manifest foo.graphql --max-query-depth 10 --max-query-cost 1000 --default-list-size 20"
+                    .split(' ')
+            )
+            .query_limits,
+            QueryLimits {
+                max_depth: Some(10),
+                max_cost: Some(1000),
+                default_list_size: 20,
+                ..Default::default()
             }
         );
     }
@@ -208,7 +534,11 @@ mod tests {
         )
         .is_err());
         assert!(Opt::from_iter_safe(
-            "test --manifest foo.graphql --tracing-endpoint https://localhost:6831".split(' '),
+            "test --manifest foo.graphql --tracing-endpoint unix://localhost:6831/".split(' '),
+        )
+        .is_err());
+        assert!(Opt::from_iter_safe(
+            "test --manifest foo.graphql --tracing-endpoint otlp+udp://localhost:6831".split(' '),
         )
         .is_err());
     }
@@ -1,45 +1,113 @@
 use crate::request_pipeline::executor::execute_query_plan;
+use crate::request_pipeline::federated_tracing::encode_ftv1;
+use crate::request_pipeline::query_limits::{QueryMetrics, SchemaIndex};
 use crate::request_pipeline::service_definition::ServiceDefinition;
 use crate::transports::http::{GraphQLResponse, RequestContext};
+use crate::usage_reporting::{stats_report_key, UsageReporter, UsageReportingConfig};
 use apollo_query_planner::helpers::directive_args_as_map;
 use apollo_query_planner::{QueryPlanner, QueryPlanningOptionsBuilder};
-use graphql_parser::schema;
+use graphql_parser::{parse_query_with_options, schema};
+use serde_json::json;
 use std::collections::HashMap;
+use std::time::Instant;
 use tracing::instrument;
 
 pub mod common;
+pub mod proto;
 mod request_pipeline;
 pub mod transports;
+pub mod usage_reporting;
 mod utilities;
 
+pub use crate::request_pipeline::query_limits::{QueryLimitError, QueryLimits};
+pub use apollo_query_planner::QueryPlanError;
+
 #[derive(Debug)]
 pub struct Stargate<'app> {
     service_list: HashMap<String, ServiceDefinition>,
     pub planner: QueryPlanner<'app>,
+    schema_index: SchemaIndex<'app>,
+    usage_reporter: Option<UsageReporter>,
+    query_limits: QueryLimits,
 }
 
 impl<'app> Stargate<'app> {
-    pub fn new(schema: &'app str) -> Stargate<'app> {
+    pub fn new(schema: &'app str) -> std::result::Result<Stargate<'app>, QueryPlanError> {
         // TODO(ran) FIXME: gql validation on schema
-        let planner = QueryPlanner::new(schema);
+        let planner = QueryPlanner::new(schema)?;
         let service_list = get_service_list(&planner.schema);
-        Stargate {
+        let schema_index = SchemaIndex::new(&planner.schema);
+        Ok(Stargate {
             planner,
+            schema_index,
             service_list,
-        }
+            usage_reporter: None,
+            query_limits: QueryLimits::default(),
+        })
+    }
+
+    /// Rejects operations exceeding `limits` with a [QueryLimitError], or a [ParseError] if
+    /// they exceed the parser limits, before planning them. Operations are parsed with the
+    /// default `ParserOptions` otherwise.
+    ///
+    /// [ParseError]: graphql_parser::ParseError
+    pub fn with_query_limits(mut self, limits: QueryLimits) -> Stargate<'app> {
+        self.query_limits = limits;
+        self
+    }
+
+    /// Enables usage reporting. Reports are only sent when [Stargate::usage_reporter]'s
+    /// `send_report` is called, which the server does every `config.send_interval`.
+    pub fn with_usage_reporting(mut self, config: UsageReportingConfig) -> Stargate<'app> {
+        self.usage_reporter = Some(UsageReporter::new(config));
+        self
+    }
+
+    pub fn usage_reporter(&self) -> Option<&UsageReporter> {
+        self.usage_reporter.as_ref()
     }
 
     #[instrument(skip(self, request_context))]
     pub async fn execute_query(&self, request_context: &RequestContext) -> Result<GraphQLResponse> {
         // TODO(ran) FIXME: gql validation on query
         // TODO(james) actual request pipeline here
+        let request = &request_context.graphql_request;
+        let document =
+            parse_query_with_options(&request.query, &self.query_limits.parser_options())?;
+        if self.query_limits.is_enabled() {
+            let metrics = QueryMetrics::analyze(
+                &self.schema_index,
+                &document,
+                request.operation_name.as_deref(),
+                request.variables.as_ref(),
+                self.query_limits.default_list_size,
+            );
+            self.query_limits.check(&metrics)?;
+        }
+
         let options = QueryPlanningOptionsBuilder::default().build().unwrap();
-        let plan = self
-            .planner
-            .plan(&request_context.graphql_request.query, options)
-            .unwrap_or_else(|_| todo!("convert QueryPlanError to generic error"));
+        let plan = self.planner.plan_document(&document, options)?;
+
+        // Field statistics for usage reporting are computed from subgraph traces, which are only
+        // requested for every operation if the reporter asks for it.
+        let include_trace = request_context.include_trace
+            || matches!(&self.usage_reporter, Some(reporter) if reporter.config().field_stats);
+        let start = Instant::now();
+        let result =
+            execute_query_plan(&plan, &self.service_list, request_context, include_trace).await;
+
+        if let Some(ref usage_reporter) = self.usage_reporter {
+            let key = stats_report_key(&document, request.operation_name.as_deref());
+            let trace = result.as_ref().ok().and_then(|(_, trace)| trace.as_ref());
+            usage_reporter.record(key, start.elapsed(), result.is_err(), trace);
+        }
+
+        let (mut response, trace) = result?;
+        if let (true, Some(trace)) = (request_context.include_trace, trace) {
+            response.extensions = Some(json!({ "ftv1": encode_ftv1(&trace) }));
+        }
 
-        execute_query_plan(&plan, &self.service_list, &request_context).await
+        Ok(response)
     }
 }
 
@@ -0,0 +1,6 @@
+//! Protobuf messages from Apollo's `reports.proto`, used for federated tracing (ftv1) and usage reporting.
+//!
+//! These are written by hand in the shape `prost-build` would generate, and only contain the
+//! messages and fields stargate reads or writes. Field tags must match `reports.proto`.
+
+pub mod reports;
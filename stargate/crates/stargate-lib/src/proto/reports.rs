@@ -0,0 +1,327 @@
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Trace {
+    /// Wallclock time when the trace began.
+    #[prost(message, optional, tag = "4")]
+    pub start_time: ::std::option::Option<::prost_types::Timestamp>,
+    /// Wallclock time when the trace ended.
+    #[prost(message, optional, tag = "3")]
+    pub end_time: ::std::option::Option<::prost_types::Timestamp>,
+    /// High precision duration of the trace; may not equal end_time-start_time.
+    #[prost(uint64, tag = "11")]
+    pub duration_ns: u64,
+    /// A tree containing information about all resolvers run directly by this service,
+    /// including errors.
+    #[prost(message, optional, tag = "14")]
+    pub root: ::std::option::Option<trace::Node>,
+    #[prost(message, optional, tag = "6")]
+    pub details: ::std::option::Option<trace::Details>,
+    #[prost(string, tag = "7")]
+    pub client_name: std::string::String,
+    #[prost(string, tag = "8")]
+    pub client_version: std::string::String,
+    /// If this Trace was created by a gateway, this is the query plan, including
+    /// sub-Traces for federated services. Note that the 'root' tree on the
+    /// top-level Trace won't contain any resolvers (though it could contain errors
+    /// that occurred in the gateway itself).
+    #[prost(message, optional, tag = "26")]
+    pub query_plan: ::std::option::Option<trace::QueryPlanNode>,
+}
+pub mod trace {
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Details {
+        /// The variables associated with this query (unless the reporting agent is
+        /// configured to keep them all private). Values are JSON.
+        #[prost(map = "string, string", tag = "4")]
+        pub variables_json: ::std::collections::HashMap<std::string::String, std::string::String>,
+        /// This is deprecated and only used for legacy applications
+        /// don't include this in traces inside a FullTracesReport; the operation
+        /// name for these traces comes from the key of the traces_per_query map.
+        #[prost(string, tag = "3")]
+        pub operation_name: std::string::String,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Error {
+        /// required
+        #[prost(string, tag = "1")]
+        pub message: std::string::String,
+        #[prost(message, repeated, tag = "2")]
+        pub location: ::std::vec::Vec<Location>,
+        #[prost(uint64, tag = "3")]
+        pub time_ns: u64,
+        #[prost(string, tag = "4")]
+        pub json: std::string::String,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Location {
+        #[prost(uint32, tag = "1")]
+        pub line: u32,
+        #[prost(uint32, tag = "2")]
+        pub column: u32,
+    }
+    /// We store information on each resolver execution as a Node on a tree.
+    /// The structure of the tree corresponds to the structure of the GraphQL
+    /// response; it does not indicate the order in which resolvers were
+    /// invoked.  Note that nodes representing indexes (and the root node)
+    /// don't contain all Node fields (eg types and times).
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Node {
+        /// The name of the field (for Nodes representing a resolver call) or the
+        /// index in a list (for intermediate Nodes representing elements of a list).
+        /// field_name is the name of the field as it appears in the GraphQL
+        /// response: ie, it may be an alias.  (In that case, the original_field_name
+        /// field holds the actual field name from the schema.) In any context where
+        /// we're building up a path, we use the response_name rather than the
+        /// original_field_name.
+        #[prost(string, tag = "14")]
+        pub original_field_name: std::string::String,
+        /// The field's return type; e.g. "String!" for User.email:String!
+        #[prost(string, tag = "3")]
+        pub r#type: std::string::String,
+        /// The field's parent type; e.g. "User" for User.email:String!
+        #[prost(string, tag = "13")]
+        pub parent_type: std::string::String,
+        /// relative to the trace's start_time, in ns
+        #[prost(uint64, tag = "8")]
+        pub start_time: u64,
+        /// relative to the trace's start_time, in ns
+        #[prost(uint64, tag = "9")]
+        pub end_time: u64,
+        #[prost(message, repeated, tag = "11")]
+        pub error: ::std::vec::Vec<Error>,
+        #[prost(message, repeated, tag = "12")]
+        pub child: ::std::vec::Vec<Node>,
+        #[prost(oneof = "node::Id", tags = "1, 2")]
+        pub id: ::std::option::Option<node::Id>,
+    }
+    pub mod node {
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Id {
+            #[prost(string, tag = "1")]
+            ResponseName(std::string::String),
+            #[prost(uint32, tag = "2")]
+            Index(u32),
+        }
+    }
+    /// represents a node in the query plan, under which there is a trace tree for that service fetch.
+    /// In particular, each fetch node represents a call to an implementing service, and calls to implementing
+    /// services may not be unique. See https://github.com/apollographql/apollo-server/blob/main/packages/apollo-gateway/src/QueryPlan.ts
+    /// for more information and details.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct QueryPlanNode {
+        #[prost(oneof = "query_plan_node::Node", tags = "1, 2, 3, 4")]
+        pub node: ::std::option::Option<query_plan_node::Node>,
+    }
+    pub mod query_plan_node {
+        /// This represents a set of nodes to be executed sequentially by the Gateway executor
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct SequenceNode {
+            #[prost(message, repeated, tag = "1")]
+            pub nodes: ::std::vec::Vec<super::QueryPlanNode>,
+        }
+        /// This represents a set of nodes to be executed in parallel by the Gateway executor
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct ParallelNode {
+            #[prost(message, repeated, tag = "1")]
+            pub nodes: ::std::vec::Vec<super::QueryPlanNode>,
+        }
+        /// This represents a node to send an operation to an implementing service
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct FetchNode {
+            /// XXX When we want to include more details about the sub-operation that was
+            /// executed against this service, we should include that here in each fetch node.
+            /// This might include an operation signature, requires directive, reference resolutions, etc.
+            #[prost(string, tag = "1")]
+            pub service_name: std::string::String,
+            #[prost(bool, tag = "2")]
+            pub trace_parsing_failed: bool,
+            /// This Trace only contains start_time, end_time, duration_ns, and root;
+            /// all timings were calculated **on the federated service**, and clock skew
+            /// will be handled by the ingress server.
+            #[prost(message, optional, tag = "3")]
+            pub trace: ::std::option::Option<super::super::Trace>,
+            /// relative to the outer trace's start_time, in ns, measured in the gateway.
+            #[prost(uint64, tag = "4")]
+            pub sent_time_offset: u64,
+            /// Wallclock times measured in the gateway for when this operation was
+            /// sent and received.
+            #[prost(message, optional, tag = "5")]
+            pub sent_time: ::std::option::Option<::prost_types::Timestamp>,
+            #[prost(message, optional, tag = "6")]
+            pub received_time: ::std::option::Option<::prost_types::Timestamp>,
+        }
+        /// This node represents a way to reach into the response path and attach related entities.
+        /// XXX Flatten is really not the right name and this node may be renamed in the query planner.
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct FlattenNode {
+            #[prost(message, repeated, tag = "1")]
+            pub response_path: ::std::vec::Vec<ResponsePathElement>,
+            #[prost(message, optional, boxed, tag = "2")]
+            pub node: ::std::option::Option<::std::boxed::Box<super::QueryPlanNode>>,
+        }
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct ResponsePathElement {
+            #[prost(oneof = "response_path_element::Id", tags = "1, 2")]
+            pub id: ::std::option::Option<response_path_element::Id>,
+        }
+        pub mod response_path_element {
+            #[derive(Clone, PartialEq, ::prost::Oneof)]
+            pub enum Id {
+                #[prost(string, tag = "1")]
+                FieldName(std::string::String),
+                #[prost(uint32, tag = "2")]
+                Index(u32),
+            }
+        }
+        #[derive(Clone, PartialEq, ::prost::Oneof)]
+        pub enum Node {
+            #[prost(message, tag = "1")]
+            Sequence(SequenceNode),
+            #[prost(message, tag = "2")]
+            Parallel(ParallelNode),
+            #[prost(message, tag = "3")]
+            Fetch(::std::boxed::Box<FetchNode>),
+            #[prost(message, tag = "4")]
+            Flatten(::std::boxed::Box<FlattenNode>),
+        }
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReportHeader {
+    /// eg "mygraph@myvariant"
+    #[prost(string, tag = "12")]
+    pub graph_ref: std::string::String,
+    /// eg "host-01.example.com"
+    #[prost(string, tag = "5")]
+    pub hostname: std::string::String,
+    /// eg "engineproxy 0.1.0"
+    #[prost(string, tag = "6")]
+    pub agent_version: std::string::String,
+    /// eg "prod-4279-20160804T065423Z-5-g3cf0aa8" (taken from `git describe --tags`)
+    #[prost(string, tag = "7")]
+    pub service_version: std::string::String,
+    /// eg "node v4.6.0"
+    #[prost(string, tag = "8")]
+    pub runtime_version: std::string::String,
+    /// eg "Linux box 4.6.5-1-ec2 #1 SMP Mon Aug 1 02:31:38 PDT 2016 x86_64 GNU/Linux"
+    #[prost(string, tag = "9")]
+    pub uname: std::string::String,
+    /// An id that is used to represent the schema to Apollo Graph Manager
+    /// Using this in place of what used to be schema_hash, since that is no longer
+    /// attached to a schema in the backend.
+    #[prost(string, tag = "11")]
+    pub executable_schema_id: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PathErrorStats {
+    #[prost(map = "string, message", tag = "1")]
+    pub children: ::std::collections::HashMap<std::string::String, PathErrorStats>,
+    #[prost(uint64, tag = "4")]
+    pub errors_count: u64,
+    #[prost(uint64, tag = "5")]
+    pub requests_with_errors_count: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryLatencyStats {
+    #[prost(int64, repeated, tag = "1")]
+    pub latency_count: ::std::vec::Vec<i64>,
+    #[prost(uint64, tag = "2")]
+    pub request_count: u64,
+    #[prost(uint64, tag = "3")]
+    pub cache_hits: u64,
+    #[prost(uint64, tag = "4")]
+    pub persisted_query_hits: u64,
+    #[prost(uint64, tag = "5")]
+    pub persisted_query_misses: u64,
+    #[prost(int64, repeated, tag = "6")]
+    pub cache_latency_count: ::std::vec::Vec<i64>,
+    #[prost(message, optional, tag = "7")]
+    pub root_error_stats: ::std::option::Option<PathErrorStats>,
+    #[prost(uint64, tag = "8")]
+    pub requests_with_errors_count: u64,
+    #[prost(int64, repeated, tag = "9")]
+    pub public_cache_ttl_count: ::std::vec::Vec<i64>,
+    #[prost(int64, repeated, tag = "10")]
+    pub private_cache_ttl_count: ::std::vec::Vec<i64>,
+    #[prost(uint64, tag = "11")]
+    pub registered_operation_count: u64,
+    #[prost(uint64, tag = "12")]
+    pub forbidden_operation_count: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StatsContext {
+    #[prost(string, tag = "1")]
+    pub client_reference_id: std::string::String,
+    #[prost(string, tag = "2")]
+    pub client_name: std::string::String,
+    #[prost(string, tag = "3")]
+    pub client_version: std::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldStat {
+    /// required; eg "String!" for User.email:String!
+    #[prost(string, tag = "3")]
+    pub return_type: std::string::String,
+    #[prost(uint64, tag = "4")]
+    pub errors_count: u64,
+    #[prost(uint64, tag = "5")]
+    pub count: u64,
+    #[prost(uint64, tag = "6")]
+    pub requests_with_errors_count: u64,
+    /// Duration histogram; see docs/histograms.md
+    #[prost(int64, repeated, tag = "8")]
+    pub latency_count: ::std::vec::Vec<i64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TypeStat {
+    /// Key is (eg) "email" for User.email:String!
+    #[prost(map = "string, message", tag = "3")]
+    pub per_field_stat: ::std::collections::HashMap<std::string::String, FieldStat>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContextualizedStats {
+    #[prost(message, optional, tag = "1")]
+    pub context: ::std::option::Option<StatsContext>,
+    #[prost(message, optional, tag = "2")]
+    pub query_latency_stats: ::std::option::Option<QueryLatencyStats>,
+    /// Key is type name.
+    #[prost(map = "string, message", tag = "3")]
+    pub per_type_stat: ::std::collections::HashMap<std::string::String, TypeStat>,
+}
+/// A sequence of traces and stats. An individual operation should either be described as a trace
+/// or as part of stats, but not both.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TracesAndStats {
+    #[prost(message, repeated, tag = "1")]
+    pub trace: ::std::vec::Vec<Trace>,
+    #[prost(message, repeated, tag = "2")]
+    pub stats_with_context: ::std::vec::Vec<ContextualizedStats>,
+}
+/// This is the top-level message used by the new traces ingress. This
+/// is designed for the apollo-engine-reporting TypeScript agent and will
+/// eventually be documented as a public ingress API. This message consists
+/// solely of traces; the equivalent of the StatsReport is automatically
+/// generated server-side from this message. Agent should either send a trace or include it in the stats
+/// for every request in this report. Generally, buffering up until a large
+/// size has been reached (say, 4MB) or 5-10 seconds has passed is appropriate.
+/// This message used to be know as FullTracesReport, but got renamed since it isn't just for traces anymore
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Report {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::std::option::Option<ReportHeader>,
+    /// key is statsReportKey (# operationName\nsignature) Note that the nested
+    /// traces will *not* have a signature or details.operationName (because the
+    /// key is adequate).
+    ///
+    /// We also assume that traces don't have
+    /// legacy_per_query_implicit_operation_name, and we don't require them to have
+    /// details.raw_query (which would consume a lot of space and has privacy/data
+    /// access issues, and isn't currently exposed by our app anyway).
+    #[prost(map = "string, message", tag = "5")]
+    pub traces_per_query: ::std::collections::HashMap<std::string::String, TracesAndStats>,
+    /// This is the time that the requests in this trace are considered to have taken place
+    /// If this field is not present the max of the end_time of each trace will be used instead.
+    /// If there are no traces and no end_time present the report will not be able to be processed.
+    /// Note: This will override the end_time from traces.
+    #[prost(message, optional, tag = "2")]
+    pub end_time: ::std::option::Option<::prost_types::Timestamp>,
+}
@@ -1,3 +1,7 @@
+use crate::proto::reports::trace::query_plan_node::FetchNode as FetchTraceNode;
+use crate::proto::reports::trace::QueryPlanNode as QueryPlanTraceNode;
+use crate::proto::reports::Trace;
+use crate::request_pipeline::federated_tracing;
 use crate::request_pipeline::service_definition::{Service, ServiceDefinition};
 use crate::transports::http::{GraphQLResponse, RequestContext};
 use crate::utilities::deep_merge::merge;
@@ -9,38 +13,68 @@ use futures::future::{BoxFuture, FutureExt};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::RwLock;
-use tracing::instrument;
+use std::time::{Instant, SystemTime};
+use tracing::{field, info_span, instrument, warn, Span};
+use tracing_futures::Instrument;
 
 pub struct ExecutionContext<'schema, 'request> {
     service_map: &'schema HashMap<String, ServiceDefinition>,
     // errors: Vec<async_graphql::Error>,
     request_context: &'request RequestContext,
+    /// Whether subgraphs are asked for federated traces (ftv1).
+    pub(crate) include_trace: bool,
+    start_time: Instant,
 }
 
+/// Executes the query plan. When `include_trace` is set, also returns
+/// the federated trace stitched from the subgraph traces.
 #[instrument(skip(query_plan, service_map, request_context))]
 pub async fn execute_query_plan(
     query_plan: &QueryPlan,
     service_map: &HashMap<String, ServiceDefinition>,
     request_context: &RequestContext,
-) -> Result<GraphQLResponse> {
+    include_trace: bool,
+) -> Result<(GraphQLResponse, Option<Trace>)> {
     // let errors: Vec<async_graphql::Error> = vec![;
 
     let context = ExecutionContext {
         service_map,
         // errors,
         request_context,
+        include_trace,
+        start_time: Instant::now(),
     };
+    let start_time = SystemTime::now();
 
     let data_lock: RwLock<Value> = RwLock::new(json!({}));
 
-    if let Some(ref node) = query_plan.node {
-        execute_node(&context, node, &data_lock, &vec![]).await;
+    let query_plan_trace = if let Some(ref node) = query_plan.node {
+        execute_node(&context, node, &data_lock, &vec![]).await
     } else {
         unimplemented!("Introspection not supported yet");
     };
 
+    let trace = if context.include_trace {
+        Some(Trace {
+            start_time: Some(federated_tracing::timestamp(start_time)),
+            end_time: Some(federated_tracing::timestamp(SystemTime::now())),
+            duration_ns: context.start_time.elapsed().as_nanos() as u64,
+            root: Some(Default::default()),
+            query_plan: Some(query_plan_trace),
+            ..Default::default()
+        })
+    } else {
+        None
+    };
+
     let data = data_lock.into_inner().unwrap();
-    Ok(GraphQLResponse { data: Some(data) })
+    Ok((
+        GraphQLResponse {
+            data: Some(data),
+            extensions: None,
+        },
+        trace,
+    ))
 }
 
 fn execute_node<'schema, 'request>(
@@ -48,13 +82,33 @@ fn execute_node<'schema, 'request>(
     node: &'request PlanNode,
     results: &'request RwLock<Value>,
     path: &'request ResponsePath,
-) -> BoxFuture<'request, ()> {
+) -> BoxFuture<'request, QueryPlanTraceNode> {
+    let span = match node {
+        PlanNode::Sequence { .. } => info_span!("sequence", path = %format_path(path)),
+        PlanNode::Parallel { .. } => info_span!("parallel", path = %format_path(path)),
+        PlanNode::Fetch(fetch_node) => info_span!(
+            "fetch",
+            service_name = %fetch_node.service_name,
+            path = %format_path(path),
+            variable_count = fetch_node.variable_usages.len() as u64,
+            entity_count = field::Empty,
+            error = false,
+        ),
+        PlanNode::Flatten(flatten_node) => info_span!(
+            "flatten",
+            path = %format_path(path),
+            flatten_path = %format_path(&flatten_node.path),
+        ),
+    };
+
     async move {
         match node {
             PlanNode::Sequence { nodes } => {
+                let mut trace_nodes = vec![];
                 for node in nodes {
-                    execute_node(context, &node, results, path).await;
+                    trace_nodes.push(execute_node(context, &node, results, path).await);
                 }
+                federated_tracing::sequence(trace_nodes)
             }
             PlanNode::Parallel { nodes } => {
                 let mut promises = vec![];
@@ -62,13 +116,37 @@ fn execute_node<'schema, 'request>(
                 for node in nodes {
                     promises.push(execute_node(context, &node, results, path));
                 }
-                futures::future::join_all(promises).await;
+                federated_tracing::parallel(futures::future::join_all(promises).await)
             }
             PlanNode::Fetch(fetch_node) => {
-                let _fetch_result = execute_fetch(context, &fetch_node, results).await;
-                //   if fetch_result.is_err() {
-                //       context.errors.push(fetch_result.errors)
-                //   }
+                let mut fetch_trace = FetchTraceNode {
+                    service_name: fetch_node.service_name.clone(),
+                    sent_time_offset: context.start_time.elapsed().as_nanos() as u64,
+                    sent_time: Some(federated_tracing::timestamp(SystemTime::now())),
+                    ..Default::default()
+                };
+
+                let fetch_result = execute_fetch(context, &fetch_node, results).await;
+                fetch_trace.received_time = Some(federated_tracing::timestamp(SystemTime::now()));
+
+                match fetch_result {
+                    Ok(Some(Ok(trace))) => fetch_trace.trace = Some(trace),
+                    Ok(Some(Err(e))) => {
+                        warn!(
+                            "failed parsing ftv1 from {}: {}",
+                            fetch_node.service_name, e
+                        );
+                        fetch_trace.trace_parsing_failed = true;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        Span::current().record("error", &true);
+                        warn!("fetch from {} failed: {}", fetch_node.service_name, e);
+                        // context.errors.push(fetch_result.errors)
+                    }
+                }
+
+                federated_tracing::fetch(fetch_trace)
             }
             PlanNode::Flatten(flatten_node) => {
                 let mut flattend_path = Vec::from(path.as_slice());
@@ -104,7 +182,8 @@ fn execute_node<'schema, 'request>(
                     .to_owned();
                 }
 
-                execute_node(context, &flatten_node.node, &inner_lock, &flattend_path).await;
+                let trace_node =
+                    execute_node(context, &flatten_node.node, &inner_lock, &flattend_path).await;
 
                 // once the node has been executed, we need to restitch it back to the parent
                 // node on the tree of result data
@@ -124,12 +203,20 @@ fn execute_node<'schema, 'request>(
                     let inner = inner_lock.write().unwrap();
                     merge_flattend_results(&mut *results_to_flatten, &inner, &flatten_node.path);
                 }
+
+                federated_tracing::flatten(&flatten_node.path, trace_node)
             }
         }
     }
+    .instrument(span)
     .boxed()
 }
 
+/// Formats a [ResponsePath] the way it is reported on spans, e.g. `topProducts/@/reviews`.
+fn format_path(path: &[String]) -> String {
+    path.join("/")
+}
+
 fn merge_flattend_results(parent_data: &mut Value, child_data: &Value, path: &ResponsePath) {
     if path.is_empty() || child_data.is_null() {
         merge(&mut *parent_data, &child_data);
@@ -154,11 +241,12 @@ fn merge_flattend_results(parent_data: &mut Value, child_data: &Value, path: &Re
     }
 }
 
+/// Executes a fetch and merges its result, returning the subgraph's trace if it sent one.
 async fn execute_fetch<'schema, 'request>(
     context: &ExecutionContext<'schema, 'request>,
     fetch: &FetchNode,
     results_lock: &'request RwLock<Value>,
-) -> Result<()> {
+) -> Result<Option<Result<Trace>>> {
     let service = &context.service_map[&fetch.service_name];
 
     let mut variables: HashMap<String, Value> = HashMap::new();
@@ -211,12 +299,17 @@ async fn execute_fetch<'schema, 'request>(
             }
         };
 
+        Span::current().record("entity_count", &(representations_to_entity.len() as u64));
         variables.insert("representations".to_string(), representation_variables);
     }
 
-    let data_received = service
+    let GraphQLResponse { data, extensions } = service
         .send_operation(context, fetch.operation.clone(), variables)
         .await?;
+    let data_received = match data {
+        Some(data) => data,
+        None => unimplemented!("Handle error cases in send_operation"),
+    };
 
     if let Some(_requires) = &fetch.requires {
         if let Some(recieved_entities) = data_received.get("_entities") {
@@ -244,7 +337,7 @@ async fn execute_fetch<'schema, 'request>(
         merge(&mut *results_to_merge, &data_received);
     }
 
-    Ok(())
+    Ok(federated_tracing::decode_ftv1(extensions.as_ref()))
 }
 
 fn flatten_results_at_path<'request>(
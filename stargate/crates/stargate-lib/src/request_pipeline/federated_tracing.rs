@@ -0,0 +1,124 @@
+//! Federated tracing (ftv1): when asked with the `apollo-federation-include-trace: ftv1` header,
+//! subgraphs return a base64 encoded protobuf [Trace] of their execution in `extensions.ftv1`.
+//! The gateway stitches those into a single trace whose `query_plan` mirrors the executed [QueryPlan].
+//!
+//! [QueryPlan]: apollo_query_planner::model::QueryPlan
+
+use crate::proto::reports::trace::query_plan_node::{
+    response_path_element, FetchNode, FlattenNode, Node, ParallelNode, ResponsePathElement,
+    SequenceNode,
+};
+use crate::proto::reports::trace::QueryPlanNode;
+use crate::proto::reports::Trace;
+use crate::Result;
+use prost::Message;
+use prost_types::Timestamp;
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FTV1_EXTENSION: &str = "ftv1";
+
+/// Decodes the subgraph trace from the `extensions` of a subgraph response.
+/// Returns `None` if the response carries no trace.
+pub(crate) fn decode_ftv1(extensions: Option<&Value>) -> Option<Result<Trace>> {
+    let encoded = extensions?.get(FTV1_EXTENSION)?;
+    Some(match encoded.as_str() {
+        Some(encoded) => decode(encoded),
+        None => Err("extensions.ftv1 is not a string".into()),
+    })
+}
+
+fn decode(encoded: &str) -> Result<Trace> {
+    let bytes = base64::decode(encoded)?;
+    Ok(Trace::decode(bytes.as_slice())?)
+}
+
+/// Encodes a trace the way subgraphs do, so a gateway can itself be traced by another gateway.
+pub fn encode_ftv1(trace: &Trace) -> String {
+    let mut bytes = Vec::with_capacity(trace.encoded_len());
+    trace
+        .encode(&mut bytes)
+        .expect("Vec<u8> always has enough capacity");
+    base64::encode(bytes)
+}
+
+pub(crate) fn sequence(nodes: Vec<QueryPlanNode>) -> QueryPlanNode {
+    QueryPlanNode {
+        node: Some(Node::Sequence(SequenceNode { nodes })),
+    }
+}
+
+pub(crate) fn parallel(nodes: Vec<QueryPlanNode>) -> QueryPlanNode {
+    QueryPlanNode {
+        node: Some(Node::Parallel(ParallelNode { nodes })),
+    }
+}
+
+pub(crate) fn flatten(path: &[String], node: QueryPlanNode) -> QueryPlanNode {
+    let response_path = path
+        .iter()
+        .map(|field_name| ResponsePathElement {
+            id: Some(response_path_element::Id::FieldName(field_name.clone())),
+        })
+        .collect();
+
+    QueryPlanNode {
+        node: Some(Node::Flatten(Box::new(FlattenNode {
+            response_path,
+            node: Some(Box::new(node)),
+        }))),
+    }
+}
+
+pub(crate) fn fetch(fetch: FetchNode) -> QueryPlanNode {
+    QueryPlanNode {
+        node: Some(Node::Fetch(Box::new(fetch))),
+    }
+}
+
+pub(crate) fn timestamp(time: SystemTime) -> Timestamp {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    Timestamp {
+        seconds: since_epoch.as_secs() as i64,
+        nanos: since_epoch.subsec_nanos() as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::reports::trace::{node, Node as TraceNode};
+    use serde_json::json;
+
+    #[test]
+    fn it_should_round_trip_ftv1() {
+        let trace = Trace {
+            duration_ns: 42,
+            root: Some(TraceNode {
+                child: vec![TraceNode {
+                    id: Some(node::Id::ResponseName(String::from("me"))),
+                    r#type: String::from("User"),
+                    parent_type: String::from("Query"),
+                    start_time: 10,
+                    end_time: 20,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let extensions = json!({ "ftv1": encode_ftv1(&trace) });
+        assert_eq!(decode_ftv1(Some(&extensions)).unwrap().unwrap(), trace);
+    }
+
+    #[test]
+    fn it_should_handle_missing_or_bad_ftv1() {
+        assert!(decode_ftv1(None).is_none());
+        assert!(decode_ftv1(Some(&json!({}))).is_none());
+        assert!(decode_ftv1(Some(&json!({ "ftv1": 1 }))).unwrap().is_err());
+        assert!(decode_ftv1(Some(&json!({ "ftv1": "not base64!" })))
+            .unwrap()
+            .is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod executor;
+pub mod federated_tracing;
+pub mod query_limits;
 pub mod service_definition;
@@ -0,0 +1,635 @@
+//! Pre-planning analysis of operations, rejecting operations which are too expensive to execute.
+//!
+//! The cost of an operation is the sum of the cost of its fields, where the cost of a field is its
+//! weight (`@cost(weight: Int)` on the field definition, 1 otherwise) plus the cost of its
+//! selection set, multiplied by the expected size of the list if the field returns a list.
+//! The size of a list is the `first` or `last` argument of the field if given,
+//! `@listSize(assumedSize: Int)` on the field definition if present,
+//! and `default_list_size` otherwise.
+
+use apollo_query_planner::get_directive;
+use apollo_query_planner::helpers::names_to_types;
+use graphql_parser::query::{
+    Definition, Document, Field, FragmentDefinition, Operation, Selection, SelectionSet, Value,
+};
+use graphql_parser::schema::{self, TypeDefinition};
+use graphql_parser::ParserOptions;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use structopt::StructOpt;
+
+const DEFAULT_WEIGHT: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, StructOpt)]
+pub struct QueryLimits {
+    /// Maximum depth of nested selections in an operation.
+    #[structopt(long = "max-query-depth")]
+    pub max_depth: Option<usize>,
+
+    /// Maximum number of aliased fields in an operation.
+    #[structopt(long = "max-query-aliases")]
+    pub max_aliases: Option<usize>,
+
+    /// Maximum number of root fields in an operation.
+    #[structopt(long = "max-root-fields")]
+    pub max_root_fields: Option<usize>,
+
+    /// Maximum cost of an operation.
+    #[structopt(long = "max-query-cost")]
+    pub max_cost: Option<u64>,
+
+    /// Maximum nesting of selection sets, values and types when parsing an operation.
+    #[structopt(long)]
+    pub max_parser_depth: Option<usize>,
+
+    /// Maximum number of tokens when parsing an operation.
+    #[structopt(long)]
+    pub max_parser_tokens: Option<usize>,
+
+    /// Maximum size of an operation, in bytes.
+    #[structopt(long)]
+    pub max_document_size: Option<usize>,
+
+    /// Size assumed for lists when computing the cost of an operation, if the field doesn't have a
+    /// `first` or `last` argument or a `@listSize(assumedSize: Int)` directive.
+    #[structopt(long, default_value = "10")]
+    pub default_list_size: u64,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        QueryLimits {
+            max_depth: None,
+            max_aliases: None,
+            max_root_fields: None,
+            max_cost: None,
+            max_parser_depth: None,
+            max_parser_tokens: None,
+            max_document_size: None,
+            default_list_size: 10,
+        }
+    }
+}
+
+impl QueryLimits {
+    /// Whether operations need to be analyzed to check them against the limits. The parser limits
+    /// don't need an analysis, they're applied by parsing with [QueryLimits::parser_options].
+    pub fn is_enabled(&self) -> bool {
+        self.max_depth.is_some()
+            || self.max_aliases.is_some()
+            || self.max_root_fields.is_some()
+            || self.max_cost.is_some()
+    }
+
+    /// The parser limits, where an unset `max_parser_depth` keeps the parser's default.
+    pub fn parser_options(&self) -> ParserOptions {
+        ParserOptions {
+            max_depth: self.max_parser_depth.or(ParserOptions::default().max_depth),
+            max_tokens: self.max_parser_tokens,
+            max_document_size: self.max_document_size,
+        }
+    }
+
+    pub fn check(&self, metrics: &QueryMetrics) -> Result<(), QueryLimitError> {
+        macro_rules! check {
+            ($max:ident, $value:ident, $error:ident) => {
+                if let Some($max) = self.$max {
+                    if metrics.$value > $max {
+                        return Err(QueryLimitError::$error {
+                            $value: metrics.$value,
+                            $max,
+                        });
+                    }
+                }
+            };
+        }
+
+        check!(max_depth, depth, MaxDepthExceeded);
+        check!(max_aliases, aliases, MaxAliasesExceeded);
+        check!(max_root_fields, root_fields, MaxRootFieldsExceeded);
+        check!(max_cost, cost, MaxCostExceeded);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryLimitError {
+    MaxDepthExceeded {
+        depth: usize,
+        max_depth: usize,
+    },
+    MaxAliasesExceeded {
+        aliases: usize,
+        max_aliases: usize,
+    },
+    MaxRootFieldsExceeded {
+        root_fields: usize,
+        max_root_fields: usize,
+    },
+    MaxCostExceeded {
+        cost: u64,
+        max_cost: u64,
+    },
+}
+
+impl QueryLimitError {
+    /// The `extensions.code` of the GraphQL error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            QueryLimitError::MaxDepthExceeded { .. } => "MAX_DEPTH_EXCEEDED",
+            QueryLimitError::MaxAliasesExceeded { .. } => "MAX_ALIASES_EXCEEDED",
+            QueryLimitError::MaxRootFieldsExceeded { .. } => "MAX_ROOT_FIELDS_EXCEEDED",
+            QueryLimitError::MaxCostExceeded { .. } => "MAX_COST_EXCEEDED",
+        }
+    }
+}
+
+impl Display for QueryLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryLimitError::MaxDepthExceeded { depth, max_depth } => write!(
+                f,
+                "operation depth {} exceeds the maximum depth of {}",
+                depth, max_depth
+            ),
+            QueryLimitError::MaxAliasesExceeded {
+                aliases,
+                max_aliases,
+            } => write!(
+                f,
+                "operation has {} aliases, more than the maximum of {}",
+                aliases, max_aliases
+            ),
+            QueryLimitError::MaxRootFieldsExceeded {
+                root_fields,
+                max_root_fields,
+            } => write!(
+                f,
+                "operation has {} root fields, more than the maximum of {}",
+                root_fields, max_root_fields
+            ),
+            QueryLimitError::MaxCostExceeded { cost, max_cost } => write!(
+                f,
+                "operation cost {} exceeds the maximum cost of {}",
+                cost, max_cost
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryLimitError {}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryMetrics {
+    pub depth: usize,
+    pub aliases: usize,
+    pub root_fields: usize,
+    pub cost: u64,
+}
+
+/// The root types and fields of a schema, as needed to analyze operations, so they're only
+/// looked up once per schema. The types are found with the planner's `names_to_types`, but the
+/// index can't hold on to that map: it borrows the schema owned by the planner, which lives in
+/// the same `Stargate`. The index keeps the weight, list size and return type of each field
+/// instead, which are all the analysis needs.
+#[derive(Debug)]
+pub struct SchemaIndex<'s> {
+    query_type: Option<&'s str>,
+    mutation_type: Option<&'s str>,
+    subscription_type: Option<&'s str>,
+    fields: HashMap<&'s str, HashMap<&'s str, FieldInfo<'s>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FieldInfo<'s> {
+    weight: u64,
+    assumed_size: Option<u64>,
+    return_type: &'s str,
+    is_list: bool,
+}
+
+impl<'s> SchemaIndex<'s> {
+    pub fn new(schema: &schema::Document<'s>) -> SchemaIndex<'s> {
+        let schema_definition = schema.definitions.iter().find_map(|d| match d {
+            schema::Definition::Schema(schema) => Some(schema),
+            _ => None,
+        });
+        // Without a schema definition, the root types are the types with the default names.
+        let (query_type, mutation_type, subscription_type) = match schema_definition {
+            Some(schema) => (schema.query, schema.mutation, schema.subscription),
+            None => (Some("Query"), Some("Mutation"), Some("Subscription")),
+        };
+
+        let fields = names_to_types(schema)
+            .into_iter()
+            .filter_map(|(name, td)| match td {
+                TypeDefinition::Object(obj) => Some((name, &obj.fields)),
+                TypeDefinition::Interface(iface) => Some((name, &iface.fields)),
+                _ => None,
+            })
+            .map(|(name, fields)| {
+                let fields = fields
+                    .iter()
+                    .map(|f| {
+                        let (return_type, is_list) = named_type(&f.field_type);
+                        let info = FieldInfo {
+                            weight: int_directive_arg(&f.directives, "cost", "weight")
+                                .unwrap_or(DEFAULT_WEIGHT),
+                            assumed_size: int_directive_arg(
+                                &f.directives,
+                                "listSize",
+                                "assumedSize",
+                            ),
+                            return_type,
+                            is_list,
+                        };
+                        (f.name, info)
+                    })
+                    .collect();
+                (name, fields)
+            })
+            .collect();
+
+        SchemaIndex {
+            query_type,
+            mutation_type,
+            subscription_type,
+            fields,
+        }
+    }
+
+    fn field(&self, parent_type: &str, field_name: &str) -> Option<FieldInfo<'s>> {
+        self.fields.get(parent_type)?.get(field_name).copied()
+    }
+}
+
+impl QueryMetrics {
+    /// Analyzes the operation named `operation_name` (or the first operation if `None`).
+    /// `variables` are used to resolve `first` and `last` arguments given as variables.
+    pub fn analyze(
+        schema: &SchemaIndex,
+        document: &Document,
+        operation_name: Option<&str>,
+        variables: Option<&serde_json::Value>,
+        default_list_size: u64,
+    ) -> QueryMetrics {
+        let operation =
+            document
+                .definitions
+                .iter()
+                .find_map(|definition| match (definition, operation_name) {
+                    (Definition::SelectionSet(ss), None) => Some((Operation::Query, ss)),
+                    (Definition::Operation(op), None) => Some((op.kind, &op.selection_set)),
+                    (Definition::Operation(op), Some(name)) if op.name == Some(name) => {
+                        Some((op.kind, &op.selection_set))
+                    }
+                    _ => None,
+                });
+
+        let (kind, selection_set) = match operation {
+            Some(operation) => operation,
+            None => return QueryMetrics::default(),
+        };
+
+        let mut analyzer = Analyzer {
+            schema,
+            fragments: document
+                .definitions
+                .iter()
+                .filter_map(|d| match d {
+                    Definition::Fragment(fragment) => Some((fragment.name, fragment)),
+                    _ => None,
+                })
+                .collect(),
+            variables,
+            default_list_size,
+            fragment_metrics: HashMap::new(),
+            visiting_fragments: vec![],
+        };
+
+        let root_type = match kind {
+            Operation::Query => schema.query_type,
+            Operation::Mutation => schema.mutation_type,
+            Operation::Subscription => schema.subscription_type,
+        };
+        let root = analyzer.selection_set(selection_set, root_type);
+
+        QueryMetrics {
+            depth: root.depth,
+            aliases: root.aliases,
+            root_fields: root.fields,
+            cost: root.cost,
+        }
+    }
+}
+
+struct Analyzer<'a, 's> {
+    schema: &'a SchemaIndex<'s>,
+    fragments: HashMap<&'a str, &'a FragmentDefinition<'a>>,
+    variables: Option<&'a serde_json::Value>,
+    default_list_size: u64,
+    /// Metrics of the fragments analyzed so far, so each fragment is analyzed once however many
+    /// times it is spread.
+    fragment_metrics: HashMap<&'a str, SelectionSetMetrics>,
+    /// Fragments currently being analyzed, so fragment cycles don't recurse forever.
+    visiting_fragments: Vec<&'a str>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct SelectionSetMetrics {
+    depth: usize,
+    fields: usize,
+    aliases: usize,
+    cost: u64,
+}
+
+impl SelectionSetMetrics {
+    fn add(&mut self, other: SelectionSetMetrics) {
+        self.depth = self.depth.max(other.depth);
+        self.fields = self.fields.saturating_add(other.fields);
+        self.aliases = self.aliases.saturating_add(other.aliases);
+        self.cost = self.cost.saturating_add(other.cost);
+    }
+}
+
+impl<'a, 's> Analyzer<'a, 's> {
+    fn selection_set(
+        &mut self,
+        selection_set: &'a SelectionSet<'a>,
+        parent_type: Option<&str>,
+    ) -> SelectionSetMetrics {
+        let mut metrics = SelectionSetMetrics::default();
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let field = self.field(field, parent_type);
+                    metrics.add(field);
+                }
+                Selection::InlineFragment(inline) => {
+                    let type_condition = inline.type_condition.or(parent_type);
+                    let inline = self.selection_set(&inline.selection_set, type_condition);
+                    metrics.add(inline);
+                }
+                Selection::FragmentSpread(spread) => {
+                    if let Some(fragment) = self.fragment(spread.fragment_name) {
+                        metrics.add(fragment);
+                    }
+                }
+            }
+        }
+        metrics
+    }
+
+    /// Returns the metrics of the fragment named `name`, or `None` if there is no such fragment
+    /// or it is already being analyzed.
+    fn fragment(&mut self, name: &'a str) -> Option<SelectionSetMetrics> {
+        if let Some(metrics) = self.fragment_metrics.get(name) {
+            return Some(*metrics);
+        }
+        if self.visiting_fragments.contains(&name) {
+            return None;
+        }
+        let fragment = self.fragments.get(name).copied()?;
+        self.visiting_fragments.push(name);
+        let metrics = self.selection_set(&fragment.selection_set, Some(fragment.type_condition));
+        self.visiting_fragments.pop();
+        self.fragment_metrics.insert(name, metrics);
+        Some(metrics)
+    }
+
+    fn field(&mut self, field: &'a Field<'a>, parent_type: Option<&str>) -> SelectionSetMetrics {
+        let aliases = if field.alias.is_some() { 1 } else { 0 };
+
+        if field.name.starts_with("__") {
+            return SelectionSetMetrics {
+                depth: 1,
+                fields: 1,
+                aliases,
+                cost: 0,
+            };
+        }
+
+        let field_def =
+            parent_type.and_then(|parent_type| self.schema.field(parent_type, field.name));
+        let weight = field_def.map_or(DEFAULT_WEIGHT, |f| f.weight);
+        let multiplier = match field_def {
+            Some(f) if f.is_list => self.list_size(field, &f),
+            _ => 1,
+        };
+
+        let children = self.selection_set(&field.selection_set, field_def.map(|f| f.return_type));
+        SelectionSetMetrics {
+            depth: children.depth + 1,
+            fields: 1,
+            aliases: children.aliases.saturating_add(aliases),
+            cost: weight.saturating_add(multiplier.saturating_mul(children.cost)),
+        }
+    }
+
+    fn list_size(&self, field: &Field, field_def: &FieldInfo) -> u64 {
+        let slicing_argument = field
+            .arguments
+            .iter()
+            .filter(|(name, _)| name.node == "first" || name.node == "last")
+            .find_map(|(_, value)| match &value.node {
+                Value::Int(size) if *size >= 0 => Some(*size as u64),
+                Value::Variable(name) => self
+                    .variables
+                    .and_then(|variables| variables.get(name))
+                    .and_then(|size| size.as_u64()),
+                _ => None,
+            });
+
+        slicing_argument
+            .or(field_def.assumed_size)
+            .unwrap_or(self.default_list_size)
+    }
+}
+
+/// Returns the name of the innermost type, and whether the type is a list.
+fn named_type<'s>(typ: &schema::Type<'s>) -> (&'s str, bool) {
+    match typ {
+        schema::Type::NamedType(name) => (name, false),
+        schema::Type::NonNullType(inner) => named_type(inner),
+        schema::Type::ListType(inner) => (named_type(inner).0, true),
+    }
+}
+
+fn int_directive_arg(directives: &[schema::Directive], directive: &str, arg: &str) -> Option<u64> {
+    get_directive!(directives, directive)
+        .flat_map(|d| d.arguments.iter())
+        .find_map(|(name, value)| match &value.node {
+            Value::Int(value) if name.node == arg && *value >= 0 => Some(*value as u64),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::{parse_query, parse_schema};
+    use serde_json::json;
+
+    static SCHEMA: &str = r#"
+        type Query {
+          me: User
+          users(first: Int): [User]
+          topProducts: [Product] @listSize(assumedSize: 5)
+        }
+
+        type User {
+          id: ID!
+          name: String
+          friends(first: Int): [User]
+          reviews: [Review] @cost(weight: 3)
+        }
+
+        type Review {
+          body: String
+          product: Product
+        }
+
+        type Product {
+          upc: String!
+          reviews: [Review]
+        }
+    "#;
+
+    fn analyze(query: &str, variables: Option<serde_json::Value>) -> QueryMetrics {
+        let schema = parse_schema(SCHEMA).unwrap();
+        let document = parse_query(query).unwrap();
+        QueryMetrics::analyze(
+            &SchemaIndex::new(&schema),
+            &document,
+            None,
+            variables.as_ref(),
+            10,
+        )
+    }
+
+    #[test]
+    fn it_should_compute_depth_aliases_and_root_fields() {
+        let metrics = analyze(
+            r#"
+            query {
+              a: me { ...UserFields }
+              b: me { id }
+              __typename
+            }
+            fragment UserFields on User { id friends(first: 2) { ... on User { name } } }
+            "#,
+            None,
+        );
+        assert_eq!(metrics.depth, 3);
+        assert_eq!(metrics.aliases, 2);
+        assert_eq!(metrics.root_fields, 3);
+    }
+
+    #[test]
+    fn it_should_compute_cost_with_list_sizes_and_weights() {
+        // me: 1 + (reviews: 3 + 10 * (body: 1))
+        assert_eq!(analyze("{ me { reviews { body } } }", None).cost, 14);
+        // users: 1 + 2 * (id: 1)
+        assert_eq!(analyze("{ users(first: 2) { id } }", None).cost, 3);
+        assert_eq!(
+            analyze(
+                "query($n: Int) { users(first: $n) { id } }",
+                Some(json!({ "n": 4 }))
+            )
+            .cost,
+            5
+        );
+        // topProducts: 1 + 5 * (upc: 1)
+        assert_eq!(analyze("{ topProducts { upc } }", None).cost, 6);
+    }
+
+    #[test]
+    fn it_should_not_recurse_forever_on_fragment_cycles() {
+        let metrics = analyze(
+            "{ me { ...A } } fragment A on User { ...B } fragment B on User { id ...A }",
+            None,
+        );
+        assert_eq!(metrics.depth, 2);
+        assert_eq!(metrics.cost, 2);
+    }
+
+    #[test]
+    fn it_should_analyze_each_fragment_once() {
+        // Each fragment spreads the next one twice, so expanding them would take 2^40 steps.
+        let fragments: String = (0..40)
+            .map(|i| {
+                format!(
+                    "fragment F{} on User {{ ...F{} ...F{} }}\n",
+                    i,
+                    i + 1,
+                    i + 1
+                )
+            })
+            .collect();
+        let query = format!(
+            "{{ me {{ ...F0 }} }}\n{}fragment F40 on User {{ a: id }}",
+            fragments
+        );
+        let metrics = analyze(&query, None);
+        assert_eq!(metrics.depth, 2);
+        assert_eq!(metrics.aliases, 1 << 40);
+        assert_eq!(metrics.cost, 1 + (1 << 40));
+    }
+
+    #[test]
+    fn it_should_use_the_root_types_of_the_schema_definition() {
+        let schema = parse_schema(
+            "schema { query: RootQuery } type RootQuery { users: [User] } type User { id: ID! }",
+        )
+        .unwrap();
+        let document = parse_query("{ users { id } }").unwrap();
+        let metrics = QueryMetrics::analyze(&SchemaIndex::new(&schema), &document, None, None, 10);
+        // users: 1 + 10 * (id: 1)
+        assert_eq!(metrics.cost, 11);
+    }
+
+    #[test]
+    fn it_should_reject_operations_over_the_limits() {
+        let metrics = QueryMetrics {
+            depth: 5,
+            aliases: 1,
+            root_fields: 2,
+            cost: 100,
+        };
+        assert_eq!(QueryLimits::default().check(&metrics), Ok(()));
+        assert!(!QueryLimits::default().is_enabled());
+        let parser_limits = QueryLimits {
+            max_parser_tokens: Some(1000),
+            ..Default::default()
+        };
+        assert!(!parser_limits.is_enabled());
+        assert_eq!(parser_limits.parser_options().max_tokens, Some(1000));
+
+        let limits = QueryLimits {
+            max_depth: Some(4),
+            ..Default::default()
+        };
+        let err = limits.check(&metrics).unwrap_err();
+        assert_eq!(
+            err,
+            QueryLimitError::MaxDepthExceeded {
+                depth: 5,
+                max_depth: 4
+            }
+        );
+        assert_eq!(err.code(), "MAX_DEPTH_EXCEEDED");
+
+        let limits = QueryLimits {
+            max_depth: Some(5),
+            max_cost: Some(99),
+            ..Default::default()
+        };
+        assert_eq!(
+            limits.check(&metrics),
+            Err(QueryLimitError::MaxCostExceeded {
+                cost: 100,
+                max_cost: 99
+            })
+        );
+    }
+}
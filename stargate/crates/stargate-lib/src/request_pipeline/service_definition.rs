@@ -1,10 +1,15 @@
 use crate::request_pipeline::executor::ExecutionContext;
-use crate::transports::http::{GraphQLRequest, GraphQLResponse};
+use crate::transports::http::{
+    GraphQLRequest, GraphQLResponse, FTV1_HEADER_NAME, FTV1_HEADER_VALUE,
+};
 use crate::Result;
 use async_trait::async_trait;
+use opentelemetry::global;
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::iter::FromIterator;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 #[derive(Debug)]
 pub struct ServiceDefinition {
@@ -18,30 +23,44 @@ pub trait Service {
         context: &ExecutionContext<'schema, 'request>,
         operation: String,
         variables: HashMap<String, Value>,
-    ) -> Result<Value>;
+    ) -> Result<GraphQLResponse>;
 }
 
 #[async_trait]
 impl Service for ServiceDefinition {
     async fn send_operation<'schema, 'request>(
         &self,
-        _context: &ExecutionContext<'schema, 'request>,
+        context: &ExecutionContext<'schema, 'request>,
         operation: String,
         variables: HashMap<String, Value>,
-    ) -> Result<Value> {
+    ) -> Result<GraphQLResponse> {
         let request = GraphQLRequest {
             query: operation,
             operation_name: None,
             variables: Some(Map::from_iter(variables.into_iter()).into()),
         };
 
+        // Propagate the context of the current fetch span so the subgraph's traces
+        // join the gateway's trace.
+        let mut trace_headers: HashMap<&'static str, String> = HashMap::new();
+        global::get_http_text_propagator(|propagator| {
+            propagator.inject_context(&Span::current().context(), &mut trace_headers)
+        });
+
         // TODO(ran) FIXME: use a single client, reuse connections.
-        let GraphQLResponse { data } = surf::post(&self.url)
-            .header("userId", "1")
+        let mut request_builder = surf::post(&self.url).header("userId", "1");
+        for (name, value) in trace_headers {
+            request_builder = request_builder.header(name, value);
+        }
+        if context.include_trace {
+            request_builder = request_builder.header(FTV1_HEADER_NAME, FTV1_HEADER_VALUE);
+        }
+
+        let response = request_builder
             .body(surf::Body::from_json(&request)?)
             .recv_json()
             .await?;
 
-        data.ok_or_else(|| unimplemented!("Handle error cases in send_operation"))
+        Ok(response)
     }
 }
@@ -2,6 +2,10 @@ use crate::Stargate;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Header asking a federated service to return a federated trace in `extensions.ftv1`.
+pub const FTV1_HEADER_NAME: &str = "apollo-federation-include-trace";
+pub const FTV1_HEADER_VALUE: &str = "ftv1";
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GraphQLRequest {
     pub query: String,
@@ -14,10 +18,15 @@ pub struct GraphQLRequest {
 pub struct GraphQLResponse {
     pub data: Option<Value>,
     // errors: 'a Option<async_graphql::http::GQLError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Value>,
 }
 
 pub struct RequestContext {
     pub graphql_request: GraphQLRequest,
+    /// Set when the client sent `apollo-federation-include-trace: ftv1`, in which case the
+    /// federated trace is returned in `extensions.ftv1` of the response.
+    pub include_trace: bool,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+const BUCKET_COUNT: usize = 384;
+
+/// A latency histogram in the format expected by Apollo's usage reporting ingress.
+///
+/// Bucket `n` holds durations up to `1.1^n` microseconds, with the first and last buckets
+/// also holding anything smaller or larger. This is the same bucketing as `DurationHistogram`
+/// in apollo-server, so reports from both gateways are comparable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DurationHistogram {
+    buckets: Vec<i64>,
+}
+
+impl Default for DurationHistogram {
+    fn default() -> Self {
+        DurationHistogram {
+            buckets: vec![0; BUCKET_COUNT],
+        }
+    }
+}
+
+impl DurationHistogram {
+    pub fn increment(&mut self, duration: Duration) {
+        self.increment_by(duration, 1);
+    }
+
+    pub fn increment_by(&mut self, duration: Duration, count: i64) {
+        self.buckets[Self::bucket(duration)] += count;
+    }
+
+    fn bucket(duration: Duration) -> usize {
+        let micros = duration.as_nanos() as f64 / 1000.0;
+        let bucket = (micros.ln() / 1.1f64.ln()).ceil();
+        if bucket.is_nan() || bucket <= 0.0 {
+            0
+        } else if bucket >= BUCKET_COUNT as f64 {
+            BUCKET_COUNT - 1
+        } else {
+            bucket as usize
+        }
+    }
+
+    /// Adds the counts of an encoded histogram, as returned by [DurationHistogram::to_vec].
+    pub fn merge_encoded(&mut self, encoded: &[i64]) {
+        let mut bucket = 0;
+        for &value in encoded {
+            if value < 0 {
+                bucket += (-value) as usize;
+            } else {
+                if bucket < BUCKET_COUNT {
+                    self.buckets[bucket] += value;
+                }
+                bucket += 1;
+            }
+        }
+    }
+
+    /// Encodes the histogram, replacing runs of more than one empty bucket with the negated
+    /// length of the run, and dropping trailing empty buckets.
+    pub fn to_vec(&self) -> Vec<i64> {
+        let mut encoded = vec![];
+        let mut buffered_zeroes = 0;
+        for &value in &self.buckets {
+            if value == 0 {
+                buffered_zeroes += 1;
+            } else {
+                if buffered_zeroes == 1 {
+                    encoded.push(0);
+                } else if buffered_zeroes > 1 {
+                    encoded.push(-buffered_zeroes);
+                }
+                encoded.push(value);
+                buffered_zeroes = 0;
+            }
+        }
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_bucket_like_apollo_server() {
+        assert_eq!(DurationHistogram::bucket(Duration::from_nanos(0)), 0);
+        assert_eq!(DurationHistogram::bucket(Duration::from_nanos(1000)), 0);
+        assert_eq!(DurationHistogram::bucket(Duration::from_nanos(1001)), 1);
+        assert_eq!(DurationHistogram::bucket(Duration::from_nanos(1100)), 1);
+        assert_eq!(DurationHistogram::bucket(Duration::from_millis(1)), 73);
+        assert_eq!(
+            DurationHistogram::bucket(Duration::from_secs(60 * 60 * 24 * 365 * 1000)),
+            BUCKET_COUNT - 1
+        );
+    }
+
+    #[test]
+    fn it_should_encode_runs_of_empty_buckets() {
+        let mut histogram = DurationHistogram::default();
+        assert_eq!(histogram.to_vec(), Vec::<i64>::new());
+
+        histogram.increment(Duration::from_nanos(1000));
+        histogram.increment(Duration::from_nanos(1100));
+        histogram.increment(Duration::from_nanos(1300));
+        histogram.increment_by(Duration::from_millis(1), 4);
+        assert_eq!(histogram.to_vec(), vec![1, 1, 0, 1, -69, 4]);
+
+        let mut merged = DurationHistogram::default();
+        merged.merge_encoded(&histogram.to_vec());
+        merged.merge_encoded(&histogram.to_vec());
+        assert_eq!(merged.to_vec(), vec![2, 2, 0, 2, -69, 8]);
+    }
+}
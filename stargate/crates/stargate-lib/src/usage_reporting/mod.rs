@@ -0,0 +1,436 @@
+//! Usage reporting: operation signatures, per-operation latency and per-field execution
+//! statistics, batched and sent periodically in Apollo's usage reporting protobuf format.
+//!
+//! Field statistics are computed from the federated traces of subgraphs. These are only
+//! requested for operations whose client asked for a trace, unless `field_stats` is set, which
+//! requests them from every subgraph for every operation at the cost of tracing each fetch.
+
+use crate::proto::reports::trace::query_plan_node::Node as QueryPlanNode;
+use crate::proto::reports::trace::{node, Node};
+use crate::proto::reports::{
+    ContextualizedStats, FieldStat, QueryLatencyStats, Report, ReportHeader, StatsContext, Trace,
+    TracesAndStats,
+};
+use crate::request_pipeline::federated_tracing;
+use crate::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use prost::Message;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, instrument};
+
+mod histogram;
+mod signature;
+
+pub use histogram::DurationHistogram;
+pub(crate) use signature::stats_report_key;
+
+const AGENT_VERSION: &str = concat!("stargate ", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageReportingConfig {
+    /// Url reports are POSTed to.
+    pub endpoint: String,
+    /// Sent in the `X-Api-Key` header.
+    pub api_key: Option<String>,
+    /// The graph and variant the reports are for, e.g. `mygraph@current`.
+    pub graph_ref: Option<String>,
+    /// How often the binary should call [UsageReporter::send_report].
+    pub send_interval: Duration,
+    /// Whether to request ftv1 traces from subgraphs for every operation, so field statistics
+    /// cover all operations rather than only those traced for their client.
+    pub field_stats: bool,
+}
+
+/// Collects statistics until the next report is sent.
+#[derive(Debug)]
+pub struct UsageReporter {
+    config: UsageReportingConfig,
+    header: ReportHeader,
+    stats: Mutex<HashMap<String, OperationStats>>,
+}
+
+/// Statistics of a single operation, kept as histograms until the report is encoded.
+#[derive(Debug, Default)]
+struct OperationStats {
+    request_count: u64,
+    requests_with_errors_count: u64,
+    latency: DurationHistogram,
+    per_type: HashMap<String, HashMap<String, FieldStats>>,
+}
+
+#[derive(Debug, Default)]
+struct FieldStats {
+    return_type: String,
+    count: u64,
+    errors_count: u64,
+    latency: DurationHistogram,
+}
+
+impl UsageReporter {
+    pub fn new(config: UsageReportingConfig) -> UsageReporter {
+        let header = ReportHeader {
+            graph_ref: config.graph_ref.clone().unwrap_or_default(),
+            hostname: std::env::var("HOSTNAME").unwrap_or_default(),
+            agent_version: String::from(AGENT_VERSION),
+            runtime_version: String::from("rust"),
+            uname: String::from(std::env::consts::OS),
+            ..Default::default()
+        };
+
+        UsageReporter {
+            config,
+            header,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &UsageReportingConfig {
+        &self.config
+    }
+
+    /// Records an executed operation. `trace` is the federated trace of the operation,
+    /// from which field statistics are collected.
+    pub fn record(
+        &self,
+        stats_report_key: String,
+        duration: Duration,
+        has_errors: bool,
+        trace: Option<&Trace>,
+    ) {
+        let mut stats = self.stats.lock().unwrap();
+        let operation = stats.entry(stats_report_key).or_default();
+
+        operation.request_count += 1;
+        operation.latency.increment(duration);
+        if has_errors {
+            operation.requests_with_errors_count += 1;
+        }
+
+        if let Some(query_plan) = trace.and_then(|trace| trace.query_plan.as_ref()) {
+            if let Some(ref node) = query_plan.node {
+                operation.record_query_plan(node);
+            }
+        }
+    }
+
+    /// Builds the report of everything recorded since the last call, or `None` if nothing
+    /// was recorded.
+    pub fn take_report(&self) -> Option<Report> {
+        let stats = std::mem::take(&mut *self.stats.lock().unwrap());
+        if stats.is_empty() {
+            return None;
+        }
+
+        let traces_per_query = stats
+            .into_iter()
+            .map(|(key, operation)| (key, operation.into_traces_and_stats()))
+            .collect();
+
+        Some(Report {
+            header: Some(self.header.clone()),
+            traces_per_query,
+            end_time: Some(federated_tracing::timestamp(SystemTime::now())),
+        })
+    }
+
+    /// Sends the report of everything recorded since the last report, if anything was recorded.
+    #[instrument(skip(self))]
+    pub async fn send_report(&self) -> Result<()> {
+        let report = match self.take_report() {
+            Some(report) => report,
+            None => return Ok(()),
+        };
+        debug!(
+            "sending usage report with {} operations",
+            report.traces_per_query.len()
+        );
+
+        let mut request = surf::post(&self.config.endpoint)
+            .header("Content-Type", "application/protobuf")
+            .header("Content-Encoding", "gzip")
+            .header("Accept", "application/json")
+            .header("User-Agent", AGENT_VERSION);
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("X-Api-Key", api_key.as_str());
+        }
+
+        let response = request.body(encode_report(&report)?).await?;
+        if !response.status().is_success() {
+            return Err(format!("usage report was rejected with {}", response.status()).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl OperationStats {
+    fn record_query_plan(&mut self, node: &QueryPlanNode) {
+        match node {
+            QueryPlanNode::Sequence(sequence) => {
+                for child in sequence.nodes.iter().filter_map(|n| n.node.as_ref()) {
+                    self.record_query_plan(child);
+                }
+            }
+            QueryPlanNode::Parallel(parallel) => {
+                for child in parallel.nodes.iter().filter_map(|n| n.node.as_ref()) {
+                    self.record_query_plan(child);
+                }
+            }
+            QueryPlanNode::Flatten(flatten) => {
+                if let Some(child) = flatten.node.as_ref().and_then(|n| n.node.as_ref()) {
+                    self.record_query_plan(child);
+                }
+            }
+            QueryPlanNode::Fetch(fetch) => {
+                if let Some(root) = fetch.trace.as_ref().and_then(|t| t.root.as_ref()) {
+                    self.record_trace_node(root);
+                }
+            }
+        }
+    }
+
+    fn record_trace_node(&mut self, node: &Node) {
+        // Nodes for list indexes and the root node don't carry types.
+        if let (Some(node::Id::ResponseName(response_name)), false) =
+            (&node.id, node.parent_type.is_empty())
+        {
+            let field_name = if node.original_field_name.is_empty() {
+                response_name
+            } else {
+                &node.original_field_name
+            };
+
+            // _entities is an implementation detail of federation, not a field of the graph.
+            if field_name != "_entities" {
+                let field = self
+                    .per_type
+                    .entry(node.parent_type.clone())
+                    .or_default()
+                    .entry(field_name.clone())
+                    .or_default();
+
+                field.return_type = node.r#type.clone();
+                field.count += 1;
+                field.errors_count += node.error.len() as u64;
+                field.latency.increment(Duration::from_nanos(
+                    node.end_time.saturating_sub(node.start_time),
+                ));
+            }
+        }
+
+        for child in &node.child {
+            self.record_trace_node(child);
+        }
+    }
+
+    fn into_traces_and_stats(self) -> TracesAndStats {
+        let per_type_stat = self
+            .per_type
+            .into_iter()
+            .map(|(type_name, fields)| {
+                let per_field_stat = fields
+                    .into_iter()
+                    .map(|(field_name, field)| {
+                        (
+                            field_name,
+                            FieldStat {
+                                return_type: field.return_type,
+                                errors_count: field.errors_count,
+                                count: field.count,
+                                latency_count: field.latency.to_vec(),
+                                ..Default::default()
+                            },
+                        )
+                    })
+                    .collect();
+                (
+                    type_name,
+                    crate::proto::reports::TypeStat { per_field_stat },
+                )
+            })
+            .collect();
+
+        TracesAndStats {
+            trace: vec![],
+            stats_with_context: vec![ContextualizedStats {
+                context: Some(StatsContext::default()),
+                query_latency_stats: Some(QueryLatencyStats {
+                    latency_count: self.latency.to_vec(),
+                    request_count: self.request_count,
+                    requests_with_errors_count: self.requests_with_errors_count,
+                    ..Default::default()
+                }),
+                per_type_stat,
+            }],
+        }
+    }
+}
+
+/// Encodes a report the way the reporting ingress expects it: gzipped protobuf.
+pub fn encode_report(report: &Report) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(report.encoded_len());
+    report.encode(&mut bytes)?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&bytes)?;
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::reports::trace::query_plan_node::FetchNode;
+    use crate::proto::reports::trace::Error;
+    use flate2::read::GzDecoder;
+    use graphql_parser::parse_query;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn config(endpoint: String) -> UsageReportingConfig {
+        UsageReportingConfig {
+            endpoint,
+            api_key: Some(String::from("service:test:key")),
+            graph_ref: Some(String::from("test@current")),
+            send_interval: Duration::from_secs(1),
+            field_stats: true,
+        }
+    }
+
+    fn field(name: &str, parent_type: &str, typ: &str, children: Vec<Node>) -> Node {
+        Node {
+            id: Some(node::Id::ResponseName(String::from(name))),
+            parent_type: String::from(parent_type),
+            r#type: String::from(typ),
+            start_time: 1_000,
+            end_time: 2_100,
+            child: children,
+            ..Default::default()
+        }
+    }
+
+    fn trace() -> Trace {
+        let mut reviews = field("reviews", "User", "[Review]", vec![]);
+        reviews.error.push(Error::default());
+        let root = Node {
+            child: vec![field(
+                "_entities",
+                "Query",
+                "[_Entity]!",
+                vec![Node {
+                    id: Some(node::Id::Index(0)),
+                    child: vec![reviews],
+                    ..Default::default()
+                }],
+            )],
+            ..Default::default()
+        };
+
+        Trace {
+            query_plan: Some(federated_tracing::fetch(FetchNode {
+                service_name: String::from("reviews"),
+                trace: Some(Trace {
+                    root: Some(root),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_should_aggregate_operations() {
+        let reporter = UsageReporter::new(config(String::from("http://localhost")));
+        assert!(reporter.take_report().is_none());
+
+        let key = stats_report_key(&parse_query("{ me { reviews { body } } }").unwrap(), None);
+        reporter.record(key.clone(), Duration::from_millis(1), false, Some(&trace()));
+        reporter.record(key.clone(), Duration::from_millis(1), true, Some(&trace()));
+
+        let report = reporter.take_report().unwrap();
+        assert_eq!(report.header.unwrap().graph_ref, "test@current");
+
+        let stats = &report.traces_per_query[&key].stats_with_context[0];
+        let latency = stats.query_latency_stats.as_ref().unwrap();
+        assert_eq!(latency.request_count, 2);
+        assert_eq!(latency.requests_with_errors_count, 1);
+        assert_eq!(latency.latency_count, vec![-73, 2]);
+
+        assert!(!stats.per_type_stat.contains_key("Query"));
+        let reviews = &stats.per_type_stat["User"].per_field_stat["reviews"];
+        assert_eq!(reviews.return_type, "[Review]");
+        assert_eq!(reviews.count, 2);
+        assert_eq!(reviews.errors_count, 2);
+        assert_eq!(reviews.latency_count, vec![0, 2]);
+
+        assert!(reporter.take_report().is_none());
+    }
+
+    #[test]
+    fn it_should_send_reports_to_the_collector() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!(
+            "http://{}/api/ingress/traces",
+            listener.local_addr().unwrap()
+        );
+
+        // A minimal mock collector: reads one request and decodes its body.
+        let collector = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                let mut header = line.splitn(2, ':');
+                let name = header.next().unwrap().trim().to_lowercase();
+                let value = header.next().unwrap_or_default().trim();
+                headers.push(format!("{}: {}", name, value));
+            }
+            let content_length: usize = headers
+                .iter()
+                .find_map(|h| h.strip_prefix("content-length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            std::io::Write::write_all(
+                &mut &stream,
+                b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+
+            let mut decoded = vec![];
+            GzDecoder::new(body.as_slice())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            (headers, Report::decode(decoded.as_slice()).unwrap())
+        });
+
+        let reporter = UsageReporter::new(config(endpoint));
+        let key = stats_report_key(&parse_query("query Me { me { id } }").unwrap(), Some("Me"));
+        reporter.record(key.clone(), Duration::from_millis(3), false, None);
+        futures::executor::block_on(reporter.send_report()).unwrap();
+
+        let (headers, report) = collector.join().unwrap();
+        assert!(headers.contains(&String::from("x-api-key: service:test:key")));
+        assert!(headers.contains(&String::from("content-encoding: gzip")));
+        assert_eq!(
+            report.traces_per_query[&key].stats_with_context[0]
+                .query_latency_stats
+                .as_ref()
+                .unwrap()
+                .request_count,
+            1
+        );
+    }
+}
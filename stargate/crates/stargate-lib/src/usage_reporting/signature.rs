@@ -0,0 +1,43 @@
+use graphql_parser::query::signature::signature;
+use graphql_parser::query::Document;
+
+/// Returns the key operations are reported under: `# <operation name>\n<signature>`.
+pub(crate) fn stats_report_key(document: &Document, operation_name: Option<&str>) -> String {
+    match signature(document, operation_name) {
+        Some(signature) => format!("# {}\n{}", operation_name.unwrap_or("-"), signature),
+        None => String::from("## GraphQLUnknownOperationName\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphql_parser::parse_query;
+
+    fn stats_report_key(query: &str, operation_name: Option<&str>) -> String {
+        super::stats_report_key(&parse_query(query).unwrap(), operation_name)
+    }
+
+    #[test]
+    fn it_should_hide_literals_and_aliases() {
+        assert_eq!(
+            stats_report_key(
+                r#"query Me($id: ID) { me: user(id: $id, first: 10, name: "me") { reviews(filter: {stars: 4}) { body } } }"#,
+                Some("Me")
+            ),
+            "# Me\nquery Me($id:ID){user(first:0 id:$id name:\"\"){reviews(filter:{}){body}}}"
+        );
+    }
+
+    #[test]
+    fn it_should_only_keep_the_executed_operation() {
+        assert_eq!(
+            stats_report_key("query A { a } query B { b }", Some("B")),
+            "# B\nquery B{b}"
+        );
+        assert_eq!(stats_report_key("{ a }", None), "# -\n{a}");
+        assert_eq!(
+            stats_report_key("query A { a }", Some("B")),
+            "## GraphQLUnknownOperationName\n"
+        );
+    }
+}
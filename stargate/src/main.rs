@@ -1,33 +1,77 @@
 use actix_cors::Cors;
-use actix_web::{dev, http, middleware, post, web, App, HttpResponse, HttpServer, Result};
+use actix_service::Service;
+use actix_web::{
+    dev, http, middleware, post, rt, web, App, HttpRequest, HttpResponse, HttpServer, Result,
+};
 use actix_web_opentelemetry::RequestMetrics;
 use apollo_stargate_lib::common::Opt;
-use apollo_stargate_lib::transports::http::{GraphQLRequest, RequestContext, ServerState};
-use apollo_stargate_lib::Stargate;
+use apollo_stargate_lib::transports::http::{
+    GraphQLRequest, RequestContext, ServerState, FTV1_HEADER_NAME, FTV1_HEADER_VALUE,
+};
+use apollo_stargate_lib::{QueryLimitError, QueryPlanError, Stargate};
+use graphql_parser::{ParseError, Pos};
 use opentelemetry::sdk;
+use serde_json::json;
 use std::fs;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, info_span, instrument, warn};
 use tracing_actix_web::TracingLogger;
+use tracing_futures::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 mod telemetry;
 
 #[post("/")]
-#[instrument(skip(request, data))]
+#[instrument(skip(http_request, request, data))]
 async fn index(
+    http_request: HttpRequest,
     request: web::Json<GraphQLRequest>,
     data: web::Data<ServerState<'static>>,
 ) -> Result<HttpResponse> {
     let ql_request = request.into_inner();
+    let include_trace = http_request
+        .headers()
+        .get(FTV1_HEADER_NAME)
+        .map(|value| value == FTV1_HEADER_VALUE)
+        .unwrap_or(false);
     let context = RequestContext {
         graphql_request: ql_request,
+        include_trace,
     };
     let result = match data.stargate.execute_query(&context).await {
         Ok(result) => result,
-        Err(_) => todo!("handle error cases when executing query"),
+        Err(e) => {
+            let mut error = json!({ "message": e.to_string() });
+            if let Some(e) = e.downcast_ref::<QueryLimitError>() {
+                error["extensions"] = json!({ "code": e.code() });
+            } else if let Some(e) = e.downcast_ref::<ParseError>() {
+                error["extensions"] = json!({ "code": "GRAPHQL_PARSE_FAILED" });
+                if let Some(position) = e.position() {
+                    error["locations"] = locations(position);
+                }
+            } else if let Some(e) = e.downcast_ref::<QueryPlanError>() {
+                let code = match e {
+                    QueryPlanError::FailedParsingQuery(_) => "GRAPHQL_PARSE_FAILED",
+                    _ => "GRAPHQL_VALIDATION_FAILED",
+                };
+                error["extensions"] = json!({ "code": code });
+                if let Some(position) = e.position() {
+                    error["locations"] = locations(position);
+                }
+            } else {
+                warn!("failed to execute query: {}", e);
+                error["extensions"] = json!({ "code": "INTERNAL_SERVER_ERROR" });
+                return Ok(HttpResponse::InternalServerError().json(json!({ "errors": [error] })));
+            }
+            return Ok(HttpResponse::BadRequest().json(json!({ "errors": [error] })));
+        }
     };
     Ok(HttpResponse::Ok().json(result))
 }
 
+fn locations(position: Pos) -> serde_json::Value {
+    json!([{ "line": position.line, "column": position.column }])
+}
+
 fn health() -> HttpResponse {
     HttpResponse::Ok().finish()
 }
@@ -49,12 +93,33 @@ async fn main() -> std::io::Result<()> {
     info!("{}", opt.pretty_print());
 
     debug!("Initializing stargate instance");
-    let stargate = unsafe {
+    let mut stargate = unsafe {
         MANIFEST = fs::read_to_string(&opt.manifest)?;
         Stargate::new(&MANIFEST)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+            .with_query_limits(opt.query_limits.clone())
     };
+    if let Some(config) = opt.usage_reporting_config() {
+        stargate = stargate.with_usage_reporting(config);
+    }
     let stargate = web::Data::new(ServerState { stargate });
 
+    if let Some(usage_reporter) = stargate.stargate.usage_reporter() {
+        let mut interval = rt::time::interval(usage_reporter.config().send_interval);
+        let state = stargate.clone();
+        rt::spawn(async move {
+            // The first tick completes immediately.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let usage_reporter = state.stargate.usage_reporter().unwrap();
+                if let Err(e) = usage_reporter.send_report().await {
+                    warn!("failed to send usage report: {}", e);
+                }
+            }
+        });
+    }
+
     HttpServer::new(move || {
         let cors = Cors::new()
             .allowed_methods(vec!["GET", "POST", "OPTIONS"])
@@ -67,6 +132,14 @@ async fn main() -> std::io::Result<()> {
             .wrap(request_metrics.clone())
             .wrap(middleware::Logger::default())
             .wrap(TracingLogger)
+            // Outside of TracingLogger, so the span of each request continues the trace of the
+            // caller when the request carries a trace context.
+            .wrap_fn(|request, service| {
+                let span = info_span!("incoming_request");
+                span.set_parent(&telemetry::remote_context(request.headers()));
+                let response = span.in_scope(|| service.call(request));
+                response.instrument(span)
+            })
             .wrap(middleware::Compress::default())
             .wrap(cors)
             .service(index)
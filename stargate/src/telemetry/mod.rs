@@ -0,0 +1,162 @@
+use actix_web::http::HeaderMap;
+use apollo_stargate_lib::common::{
+    Opt, SamplerConfig, TracingConfig, TracingExporter, TracingProtocol,
+};
+use opentelemetry::api::{self, Provider, TraceContextPropagator};
+use opentelemetry::exporter::trace::SpanExporter;
+use opentelemetry::sdk::BatchSpanProcessor;
+use opentelemetry::{global, sdk};
+use tracing::debug;
+use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
+use tracing_log::LogTracer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::EnvFilter;
+
+mod otlp_http;
+mod unix;
+
+macro_rules! subscriber {
+    ($ ( $ layer : expr , ) +) => {
+        let subscriber = {
+            tracing_subscriber::Registry::default()
+                $(.with($layer))+
+        };
+
+        tracing::subscriber::set_global_default(subscriber)?;
+    };
+}
+
+pub(crate) fn init(opts: &Opt) -> Result<(), Box<dyn std::error::Error>> {
+    LogTracer::init()?;
+
+    if let Some(tracing) = &opts.tracing_endpoint {
+        let exporter = span_processor(tracing)?;
+
+        debug!("initializing trace provider");
+        let provider = sdk::Provider::builder()
+            .with_batch_exporter(exporter)
+            .with_config(sdk::Config {
+                default_sampler: Box::new(sampler(&opts.tracing_sampler)),
+                ..Default::default()
+            })
+            .build();
+
+        if opts.structured_logging {
+            subscriber!(
+                tracing_opentelemetry::layer().with_tracer(provider.get_tracer("stargate")),
+                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+                JsonStorageLayer,
+                BunyanFormattingLayer::new(String::from("stargate"), std::io::stdout),
+            );
+        } else {
+            subscriber!(
+                tracing_opentelemetry::layer().with_tracer(provider.get_tracer("stargate")),
+                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+                tracing_subscriber::fmt::layer(),
+            );
+        };
+
+        debug!("setting global trace provider");
+        opentelemetry::global::set_provider(provider);
+
+        debug!("setting global trace context propagator");
+        opentelemetry::global::set_http_text_propagator(TraceContextPropagator::new());
+    } else if opts.structured_logging {
+        subscriber!(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+            JsonStorageLayer,
+            BunyanFormattingLayer::new(String::from("stargate"), std::io::stdout),
+        );
+    } else {
+        subscriber!(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+            tracing_subscriber::fmt::layer(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the trace context an incoming request carries in its headers, which is empty if it
+/// carries none or tracing is disabled.
+pub(crate) fn remote_context(headers: &HeaderMap) -> api::Context {
+    global::get_http_text_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl api::Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+}
+
+fn span_processor(
+    tracing: &TracingConfig,
+) -> Result<BatchSpanProcessor, Box<dyn std::error::Error>> {
+    match tracing.exporter {
+        TracingExporter::Jaeger => {
+            debug!("initializing jaeger trace exporter");
+
+            let mut exporter_builder = opentelemetry_jaeger::Exporter::builder().with_process(
+                opentelemetry_jaeger::Process {
+                    service_name: String::from("stargate"),
+                    tags: vec![],
+                },
+            );
+
+            exporter_builder = match tracing.protocol {
+                TracingProtocol::UDP => {
+                    exporter_builder.with_agent_endpoint(tracing.host_port_path.clone())
+                }
+                TracingProtocol::UNIX => {
+                    exporter_builder.with_agent_endpoint(unix::relay(&tracing.host_port_path)?)
+                }
+                _ => exporter_builder.with_collector_endpoint(tracing.to_string()),
+            };
+
+            Ok(batch(exporter_builder.init()?))
+        }
+        TracingExporter::Otlp if tracing.protocol == TracingProtocol::GRPC => {
+            debug!("initializing otlp grpc trace exporter");
+
+            Ok(batch(opentelemetry_otlp::Exporter::new(
+                opentelemetry_otlp::ExporterConfig {
+                    endpoint: tracing.host_port_path.clone(),
+                    ..Default::default()
+                },
+            )))
+        }
+        TracingExporter::Otlp => {
+            debug!("initializing otlp http trace exporter");
+
+            let scheme = if tracing.protocol == TracingProtocol::HTTPS {
+                "https"
+            } else {
+                "http"
+            };
+            Ok(batch(otlp_http::Exporter::new(format!(
+                "{}://{}",
+                scheme, tracing.host_port_path
+            ))?))
+        }
+    }
+}
+
+fn batch<E: SpanExporter + 'static>(exporter: E) -> BatchSpanProcessor {
+    BatchSpanProcessor::builder(
+        exporter,
+        actix_web::rt::spawn,
+        actix_web::rt::time::interval,
+    )
+    .build()
+}
+
+fn sampler(config: &SamplerConfig) -> sdk::Sampler {
+    match config {
+        SamplerConfig::AlwaysOn => sdk::Sampler::AlwaysOn,
+        SamplerConfig::AlwaysOff => sdk::Sampler::AlwaysOff,
+        SamplerConfig::Ratio(ratio) => sdk::Sampler::Probability(*ratio),
+        SamplerConfig::ParentBased(root) => sdk::Sampler::ParentOrElse(Box::new(sampler(root))),
+    }
+}
@@ -0,0 +1,179 @@
+use isahc::prelude::*;
+use opentelemetry::api::{Key, SpanKind, StatusCode, Value};
+use opentelemetry::exporter::trace::{ExportResult, SpanData, SpanExporter};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Exports spans as OTLP/HTTP requests with the JSON encoding of the OTLP protobuf messages.
+///
+/// opentelemetry-otlp only speaks gRPC, so this is a minimal exporter
+/// following https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/protocol/otlp.md#otlphttp
+#[derive(Debug)]
+pub(crate) struct Exporter {
+    endpoint: String,
+    client: HttpClient,
+}
+
+impl Exporter {
+    pub(crate) fn new(endpoint: String) -> Result<Exporter, isahc::Error> {
+        Ok(Exporter {
+            endpoint,
+            client: HttpClient::new()?,
+        })
+    }
+}
+
+impl SpanExporter for Exporter {
+    /// The batch span processor calls this on the actix runtime, so the request is sent in
+    /// the background instead of blocking the runtime until the collector answers, and
+    /// failures are only logged.
+    fn export(&self, batch: Vec<Arc<SpanData>>) -> ExportResult {
+        let body = export_trace_service_request(&batch).to_string();
+        let request = match Request::post(&self.endpoint)
+            .header("Content-Type", "application/json")
+            .body(body)
+        {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("failed to build otlp export request: {}", e);
+                return ExportResult::FailedNotRetryable;
+            }
+        };
+
+        let client = self.client.clone();
+        actix_web::rt::spawn(async move {
+            match client.send_async(request).await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => warn!("otlp collector rejected spans: {}", response.status()),
+                Err(e) => warn!("failed to export spans to otlp collector: {}", e),
+            }
+        });
+
+        ExportResult::Success
+    }
+
+    fn shutdown(&self) {}
+}
+
+type InstrumentationLibrarySpans = (serde_json::Value, Vec<serde_json::Value>);
+
+/// Groups the spans of the batch by resource, then by instrumentation library. Spans from
+/// the same provider share a resource, so there is usually a single `ResourceSpans`.
+fn export_trace_service_request(batch: &[Arc<SpanData>]) -> serde_json::Value {
+    let mut resource_spans: Vec<(serde_json::Value, Vec<InstrumentationLibrarySpans>)> = vec![];
+    for span_data in batch {
+        let resource = json!({
+            "attributes": span_data
+                .resource
+                .iter()
+                .map(|(key, value)| attribute(key, value))
+                .collect::<Vec<_>>(),
+        });
+        let library = json!({
+            "name": span_data.instrumentation_lib.name,
+            "version": span_data.instrumentation_lib.version.unwrap_or_default(),
+        });
+
+        let libraries = match resource_spans.iter().position(|(r, _)| *r == resource) {
+            Some(i) => &mut resource_spans[i].1,
+            None => {
+                resource_spans.push((resource, vec![]));
+                &mut resource_spans.last_mut().unwrap().1
+            }
+        };
+        let spans = match libraries.iter().position(|(l, _)| *l == library) {
+            Some(i) => &mut libraries[i].1,
+            None => {
+                libraries.push((library, vec![]));
+                &mut libraries.last_mut().unwrap().1
+            }
+        };
+        spans.push(span(span_data));
+    }
+
+    let resource_spans: Vec<serde_json::Value> = resource_spans
+        .into_iter()
+        .map(|(resource, libraries)| {
+            json!({
+                "resource": resource,
+                "instrumentationLibrarySpans": libraries
+                    .into_iter()
+                    .map(|(library, spans)| {
+                        json!({ "instrumentationLibrary": library, "spans": spans })
+                    })
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({ "resourceSpans": resource_spans })
+}
+
+fn span(span: &SpanData) -> serde_json::Value {
+    let mut value = json!({
+        "traceId": format!("{:032x}", span.span_context.trace_id().to_u128()),
+        "spanId": format!("{:016x}", span.span_context.span_id().to_u64()),
+        "name": span.name,
+        "kind": kind(&span.span_kind),
+        "startTimeUnixNano": unix_nanos(span.start_time).to_string(),
+        "endTimeUnixNano": unix_nanos(span.end_time).to_string(),
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| attribute(key, value))
+            .collect::<Vec<_>>(),
+        "status": status(&span.status_code, &span.status_message),
+    });
+
+    let parent_span_id = span.parent_span_id.to_u64();
+    if parent_span_id != 0 {
+        value["parentSpanId"] = json!(format!("{:016x}", parent_span_id));
+    }
+
+    value
+}
+
+fn attribute(key: &Key, value: &Value) -> serde_json::Value {
+    json!({ "key": key.as_str(), "value": any_value(value) })
+}
+
+fn any_value(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::I64(i) => json!({ "intValue": i.to_string() }),
+        Value::U64(u) => json!({ "intValue": u.to_string() }),
+        Value::F64(f) => json!({ "doubleValue": f }),
+        Value::String(s) => json!({ "stringValue": s }),
+        Value::Bytes(bytes) => json!({ "stringValue": String::from_utf8_lossy(bytes) }),
+        Value::Array(values) => json!({
+            "arrayValue": { "values": values.iter().map(any_value).collect::<Vec<_>>() }
+        }),
+    }
+}
+
+// See SpanKind in opentelemetry/proto/trace/v1/trace.proto
+fn kind(kind: &SpanKind) -> u8 {
+    match kind {
+        SpanKind::Internal => 1,
+        SpanKind::Server => 2,
+        SpanKind::Client => 3,
+        SpanKind::Producer => 4,
+        SpanKind::Consumer => 5,
+    }
+}
+
+// See Status.StatusCode in opentelemetry/proto/trace/v1/trace.proto
+fn status(code: &StatusCode, message: &str) -> serde_json::Value {
+    match code {
+        StatusCode::OK => json!({ "code": 0 }),
+        _ => json!({ "code": 2, "message": message }),
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default()
+}
@@ -0,0 +1,55 @@
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+// Same as the max packet size used by the jaeger agent client.
+const MAX_PACKET_SIZE: usize = 65_000;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The jaeger agent protocol sends one thrift batch per datagram, and the exporter only
+/// knows how to talk UDP. We point it at a loopback socket and forward every datagram
+/// as is to the unix domain socket at `socket_path`.
+pub(crate) fn relay(socket_path: &str) -> io::Result<SocketAddr> {
+    let udp = UdpSocket::bind("127.0.0.1:0")?;
+    let address = udp.local_addr()?;
+    let unix = UnixDatagram::unbound()?;
+    let socket_path = PathBuf::from(socket_path);
+
+    thread::Builder::new()
+        .name(String::from("jaeger-unix-relay"))
+        .spawn(move || {
+            let mut buf = vec![0; MAX_PACKET_SIZE];
+            // Doubled after every failure in a row, so an agent that is down doesn't
+            // flood the logs. Datagrams arriving meanwhile queue up in the UDP socket.
+            let mut backoff = INITIAL_BACKOFF;
+            loop {
+                let forwarded = match udp.recv(&mut buf) {
+                    Ok(len) => match unix.send_to(&buf[..len], &socket_path) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            warn!("failed to forward spans to {:?}: {}", socket_path, e);
+                            false
+                        }
+                    },
+                    Err(e) => {
+                        warn!("failed to receive spans from the jaeger exporter: {}", e);
+                        false
+                    }
+                };
+                if forwarded {
+                    backoff = INITIAL_BACKOFF;
+                } else {
+                    thread::sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        })?;
+
+    Ok(address)
+}